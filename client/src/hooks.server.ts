@@ -2,6 +2,7 @@ import backendConfig from '$server/backend.config';
 import serverConfig from '$server/server.config';
 import type { Handle, RequestEvent } from '@sveltejs/kit';
 import type { User } from '$shared/domain';
+import { forwardClientAddress } from '$shared/proxy';
 
 // Invoked for each endpoint called, and initially for SSR router
 export const handle: Handle = async ({ event, resolve }) => {
@@ -16,7 +17,7 @@ export const handle: Handle = async ({ event, resolve }) => {
 
 // Attach authorization to each server request
 async function attachUserToRequestEvent(sessionId: string, event: RequestEvent) {
-	const user: User | null = await callWhoAmIApi(sessionId);
+	const user: User | null = await callWhoAmIApi(sessionId, event.getClientAddress);
 
 	if (user) {
 		event.locals.user = {
@@ -27,11 +28,15 @@ async function attachUserToRequestEvent(sessionId: string, event: RequestEvent)
 	}
 }
 
-async function callWhoAmIApi(sessionId: string): Promise<User | null> {
+async function callWhoAmIApi(
+	sessionId: string,
+	getClientAddress: () => string
+): Promise<User | null> {
 	const response = await fetch(backendConfig.baseUrl + '/user/me', {
 		method: 'GET',
 		headers: {
 			Cookie: backendConfig.serverSessionCookieName + '=' + sessionId,
+			...forwardClientAddress(getClientAddress),
 			...backendConfig.baseHeaders
 		}
 	});
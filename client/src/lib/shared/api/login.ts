@@ -2,3 +2,15 @@ export type LoginRequestData = {
 	email: string;
 	password: string;
 };
+
+export type TwoFactorLoginRequestData = {
+	pendingToken: string;
+	code: string;
+};
+
+// Users with two-factor authentication enabled get no session after the password,
+// they have to send a code together with the pending token first
+export type LoginResult = {
+	twoFactorRequired: boolean;
+	pendingToken?: string;
+};
@@ -0,0 +1,7 @@
+export type OidcProvider = {
+	name: string;
+	displayName: string;
+};
+
+// The server keeps the state of a login at a provider in this cookie, the client relays it
+export const OIDC_LOGIN_COOKIE_NAME = 'OIDC_LOGIN';
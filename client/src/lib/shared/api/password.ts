@@ -0,0 +1,5 @@
+export type ResetPasswordRequestData = {
+	token: string;
+	password: string;
+	confirmPassword: string;
+};
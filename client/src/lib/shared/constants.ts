@@ -3,3 +3,10 @@ export const REQUIRED_VIOLATION_MSG = 'You need to fill this.';
 export const MAX_LENGTH_VIOLATION_MSG = 'You need to choose a shorter one.';
 export const EMAIL_VIOLATION_MSG = 'You need to type a valid email address.';
 export const UNSUCCESFUL_LOGIN_ALERT_MSG = 'Incorrect username or password.';
+export const UNSUCCESFUL_TWO_FACTOR_LOGIN_ALERT_MSG =
+	'Incorrect or expired code, start over if it keeps failing.';
+export const UNSUCCESFUL_OIDC_LOGIN_ALERT_MSG =
+	'Signing in with the provider did not succeed, please try again.';
+export const INVALID_LINK_ALERT_MSG = 'This link is invalid or has expired.';
+export const STRONG_PASSWORD_PATTERN =
+	/^(?=.*[a-z])(?=.*[A-Z])(?=.*\d)(?=.*[@$!%*?&])[A-Za-z\d@$!%*?&]{8,120}$/;
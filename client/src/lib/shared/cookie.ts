@@ -9,3 +9,22 @@ export const transferSessionCookie = (cookies: Cookies): CookieHeader | Record<s
 	const sessionId = cookies.get(backendConfig.serverSessionCookieName);
 	return sessionId ? { cookie: `${backendConfig.serverSessionCookieName}=${sessionId}` } : {};
 };
+
+// Multiple cookies arrive joined by commas, the expiry dates contain commas too but no '=' after them
+export const readServerCookie = (response: Response, name: string): string | undefined => {
+	const value = response.headers
+		.get('Set-Cookie')
+		?.split(/,\s*(?=[^;=\s]+=)/)
+		.find((cookie) => cookie.startsWith(`${name}=`))
+		?.slice(name.length + 1)
+		.split(';')[0];
+	return value || undefined;
+};
+
+export const storeServerSessionCookie = (response: Response, cookies: Cookies): boolean => {
+	const authCookie = readServerCookie(response, backendConfig.serverSessionCookieName);
+	if (!authCookie) return false;
+
+	cookies.set(backendConfig.serverSessionCookieName, authCookie, { path: '/' });
+	return true;
+};
@@ -16,14 +16,31 @@ export interface Currency {
 	name: string;
 }
 
+export interface Participant {
+	id: number;
+	name: string;
+	householdId: number | null;
+	accountId: number | null;
+}
+
 export type Transaction = {
 	id: number;
-	donorName: string;
+	participant: Participant;
 	value: number;
 	date: string;
 	currency: Currency;
 };
 
+export interface Attachment {
+	id: number;
+	expenseId: number | null;
+	transactionId: number | null;
+	fileName: string;
+	contentType: string;
+	size: number;
+	createdAt: string;
+}
+
 export interface PredefinedExpense {
 	id: number;
 	name: string;
@@ -31,6 +48,9 @@ export interface PredefinedExpense {
 	value: number;
 	currency: Currency;
 	recurrence: Recurrence;
+	userId: number | null;
+	householdId: number | null;
+	visibility: 'private' | 'household' | 'public';
 }
 
 export interface Expense {
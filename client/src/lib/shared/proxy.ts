@@ -2,9 +2,19 @@ import backendConfig from '$server/backend.config';
 import type { Cookies } from '@sveltejs/kit';
 import { transferSessionCookie } from './cookie';
 
+type ForwardedForHeader = {
+	'X-Forwarded-For': string;
+};
+
+// Every request reaches the server from here, so the address of the user is passed on in a header
+export const forwardClientAddress = (getClientAddress: () => string): ForwardedForHeader => ({
+	'X-Forwarded-For': getClientAddress()
+});
+
 interface RequestProxyConfig {
 	fetch: typeof fetch;
 	cookies: Cookies;
+	getClientAddress: () => string;
 	method: 'GET' | 'POST' | 'DELETE';
 	path: string;
 	request?: Request;
@@ -13,6 +23,7 @@ interface RequestProxyConfig {
 export const requestAsProxy = async ({
 	method,
 	cookies,
+	getClientAddress,
 	path,
 	request
 }: RequestProxyConfig): Promise<Response> => {
@@ -20,6 +31,7 @@ export const requestAsProxy = async ({
 		method,
 		headers: {
 			...transferSessionCookie(cookies),
+			...forwardClientAddress(getClientAddress),
 			...backendConfig.baseHeaders
 		},
 		body: request ? await request.text() : undefined
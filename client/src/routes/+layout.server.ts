@@ -18,7 +18,12 @@ export const load: LayoutServerLoad = ({ locals, route }) => {
 };
 
 function isPublicRoute(route: LayoutRouteId) {
-	const publicRoutes = ['getting-started'];
+	const publicRoutes = [
+		'getting-started',
+		'/login/oidc/[provider]/callback',
+		'/password/reset/[token]',
+		'/email/confirm/[token]'
+	];
 
 	if (!route) {
 		return false;
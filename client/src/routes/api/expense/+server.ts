@@ -1,10 +1,11 @@
 import { requestAsProxy } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const POST = (async ({ fetch, cookies, request }) => {
+export const POST = (async ({ fetch, cookies, request, getClientAddress }) => {
 	return await requestAsProxy({
 		fetch,
 		cookies,
+		getClientAddress,
 		method: 'POST',
 		path: `/expense`,
 		request
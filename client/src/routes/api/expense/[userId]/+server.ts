@@ -1,11 +1,12 @@
 import { requestAsProxy } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const GET = (async ({ fetch, cookies, params }) => {
+export const GET = (async ({ fetch, cookies, params, getClientAddress }) => {
 	const id = params.userId;
 	return await requestAsProxy({
 		fetch,
 		cookies,
+		getClientAddress,
 		method: 'GET',
 		path: `/expense/${id}`
 	});
@@ -1,19 +1,21 @@
 import { requestAsProxy } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const GET = (async ({ fetch, cookies }) => {
+export const GET = (async ({ fetch, cookies, getClientAddress }) => {
 	return await requestAsProxy({
 		fetch,
 		cookies,
+		getClientAddress,
 		method: 'GET',
 		path: `/expense/predefined`
 	});
 }) satisfies RequestHandler;
 
-export const POST = (async ({ fetch, cookies, request }) => {
+export const POST = (async ({ fetch, cookies, request, getClientAddress }) => {
 	return await requestAsProxy({
 		fetch,
 		cookies,
+		getClientAddress,
 		method: 'POST',
 		path: `/expense/predefined`,
 		request
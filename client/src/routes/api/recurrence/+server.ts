@@ -1,10 +1,11 @@
 import { requestAsProxy } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const GET = (async ({ fetch, cookies }) => {
+export const GET = (async ({ fetch, cookies, getClientAddress }) => {
 	return await requestAsProxy({
 		fetch,
 		cookies,
+		getClientAddress,
 		method: 'GET',
 		path: '/recurrence'
 	});
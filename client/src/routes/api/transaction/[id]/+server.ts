@@ -1,11 +1,12 @@
 import { requestAsProxy } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const DELETE = (async ({ fetch, cookies, params }) => {
+export const DELETE = (async ({ fetch, cookies, params, getClientAddress }) => {
 	const id = params.id;
 	return await requestAsProxy({
 		fetch,
 		cookies,
+		getClientAddress,
 		method: 'DELETE',
 		path: `/transaction/${id}`
 	});
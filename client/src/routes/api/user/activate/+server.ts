@@ -1,7 +1,8 @@
 import backendConfig from '$server/backend.config';
+import { forwardClientAddress } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const POST = (async ({ fetch, url }) => {
+export const POST = (async ({ fetch, url, getClientAddress }) => {
 	const token = url.searchParams.get('token');
 
 	if (!token) {
@@ -9,7 +10,8 @@ export const POST = (async ({ fetch, url }) => {
 	}
 
 	const response = await fetch(backendConfig.baseUrl + `/user/activate/${token}`, {
-		method: 'POST'
+		method: 'POST',
+		headers: forwardClientAddress(getClientAddress)
 	});
 
 	return new Response(`Activation ${response.ok ? 'successful' : 'failed'}`, {
@@ -0,0 +1,20 @@
+import backendConfig from '$server/backend.config';
+import { forwardClientAddress } from '$shared/proxy';
+import type { RequestHandler } from './$types';
+
+export const POST = (async ({ fetch, url, getClientAddress }) => {
+	const token = url.searchParams.get('token');
+
+	if (!token) {
+		return new Response('Missing confirmation token', { status: 400 });
+	}
+
+	const response = await fetch(backendConfig.baseUrl + `/user/email/confirm/${token}`, {
+		method: 'POST',
+		headers: forwardClientAddress(getClientAddress)
+	});
+
+	return new Response(`Email confirmation ${response.ok ? 'successful' : 'failed'}`, {
+		status: response.status
+	});
+}) satisfies RequestHandler;
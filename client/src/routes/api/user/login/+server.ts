@@ -1,10 +1,21 @@
 import backendConfig from '$server/backend.config';
+import { storeServerSessionCookie } from '$shared/cookie';
+import { forwardClientAddress } from '$shared/proxy';
+import type { LoginResult } from '$shared/api/login';
 import type { RequestHandler } from './$types';
 
-export const POST = (async ({ fetch, request, cookies }) => {
+type ServerLoginResponse = {
+	two_factor_required: boolean;
+	pending_token: string | null;
+};
+
+export const POST = (async ({ fetch, request, cookies, getClientAddress }) => {
 	const response = await fetch(backendConfig.baseUrl + '/user/auth', {
 		method: 'POST',
-		headers: backendConfig.baseHeaders,
+		headers: {
+			...forwardClientAddress(getClientAddress),
+			...backendConfig.baseHeaders
+		},
 		body: await request.text()
 	});
 
@@ -14,17 +25,27 @@ export const POST = (async ({ fetch, request, cookies }) => {
 		});
 	}
 
-	const authCookie = response.headers
-		.get('Set-Cookie')
-		?.replace(`${backendConfig.serverSessionCookieName}=`, '');
-	if (!authCookie) {
+	const loginResponse: ServerLoginResponse = await response.json();
+	if (loginResponse.two_factor_required && loginResponse.pending_token) {
+		const result: LoginResult = {
+			twoFactorRequired: true,
+			pendingToken: loginResponse.pending_token
+		};
+		return new Response(JSON.stringify(result), {
+			status: response.status,
+			headers: backendConfig.baseHeaders
+		});
+	}
+
+	if (!storeServerSessionCookie(response, cookies)) {
 		return new Response('Login failed, no cookie in server response', {
 			status: 500
 		});
 	}
 
-	cookies.set(backendConfig.serverSessionCookieName, authCookie, { path: '/' });
-	return new Response('Login successful', {
-		status: response.status
+	const result: LoginResult = { twoFactorRequired: false };
+	return new Response(JSON.stringify(result), {
+		status: response.status,
+		headers: backendConfig.baseHeaders
 	});
 }) satisfies RequestHandler;
@@ -0,0 +1,31 @@
+import backendConfig from '$server/backend.config';
+import { storeServerSessionCookie } from '$shared/cookie';
+import { forwardClientAddress } from '$shared/proxy';
+import type { RequestHandler } from './$types';
+
+export const POST = (async ({ fetch, request, cookies, getClientAddress }) => {
+	const response = await fetch(backendConfig.baseUrl + '/user/auth/2fa', {
+		method: 'POST',
+		headers: {
+			...forwardClientAddress(getClientAddress),
+			...backendConfig.baseHeaders
+		},
+		body: await request.text()
+	});
+
+	if (!response.ok) {
+		return new Response('Login failed', {
+			status: response.status
+		});
+	}
+
+	if (!storeServerSessionCookie(response, cookies)) {
+		return new Response('Login failed, no cookie in server response', {
+			status: 500
+		});
+	}
+
+	return new Response('Login successful', {
+		status: response.status
+	});
+}) satisfies RequestHandler;
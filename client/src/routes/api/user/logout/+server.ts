@@ -1,8 +1,9 @@
 import backendConfig from '$server/backend.config';
 import serverConfig from '$server/server.config';
+import { forwardClientAddress } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const POST = (async ({ fetch, cookies }) => {
+export const POST = (async ({ fetch, cookies, getClientAddress }) => {
 	const sessionId = cookies.get(serverConfig.clientSessionCookieName);
 	if (!sessionId) {
 		return new Response('Cookie not set', {
@@ -14,6 +15,7 @@ export const POST = (async ({ fetch, cookies }) => {
 		method: 'POST',
 		headers: {
 			Cookie: `${backendConfig.serverSessionCookieName}=${sessionId}`,
+			...forwardClientAddress(getClientAddress),
 			...backendConfig.baseHeaders
 		}
 	});
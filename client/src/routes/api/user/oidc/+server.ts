@@ -0,0 +1,12 @@
+import { requestAsProxy } from '$shared/proxy';
+import type { RequestHandler } from './$types';
+
+export const GET = (async ({ fetch, cookies, getClientAddress }) => {
+	return await requestAsProxy({
+		fetch,
+		cookies,
+		getClientAddress,
+		method: 'GET',
+		path: `/user/oidc`
+	});
+}) satisfies RequestHandler;
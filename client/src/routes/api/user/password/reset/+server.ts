@@ -0,0 +1,18 @@
+import backendConfig from '$server/backend.config';
+import { forwardClientAddress } from '$shared/proxy';
+import type { RequestHandler } from './$types';
+
+export const POST = (async ({ fetch, request, getClientAddress }) => {
+	const response = await fetch(backendConfig.baseUrl + '/user/password/reset', {
+		method: 'POST',
+		headers: {
+			...forwardClientAddress(getClientAddress),
+			...backendConfig.baseHeaders
+		},
+		body: await request.text()
+	});
+
+	return new Response(`Password reset ${response.ok ? 'successful' : 'failed'}`, {
+		status: response.status
+	});
+}) satisfies RequestHandler;
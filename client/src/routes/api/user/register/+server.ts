@@ -1,10 +1,14 @@
 import serverConfig from '$server/backend.config';
+import { forwardClientAddress } from '$shared/proxy';
 import type { RequestHandler } from './$types';
 
-export const POST = (async ({ request }) => {
+export const POST = (async ({ request, getClientAddress }) => {
 	const response = await fetch(serverConfig.baseUrl + '/user/register', {
 		method: 'POST',
-		headers: serverConfig.baseHeaders,
+		headers: {
+			...forwardClientAddress(getClientAddress),
+			...serverConfig.baseHeaders
+		},
 		body: await request.text()
 	});
 
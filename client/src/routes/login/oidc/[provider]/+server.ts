@@ -0,0 +1,28 @@
+import backendConfig from '$server/backend.config';
+import { readServerCookie } from '$shared/cookie';
+import { OIDC_LOGIN_COOKIE_NAME } from '$shared/api/oidc';
+import { forwardClientAddress } from '$shared/proxy';
+import { error, redirect } from '@sveltejs/kit';
+import type { RequestHandler } from './$types';
+
+// The server remembers the login in a cookie, it is kept here until the provider redirects back to the callback page
+export const GET = (async ({ fetch, params, cookies, getClientAddress }) => {
+	const response = await fetch(
+		`${backendConfig.baseUrl}/user/oidc/${encodeURIComponent(params.provider)}`,
+		{ redirect: 'manual', headers: forwardClientAddress(getClientAddress) }
+	);
+
+	const authorizeUrl = response.headers.get('Location');
+	const loginState = readServerCookie(response, OIDC_LOGIN_COOKIE_NAME);
+	if (!authorizeUrl || !loginState) {
+		throw error(response.status === 404 ? 404 : 500, 'Login with this provider is not possible');
+	}
+
+	cookies.set(OIDC_LOGIN_COOKIE_NAME, loginState, {
+		path: '/',
+		httpOnly: true,
+		sameSite: 'lax',
+		maxAge: 10 * 60
+	});
+	throw redirect(303, authorizeUrl);
+}) satisfies RequestHandler;
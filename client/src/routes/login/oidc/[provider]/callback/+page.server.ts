@@ -0,0 +1,56 @@
+import backendConfig from '$server/backend.config';
+import { storeServerSessionCookie } from '$shared/cookie';
+import { OIDC_LOGIN_COOKIE_NAME } from '$shared/api/oidc';
+import { forwardClientAddress } from '$shared/proxy';
+import { redirect } from '@sveltejs/kit';
+import type { PageServerLoad } from './$types';
+
+type ServerLoginResponse = {
+	two_factor_required: boolean;
+	pending_token: string | null;
+};
+
+type OidcCallbackData = {
+	// only set if the second login step is needed, a failed login has none either
+	pendingToken: string | null;
+};
+
+// Relays the redirect of the provider to the server together with the remembered login state,
+// the session cookie is kept here the same way as after a password login
+export const load = (async ({
+	fetch,
+	params,
+	url,
+	cookies,
+	getClientAddress
+}): Promise<OidcCallbackData> => {
+	const loginState = cookies.get(OIDC_LOGIN_COOKIE_NAME);
+	cookies.delete(OIDC_LOGIN_COOKIE_NAME, { path: '/' });
+	if (!loginState) {
+		return { pendingToken: null };
+	}
+
+	const provider = encodeURIComponent(params.provider);
+	const response = await fetch(
+		`${backendConfig.baseUrl}/user/oidc/${provider}/callback${url.search}`,
+		{
+			headers: {
+				cookie: `${OIDC_LOGIN_COOKIE_NAME}=${loginState}`,
+				...forwardClientAddress(getClientAddress),
+				...backendConfig.baseHeaders
+			}
+		}
+	);
+	if (!response.ok) {
+		return { pendingToken: null };
+	}
+
+	const loginResponse: ServerLoginResponse = await response.json();
+	if (loginResponse.two_factor_required && loginResponse.pending_token) {
+		return { pendingToken: loginResponse.pending_token };
+	}
+	if (!storeServerSessionCookie(response, cookies)) {
+		return { pendingToken: null };
+	}
+	throw redirect(303, '/');
+}) satisfies PageServerLoad;
@@ -1,26 +1,39 @@
-use std::num::ParseIntError;
-
 use crate::routes::{error::ErrorMsg, AppState};
 use axum::{
     async_trait,
     extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode},
 };
-use axum_extra::extract::cookie::{Key, PrivateCookieJar};
+use axum_extra::extract::cookie::{Cookie, Key, PrivateCookieJar, SameSite};
 use tracing::debug;
+use veryrezsi_core::config::SessionConfig;
+use veryrezsi_core::logic::{api_token_operations, session_operations, user_operations};
 use veryrezsi_core::Id;
 
 /// Defines the name of the cookie used to authenticate users.
 pub const AUTH_COOKIE_NAME: &str = "JSESSIONID";
 
-/// Identifies a user. It is used to hold the decrypted content of the authentication cookie.
+/// Identifies a user. It is resolved either from an API token sent as a bearer token,
+/// or from the session token held by the authentication cookie.
 pub struct AuthenticatedUser {
     pub id: Id,
+    /// The session the request was made with, `None` if an API token was used.
+    pub session_id: Option<Id>,
 }
 
 impl AuthenticatedUser {
-    fn new(id: Id) -> Self {
-        AuthenticatedUser { id }
+    fn new(id: Id, session_id: Option<Id>) -> Self {
+        AuthenticatedUser { id, session_id }
+    }
+
+    /// Returns the session of the user, account management is not allowed with API tokens.
+    pub fn require_session(&self) -> Result<Id, ErrorMsg<()>> {
+        self.session_id.ok_or_else(|| {
+            ErrorMsg::new(
+                StatusCode::FORBIDDEN,
+                "this action is not allowed with an api token",
+            )
+        })
     }
 }
 
@@ -29,26 +42,98 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
     type Rejection = ErrorMsg<()>;
 
     /// Extracts the authenticated user from the request.
+    /// A bearer token has to belong to an API token that is not expired, read-only tokens are only accepted on safe methods.
+    /// Otherwise the session in the cookie has to exist and must not be expired.
     async fn from_request_parts(
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
+        if let Some(token) = bearer_token(&parts.headers) {
+            let Some(api_token) =
+                api_token_operations::authenticate_api_token(&state.conn, token).await?
+            else {
+                debug!("API token in authorization header is invalid or expired");
+                return Err(ErrorMsg::new(StatusCode::UNAUTHORIZED, "invalid api token"));
+            };
+            if !api_token.scope.allows_writes() && !parts.method.is_safe() {
+                return Err(ErrorMsg::new(
+                    StatusCode::FORBIDDEN,
+                    "api token is read-only",
+                ));
+            }
+            return Ok(AuthenticatedUser::new(api_token.user_id, None));
+        }
+
         let jar = PrivateCookieJar::<Key>::from_request_parts(parts, &state.secret_key)
             .await
             .expect("this should have been infalliable");
-        return if let Some(cookie) = jar.get(AUTH_COOKIE_NAME) {
-            let id = cookie.value().parse()?;
-            Ok(AuthenticatedUser::new(id))
-        } else {
+        let Some(cookie) = jar.get(AUTH_COOKIE_NAME) else {
             debug!("No authentication cookie found");
-            Err(ErrorMsg::new(StatusCode::UNAUTHORIZED, "not logged in"))
+            return Err(ErrorMsg::new(StatusCode::UNAUTHORIZED, "not logged in"));
         };
+        match session_operations::authenticate_session(
+            &state.conn,
+            &state.config.session_config,
+            cookie.value(),
+        )
+        .await?
+        {
+            Some(session) => Ok(AuthenticatedUser::new(session.user_id, Some(session.id))),
+            None => {
+                debug!("Session in authentication cookie is expired or revoked");
+                Err(ErrorMsg::new(StatusCode::UNAUTHORIZED, "session expired"))
+            }
+        }
     }
 }
 
-impl From<ParseIntError> for ErrorMsg<()> {
-    fn from(e: ParseIntError) -> Self {
-        debug!("{e}");
-        ErrorMsg::new(StatusCode::BAD_REQUEST, "malformed cookie, non-parsable")
+/// Identifies a user with the administrator role, resolved the same way as `AuthenticatedUser`.
+pub struct AuthenticatedAdmin {
+    pub id: Id,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthenticatedAdmin {
+    type Rejection = ErrorMsg<()>;
+
+    /// Extracts the authenticated user from the request, who has to be an administrator.
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = AuthenticatedUser::from_request_parts(parts, state).await?;
+        if !user_operations::is_admin(&state.conn, user.id).await? {
+            debug!("User {} is not an administrator", user.id);
+            return Err(ErrorMsg::new(
+                StatusCode::FORBIDDEN,
+                "administrator role required",
+            ));
+        }
+        Ok(AuthenticatedAdmin { id: user.id })
     }
 }
+
+/// Returns the token of an `Authorization: Bearer <token>` header, if there is one.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Creates the authentication cookie holding the session token.
+pub fn session_cookie(token: String, config: &SessionConfig) -> Cookie<'static> {
+    Cookie::build(AUTH_COOKIE_NAME, token)
+        .path("/")
+        .http_only(true)
+        .secure(config.secure_cookie)
+        .same_site(SameSite::Strict)
+        .max_age(cookie::time::Duration::hours(config.absolute_timeout_hours))
+        .finish()
+}
+
+/// Creates a cookie that removes the authentication cookie from the client.
+pub fn removal_cookie() -> Cookie<'static> {
+    Cookie::build(AUTH_COOKIE_NAME, "").path("/").finish()
+}
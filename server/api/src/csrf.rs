@@ -0,0 +1,196 @@
+use crate::auth::bearer_token;
+use crate::routes::error::ErrorMsg;
+use axum::{
+    extract::State,
+    http::{
+        header::{HOST, ORIGIN, REFERER},
+        HeaderMap, Method, Request, StatusCode,
+    },
+    middleware::Next,
+    response::Response,
+};
+use tracing::debug;
+use veryrezsi_core::config::AppConfig;
+
+/// Rejects state changing requests sent from other origins, as the authentication cookie is attached to those as well.
+pub async fn verify_origin<B>(
+    State(config): State<AppConfig>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ErrorMsg<()>> {
+    if !is_allowed(&config.client_url, request.method(), request.headers()) {
+        return Err(ErrorMsg::new(
+            StatusCode::FORBIDDEN,
+            "cross-origin request is not allowed",
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+/// The origin of the request is taken from the `Origin` header, or from the `Referer` header if the former is missing.
+/// It has to be the origin of the client or of the server itself.
+/// Requests without both headers are allowed on purpose: the server side of the client proxies the requests of the
+/// users without them, while browsers send at least one of them on every cross-origin request with an unsafe method.
+/// Requests authenticated by an API token are exempt, as they carry no cookie and browsers can not attach the token.
+fn is_allowed(client_url: &str, method: &Method, headers: &HeaderMap) -> bool {
+    if method.is_safe() || bearer_token(headers).is_some() {
+        return true;
+    }
+
+    let request_origin = match headers.get(ORIGIN) {
+        Some(origin) => origin.to_str().ok(),
+        None => match headers.get(REFERER) {
+            Some(referer) => referer.to_str().ok().and_then(origin_of),
+            None => return true,
+        },
+    };
+    match request_origin {
+        Some(origin) if is_trusted_origin(client_url, headers, origin) => true,
+        _ => {
+            debug!("Cross-origin request from {request_origin:?} is rejected");
+            false
+        }
+    }
+}
+
+/// The origin is trusted if it is the origin of the client, or the server the request was sent to.
+fn is_trusted_origin(client_url: &str, headers: &HeaderMap, origin: &str) -> bool {
+    if let Some(client_origin) = origin_of(client_url) {
+        if client_origin.eq_ignore_ascii_case(origin) {
+            return true;
+        }
+    }
+    let server_host = headers.get(HOST).and_then(|host| host.to_str().ok());
+    let origin_host = origin.split_once("://").map(|(_, host)| host);
+    match (origin_host, server_host) {
+        (Some(origin_host), Some(server_host)) => origin_host.eq_ignore_ascii_case(server_host),
+        _ => false,
+    }
+}
+
+/// Returns the scheme, host and port part of an absolute url.
+fn origin_of(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    let host_length = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    if host_length == 0 {
+        return None;
+    }
+    Some(&url[..scheme.len() + "://".len() + host_length])
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use axum::http::{
+        header::{AUTHORIZATION, HOST, ORIGIN, REFERER},
+        HeaderMap, HeaderValue, Method,
+    };
+
+    use super::{is_allowed, is_trusted_origin, origin_of};
+
+    const CLIENT_URL: &str = "https://veryrezsi.example";
+    const SERVER_HOST: &str = "api.veryrezsi.example";
+
+    fn headers(entries: &[(axum::http::HeaderName, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOST, HeaderValue::from_static(SERVER_HOST));
+        for (name, value) in entries {
+            headers.insert(name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn origin_of_keeps_scheme_host_and_port() {
+        check!(
+            origin_of("https://veryrezsi.example:8443/expenses?page=2")
+                == Some("https://veryrezsi.example:8443")
+        );
+        check!(origin_of("http://localhost:3000") == Some("http://localhost:3000"));
+        check!(origin_of("https://veryrezsi.example#top") == Some("https://veryrezsi.example"));
+        check!(origin_of("https:///expenses").is_none());
+        check!(origin_of("/expenses").is_none());
+    }
+
+    #[test]
+    fn client_and_server_origins_are_trusted() {
+        let headers = headers(&[]);
+        check!(is_trusted_origin(
+            CLIENT_URL,
+            &headers,
+            "https://veryrezsi.example"
+        ));
+        check!(is_trusted_origin(
+            CLIENT_URL,
+            &headers,
+            "HTTPS://VeryRezsi.example"
+        ));
+        check!(is_trusted_origin(
+            CLIENT_URL,
+            &headers,
+            "https://api.veryrezsi.example"
+        ));
+        check!(!is_trusted_origin(
+            CLIENT_URL,
+            &headers,
+            "https://evil.example"
+        ));
+        check!(!is_trusted_origin(
+            CLIENT_URL,
+            &headers,
+            "https://veryrezsi.example:8443"
+        ));
+        check!(!is_trusted_origin(
+            CLIENT_URL,
+            &HeaderMap::new(),
+            "https://api.veryrezsi.example"
+        ));
+    }
+
+    #[test]
+    fn trusted_origin_is_allowed() {
+        let headers = headers(&[(ORIGIN, "https://veryrezsi.example")]);
+        check!(is_allowed(CLIENT_URL, &Method::POST, &headers));
+    }
+
+    #[test]
+    fn untrusted_origin_is_rejected() {
+        let headers = headers(&[(ORIGIN, "https://evil.example")]);
+        check!(!is_allowed(CLIENT_URL, &Method::POST, &headers));
+        check!(!is_allowed(CLIENT_URL, &Method::DELETE, &headers));
+        check!(is_allowed(CLIENT_URL, &Method::GET, &headers));
+    }
+
+    #[test]
+    fn origin_header_is_preferred_to_the_referer() {
+        let headers = headers(&[
+            (ORIGIN, "https://evil.example"),
+            (REFERER, "https://veryrezsi.example/expenses"),
+        ]);
+        check!(!is_allowed(CLIENT_URL, &Method::PUT, &headers));
+    }
+
+    #[test]
+    fn referer_is_checked_without_origin() {
+        let trusted = headers(&[(REFERER, "https://veryrezsi.example/expenses")]);
+        let untrusted = headers(&[(REFERER, "https://evil.example/form")]);
+        let invalid = headers(&[(REFERER, "not an url")]);
+        check!(is_allowed(CLIENT_URL, &Method::POST, &trusted));
+        check!(!is_allowed(CLIENT_URL, &Method::POST, &untrusted));
+        check!(!is_allowed(CLIENT_URL, &Method::POST, &invalid));
+    }
+
+    #[test]
+    fn request_without_origin_and_referer_is_allowed() {
+        check!(is_allowed(CLIENT_URL, &Method::POST, &headers(&[])));
+    }
+
+    #[test]
+    fn api_token_requests_are_exempt() {
+        let headers = headers(&[
+            (ORIGIN, "https://evil.example"),
+            (AUTHORIZATION, "Bearer token"),
+        ]);
+        check!(is_allowed(CLIENT_URL, &Method::POST, &headers));
+    }
+}
@@ -1,12 +1,17 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use axum::{Router, Server};
 use axum_extra::extract::cookie::Key;
 use tokio::signal;
-use tracing::info;
-use veryrezsi_core::config::AppConfig;
+use tracing::{error, info, warn};
+use veryrezsi_core::config::{AccountConfig, AppConfig, TrashConfig};
+use veryrezsi_core::logic::{trash_operations, user_operations};
+use veryrezsi_core::storage::Storage;
+use veryrezsi_core::DatabaseConnection;
 
 mod auth;
+mod csrf;
 pub mod routes;
 
 #[tokio::main]
@@ -14,7 +19,7 @@ pub async fn start() {
     let (server_address, router) = init().await;
     info!("Server is listening on {}...", server_address);
     let _ = Server::bind(&server_address)
-        .serve(router.into_make_service())
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal())
         .await;
     info!("Shutting down...");
@@ -35,6 +40,20 @@ async fn init() -> (SocketAddr, Router) {
     let conn = veryrezsi_core::database::init(&config).await;
     info!("Successfully established database connection");
 
+    if let Some(email) = &config.account_config.initial_admin_email {
+        grant_initial_admin_role(&conn, email).await;
+    }
+
+    info!("Starting the sweep of stale unactivated accounts...");
+    spawn_unactivated_account_sweep(conn.clone(), config.account_config.clone());
+
+    info!("Initializing attachment storage...");
+    let storage = veryrezsi_core::storage::init(&config.attachment_config.storage);
+    info!("Successfully initialized attachment storage");
+
+    info!("Starting the purge of expired trash items...");
+    spawn_trash_purge(conn.clone(), storage.clone(), config.trash_config.clone());
+
     info!(
         "Initializing mail transport with with relay: {}",
         &config.mail_config.smtp_address
@@ -48,12 +67,52 @@ async fn init() -> (SocketAddr, Router) {
         conn,
         Key::from(config.cookie_key.as_bytes()),
         mail_transport,
+        storage,
     );
     info!("Successfully created api routes with extensions");
 
     (config.server_address, router)
 }
 
+/// Makes the user with the configured email an administrator, so there is someone who can manage the others.
+async fn grant_initial_admin_role(conn: &DatabaseConnection, email: &str) {
+    match user_operations::grant_admin_role_by_email(conn, email).await {
+        Ok(true) => info!("User {email} has the administrator role"),
+        Ok(false) => warn!("Initial administrator {email} is not registered yet"),
+        Err(e) => error!("Initial administrator {email} could not be set up: {e}"),
+    }
+}
+
+/// Periodically deletes the accounts which were not activated in time, so their email address can be registered again.
+fn spawn_unactivated_account_sweep(conn: DatabaseConnection, config: AccountConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.unactivated_account_sweep_interval_minutes);
+        loop {
+            interval.tick().await;
+            match user_operations::delete_stale_unactivated_users(&conn, &config).await {
+                Ok(0) => {}
+                Ok(deleted) => info!("Deleted {deleted} stale unactivated accounts"),
+                Err(e) => error!("Stale unactivated accounts could not be deleted: {e}"),
+            }
+        }
+    });
+}
+
+/// Periodically deletes the expenses and transactions which have been in the trash for longer than the retention period.
+fn spawn_trash_purge(conn: DatabaseConnection, storage: Arc<dyn Storage>, config: TrashConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.purge_interval_minutes);
+        loop {
+            interval.tick().await;
+            match trash_operations::purge_trash(&conn, storage.as_ref(), &config).await {
+                Ok(0) => {}
+                Ok(purged) => info!("Purged {purged} items from the trash"),
+                Err(e) => error!("The trash could not be purged: {e}"),
+            }
+        }
+    });
+}
+
 fn print_logo() {
     println!(
         r#"
@@ -1,19 +1,30 @@
+use crate::csrf;
 use axum::{
-    routing::{delete, get, post},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post, put},
     Router,
 };
 use axum_extra::extract::cookie::Key;
 use axum_macros::FromRef;
 use std::sync::Arc;
+use veryrezsi_core::storage::Storage;
 use veryrezsi_core::DatabaseConnection;
 use veryrezsi_core::{config::AppConfig, email::MailTransport};
 
+pub mod admin;
+pub mod attachments;
+pub mod categories;
 pub mod common;
 pub mod currencies;
 pub mod error;
 pub mod expenses;
+pub mod households;
+pub mod oidc;
+pub mod participants;
 pub mod recurrences;
 pub mod transactions;
+pub mod trash;
 pub mod users;
 
 #[derive(Clone, FromRef)]
@@ -22,49 +33,211 @@ pub struct AppState {
     pub conn: DatabaseConnection,
     pub secret_key: Key,
     pub mail_transport: Arc<MailTransport>,
+    pub storage: Arc<dyn Storage>,
 }
 
+/// Room for the multipart boundaries and part headers around the uploaded file.
+const MULTIPART_OVERHEAD_BYTES: usize = 16 * 1024;
+
 /// Initializes the router with the extension layers and the route handlers.
+/// Every route is protected from cross-site request forgery by checking the origin of state changing requests.
 pub fn init(
     config: AppConfig,
     conn: DatabaseConnection,
     secret_key: Key,
     mail_transport: MailTransport,
+    storage: Arc<dyn Storage>,
 ) -> Router {
+    let csrf_layer = middleware::from_fn_with_state(config.clone(), csrf::verify_origin);
+    let upload_limit = DefaultBodyLimit::max(
+        config.attachment_config.max_file_size_bytes as usize + MULTIPART_OVERHEAD_BYTES,
+    );
+
     let user_api = Router::new()
         .route("/auth", post(users::login))
-        .route("/me", get(users::me))
+        .route("/auth/2fa", post(users::login_two_factor))
+        .route("/oidc", get(oidc::get_providers))
+        .route("/oidc/:provider", get(oidc::login))
+        .route("/oidc/:provider/callback", get(oidc::callback))
+        .route(
+            "/me",
+            get(users::me)
+                .put(users::update_me)
+                .delete(users::delete_me),
+        )
+        .route("/export", get(users::export_data))
         .route("/logout", post(users::logout))
         .route("/register", post(users::register))
-        .route("/activate/:token", post(users::activate_account));
+        .route("/activate/resend", post(users::resend_activation))
+        .route("/activate/:token", post(users::activate_account))
+        .route("/password/forgot", post(users::forgot_password))
+        .route("/password/reset", post(users::reset_password))
+        .route("/password", put(users::change_password))
+        .route("/email", put(users::change_email))
+        .route("/email/confirm/:token", post(users::confirm_email_change))
+        .route("/2fa/enroll", post(users::enroll_two_factor))
+        .route("/2fa/confirm", post(users::confirm_two_factor))
+        .route("/2fa/disable", post(users::disable_two_factor))
+        .route("/sessions", get(users::get_sessions))
+        .route("/sessions/:session_id", delete(users::delete_session))
+        .route("/security-events", get(users::get_security_events))
+        .route(
+            "/tokens",
+            get(users::get_api_tokens).post(users::create_api_token),
+        )
+        .route("/tokens/:api_token_id", delete(users::delete_api_token));
 
     let expense_api = Router::new()
         .route("/:user_id", get(expenses::get_expenses))
+        .route(
+            "/item/:expense_id",
+            put(expenses::update_expense)
+                .patch(expenses::patch_expense)
+                .delete(expenses::delete_expense),
+        )
+        .route("/item/:expense_id/restore", post(expenses::restore_expense))
+        .route("/item/:expense_id/cancel", post(expenses::cancel_expense))
+        .route("/item/:expense_id/pause", post(expenses::pause_expense))
+        .route(
+            "/item/:expense_id/pause/:pause_id",
+            delete(expenses::delete_expense_pause),
+        )
+        .route("/item/:expense_id/price", post(expenses::add_price_change))
+        .route(
+            "/item/:expense_id/price/:price_id",
+            delete(expenses::delete_price_change),
+        )
+        .route(
+            "/item/:expense_id/split",
+            put(expenses::set_expense_split).delete(expenses::delete_expense_split),
+        )
+        .route(
+            "/item/:expense_id/attachment",
+            get(attachments::get_expense_attachments)
+                .post(attachments::upload_expense_attachment)
+                .layer(upload_limit.clone()),
+        )
+        .route("/balance", get(expenses::get_balance))
         .route("/", post(expenses::create_expense))
         .route("/predefined", get(expenses::get_predefined_expenses))
-        .route("/predefined", post(expenses::create_predefined_expense));
+        .route("/predefined", post(expenses::create_predefined_expense))
+        .route(
+            "/predefined/:predefined_expense_id",
+            put(expenses::update_predefined_expense).delete(expenses::delete_predefined_expense),
+        );
 
     let transaction_api = Router::new()
         .route("/", post(transactions::create_transaction))
-        .route("/:transaction_id", delete(transactions::delete_transaction));
+        .route("/:transaction_id", delete(transactions::delete_transaction))
+        .route(
+            "/:transaction_id/restore",
+            post(transactions::restore_transaction),
+        )
+        .route(
+            "/:transaction_id/attachment",
+            get(attachments::get_transaction_attachments)
+                .post(attachments::upload_transaction_attachment)
+                .layer(upload_limit),
+        );
+
+    let attachment_api = Router::new().route(
+        "/:attachment_id",
+        get(attachments::download_attachment).delete(attachments::delete_attachment),
+    );
+
+    let trash_api = Router::new().route("/", get(trash::get_trash));
+
+    let category_api = Router::new()
+        .route(
+            "/",
+            get(categories::get_categories).post(categories::create_category),
+        )
+        .route(
+            "/:category_id",
+            put(categories::update_category).delete(categories::delete_category),
+        );
+
+    let household_api = Router::new()
+        .route(
+            "/",
+            get(households::get_households).post(households::create_household),
+        )
+        .route(
+            "/join/:token",
+            post(households::accept_household_invitation),
+        )
+        .route("/:household_id", delete(households::delete_household))
+        .route(
+            "/:household_id/invitation",
+            post(households::invite_to_household),
+        )
+        .route(
+            "/:household_id/member/:user_id",
+            delete(households::remove_household_member),
+        );
+
+    let participant_api = Router::new()
+        .route(
+            "/",
+            get(participants::get_participants).post(participants::create_participant),
+        )
+        .route("/contributions", get(participants::get_contributions))
+        .route(
+            "/:participant_id",
+            put(participants::update_participant).delete(participants::delete_participant),
+        )
+        .route(
+            "/:participant_id/merge/:target_id",
+            post(participants::merge_participants),
+        );
+
+    let currency_api = Router::new()
+        .route(
+            "/",
+            get(currencies::get_currencies).post(currencies::create_currency),
+        )
+        .route(
+            "/:currency_id",
+            put(currencies::update_currency).delete(currencies::delete_currency),
+        );
 
-    let currency_api = Router::new().route("/", get(currencies::get_currencies));
+    let recurrence_api = Router::new()
+        .route(
+            "/",
+            get(recurrences::get_recurrences).post(recurrences::create_recurrence),
+        )
+        .route(
+            "/:recurrence_id",
+            put(recurrences::update_recurrence).delete(recurrences::delete_recurrence),
+        );
 
-    let recurrence_api = Router::new().route("/", get(recurrences::get_recurrences));
+    let admin_api = Router::new()
+        .route("/user", get(admin::get_users))
+        .route("/user/:user_id", delete(admin::delete_user))
+        .route("/user/:user_id/activate", post(admin::activate_user))
+        .route("/user/:user_id/deactivate", post(admin::deactivate_user));
 
     let api = Router::new()
         .route("/", get(|| async {}))
         .nest("/user", user_api)
         .nest("/expense", expense_api)
         .nest("/transaction", transaction_api)
+        .nest("/attachment", attachment_api)
+        .nest("/trash", trash_api)
+        .nest("/category", category_api)
+        .nest("/household", household_api)
+        .nest("/participant", participant_api)
         .nest("/currency", currency_api)
-        .nest("/recurrence", recurrence_api);
+        .nest("/recurrence", recurrence_api)
+        .nest("/admin", admin_api)
+        .layer(csrf_layer);
 
     let state = AppState {
         config,
         conn,
         secret_key,
         mail_transport: Arc::new(mail_transport),
+        storage,
     };
 
     Router::new().nest("/api", api).with_state(state)
@@ -0,0 +1,54 @@
+use super::error::ErrorMsg;
+use crate::auth;
+
+use axum::extract::{Path, State};
+use axum::Json;
+use std::sync::Arc;
+use veryrezsi_core::dto::users::UserAccountResponse;
+use veryrezsi_core::logic::user_operations;
+use veryrezsi_core::storage::Storage;
+use veryrezsi_core::{DatabaseConnection, Id};
+
+pub async fn get_users(
+    _: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<UserAccountResponse>>, ErrorMsg<()>> {
+    match user_operations::find_users(conn).await {
+        Ok(users) => Ok(Json(users)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn activate_user(
+    admin: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    Path(user_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::set_user_activated(conn, admin.id, user_id, true).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn deactivate_user(
+    admin: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    Path(user_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::set_user_activated(conn, admin.id, user_id, false).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_user(
+    admin: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    State(storage): State<Arc<dyn Storage>>,
+    Path(user_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::delete_user(conn, storage.as_ref(), admin.id, user_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
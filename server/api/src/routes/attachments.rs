@@ -0,0 +1,170 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+        StatusCode,
+    },
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use veryrezsi_core::config::AppConfig;
+use veryrezsi_core::dto::attachments::{AttachmentResponse, NewAttachmentRequest};
+use veryrezsi_core::logic::attachment_operations::{self, AttachmentParent};
+use veryrezsi_core::storage::Storage;
+use veryrezsi_core::{DatabaseConnection, Id};
+
+use crate::auth;
+
+use super::error::ErrorMsg;
+
+/// The name of the multipart field carrying the uploaded file.
+const FILE_FIELD_NAME: &str = "file";
+
+pub async fn upload_expense_attachment(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(storage): State<Arc<dyn Storage>>,
+    State(ref config): State<AppConfig>,
+    Path(expense_id): Path<Id>,
+    multipart: Multipart,
+) -> Result<Json<AttachmentResponse>, ErrorMsg<()>> {
+    let parent = AttachmentParent::Expense(expense_id);
+    upload_attachment(conn, storage, config, user.id, parent, multipart).await
+}
+
+pub async fn upload_transaction_attachment(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(storage): State<Arc<dyn Storage>>,
+    State(ref config): State<AppConfig>,
+    Path(transaction_id): Path<Id>,
+    multipart: Multipart,
+) -> Result<Json<AttachmentResponse>, ErrorMsg<()>> {
+    let parent = AttachmentParent::Transaction(transaction_id);
+    upload_attachment(conn, storage, config, user.id, parent, multipart).await
+}
+
+pub async fn get_expense_attachments(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+) -> Result<Json<Vec<AttachmentResponse>>, ErrorMsg<()>> {
+    let parent = AttachmentParent::Expense(expense_id);
+    match attachment_operations::find_attachments(conn, user.id, parent).await {
+        Ok(attachments) => Ok(Json(attachments)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn get_transaction_attachments(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(transaction_id): Path<Id>,
+) -> Result<Json<Vec<AttachmentResponse>>, ErrorMsg<()>> {
+    let parent = AttachmentParent::Transaction(transaction_id);
+    match attachment_operations::find_attachments(conn, user.id, parent).await {
+        Ok(attachments) => Ok(Json(attachments)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Serves the file as a download, the browser must not guess a different type from the content.
+pub async fn download_attachment(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(storage): State<Arc<dyn Storage>>,
+    Path(attachment_id): Path<Id>,
+) -> Result<impl IntoResponse, ErrorMsg<()>> {
+    match attachment_operations::download_attachment(conn, storage.as_ref(), user.id, attachment_id)
+        .await
+    {
+        Ok(content) => Ok((
+            [
+                (CONTENT_TYPE, content.content_type),
+                (CONTENT_DISPOSITION, content_disposition(&content.file_name)),
+                (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            ],
+            content.data,
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_attachment(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(storage): State<Arc<dyn Storage>>,
+    Path(attachment_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match attachment_operations::delete_attachment(conn, storage.as_ref(), user.id, attachment_id)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn upload_attachment(
+    conn: &DatabaseConnection,
+    storage: Arc<dyn Storage>,
+    config: &AppConfig,
+    user_id: Id,
+    parent: AttachmentParent,
+    multipart: Multipart,
+) -> Result<Json<AttachmentResponse>, ErrorMsg<()>> {
+    let req = read_file_field(multipart).await?;
+    match attachment_operations::upload_attachment(
+        conn,
+        storage.as_ref(),
+        &config.attachment_config,
+        user_id,
+        parent,
+        req,
+    )
+    .await
+    {
+        Ok(attachment) => Ok(Json(attachment)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Reads the file from the multipart body, the other fields are ignored.
+async fn read_file_field(mut multipart: Multipart) -> Result<NewAttachmentRequest, ErrorMsg<()>> {
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() != Some(FILE_FIELD_NAME) {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or_default().to_string();
+        let data = field.bytes().await?;
+        return Ok(NewAttachmentRequest {
+            file_name,
+            data: data.to_vec(),
+        });
+    }
+    Err(ErrorMsg::new(
+        StatusCode::BAD_REQUEST,
+        format!("multipart field '{FILE_FIELD_NAME}' is missing"),
+    ))
+}
+
+/// Keeps the original name of the file on download, with a plain fallback for clients not supporting encoded names.
+fn content_disposition(file_name: &str) -> String {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+    let encoded: String = file_name
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect();
+    format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
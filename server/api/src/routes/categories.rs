@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use veryrezsi_core::dto::categories::{CategoryRequest, CategoryResponse};
+use veryrezsi_core::logic::category_operations;
+use veryrezsi_core::{DatabaseConnection, Id};
+
+use crate::auth;
+
+use super::common::ValidatedJson;
+use super::error::ErrorMsg;
+
+pub async fn get_categories(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<CategoryResponse>>, ErrorMsg<()>> {
+    match category_operations::find_categories(conn, user.id).await {
+        Ok(categories) => Ok(Json(categories)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn create_category(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    ValidatedJson(req): ValidatedJson<CategoryRequest>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match category_operations::create_category(conn, user.id, req).await {
+        Ok(category_id) => Ok(Json(category_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn update_category(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(category_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<CategoryRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match category_operations::update_category(conn, user.id, category_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_category(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(category_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match category_operations::delete_category(conn, user.id, category_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -1,7 +1,23 @@
-use super::error::ErrorMsg;
-use axum::{async_trait, body::HttpBody, extract::FromRequest, http::Request, BoxError, Json};
+use super::{error::ErrorMsg, AppState};
+use axum::{
+    async_trait,
+    body::HttpBody,
+    extract::{ConnectInfo, FromRequest, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts, HeaderMap, Request},
+    BoxError, Json,
+};
 use serde::de::DeserializeOwned;
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
 use validator::{Validate, ValidationErrors};
+use veryrezsi_core::dto::sessions::ClientInfo;
+
+/// User agents are truncated to this length before they are stored.
+const MAX_USER_AGENT_LENGTH: usize = 512;
+/// The header in which the trusted proxies pass on the address of the client.
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
 
 /// A generic structure that represents a requests body, that is validated according to its defined validation rules.
 /// Should be placed last in a function parameter signature because is consumes the request body.
@@ -27,3 +43,141 @@ where
         Ok(ValidatedJson(value))
     }
 }
+
+/// Holds the ip address and user agent of the client that sent the request, if they are known.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractClientInfo(pub ClientInfo);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ExtractClientInfo {
+    type Rejection = Infallible;
+
+    /// Extracts the client information from the connection info and the headers, this never fails.
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let peer_address = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(address)| address.ip());
+        let ip_address = peer_address
+            .map(|peer_address| {
+                resolve_client_ip(peer_address, &parts.headers, &state.config.trusted_proxies)
+            })
+            .map(|address| address.to_string());
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|user_agent| user_agent.to_str().ok())
+            .map(|user_agent| user_agent.chars().take(MAX_USER_AGENT_LENGTH).collect());
+        Ok(ExtractClientInfo(ClientInfo {
+            ip_address,
+            user_agent,
+        }))
+    }
+}
+
+/// Finds the address of the client that sent the request.
+/// Only requests coming from a trusted proxy are attributed to an address of the X-Forwarded-For header.
+/// The header is read from the right, skipping the further trusted proxies,
+/// because the addresses on its left could have been made up by the client itself.
+fn resolve_client_ip(
+    peer_address: IpAddr,
+    headers: &HeaderMap,
+    trusted_proxies: &[IpAddr],
+) -> IpAddr {
+    let forwarded_addresses: Vec<&str> = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect();
+
+    let mut client_address = peer_address;
+    for forwarded_address in forwarded_addresses.into_iter().rev() {
+        if !trusted_proxies.contains(&client_address) {
+            break;
+        }
+        let Ok(forwarded_address) = forwarded_address.trim().parse() else {
+            break;
+        };
+        client_address = forwarded_address;
+    }
+    client_address
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use axum::http::{HeaderMap, HeaderValue};
+    use std::net::IpAddr;
+
+    use super::{resolve_client_ip, X_FORWARDED_FOR};
+
+    const PROXY: &str = "10.0.0.2";
+    const CLIENT: &str = "203.0.113.7";
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn forwarded_for(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(X_FORWARDED_FOR, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn forwarded_address_is_used_from_trusted_proxy() {
+        let headers = forwarded_for(&[CLIENT]);
+
+        check!(resolve_client_ip(ip(PROXY), &headers, &[ip(PROXY)]) == ip(CLIENT));
+    }
+
+    #[test]
+    fn forwarded_address_is_ignored_from_untrusted_peer() {
+        let headers = forwarded_for(&[CLIENT]);
+
+        check!(resolve_client_ip(ip(PROXY), &headers, &[]) == ip(PROXY));
+        check!(resolve_client_ip(ip(CLIENT), &headers, &[ip(PROXY)]) == ip(CLIENT));
+    }
+
+    #[test]
+    fn peer_address_is_used_without_forwarded_header() {
+        check!(resolve_client_ip(ip(PROXY), &HeaderMap::new(), &[ip(PROXY)]) == ip(PROXY));
+    }
+
+    #[test]
+    fn addresses_made_up_by_the_client_are_skipped() {
+        let headers = forwarded_for(&["198.51.100.1", CLIENT]);
+
+        check!(resolve_client_ip(ip(PROXY), &headers, &[ip(PROXY)]) == ip(CLIENT));
+    }
+
+    #[test]
+    fn chained_trusted_proxies_are_skipped() {
+        let trusted_proxies = [ip(PROXY), ip("10.0.0.3")];
+        let joined = forwarded_for(&[&format!("{CLIENT}, 10.0.0.3")]);
+        let separate = forwarded_for(&[CLIENT, "10.0.0.3"]);
+
+        check!(resolve_client_ip(ip(PROXY), &joined, &trusted_proxies) == ip(CLIENT));
+        check!(resolve_client_ip(ip(PROXY), &separate, &trusted_proxies) == ip(CLIENT));
+    }
+
+    #[test]
+    fn invalid_forwarded_address_stops_the_resolution() {
+        let headers = forwarded_for(&[&format!("{CLIENT}, not-an-address")]);
+
+        check!(resolve_client_ip(ip(PROXY), &headers, &[ip(PROXY)]) == ip(PROXY));
+    }
+
+    #[test]
+    fn ipv6_forwarded_address_is_accepted() {
+        let headers = forwarded_for(&["2001:db8::1"]);
+
+        check!(resolve_client_ip(ip("::1"), &headers, &[ip("::1")]) == ip("2001:db8::1"));
+    }
+}
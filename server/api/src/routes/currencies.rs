@@ -1,9 +1,14 @@
 use crate::auth;
 
+use super::common::ValidatedJson;
 use super::error::ErrorMsg;
-use axum::{extract::State, Json};
-use veryrezsi_core::DatabaseConnection;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use veryrezsi_core::dto::currencies::CurrencyRequest;
 use veryrezsi_core::{dto::currencies::CurrencyResponse, logic::currency_operations};
+use veryrezsi_core::{DatabaseConnection, Id};
 
 pub async fn get_currencies(
     _: auth::AuthenticatedUser,
@@ -14,3 +19,37 @@ pub async fn get_currencies(
         Err(e) => Err(e.into()),
     }
 }
+
+pub async fn create_currency(
+    _: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    ValidatedJson(req): ValidatedJson<CurrencyRequest>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match currency_operations::create_currency(conn, req).await {
+        Ok(currency_id) => Ok(Json(currency_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn update_currency(
+    _: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    Path(currency_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<CurrencyRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match currency_operations::update_currency(conn, currency_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_currency(
+    _: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    Path(currency_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match currency_operations::delete_currency(conn, currency_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
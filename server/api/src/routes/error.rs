@@ -1,5 +1,5 @@
 use axum::{
-    extract::rejection::JsonRejection,
+    extract::{multipart::MultipartError, rejection::JsonRejection},
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
@@ -8,12 +8,49 @@ use migration::DbErr;
 use serde::Serialize;
 use validator::ValidationErrors;
 use veryrezsi_core::logic::{
+    api_token_operations::errors::RevokeApiTokenError,
+    attachment_operations::errors::{
+        DeleteAttachmentError, DownloadAttachmentError, FindAttachmentsError, UploadAttachmentError,
+    },
+    balance_operations::errors::FindBalanceError,
+    category_operations::errors::{CreateCategoryError, DeleteCategoryError, UpdateCategoryError},
+    currency_operations::errors::{CreateCurrencyError, DeleteCurrencyError, UpdateCurrencyError},
     expense_operations::errors::{
-        CreateExpenseError, CreatePredefinedExpenseError, FindExpensesWithTransactionsByUserIdError,
+        AddPriceChangeError, CancelExpenseError, CreateExpenseError, CreatePredefinedExpenseError,
+        DeleteExpenseError, DeleteExpensePauseError, DeleteExpenseSplitError,
+        DeletePredefinedExpenseError, DeletePriceChangeError,
+        FindExpensesWithTransactionsByUserIdError, PauseExpenseError, RestoreExpenseError,
+        SetExpenseSplitError, UpdateExpenseError, UpdatePredefinedExpenseError,
+        ValidatePredefinedExpenseVisibilityError,
+    },
+    household_operations::errors::{
+        AcceptHouseholdInvitationError, DeleteHouseholdError, InviteToHouseholdError,
+        RemoveHouseholdMemberError,
+    },
+    oidc_operations::errors::OidcLoginError,
+    participant_operations::errors::{
+        CreateParticipantError, DeleteParticipantError, FindContributionsError,
+        MergeParticipantsError, UpdateParticipantError,
+    },
+    recurrence_operations::errors::{
+        CreateRecurrenceError, DeleteRecurrenceError, UpdateRecurrenceError,
+    },
+    session_operations::errors::RevokeSessionByIdError,
+    transaction_operations::errors::{
+        CreateTransactionError, DeleteTransactionByIdError, RestoreTransactionError,
+    },
+    two_factor_operations::errors::{
+        CompleteTwoFactorLoginError, ConfirmTwoFactorError, DisableTwoFactorError,
+        EnrollTwoFactorError,
+    },
+    user_operations::errors::{
+        ActivateAccountError, ChangeEmailError, ChangePasswordError, ConfirmEmailChangeError,
+        DeleteAccountError, DeleteUserError, ExportUserDataError, ResendActivationError,
+        ResetPasswordError, SaveUserError, SetUserActivatedError, UpdateProfileError,
+        VerifyLoginError,
     },
-    transaction_operations::errors::{CreateTransactionError, DeleteTransactionByIdError},
-    user_operations::errors::{ActivateAccountError, SaveUserError, VerifyLoginError},
 };
+use veryrezsi_core::oidc::errors::OidcError;
 
 /// A struct that can be returned from route handlers on error.
 /// It has an optional generic details parameter, which is used to return more detailed information about the error (e.g. validation errors).
@@ -74,6 +111,12 @@ impl<D: Serialize> From<JsonRejection> for ErrorMsg<D> {
     }
 }
 
+impl<D: Serialize> From<MultipartError> for ErrorMsg<D> {
+    fn from(e: MultipartError) -> Self {
+        Self::new(e.status(), e.body_text())
+    }
+}
+
 impl From<ValidationErrors> for ErrorMsg<ValidationErrors> {
     fn from(e: ValidationErrors) -> Self {
         Self::new(StatusCode::BAD_REQUEST, "validation of inputs failed").details(e)
@@ -95,6 +138,9 @@ impl<D: Serialize> From<VerifyLoginError> for ErrorMsg<D> {
             VerifyLoginError::IncorrectCredentials => {
                 Self::new(StatusCode::UNAUTHORIZED, e.to_string())
             }
+            VerifyLoginError::TooManyAttempts(_) | VerifyLoginError::TemporarilyLocked(_) => {
+                Self::new(StatusCode::TOO_MANY_REQUESTS, e.to_string())
+            }
             VerifyLoginError::DatabaseError(db_error) => db_error.into(),
         }
     }
@@ -104,6 +150,9 @@ impl<D: Serialize> From<SaveUserError> for ErrorMsg<D> {
     fn from(e: SaveUserError) -> Self {
         match e {
             SaveUserError::UserAlreadyExists => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            SaveUserError::TooManyRegistrations => {
+                Self::new(StatusCode::TOO_MANY_REQUESTS, e.to_string())
+            }
             SaveUserError::PasswordCannotBeHashed(msg) => {
                 Self::new(StatusCode::INTERNAL_SERVER_ERROR, msg)
             }
@@ -124,12 +173,232 @@ impl<D: Serialize> From<ActivateAccountError> for ErrorMsg<D> {
     }
 }
 
+impl<D: Serialize> From<ExportUserDataError> for ErrorMsg<D> {
+    fn from(e: ExportUserDataError) -> Self {
+        match e {
+            ExportUserDataError::UserNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            ExportUserDataError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<OidcError> for ErrorMsg<D> {
+    fn from(e: OidcError) -> Self {
+        match e {
+            OidcError::Configuration(_) | OidcError::Discovery(_) | OidcError::TokenExchange(_) => {
+                Self::new(StatusCode::BAD_GATEWAY, e.to_string())
+            }
+            OidcError::InvalidState | OidcError::InvalidIdToken(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            OidcError::EmailNotVerified => Self::new(StatusCode::FORBIDDEN, e.to_string()),
+        }
+    }
+}
+
+impl<D: Serialize> From<OidcLoginError> for ErrorMsg<D> {
+    fn from(e: OidcLoginError) -> Self {
+        match e {
+            OidcLoginError::AccountDeactivated => Self::new(StatusCode::FORBIDDEN, e.to_string()),
+            OidcLoginError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<UpdateProfileError> for ErrorMsg<D> {
+    fn from(e: UpdateProfileError) -> Self {
+        match e {
+            UpdateProfileError::InvalidCurrency => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            UpdateProfileError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteAccountError> for ErrorMsg<D> {
+    fn from(e: DeleteAccountError) -> Self {
+        match e {
+            DeleteAccountError::UserNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            DeleteAccountError::IncorrectPassword => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteAccountError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<SetUserActivatedError> for ErrorMsg<D> {
+    fn from(e: SetUserActivatedError) -> Self {
+        match e {
+            SetUserActivatedError::UserNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            SetUserActivatedError::OwnAccount => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            SetUserActivatedError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteUserError> for ErrorMsg<D> {
+    fn from(e: DeleteUserError) -> Self {
+        match e {
+            DeleteUserError::UserNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            DeleteUserError::OwnAccount => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            DeleteUserError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<ResendActivationError> for ErrorMsg<D> {
+    fn from(e: ResendActivationError) -> Self {
+        match e {
+            ResendActivationError::EmailCannotBeSent(reason) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, reason)
+            }
+            ResendActivationError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<ResetPasswordError> for ErrorMsg<D> {
+    fn from(e: ResetPasswordError) -> Self {
+        match e {
+            ResetPasswordError::InvalidToken => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            ResetPasswordError::PasswordCannotBeHashed(msg) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, msg)
+            }
+            ResetPasswordError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<ChangePasswordError> for ErrorMsg<D> {
+    fn from(e: ChangePasswordError) -> Self {
+        match e {
+            ChangePasswordError::UserNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            ChangePasswordError::IncorrectPassword => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            ChangePasswordError::PasswordCannotBeHashed(msg) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, msg)
+            }
+            ChangePasswordError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<ChangeEmailError> for ErrorMsg<D> {
+    fn from(e: ChangeEmailError) -> Self {
+        match e {
+            ChangeEmailError::UserNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            ChangeEmailError::IncorrectPassword => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            ChangeEmailError::EmailAlreadyInUse => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            ChangeEmailError::EmailCannotBeSent(reason) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, reason)
+            }
+            ChangeEmailError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<ConfirmEmailChangeError> for ErrorMsg<D> {
+    fn from(e: ConfirmEmailChangeError) -> Self {
+        match e {
+            ConfirmEmailChangeError::InvalidToken | ConfirmEmailChangeError::EmailAlreadyInUse => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            ConfirmEmailChangeError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<EnrollTwoFactorError> for ErrorMsg<D> {
+    fn from(e: EnrollTwoFactorError) -> Self {
+        match e {
+            EnrollTwoFactorError::UserNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            EnrollTwoFactorError::AlreadyEnabled => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            EnrollTwoFactorError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<ConfirmTwoFactorError> for ErrorMsg<D> {
+    fn from(e: ConfirmTwoFactorError) -> Self {
+        match e {
+            ConfirmTwoFactorError::NotEnrolled
+            | ConfirmTwoFactorError::AlreadyEnabled
+            | ConfirmTwoFactorError::InvalidCode => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            ConfirmTwoFactorError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DisableTwoFactorError> for ErrorMsg<D> {
+    fn from(e: DisableTwoFactorError) -> Self {
+        match e {
+            DisableTwoFactorError::NotEnabled => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            DisableTwoFactorError::InvalidCode => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DisableTwoFactorError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<CompleteTwoFactorLoginError> for ErrorMsg<D> {
+    fn from(e: CompleteTwoFactorLoginError) -> Self {
+        match e {
+            CompleteTwoFactorLoginError::InvalidPendingLogin
+            | CompleteTwoFactorLoginError::InvalidCode => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            CompleteTwoFactorLoginError::TooManyAttempts(_)
+            | CompleteTwoFactorLoginError::TemporarilyLocked(_) => {
+                Self::new(StatusCode::TOO_MANY_REQUESTS, e.to_string())
+            }
+            CompleteTwoFactorLoginError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<RevokeSessionByIdError> for ErrorMsg<D> {
+    fn from(e: RevokeSessionByIdError) -> Self {
+        match e {
+            RevokeSessionByIdError::InvalidSession => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            RevokeSessionByIdError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<RevokeApiTokenError> for ErrorMsg<D> {
+    fn from(e: RevokeApiTokenError) -> Self {
+        match e {
+            RevokeApiTokenError::InvalidApiToken => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            RevokeApiTokenError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            RevokeApiTokenError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
 impl<D: Serialize> From<FindExpensesWithTransactionsByUserIdError> for ErrorMsg<D> {
     fn from(e: FindExpensesWithTransactionsByUserIdError) -> Self {
         match e {
             FindExpensesWithTransactionsByUserIdError::UnauthorizedUser(_) => {
                 Self::new(StatusCode::FORBIDDEN, e.to_string())
             }
+            FindExpensesWithTransactionsByUserIdError::InvalidPageSize
+            | FindExpensesWithTransactionsByUserIdError::InvalidDate(_) => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
             FindExpensesWithTransactionsByUserIdError::DatabaseError(db_error) => db_error.into(),
         }
     }
@@ -139,10 +408,12 @@ impl<D: Serialize> From<CreateExpenseError> for ErrorMsg<D> {
     fn from(e: CreateExpenseError) -> Self {
         match e {
             CreateExpenseError::InvalidPredefinedExpense
+            | CreateExpenseError::InvalidCategory
+            | CreateExpenseError::InvalidHousehold
             | CreateExpenseError::InvalidRelatedType(_) => {
                 Self::new(StatusCode::NOT_FOUND, e.to_string())
             }
-            CreateExpenseError::InvalidStartDate(_) => {
+            CreateExpenseError::InvalidStartDate(_) | CreateExpenseError::InvalidEndDate => {
                 Self::new(StatusCode::BAD_REQUEST, e.to_string())
             }
             CreateExpenseError::DatabaseError(db_error) => db_error.into(),
@@ -150,21 +421,222 @@ impl<D: Serialize> From<CreateExpenseError> for ErrorMsg<D> {
     }
 }
 
+impl<D: Serialize> From<UpdateExpenseError> for ErrorMsg<D> {
+    fn from(e: UpdateExpenseError) -> Self {
+        match e {
+            UpdateExpenseError::ExpenseNotFound
+            | UpdateExpenseError::InvalidPredefinedExpense
+            | UpdateExpenseError::InvalidCategory
+            | UpdateExpenseError::InvalidHousehold
+            | UpdateExpenseError::InvalidRelatedType(_) => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            UpdateExpenseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            UpdateExpenseError::InvalidStartDate(_)
+            | UpdateExpenseError::InvalidEndDate
+            | UpdateExpenseError::PriceHistoryExists
+            | UpdateExpenseError::ForeignPayers => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            UpdateExpenseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteExpenseError> for ErrorMsg<D> {
+    fn from(e: DeleteExpenseError) -> Self {
+        match e {
+            DeleteExpenseError::ExpenseNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            DeleteExpenseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteExpenseError::InvalidReassignTarget | DeleteExpenseError::ForeignPayers => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            DeleteExpenseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<RestoreExpenseError> for ErrorMsg<D> {
+    fn from(e: RestoreExpenseError) -> Self {
+        match e {
+            RestoreExpenseError::ExpenseNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            RestoreExpenseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            RestoreExpenseError::ExpenseNotInTrash => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            RestoreExpenseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<CancelExpenseError> for ErrorMsg<D> {
+    fn from(e: CancelExpenseError) -> Self {
+        match e {
+            CancelExpenseError::ExpenseNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            CancelExpenseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            CancelExpenseError::InvalidDate(_) | CancelExpenseError::InvalidEndDate => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            CancelExpenseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<PauseExpenseError> for ErrorMsg<D> {
+    fn from(e: PauseExpenseError) -> Self {
+        match e {
+            PauseExpenseError::ExpenseNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            PauseExpenseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            PauseExpenseError::InvalidDate(_)
+            | PauseExpenseError::InvalidInterval
+            | PauseExpenseError::OverlappingPause => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            PauseExpenseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteExpensePauseError> for ErrorMsg<D> {
+    fn from(e: DeleteExpensePauseError) -> Self {
+        match e {
+            DeleteExpensePauseError::ExpenseNotFound | DeleteExpensePauseError::PauseNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteExpensePauseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteExpensePauseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<AddPriceChangeError> for ErrorMsg<D> {
+    fn from(e: AddPriceChangeError) -> Self {
+        match e {
+            AddPriceChangeError::ExpenseNotFound | AddPriceChangeError::InvalidCurrency => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            AddPriceChangeError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            AddPriceChangeError::InvalidDate(_)
+            | AddPriceChangeError::InvalidEffectiveDate
+            | AddPriceChangeError::PriceChangeExists => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            AddPriceChangeError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeletePriceChangeError> for ErrorMsg<D> {
+    fn from(e: DeletePriceChangeError) -> Self {
+        match e {
+            DeletePriceChangeError::ExpenseNotFound
+            | DeletePriceChangeError::PriceChangeNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeletePriceChangeError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeletePriceChangeError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<SetExpenseSplitError> for ErrorMsg<D> {
+    fn from(e: SetExpenseSplitError) -> Self {
+        match e {
+            SetExpenseSplitError::ExpenseNotFound | SetExpenseSplitError::InvalidParticipant => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            SetExpenseSplitError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            SetExpenseSplitError::DuplicateParticipant
+            | SetExpenseSplitError::InvalidShareValue
+            | SetExpenseSplitError::InvalidPercentages => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            SetExpenseSplitError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteExpenseSplitError> for ErrorMsg<D> {
+    fn from(e: DeleteExpenseSplitError) -> Self {
+        match e {
+            DeleteExpenseSplitError::ExpenseNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteExpenseSplitError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteExpenseSplitError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<FindBalanceError> for ErrorMsg<D> {
+    fn from(e: FindBalanceError) -> Self {
+        match e {
+            FindBalanceError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            FindBalanceError::InvalidDate(_) | FindBalanceError::InvalidPeriod => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            FindBalanceError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
 impl<D: Serialize> From<CreatePredefinedExpenseError> for ErrorMsg<D> {
     fn from(e: CreatePredefinedExpenseError) -> Self {
         match e {
             CreatePredefinedExpenseError::InvalidRelatedType(_) => {
                 Self::new(StatusCode::NOT_FOUND, e.to_string())
             }
+            CreatePredefinedExpenseError::InvalidVisibility(visibility_error) => {
+                visibility_error.into()
+            }
             CreatePredefinedExpenseError::DatabaseError(db_error) => db_error.into(),
         }
     }
 }
 
+impl<D: Serialize> From<ValidatePredefinedExpenseVisibilityError> for ErrorMsg<D> {
+    fn from(e: ValidatePredefinedExpenseVisibilityError) -> Self {
+        match e {
+            ValidatePredefinedExpenseVisibilityError::InvalidHousehold => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            ValidatePredefinedExpenseVisibilityError::PublicationUnauthorized => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            ValidatePredefinedExpenseVisibilityError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
 impl<D: Serialize> From<CreateTransactionError> for ErrorMsg<D> {
     fn from(e: CreateTransactionError) -> Self {
         match e {
-            CreateTransactionError::InvalidExpenseId | CreateTransactionError::InvalidCurrency => {
+            CreateTransactionError::InvalidExpenseId
+            | CreateTransactionError::InvalidCurrency
+            | CreateTransactionError::InvalidCategory
+            | CreateTransactionError::InvalidParticipant => {
                 Self::new(StatusCode::NOT_FOUND, e.to_string())
             }
             CreateTransactionError::UserUnauthorized(_) => {
@@ -191,3 +663,392 @@ impl<D: Serialize> From<DeleteTransactionByIdError> for ErrorMsg<D> {
         }
     }
 }
+
+impl<D: Serialize> From<RestoreTransactionError> for ErrorMsg<D> {
+    fn from(e: RestoreTransactionError) -> Self {
+        match e {
+            RestoreTransactionError::TransactionNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            RestoreTransactionError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            RestoreTransactionError::TransactionNotInTrash
+            | RestoreTransactionError::ExpenseInTrash => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            RestoreTransactionError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<UpdatePredefinedExpenseError> for ErrorMsg<D> {
+    fn from(e: UpdatePredefinedExpenseError) -> Self {
+        match e {
+            UpdatePredefinedExpenseError::PredefinedExpenseNotFound
+            | UpdatePredefinedExpenseError::InvalidRelatedType(_) => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            UpdatePredefinedExpenseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            UpdatePredefinedExpenseError::InvalidVisibility(visibility_error) => {
+                visibility_error.into()
+            }
+            UpdatePredefinedExpenseError::PredefinedExpenseInUse => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            UpdatePredefinedExpenseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeletePredefinedExpenseError> for ErrorMsg<D> {
+    fn from(e: DeletePredefinedExpenseError) -> Self {
+        match e {
+            DeletePredefinedExpenseError::PredefinedExpenseNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeletePredefinedExpenseError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeletePredefinedExpenseError::PredefinedExpenseInUse => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            DeletePredefinedExpenseError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<CreateCurrencyError> for ErrorMsg<D> {
+    fn from(e: CreateCurrencyError) -> Self {
+        match e {
+            CreateCurrencyError::CurrencyAlreadyExists => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            CreateCurrencyError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<UpdateCurrencyError> for ErrorMsg<D> {
+    fn from(e: UpdateCurrencyError) -> Self {
+        match e {
+            UpdateCurrencyError::CurrencyNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            UpdateCurrencyError::CurrencyAlreadyExists => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            UpdateCurrencyError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteCurrencyError> for ErrorMsg<D> {
+    fn from(e: DeleteCurrencyError) -> Self {
+        match e {
+            DeleteCurrencyError::CurrencyNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteCurrencyError::CurrencyInUse => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            DeleteCurrencyError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<CreateRecurrenceError> for ErrorMsg<D> {
+    fn from(e: CreateRecurrenceError) -> Self {
+        match e {
+            CreateRecurrenceError::RecurrenceAlreadyExists => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            CreateRecurrenceError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<UpdateRecurrenceError> for ErrorMsg<D> {
+    fn from(e: UpdateRecurrenceError) -> Self {
+        match e {
+            UpdateRecurrenceError::RecurrenceNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            UpdateRecurrenceError::RecurrenceAlreadyExists => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            UpdateRecurrenceError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteRecurrenceError> for ErrorMsg<D> {
+    fn from(e: DeleteRecurrenceError) -> Self {
+        match e {
+            DeleteRecurrenceError::RecurrenceNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteRecurrenceError::RecurrenceInUse => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            DeleteRecurrenceError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<CreateCategoryError> for ErrorMsg<D> {
+    fn from(e: CreateCategoryError) -> Self {
+        match e {
+            CreateCategoryError::InvalidParent => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            CreateCategoryError::CategoryAlreadyExists => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            CreateCategoryError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<UpdateCategoryError> for ErrorMsg<D> {
+    fn from(e: UpdateCategoryError) -> Self {
+        match e {
+            UpdateCategoryError::CategoryNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            UpdateCategoryError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            UpdateCategoryError::InvalidParent | UpdateCategoryError::CategoryAlreadyExists => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            UpdateCategoryError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteCategoryError> for ErrorMsg<D> {
+    fn from(e: DeleteCategoryError) -> Self {
+        match e {
+            DeleteCategoryError::CategoryNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteCategoryError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteCategoryError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteHouseholdError> for ErrorMsg<D> {
+    fn from(e: DeleteHouseholdError) -> Self {
+        match e {
+            DeleteHouseholdError::HouseholdNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteHouseholdError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteHouseholdError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<InviteToHouseholdError> for ErrorMsg<D> {
+    fn from(e: InviteToHouseholdError) -> Self {
+        match e {
+            InviteToHouseholdError::HouseholdNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            InviteToHouseholdError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            InviteToHouseholdError::AlreadyMember => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            InviteToHouseholdError::EmailCannotBeSent(reason) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, reason)
+            }
+            InviteToHouseholdError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<AcceptHouseholdInvitationError> for ErrorMsg<D> {
+    fn from(e: AcceptHouseholdInvitationError) -> Self {
+        match e {
+            AcceptHouseholdInvitationError::InvalidToken
+            | AcceptHouseholdInvitationError::AlreadyMember => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            AcceptHouseholdInvitationError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<RemoveHouseholdMemberError> for ErrorMsg<D> {
+    fn from(e: RemoveHouseholdMemberError) -> Self {
+        match e {
+            RemoveHouseholdMemberError::HouseholdNotFound
+            | RemoveHouseholdMemberError::MemberNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            RemoveHouseholdMemberError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            RemoveHouseholdMemberError::OwnerCannotLeave => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            RemoveHouseholdMemberError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<CreateParticipantError> for ErrorMsg<D> {
+    fn from(e: CreateParticipantError) -> Self {
+        match e {
+            CreateParticipantError::InvalidHousehold | CreateParticipantError::InvalidAccount => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            CreateParticipantError::NameTaken => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            CreateParticipantError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<UpdateParticipantError> for ErrorMsg<D> {
+    fn from(e: UpdateParticipantError) -> Self {
+        match e {
+            UpdateParticipantError::ParticipantNotFound
+            | UpdateParticipantError::InvalidAccount => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            UpdateParticipantError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            UpdateParticipantError::NameTaken => Self::new(StatusCode::BAD_REQUEST, e.to_string()),
+            UpdateParticipantError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteParticipantError> for ErrorMsg<D> {
+    fn from(e: DeleteParticipantError) -> Self {
+        match e {
+            DeleteParticipantError::ParticipantNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteParticipantError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteParticipantError::ParticipantInUse => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            DeleteParticipantError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<MergeParticipantsError> for ErrorMsg<D> {
+    fn from(e: MergeParticipantsError) -> Self {
+        match e {
+            MergeParticipantsError::ParticipantNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            MergeParticipantsError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            MergeParticipantsError::SameParticipant
+            | MergeParticipantsError::DifferentOwners
+            | MergeParticipantsError::ConflictingShares => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            MergeParticipantsError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<FindContributionsError> for ErrorMsg<D> {
+    fn from(e: FindContributionsError) -> Self {
+        match e {
+            FindContributionsError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            FindContributionsError::InvalidDate(_) | FindContributionsError::InvalidPeriod => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            FindContributionsError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<UploadAttachmentError> for ErrorMsg<D> {
+    fn from(e: UploadAttachmentError) -> Self {
+        match e {
+            UploadAttachmentError::ParentNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            UploadAttachmentError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            UploadAttachmentError::FileTooLarge => {
+                Self::new(StatusCode::PAYLOAD_TOO_LARGE, e.to_string())
+            }
+            UploadAttachmentError::UnsupportedFileType => {
+                Self::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, e.to_string())
+            }
+            UploadAttachmentError::QuotaExceeded => {
+                Self::new(StatusCode::BAD_REQUEST, e.to_string())
+            }
+            UploadAttachmentError::StorageError(_) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+            UploadAttachmentError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<FindAttachmentsError> for ErrorMsg<D> {
+    fn from(e: FindAttachmentsError) -> Self {
+        match e {
+            FindAttachmentsError::ParentNotFound => Self::new(StatusCode::NOT_FOUND, e.to_string()),
+            FindAttachmentsError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            FindAttachmentsError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DownloadAttachmentError> for ErrorMsg<D> {
+    fn from(e: DownloadAttachmentError) -> Self {
+        match e {
+            DownloadAttachmentError::AttachmentNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DownloadAttachmentError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DownloadAttachmentError::StorageError(_) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+            DownloadAttachmentError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
+
+impl<D: Serialize> From<DeleteAttachmentError> for ErrorMsg<D> {
+    fn from(e: DeleteAttachmentError) -> Self {
+        match e {
+            DeleteAttachmentError::AttachmentNotFound => {
+                Self::new(StatusCode::NOT_FOUND, e.to_string())
+            }
+            DeleteAttachmentError::UserUnauthorized(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            DeleteAttachmentError::StorageError(_) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+            DeleteAttachmentError::DatabaseError(db_error) => db_error.into(),
+        }
+    }
+}
@@ -1,14 +1,17 @@
+use veryrezsi_core::dto::balances::{BalanceQuery, BalanceResponse};
 use veryrezsi_core::dto::expenses::{
-    ExpenseResponse, NewExpenseRequest, NewPredefinedExpenseRequest, PredefinedExpenseResponse,
+    CancelExpenseRequest, DeleteExpenseQuery, ExpenseListQuery, ExpenseResponse,
+    ExpenseSplitRequest, NewExpensePauseRequest, NewExpensePriceRequest, NewExpenseRequest,
+    NewPredefinedExpenseRequest, PatchExpenseRequest, PredefinedExpenseResponse,
 };
-use veryrezsi_core::logic::expense_operations;
+use veryrezsi_core::logic::{balance_operations, expense_operations};
 use veryrezsi_core::DatabaseConnection;
 
 use super::common::ValidatedJson;
 use super::error::ErrorMsg;
 use crate::auth;
 
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::Json;
 use veryrezsi_core::Id;
 
@@ -16,8 +19,9 @@ pub async fn get_expenses(
     user: auth::AuthenticatedUser,
     State(ref conn): State<DatabaseConnection>,
     Path(user_id): Path<Id>,
+    Query(query): Query<ExpenseListQuery>,
 ) -> Result<Json<Vec<ExpenseResponse>>, ErrorMsg<()>> {
-    match expense_operations::find_expenses_by_user_id(conn, user.id, user_id).await {
+    match expense_operations::find_expenses_by_user_id(conn, user.id, user_id, query).await {
         Ok(expenses_with_transactions) => Ok(Json(expenses_with_transactions)),
         Err(e) => Err(e.into()),
     }
@@ -34,23 +38,188 @@ pub async fn create_expense(
     }
 }
 
+pub async fn update_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<NewExpenseRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::update_expense(conn, user.id, expense_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn patch_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<PatchExpenseRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::patch_expense(conn, user.id, expense_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+    Query(query): Query<DeleteExpenseQuery>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::delete_expense(conn, user.id, expense_id, query).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn restore_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::restore_expense(conn, user.id, expense_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn cancel_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<CancelExpenseRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::cancel_expense(conn, user.id, expense_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn pause_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<NewExpensePauseRequest>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match expense_operations::pause_expense(conn, user.id, expense_id, req).await {
+        Ok(pause_id) => Ok(Json(pause_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_expense_pause(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path((expense_id, pause_id)): Path<(Id, Id)>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::delete_expense_pause(conn, user.id, expense_id, pause_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn add_price_change(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<NewExpensePriceRequest>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match expense_operations::add_price_change(conn, user.id, expense_id, req).await {
+        Ok(price_id) => Ok(Json(price_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_price_change(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path((expense_id, price_id)): Path<(Id, Id)>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::delete_price_change(conn, user.id, expense_id, price_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn set_expense_split(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<ExpenseSplitRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::set_expense_split(conn, user.id, expense_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_expense_split(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(expense_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::delete_expense_split(conn, user.id, expense_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn get_balance(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Query(query): Query<BalanceQuery>,
+) -> Result<Json<BalanceResponse>, ErrorMsg<()>> {
+    match balance_operations::find_balance(conn, user.id, query).await {
+        Ok(balance) => Ok(Json(balance)),
+        Err(e) => Err(e.into()),
+    }
+}
+
 pub async fn get_predefined_expenses(
-    _: auth::AuthenticatedUser,
+    user: auth::AuthenticatedUser,
     State(ref conn): State<DatabaseConnection>,
 ) -> Result<Json<Vec<PredefinedExpenseResponse>>, ErrorMsg<()>> {
-    match expense_operations::find_predefined_expenses(conn).await {
+    match expense_operations::find_predefined_expenses(conn, user.id).await {
         Ok(expenses) => Ok(Json(expenses)),
         Err(e) => Err(e.into()),
     }
 }
 
 pub async fn create_predefined_expense(
-    _: auth::AuthenticatedUser,
+    user: auth::AuthenticatedUser,
     State(ref conn): State<DatabaseConnection>,
     ValidatedJson(req): ValidatedJson<NewPredefinedExpenseRequest>,
 ) -> Result<Json<Id>, ErrorMsg<()>> {
-    match expense_operations::create_predefined_expense(conn, req).await {
+    match expense_operations::create_predefined_expense(conn, user.id, req).await {
         Ok(predefined_expense_id) => Ok(Json(predefined_expense_id)),
         Err(e) => Err(e.into()),
     }
 }
+
+pub async fn update_predefined_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(predefined_expense_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<NewPredefinedExpenseRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::update_predefined_expense(conn, user.id, predefined_expense_id, req)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_predefined_expense(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(predefined_expense_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match expense_operations::delete_predefined_expense(conn, user.id, predefined_expense_id).await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use veryrezsi_core::dto::households::{
+    HouseholdInvitationRequest, HouseholdResponse, NewHouseholdRequest,
+};
+use veryrezsi_core::logic::household_operations;
+use veryrezsi_core::{DatabaseConnection, Id};
+
+use crate::auth;
+
+use super::common::ValidatedJson;
+use super::error::ErrorMsg;
+use super::AppState;
+
+pub async fn get_households(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<HouseholdResponse>>, ErrorMsg<()>> {
+    match household_operations::find_households(conn, user.id).await {
+        Ok(households) => Ok(Json(households)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn create_household(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    ValidatedJson(req): ValidatedJson<NewHouseholdRequest>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match household_operations::create_household(conn, user.id, req).await {
+        Ok(household_id) => Ok(Json(household_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_household(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(household_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match household_operations::delete_household(conn, user.id, household_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn invite_to_household(
+    user: auth::AuthenticatedUser,
+    State(app_state): State<AppState>,
+    Path(household_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<HouseholdInvitationRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match household_operations::invite_to_household(
+        &app_state.config,
+        &app_state.conn,
+        app_state.mail_transport,
+        user.id,
+        household_id,
+        req,
+    )
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn accept_household_invitation(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(token): Path<String>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match household_operations::accept_household_invitation(conn, user.id, token).await {
+        Ok(household_id) => Ok(Json(household_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn remove_household_member(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path((household_id, member_user_id)): Path<(Id, Id)>,
+) -> Result<(), ErrorMsg<()>> {
+    match household_operations::remove_household_member(conn, user.id, household_id, member_user_id)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,93 @@
+use super::common::ExtractClientInfo;
+use super::error::ErrorMsg;
+use super::users::complete_first_login_step;
+
+use axum::extract::{Path, Query, State};
+use axum::response::Redirect;
+use axum::{http::StatusCode, Json};
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use axum_extra::extract::PrivateCookieJar;
+use veryrezsi_core::config::{AppConfig, OidcProviderConfig, SessionConfig};
+use veryrezsi_core::dto::oidc::{OidcCallbackQuery, OidcProviderResponse};
+use veryrezsi_core::dto::two_factor::LoginResponse;
+use veryrezsi_core::logic::oidc_operations;
+use veryrezsi_core::oidc::{self, OidcLoginState};
+use veryrezsi_core::DatabaseConnection;
+
+/// Defines the name of the cookie holding the state of a login in progress at a provider.
+const OIDC_LOGIN_COOKIE_NAME: &str = "OIDC_LOGIN";
+/// The user has this many minutes to sign in at the provider.
+const OIDC_LOGIN_VALIDITY_MINUTES: i64 = 10;
+
+pub async fn get_providers(State(ref config): State<AppConfig>) -> Json<Vec<OidcProviderResponse>> {
+    Json(
+        config
+            .oidc_providers
+            .iter()
+            .map(OidcProviderResponse::from)
+            .collect(),
+    )
+}
+
+/// Redirects the user to the provider to sign in, the state of the login is remembered in a private cookie.
+pub async fn login(
+    cookies: PrivateCookieJar,
+    State(ref config): State<AppConfig>,
+    Path(provider): Path<String>,
+) -> Result<(PrivateCookieJar, Redirect), ErrorMsg<()>> {
+    let provider = find_provider(config, &provider)?;
+    let (authorize_url, login_state) = oidc::begin_login(provider).await?;
+    Ok((
+        cookies.add(login_state_cookie(&login_state, &config.session_config)),
+        Redirect::to(&authorize_url),
+    ))
+}
+
+/// Completes the login at the provider, which counts as the first login step.
+/// A session cookie is issued the same way as after a password, so users with two-factor authentication still have to send a code.
+/// The client relays the provider's redirect here, it receives the cookies the same way as on a password login.
+pub async fn callback(
+    cookies: PrivateCookieJar,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref config): State<AppConfig>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    Path(provider): Path<String>,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<(PrivateCookieJar, Json<LoginResponse>), ErrorMsg<()>> {
+    let provider = find_provider(config, &provider)?;
+    let Some(login_state) = cookies
+        .get(OIDC_LOGIN_COOKIE_NAME)
+        .and_then(|cookie| serde_json::from_str::<OidcLoginState>(cookie.value()).ok())
+    else {
+        return Err(ErrorMsg::new(
+            StatusCode::BAD_REQUEST,
+            "login was not started or has expired",
+        ));
+    };
+    let cookies = cookies.remove(Cookie::build(OIDC_LOGIN_COOKIE_NAME, "").path("/").finish());
+
+    let identity = oidc::complete_login(provider, login_state, query.code, &query.state).await?;
+    let user_id = oidc_operations::login_with_oidc_identity(conn, &client, identity).await?;
+    complete_first_login_step(cookies, conn, config, client, user_id).await
+}
+
+fn find_provider<'a>(
+    config: &'a AppConfig,
+    name: &str,
+) -> Result<&'a OidcProviderConfig, ErrorMsg<()>> {
+    config
+        .oidc_provider(name)
+        .ok_or_else(|| ErrorMsg::new(StatusCode::NOT_FOUND, "unknown login provider"))
+}
+
+/// The provider redirects the user back with a top-level navigation, so the cookie has to be sent on it.
+fn login_state_cookie(login_state: &OidcLoginState, config: &SessionConfig) -> Cookie<'static> {
+    let value = serde_json::to_string(login_state).expect("login state should be serializable");
+    Cookie::build(OIDC_LOGIN_COOKIE_NAME, value)
+        .path("/")
+        .http_only(true)
+        .secure(config.secure_cookie)
+        .same_site(SameSite::Lax)
+        .max_age(cookie::time::Duration::minutes(OIDC_LOGIN_VALIDITY_MINUTES))
+        .finish()
+}
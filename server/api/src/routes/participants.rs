@@ -0,0 +1,82 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use veryrezsi_core::dto::participants::{
+    ContributionQuery, ContributionResponse, NewParticipantRequest, ParticipantResponse,
+    UpdateParticipantRequest,
+};
+use veryrezsi_core::logic::participant_operations;
+use veryrezsi_core::{DatabaseConnection, Id};
+
+use crate::auth;
+
+use super::common::ValidatedJson;
+use super::error::ErrorMsg;
+
+pub async fn get_participants(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<ParticipantResponse>>, ErrorMsg<()>> {
+    match participant_operations::find_participants(conn, user.id).await {
+        Ok(participants) => Ok(Json(participants)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn create_participant(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    ValidatedJson(req): ValidatedJson<NewParticipantRequest>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match participant_operations::create_participant(conn, user.id, req).await {
+        Ok(participant_id) => Ok(Json(participant_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn update_participant(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(participant_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<UpdateParticipantRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match participant_operations::update_participant(conn, user.id, participant_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_participant(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(participant_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match participant_operations::delete_participant(conn, user.id, participant_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn merge_participants(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path((participant_id, target_id)): Path<(Id, Id)>,
+) -> Result<(), ErrorMsg<()>> {
+    match participant_operations::merge_participants(conn, user.id, participant_id, target_id).await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn get_contributions(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Query(query): Query<ContributionQuery>,
+) -> Result<Json<Vec<ContributionResponse>>, ErrorMsg<()>> {
+    match participant_operations::find_contributions(conn, user.id, query).await {
+        Ok(contributions) => Ok(Json(contributions)),
+        Err(e) => Err(e.into()),
+    }
+}
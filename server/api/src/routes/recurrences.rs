@@ -1,9 +1,14 @@
-use axum::{extract::State, Json};
-use veryrezsi_core::DatabaseConnection;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use veryrezsi_core::dto::recurrences::RecurrenceRequest;
 use veryrezsi_core::{dto::recurrences::RecurrenceResponse, logic::recurrence_operations};
+use veryrezsi_core::{DatabaseConnection, Id};
 
 use crate::auth;
 
+use super::common::ValidatedJson;
 use super::error::ErrorMsg;
 
 pub async fn get_recurrences(
@@ -15,3 +20,37 @@ pub async fn get_recurrences(
         Err(e) => Err(e.into()),
     }
 }
+
+pub async fn create_recurrence(
+    _: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    ValidatedJson(req): ValidatedJson<RecurrenceRequest>,
+) -> Result<Json<Id>, ErrorMsg<()>> {
+    match recurrence_operations::create_recurrence(conn, req).await {
+        Ok(recurrence_id) => Ok(Json(recurrence_id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn update_recurrence(
+    _: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    Path(recurrence_id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<RecurrenceRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match recurrence_operations::update_recurrence(conn, recurrence_id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_recurrence(
+    _: auth::AuthenticatedAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    Path(recurrence_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match recurrence_operations::delete_recurrence(conn, recurrence_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
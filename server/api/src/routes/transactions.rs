@@ -31,3 +31,14 @@ pub async fn delete_transaction(
         Err(e) => Err(e.into()),
     }
 }
+
+pub async fn restore_transaction(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(transaction_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    match transaction_operations::restore_transaction(conn, user.id, transaction_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,21 @@
+use veryrezsi_core::config::AppConfig;
+use veryrezsi_core::dto::trash::TrashResponse;
+use veryrezsi_core::logic::trash_operations;
+use veryrezsi_core::DatabaseConnection;
+
+use super::error::ErrorMsg;
+use crate::auth;
+
+use axum::extract::State;
+use axum::Json;
+
+pub async fn get_trash(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref config): State<AppConfig>,
+) -> Result<Json<TrashResponse>, ErrorMsg<()>> {
+    match trash_operations::find_trash(conn, &config.trash_config, user.id).await {
+        Ok(trash) => Ok(Json(trash)),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -1,23 +1,142 @@
-use super::common::ValidatedJson;
+use super::common::{ExtractClientInfo, ValidatedJson};
 use super::error::ErrorMsg;
 use super::AppState;
 use crate::auth::{self, AUTH_COOKIE_NAME};
 use axum::extract::{Path, State};
 use axum::{http::StatusCode, Json};
-use axum_extra::extract::{cookie::Cookie, PrivateCookieJar};
-use veryrezsi_core::dto::users::{LoginRequest, NewUserRequest, UserResponse};
-use veryrezsi_core::logic::user_operations;
-use veryrezsi_core::DatabaseConnection;
+use axum_extra::extract::PrivateCookieJar;
+use std::sync::Arc;
+use veryrezsi_core::config::AppConfig;
+use veryrezsi_core::dto::api_tokens::{ApiTokenResponse, NewApiTokenRequest, NewApiTokenResponse};
+use veryrezsi_core::dto::security_events::{SecurityEventResponse, SecurityEventType};
+use veryrezsi_core::dto::sessions::{ClientInfo, SessionResponse};
+use veryrezsi_core::dto::two_factor::{
+    LoginResponse, RecoveryCodesResponse, TwoFactorCodeRequest, TwoFactorEnrollmentResponse,
+    TwoFactorLoginRequest,
+};
+use veryrezsi_core::dto::users::{
+    ChangeEmailRequest, ChangePasswordRequest, DeleteAccountRequest, ForgotPasswordRequest,
+    LoginRequest, NewUserRequest, ResendActivationRequest, ResetPasswordRequest,
+    UpdateProfileRequest, UserDataExportResponse, UserResponse,
+};
+use veryrezsi_core::logic::{
+    api_token_operations, security_event_operations, session_operations, two_factor_operations,
+    user_operations,
+};
+use veryrezsi_core::storage::Storage;
+use veryrezsi_core::{DatabaseConnection, Id};
 
 pub async fn login(
     cookies: PrivateCookieJar,
     State(ref conn): State<DatabaseConnection>,
+    State(ref config): State<AppConfig>,
+    ExtractClientInfo(client): ExtractClientInfo,
     ValidatedJson(req): ValidatedJson<LoginRequest>,
+) -> Result<(PrivateCookieJar, Json<LoginResponse>), ErrorMsg<()>> {
+    let user_id = user_operations::verify_login(config, conn, &client, req).await?;
+    complete_first_login_step(cookies, conn, config, client, user_id).await
+}
+
+/// Issues a session to a user who passed the first login step, by a password or by a login provider.
+/// If the user has two-factor authentication enabled, only a pending login is started, which needs a code to complete.
+pub(super) async fn complete_first_login_step(
+    cookies: PrivateCookieJar,
+    conn: &DatabaseConnection,
+    config: &AppConfig,
+    client: ClientInfo,
+    user_id: Id,
+) -> Result<(PrivateCookieJar, Json<LoginResponse>), ErrorMsg<()>> {
+    if let Some(pending_token) = two_factor_operations::start_pending_login(conn, user_id).await? {
+        let response = LoginResponse {
+            two_factor_required: true,
+            pending_token: Some(pending_token),
+        };
+        return Ok((cookies, Json(response)));
+    }
+    security_event_operations::record_security_event(
+        conn,
+        user_id,
+        SecurityEventType::Login,
+        &client,
+    )
+    .await?;
+    let token =
+        session_operations::create_session(conn, &config.session_config, user_id, client).await?;
+    let response = LoginResponse {
+        two_factor_required: false,
+        pending_token: None,
+    };
+    Ok((
+        cookies.add(auth::session_cookie(token, &config.session_config)),
+        Json(response),
+    ))
+}
+
+pub async fn login_two_factor(
+    cookies: PrivateCookieJar,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref config): State<AppConfig>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    ValidatedJson(req): ValidatedJson<TwoFactorLoginRequest>,
 ) -> Result<PrivateCookieJar, ErrorMsg<()>> {
-    let user_id = user_operations::verify_login(conn, req).await?;
-    let mut cookie = Cookie::new(auth::AUTH_COOKIE_NAME, user_id.to_string());
-    cookie.set_path("/");
-    Ok(cookies.add(cookie))
+    let user_id = two_factor_operations::complete_pending_login(config, conn, &client, req).await?;
+    security_event_operations::record_security_event(
+        conn,
+        user_id,
+        SecurityEventType::Login,
+        &client,
+    )
+    .await?;
+    let token =
+        session_operations::create_session(conn, &config.session_config, user_id, client).await?;
+    Ok(cookies.add(auth::session_cookie(token, &config.session_config)))
+}
+
+pub async fn enroll_two_factor(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<TwoFactorEnrollmentResponse>, ErrorMsg<()>> {
+    user.require_session()?;
+    match two_factor_operations::enroll_two_factor(conn, user.id).await {
+        Ok(enrollment) => Ok(Json(enrollment)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn confirm_two_factor(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    ValidatedJson(req): ValidatedJson<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, ErrorMsg<()>> {
+    user.require_session()?;
+    let recovery_codes = two_factor_operations::confirm_two_factor(conn, user.id, req).await?;
+    security_event_operations::record_security_event(
+        conn,
+        user.id,
+        SecurityEventType::TwoFactorEnabled,
+        &client,
+    )
+    .await?;
+    Ok(Json(recovery_codes))
+}
+
+pub async fn disable_two_factor(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    ValidatedJson(req): ValidatedJson<TwoFactorCodeRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    user.require_session()?;
+    two_factor_operations::disable_two_factor(conn, &client, user.id, req).await?;
+    security_event_operations::record_security_event(
+        conn,
+        user.id,
+        SecurityEventType::TwoFactorDisabled,
+        &client,
+    )
+    .await?;
+    Ok(())
 }
 
 pub async fn me(
@@ -30,21 +149,232 @@ pub async fn me(
     }
 }
 
-pub async fn logout(cookies: PrivateCookieJar) -> Result<PrivateCookieJar, ErrorMsg<()>> {
-    match cookies.get(AUTH_COOKIE_NAME) {
-        Some(cookie) => Ok(cookies.remove(cookie)),
-        None => Err(ErrorMsg::new(StatusCode::BAD_REQUEST, "not logged in")),
+pub async fn update_me(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    ValidatedJson(req): ValidatedJson<UpdateProfileRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::update_profile(conn, user.id, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn export_data(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<UserDataExportResponse>, ErrorMsg<()>> {
+    match user_operations::export_user_data(conn, user.id).await {
+        Ok(export) => Ok(Json(export)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_me(
+    cookies: PrivateCookieJar,
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(storage): State<Arc<dyn Storage>>,
+    ValidatedJson(req): ValidatedJson<DeleteAccountRequest>,
+) -> Result<PrivateCookieJar, ErrorMsg<()>> {
+    user.require_session()?;
+    match user_operations::delete_account(conn, storage.as_ref(), user.id, req).await {
+        Ok(_) => Ok(cookies.remove(auth::removal_cookie())),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn logout(
+    cookies: PrivateCookieJar,
+    State(ref conn): State<DatabaseConnection>,
+    ExtractClientInfo(client): ExtractClientInfo,
+) -> Result<PrivateCookieJar, ErrorMsg<()>> {
+    let Some(cookie) = cookies.get(AUTH_COOKIE_NAME) else {
+        return Err(ErrorMsg::new(StatusCode::BAD_REQUEST, "not logged in"));
+    };
+    if let Some(user_id) = session_operations::revoke_session(conn, cookie.value()).await? {
+        security_event_operations::record_security_event(
+            conn,
+            user_id,
+            SecurityEventType::Logout,
+            &client,
+        )
+        .await?;
+    }
+    Ok(cookies.remove(auth::removal_cookie()))
+}
+
+pub async fn forgot_password(
+    State(app_state): State<AppState>,
+    ValidatedJson(req): ValidatedJson<ForgotPasswordRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::request_password_reset(
+        &app_state.config,
+        &app_state.conn,
+        app_state.mail_transport,
+        req,
+    )
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn reset_password(
+    State(ref conn): State<DatabaseConnection>,
+    State(ref config): State<AppConfig>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    ValidatedJson(req): ValidatedJson<ResetPasswordRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::reset_password(config, conn, &client, req).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn change_password(
+    user: auth::AuthenticatedUser,
+    State(app_state): State<AppState>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    ValidatedJson(req): ValidatedJson<ChangePasswordRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    let session_id = user.require_session()?;
+    match user_operations::change_password(
+        &app_state.config,
+        &app_state.conn,
+        app_state.mail_transport,
+        &client,
+        user.id,
+        session_id,
+        req,
+    )
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn change_email(
+    user: auth::AuthenticatedUser,
+    State(app_state): State<AppState>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    ValidatedJson(req): ValidatedJson<ChangeEmailRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    let session_id = user.require_session()?;
+    match user_operations::request_email_change(
+        &app_state.config,
+        &app_state.conn,
+        app_state.mail_transport,
+        &client,
+        user.id,
+        session_id,
+        req,
+    )
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn confirm_email_change(
+    State(ref conn): State<DatabaseConnection>,
+    ExtractClientInfo(client): ExtractClientInfo,
+    Path(token): Path<String>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::confirm_email_change(conn, &client, token).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn get_sessions(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref config): State<AppConfig>,
+) -> Result<Json<Vec<SessionResponse>>, ErrorMsg<()>> {
+    let session_id = user.require_session()?;
+    match session_operations::find_sessions_by_user_id(
+        conn,
+        &config.session_config,
+        user.id,
+        session_id,
+    )
+    .await
+    {
+        Ok(sessions) => Ok(Json(sessions)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_session(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(session_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    user.require_session()?;
+    match session_operations::revoke_session_by_id(conn, user.id, session_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn get_security_events(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<SecurityEventResponse>>, ErrorMsg<()>> {
+    match security_event_operations::find_security_events_by_user_id(conn, user.id).await {
+        Ok(security_events) => Ok(Json(security_events)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn get_api_tokens(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<ApiTokenResponse>>, ErrorMsg<()>> {
+    match api_token_operations::find_api_tokens_by_user_id(conn, user.id).await {
+        Ok(api_tokens) => Ok(Json(api_tokens)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn create_api_token(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    ValidatedJson(req): ValidatedJson<NewApiTokenRequest>,
+) -> Result<Json<NewApiTokenResponse>, ErrorMsg<()>> {
+    user.require_session()?;
+    match api_token_operations::create_api_token(conn, user.id, req).await {
+        Ok(api_token) => Ok(Json(api_token)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn delete_api_token(
+    user: auth::AuthenticatedUser,
+    State(ref conn): State<DatabaseConnection>,
+    Path(api_token_id): Path<Id>,
+) -> Result<(), ErrorMsg<()>> {
+    user.require_session()?;
+    match api_token_operations::revoke_api_token(conn, user.id, api_token_id).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
     }
 }
 
 pub async fn register(
     State(app_state): State<AppState>,
+    ExtractClientInfo(client): ExtractClientInfo,
     ValidatedJson(new_user): ValidatedJson<NewUserRequest>,
 ) -> Result<Json<UserResponse>, ErrorMsg<()>> {
     match user_operations::save_user(
         &app_state.config,
         &app_state.conn,
         app_state.mail_transport,
+        &client,
         new_user,
     )
     .await
@@ -54,11 +384,29 @@ pub async fn register(
     }
 }
 
+pub async fn resend_activation(
+    State(app_state): State<AppState>,
+    ValidatedJson(req): ValidatedJson<ResendActivationRequest>,
+) -> Result<(), ErrorMsg<()>> {
+    match user_operations::resend_activation_email(
+        &app_state.config,
+        &app_state.conn,
+        app_state.mail_transport,
+        req,
+    )
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
 pub async fn activate_account(
     State(ref conn): State<DatabaseConnection>,
+    ExtractClientInfo(client): ExtractClientInfo,
     Path(token): Path<String>,
 ) -> Result<(), ErrorMsg<()>> {
-    match user_operations::activate_account(conn, token).await {
+    match user_operations::activate_account(conn, &client, token).await {
         Ok(_) => Ok(()),
         Err(e) => Err(e.into()),
     }
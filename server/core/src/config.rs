@@ -1,20 +1,48 @@
 use confique::Config;
-use serde::Deserialize;
-use std::net::SocketAddr;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
 use tracing::metadata::LevelFilter;
 
 #[derive(Debug, Clone, Config)]
 pub struct AppConfig {
     #[config(env = "SERVER_ADDRESS")]
     pub server_address: SocketAddr,
+    /// The base url of the web client, used to create links in emails which lead to a client page.
+    #[config(env = "CLIENT_URL", default = "http://localhost:3000")]
+    pub client_url: String,
     #[config(env = "DATABASE_URL")]
     pub database_url: String,
     #[config(env = "COOKIE_KEY")]
     pub cookie_key: String,
     #[config(env = "LOG_LEVEL")]
     pub log_level: LogLevel,
+    /// The addresses of the reverse proxies in front of the server, like the server side of the web client.
+    /// The address of the user is taken from the X-Forwarded-For header of the requests coming from them.
+    #[config(
+        env = "TRUSTED_PROXIES",
+        parse_env = confique::env::parse::list_by_comma,
+        default = []
+    )]
+    pub trusted_proxies: Vec<IpAddr>,
     #[config(nested)]
     pub mail_config: MailConfig,
+    #[config(nested)]
+    pub session_config: SessionConfig,
+    #[config(nested)]
+    pub account_config: AccountConfig,
+    #[config(nested)]
+    pub rate_limit_config: RateLimitConfig,
+    #[config(nested)]
+    pub password_hash_config: PasswordHashConfig,
+    #[config(nested)]
+    pub trash_config: TrashConfig,
+    #[config(nested)]
+    pub attachment_config: AttachmentConfig,
+    /// The OpenID Connect providers users can sign in with besides their local accounts.
+    #[config(default = [])]
+    pub oidc_providers: Vec<OidcProviderConfig>,
 }
 
 #[derive(Debug, Clone, Config)]
@@ -29,6 +57,149 @@ pub struct MailConfig {
     pub smtp_password: String,
 }
 
+#[derive(Debug, Clone, Config)]
+pub struct SessionConfig {
+    /// A session expires after this many minutes without a request.
+    #[config(env = "SESSION_IDLE_TIMEOUT_MINUTES", default = 120)]
+    pub idle_timeout_minutes: i64,
+    /// A session expires this many hours after login, regardless of activity.
+    #[config(env = "SESSION_ABSOLUTE_TIMEOUT_HOURS", default = 168)]
+    pub absolute_timeout_hours: i64,
+    /// Whether the session cookie should only be sent over https.
+    #[config(env = "SESSION_SECURE_COOKIE", default = true)]
+    pub secure_cookie: bool,
+}
+
+#[derive(Debug, Clone, Config)]
+pub struct AccountConfig {
+    /// A new activation email can be requested for an address only after this many minutes passed since the last one.
+    #[config(env = "ACTIVATION_RESEND_COOLDOWN_MINUTES", default = 5)]
+    pub activation_resend_cooldown_minutes: i64,
+    /// Unactivated accounts are deleted this many hours after their activation link expired.
+    #[config(env = "UNACTIVATED_ACCOUNT_GRACE_PERIOD_HOURS", default = 72)]
+    pub unactivated_account_grace_period_hours: i64,
+    /// How often the stale unactivated accounts are looked for, configured in minutes.
+    #[config(
+        env = "UNACTIVATED_ACCOUNT_SWEEP_INTERVAL_MINUTES",
+        deserialize_with = deserialize_interval_minutes,
+        default = 60
+    )]
+    pub unactivated_account_sweep_interval_minutes: Duration,
+    /// The user registered with this email is made an administrator on startup, to set up the first administrator.
+    #[config(env = "INITIAL_ADMIN_EMAIL")]
+    pub initial_admin_email: Option<String>,
+}
+
+#[derive(Debug, Clone, Config)]
+pub struct RateLimitConfig {
+    /// An account is locked temporarily after this many failed logins within the failure window.
+    #[config(env = "LOGIN_MAX_FAILED_ATTEMPTS_PER_ACCOUNT", default = 5)]
+    pub login_max_failed_attempts_per_account: i32,
+    /// An ip address is locked temporarily after this many failed logins within the failure window.
+    #[config(env = "LOGIN_MAX_FAILED_ATTEMPTS_PER_IP", default = 20)]
+    pub login_max_failed_attempts_per_ip: i32,
+    /// Failed logins are forgotten after this many minutes without a further failure.
+    #[config(env = "LOGIN_FAILURE_WINDOW_MINUTES", default = 60)]
+    pub login_failure_window_minutes: i64,
+    /// The length of the temporary lockout in minutes.
+    #[config(env = "LOGIN_LOCKOUT_MINUTES", default = 15)]
+    pub login_lockout_minutes: i64,
+    /// The wait after the first failed login in seconds, it doubles with every further failure until the lockout.
+    #[config(env = "LOGIN_BACKOFF_BASE_SECONDS", default = 1)]
+    pub login_backoff_base_seconds: i64,
+    /// At most this many registration emails are sent to requests from the same ip address in an hour.
+    #[config(env = "REGISTRATION_MAILS_PER_IP_PER_HOUR", default = 5)]
+    pub registration_mails_per_ip_per_hour: i32,
+}
+
+#[derive(Debug, Clone, Config)]
+pub struct TrashConfig {
+    /// Deleted expenses and transactions can be restored for this many days, then they are purged.
+    #[config(env = "TRASH_RETENTION_DAYS", default = 30)]
+    pub retention_days: i64,
+    /// How often the expired items of the trash are purged, configured in minutes.
+    #[config(
+        env = "TRASH_PURGE_INTERVAL_MINUTES",
+        deserialize_with = deserialize_interval_minutes,
+        default = 60
+    )]
+    pub purge_interval_minutes: Duration,
+}
+
+/// Limits of the files attached to expenses and transactions, and the storage they are kept in.
+#[derive(Debug, Clone, Config)]
+pub struct AttachmentConfig {
+    /// The largest file which can be uploaded, in bytes.
+    #[config(env = "ATTACHMENT_MAX_FILE_SIZE_BYTES", default = 10485760)]
+    pub max_file_size_bytes: u64,
+    /// The attachments uploaded by a user can take up this many bytes together.
+    #[config(env = "ATTACHMENT_USER_QUOTA_BYTES", default = 104857600)]
+    pub user_quota_bytes: u64,
+    /// The accepted types of files. The type is recognized from the content of the file, not from its name.
+    #[config(default = ["application/pdf", "image/jpeg", "image/png", "image/webp"])]
+    pub allowed_content_types: Vec<String>,
+    #[config(nested)]
+    pub storage: StorageConfig,
+}
+
+/// The files are stored in a local directory, or in a bucket of an S3 compatible object storage like MinIO.
+/// The S3 settings are only needed with the `s3` backend.
+#[derive(Debug, Clone, Config)]
+pub struct StorageConfig {
+    #[config(env = "STORAGE_BACKEND", default = "local")]
+    pub backend: StorageBackend,
+    /// The directory of the files with the `local` backend, it is created if it does not exist.
+    #[config(env = "STORAGE_LOCAL_PATH", default = "attachments")]
+    pub local_path: String,
+    /// The base url of the service, the bucket is addressed in the path, e.g. `http://localhost:9000`.
+    #[config(env = "S3_ENDPOINT")]
+    pub s3_endpoint: Option<String>,
+    #[config(env = "S3_BUCKET")]
+    pub s3_bucket: Option<String>,
+    #[config(env = "S3_REGION", default = "us-east-1")]
+    pub s3_region: String,
+    #[config(env = "S3_ACCESS_KEY")]
+    pub s3_access_key: Option<String>,
+    #[config(env = "S3_SECRET_KEY")]
+    pub s3_secret_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    Local,
+    S3,
+}
+
+/// Cost parameters of the Argon2id password hashes. Raising them rehashes passwords on the next successful login.
+#[derive(Debug, Clone, Config)]
+pub struct PasswordHashConfig {
+    /// The memory used for hashing a password, in kibibytes.
+    #[config(env = "ARGON2_MEMORY_KIB", default = 19456)]
+    pub argon2_memory_kib: u32,
+    /// The number of passes over the memory.
+    #[config(env = "ARGON2_ITERATIONS", default = 2)]
+    pub argon2_iterations: u32,
+    /// The degree of parallelism.
+    #[config(env = "ARGON2_PARALLELISM", default = 1)]
+    pub argon2_parallelism: u32,
+}
+
+/// An OpenID Connect provider, its endpoints and keys are discovered from the issuer.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcProviderConfig {
+    /// Identifies the provider in the login urls, must not change once users signed in with it.
+    pub name: String,
+    /// The name of the provider shown to the users.
+    pub display_name: String,
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// The callback url of the provider, it has to be registered at the provider as well.
+    /// It points to the callback page of the client, which relays the login to the server.
+    pub redirect_url: String,
+}
+
 impl AppConfig {
     #[must_use]
     pub fn init() -> Self {
@@ -38,6 +209,35 @@ impl AppConfig {
             .load()
             .expect("config loading failed")
     }
+
+    /// Finds the OpenID Connect provider with the given name.
+    pub fn oidc_provider(&self, name: &str) -> Option<&OidcProviderConfig> {
+        self.oidc_providers
+            .iter()
+            .find(|provider| provider.name == name)
+    }
+}
+
+/// The periodic jobs run at least once a year, much longer intervals would overflow their timers.
+const MAX_INTERVAL_MINUTES: u64 = 365 * 24 * 60;
+
+/// Reads the interval of a periodic job, which is configured in minutes.
+/// A zero or too long interval is reported when the config is loaded.
+fn deserialize_interval_minutes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    let minutes = u64::deserialize(deserializer)?;
+    if minutes == 0 {
+        return Err(D::Error::custom(
+            "the interval has to be at least one minute",
+        ));
+    }
+    match minutes.checked_mul(60) {
+        Some(seconds) if minutes <= MAX_INTERVAL_MINUTES => Ok(Duration::from_secs(seconds)),
+        _ => Err(D::Error::custom(format!(
+            "the interval can be at most {MAX_INTERVAL_MINUTES} minutes"
+        ))),
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -65,3 +265,38 @@ impl From<&LogLevel> for LevelFilter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::{check, let_assert};
+    use serde::de::value::{Error as ValueError, U64Deserializer};
+    use serde::de::IntoDeserializer;
+
+    fn interval(minutes: u64) -> Result<Duration, ValueError> {
+        let deserializer: U64Deserializer<ValueError> = minutes.into_deserializer();
+        deserialize_interval_minutes(deserializer)
+    }
+
+    #[test]
+    fn interval_minutes_are_validated() {
+        check!(interval(1) == Ok(Duration::from_secs(60)));
+        check!(
+            interval(MAX_INTERVAL_MINUTES) == Ok(Duration::from_secs(MAX_INTERVAL_MINUTES * 60))
+        );
+        let_assert!(Err(zero) = interval(0));
+        check!(zero.to_string() == "the interval has to be at least one minute");
+        check!(interval(MAX_INTERVAL_MINUTES + 1).is_err());
+        check!(interval(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn default_intervals_are_valid() {
+        let_assert!(Ok(account_config) = AccountConfig::builder().load());
+        check!(
+            account_config.unactivated_account_sweep_interval_minutes == Duration::from_secs(3600)
+        );
+        let_assert!(Ok(trash_config) = TrashConfig::builder().load());
+        check!(trash_config.purge_interval_minutes == Duration::from_secs(3600));
+    }
+}
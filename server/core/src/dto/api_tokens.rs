@@ -0,0 +1,67 @@
+use entity::api_token::{self, ApiTokenScope};
+use entity::Id;
+use sea_orm::prelude::DateTimeLocal;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewApiTokenRequest {
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "name must be between 1 and 64 characters"
+    ))]
+    pub name: String,
+    pub scope: ApiTokenScope,
+    /// The token never expires if this is not given.
+    #[validate(range(
+        min = 1,
+        max = 3650,
+        message = "expiration must be between 1 and 3650 days"
+    ))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenResponse {
+    pub id: Id,
+    pub name: String,
+    pub scope: ApiTokenScope,
+    pub created_at: DateTimeLocal,
+    pub expires_at: Option<DateTimeLocal>,
+    pub last_used_at: Option<DateTimeLocal>,
+}
+
+impl From<api_token::Model> for ApiTokenResponse {
+    fn from(api_token: api_token::Model) -> Self {
+        Self {
+            id: api_token.id,
+            name: api_token.name,
+            scope: api_token.scope,
+            created_at: api_token.created_at,
+            expires_at: api_token.expires_at,
+            last_used_at: api_token.last_used_at,
+        }
+    }
+}
+
+/// A newly created API token, the token itself is only ever shown here, only its hash is stored.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct NewApiTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub api_token: ApiTokenResponse,
+}
+
+/// A newly created API token and the token itself.
+pub type NewApiTokenResponseParts = (api_token::Model, String);
+impl From<NewApiTokenResponseParts> for NewApiTokenResponse {
+    fn from((api_token, token): NewApiTokenResponseParts) -> Self {
+        Self {
+            token,
+            api_token: api_token.into(),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use entity::{attachment, Id};
+use sea_orm::prelude::DateTimeLocal;
+use serde::Serialize;
+
+/// A file uploaded in a multipart request, its type is recognized from the content.
+#[derive(Clone)]
+pub struct NewAttachmentRequest {
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+/// Exactly one of the expense and the transaction is set, the one the file is attached to.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentResponse {
+    pub id: Id,
+    pub expense_id: Option<Id>,
+    pub transaction_id: Option<Id>,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: u64,
+    pub created_at: DateTimeLocal,
+}
+
+impl From<attachment::Model> for AttachmentResponse {
+    fn from(attachment: attachment::Model) -> Self {
+        Self {
+            id: attachment.id,
+            expense_id: attachment.expense_id,
+            transaction_id: attachment.transaction_id,
+            file_name: attachment.file_name,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            created_at: attachment.created_at,
+        }
+    }
+}
+
+/// The content of an attachment with what is needed to serve it as a download.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttachmentContent {
+    pub file_name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
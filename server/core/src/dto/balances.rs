@@ -0,0 +1,62 @@
+use entity::{currency, participant, Id, MoneyAmount};
+use serde::{Deserialize, Serialize};
+
+use super::currencies::CurrencyResponse;
+use super::participants::ParticipantResponse;
+
+/// The period of the balance in the date format of the user, both ends are included.
+/// The balance covers the expenses of the household if it is given, the own expenses of the user otherwise.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceQuery {
+    pub from: String,
+    pub to: String,
+    pub household_id: Option<Id>,
+}
+
+/// The balance is what the participant paid minus their share of the expected costs,
+/// a positive balance is owed to the participant.
+#[derive(Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantBalanceResponse {
+    pub participant: ParticipantResponse,
+    pub currency: CurrencyResponse,
+    pub paid: MoneyAmount,
+    pub owed: MoneyAmount,
+    pub balance: MoneyAmount,
+}
+
+pub type ParticipantBalanceResponseParts = (
+    participant::Model,
+    currency::Model,
+    MoneyAmount,
+    MoneyAmount,
+);
+impl From<ParticipantBalanceResponseParts> for ParticipantBalanceResponse {
+    fn from((participant, currency, paid, owed): ParticipantBalanceResponseParts) -> Self {
+        Self {
+            participant: participant.into(),
+            currency: currency.into(),
+            paid,
+            owed,
+            balance: paid - owed,
+        }
+    }
+}
+
+/// A suggested payment from a participant in debt to a participant in credit.
+#[derive(Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementResponse {
+    pub from: ParticipantResponse,
+    pub to: ParticipantResponse,
+    pub amount: MoneyAmount,
+    pub currency: CurrencyResponse,
+}
+
+/// The balances and settlements are given per currency, amounts in different currencies are never netted.
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct BalanceResponse {
+    pub participants: Vec<ParticipantBalanceResponse>,
+    pub settlements: Vec<SettlementResponse>,
+}
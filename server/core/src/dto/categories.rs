@@ -0,0 +1,34 @@
+use entity::{category, Id};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// A category of the user, it is a subcategory if the parent is given.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "category name must not be empty or longer than 255 characters"
+    ))]
+    pub name: String,
+    pub parent_id: Option<Id>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryResponse {
+    pub id: Id,
+    pub name: String,
+    pub parent_id: Option<Id>,
+}
+
+impl From<category::Model> for CategoryResponse {
+    fn from(category: category::Model) -> Self {
+        Self {
+            id: category.id,
+            name: category.name,
+            parent_id: category.parent_id,
+        }
+    }
+}
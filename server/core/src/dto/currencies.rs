@@ -1,5 +1,22 @@
 use entity::{currency, Id};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Clone, Deserialize, Validate)]
+pub struct CurrencyRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "abbreviation must be between 1 and 255 characters"
+    ))]
+    pub abbreviation: String,
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+}
 
 #[derive(Clone, Serialize, PartialEq, Eq)]
 pub struct CurrencyResponse {
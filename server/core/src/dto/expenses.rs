@@ -1,9 +1,16 @@
-use entity::{currency, expense, predefined_expense, recurrence, Id, MoneyAmount};
+use entity::expense::SplitMethod;
+use entity::predefined_expense::PredefinedExpenseVisibility;
+use entity::user::DateFormat;
+use entity::{
+    currency, expense, expense_pause, expense_price, expense_share, participant,
+    predefined_expense, recurrence, Id, MoneyAmount,
+};
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 use super::{
     currencies::CurrencyResponse,
+    participants::ParticipantResponse,
     recurrences::RecurrenceResponse,
     transactions::{TransactionResponse, TransactionResponseParts},
 };
@@ -32,9 +39,170 @@ pub struct NewExpenseRequest {
 
     pub start_date: String,
 
+    /// The expense is cancelled from this date, it is not paid on this day anymore.
+    pub end_date: Option<String>,
+
     pub value: MoneyAmount,
+
+    /// The category is taken from the predefined expense if it is left out and the predefined expense has a default one.
+    pub category_id: Option<Id>,
+
+    #[serde(default)]
+    #[validate(custom = "validate_tags")]
+    pub tags: Vec<String>,
+
+    /// The expense is shared with the members of the household, the user must be one of them.
+    pub household_id: Option<Id>,
+}
+
+/// Changes some fields of an expense, the fields left out keep their current value.
+/// The optional fields are cleared by sending them as null.
+#[derive(Clone, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchExpenseRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "expense name must not be empty or longer than 255 characters"
+    ))]
+    pub name: Option<String>,
+
+    #[validate(length(
+        max = 2000,
+        message = "expense description must not be longer than 2000 characters"
+    ))]
+    pub description: Option<String>,
+
+    pub currency_id: Option<Id>,
+
+    pub recurrence_id: Option<Id>,
+
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub predefined_expense_id: Option<Option<Id>>,
+
+    pub start_date: Option<String>,
+
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub end_date: Option<Option<String>>,
+
+    pub value: Option<MoneyAmount>,
+
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub category_id: Option<Option<Id>>,
+
+    /// Replaces every tag of the expense.
+    #[validate(custom = "validate_tags")]
+    pub tags: Option<Vec<String>>,
+
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub household_id: Option<Option<Id>>,
+
+    pub archived: Option<bool>,
 }
 
+/// Cancels an expense from the given date, it is not paid on this day anymore.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelExpenseRequest {
+    pub date: String,
+}
+
+/// Changes the price of an expense from the effective date, which can be in the future to schedule the change.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewExpensePriceRequest {
+    pub value: MoneyAmount,
+    pub currency_id: Id,
+    pub effective_from: String,
+}
+
+/// Pauses an expense from the start date until the end date (exclusive), or indefinitely if the end date is left out.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewExpensePauseRequest {
+    pub start_date: String,
+    pub end_date: Option<String>,
+}
+
+/// Splits the costs of an expense between the participants, replacing its previous split.
+/// The values of the shares are percentages adding up to 100, fixed amounts of every payment or weights,
+/// they are ignored by the equal split.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpenseSplitRequest {
+    pub method: SplitMethod,
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "an expense must be split between 1 and 50 participants"
+    ))]
+    pub shares: Vec<ExpenseShareRequest>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpenseShareRequest {
+    /// The participant has to belong to the owner or the household of the expense.
+    pub participant_id: Id,
+    pub value: Option<MoneyAmount>,
+}
+
+/// Narrows, orders and pages the expense listing, the category filter includes the subcategories of the category.
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpenseListQuery {
+    pub category_id: Option<Id>,
+    pub tag: Option<String>,
+    /// A part of the expense name.
+    pub name: Option<String>,
+    pub currency_id: Option<Id>,
+    pub recurrence_id: Option<Id>,
+    pub predefined_expense_id: Option<Id>,
+    pub household_id: Option<Id>,
+    /// Only the expenses that have not ended before this date, in the date format of the user.
+    pub from: Option<String>,
+    /// Only the expenses started until this date, in the date format of the user.
+    pub to: Option<String>,
+    #[serde(default)]
+    pub sort: ExpenseSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+    /// Embeds only this many of the latest transactions of each expense, every transaction is embedded if not given.
+    pub transactions: Option<u64>,
+    /// The archived expenses are left out unless this is set.
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExpenseSort {
+    #[default]
+    Created,
+    Name,
+    StartDate,
+    Value,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// The transactions of a deleted expense are moved to this other expense of the user, or deleted if it is not given.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteExpenseQuery {
+    pub reassign_to: Option<Id>,
+}
+
+/// The predefined expense is private by default, household visibility shares it with the members of the household,
+/// which the user must be a member of. Only administrators can make it public.
 #[derive(Clone, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct NewPredefinedExpenseRequest {
@@ -52,6 +220,15 @@ pub struct NewPredefinedExpenseRequest {
     pub value: MoneyAmount,
     pub currency_id: Id,
     pub recurrence_id: Id,
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "default category name must not be empty or longer than 255 characters"
+    ))]
+    pub default_category: Option<String>,
+    #[serde(default)]
+    pub visibility: PredefinedExpenseVisibility,
+    pub household_id: Option<Id>,
 }
 
 #[derive(Clone, Serialize, PartialEq)]
@@ -62,6 +239,10 @@ pub struct PredefinedExpenseResponse {
     pub value: MoneyAmount,
     pub currency: CurrencyResponse,
     pub recurrence: RecurrenceResponse,
+    pub default_category: Option<String>,
+    pub user_id: Option<Id>,
+    pub household_id: Option<Id>,
+    pub visibility: PredefinedExpenseVisibility,
 }
 
 pub type PredefinedExpenseResponseParts = (
@@ -78,10 +259,88 @@ impl From<PredefinedExpenseResponseParts> for PredefinedExpenseResponse {
             value: predefined_expense.value,
             currency: currency.into(),
             recurrence: recurrence.into(),
+            default_category: predefined_expense.default_category,
+            user_id: predefined_expense.user_id,
+            household_id: predefined_expense.household_id,
+            visibility: predefined_expense.visibility,
+        }
+    }
+}
+
+/// Whether the expense is paid currently.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpenseStatus {
+    Active,
+    Paused,
+    Ended,
+}
+
+#[derive(Clone, Serialize, PartialEq)]
+pub struct ExpensePauseResponse {
+    pub id: Id,
+    pub start_date: String,
+    pub end_date: Option<String>,
+}
+
+pub type ExpensePauseResponseParts = (expense_pause::Model, DateFormat);
+impl From<ExpensePauseResponseParts> for ExpensePauseResponse {
+    fn from((pause, date_format): ExpensePauseResponseParts) -> Self {
+        Self {
+            id: pause.id,
+            start_date: pause.start_date.format(date_format.pattern()).to_string(),
+            end_date: pause
+                .end_date
+                .map(|end_date| end_date.format(date_format.pattern()).to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, PartialEq)]
+pub struct ExpensePriceResponse {
+    pub id: Id,
+    pub value: MoneyAmount,
+    pub currency: CurrencyResponse,
+    pub effective_from: String,
+}
+
+pub type ExpensePriceResponseParts = (expense_price::Model, currency::Model, DateFormat);
+impl From<ExpensePriceResponseParts> for ExpensePriceResponse {
+    fn from((price, currency, date_format): ExpensePriceResponseParts) -> Self {
+        Self {
+            id: price.id,
+            value: price.value,
+            currency: currency.into(),
+            effective_from: price
+                .effective_from
+                .format(date_format.pattern())
+                .to_string(),
         }
     }
 }
 
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct ExpenseShareResponse {
+    pub participant: ParticipantResponse,
+    pub value: Option<MoneyAmount>,
+}
+
+pub type ExpenseShareResponseParts = (expense_share::Model, participant::Model);
+impl From<ExpenseShareResponseParts> for ExpenseShareResponse {
+    fn from((share, participant): ExpenseShareResponseParts) -> Self {
+        Self {
+            participant: participant.into(),
+            value: share.value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct ExpenseSplitResponse {
+    pub method: SplitMethod,
+    pub shares: Vec<ExpenseShareResponse>,
+}
+
 #[derive(Clone, Serialize, PartialEq)]
 pub struct ExpenseResponse {
     pub id: Id,
@@ -89,39 +348,111 @@ pub struct ExpenseResponse {
     pub description: String,
     pub value: MoneyAmount,
     pub start_date: String,
+    pub end_date: Option<String>,
+    pub status: ExpenseStatus,
+    pub current_value: MoneyAmount,
+    pub current_currency: CurrencyResponse,
     pub user_id: Id,
     pub currency: CurrencyResponse,
     pub recurrence: RecurrenceResponse,
     pub predefined_expense: Option<PredefinedExpenseResponse>,
+    pub category_id: Option<Id>,
+    pub household_id: Option<Id>,
+    pub tags: Vec<String>,
+    pub archived: bool,
     pub transactions: Vec<TransactionResponse>,
+    pub pauses: Vec<ExpensePauseResponse>,
+    pub prices: Vec<ExpensePriceResponse>,
+    pub split: Option<ExpenseSplitResponse>,
 }
 
+/// The value and currency of the price effective on the current day.
+pub type CurrentPriceParts = (MoneyAmount, currency::Model);
+
+/// The dates of the expense are rendered in the format preferred by the user.
+/// The value and currency of the expense are its price from its start date, the price changes come after them.
+/// The shares are only returned when the expense has a split method.
 pub type ExpenseResponseParts = (
     expense::Model,
     currency::Model,
     recurrence::Model,
     Option<PredefinedExpenseResponseParts>,
+    Vec<String>,
     Vec<TransactionResponseParts>,
+    Vec<expense_pause::Model>,
+    Vec<ExpensePriceResponseParts>,
+    Vec<ExpenseShareResponseParts>,
+    ExpenseStatus,
+    CurrentPriceParts,
+    DateFormat,
 );
 impl From<ExpenseResponseParts> for ExpenseResponse {
     fn from(
-        (expense, currency, recurrence, predefined_expense, transactions): ExpenseResponseParts,
+        (
+            expense,
+            currency,
+            recurrence,
+            predefined_expense,
+            tags,
+            transactions,
+            pauses,
+            prices,
+            shares,
+            status,
+            (current_value, current_currency),
+            date_format,
+        ): ExpenseResponseParts,
     ) -> Self {
         Self {
             id: expense.id,
             name: expense.name,
             description: expense.description,
             value: expense.value,
-            start_date: expense.start_date.to_string(),
+            start_date: expense.start_date.format(date_format.pattern()).to_string(),
+            end_date: expense
+                .end_date
+                .map(|end_date| end_date.format(date_format.pattern()).to_string()),
+            status,
+            current_value,
+            current_currency: current_currency.into(),
             user_id: expense.user_id,
             currency: currency.into(),
             recurrence: recurrence.into(),
             predefined_expense: predefined_expense
                 .map(|predefined_expense| predefined_expense.into()),
+            category_id: expense.category_id,
+            household_id: expense.household_id,
+            tags,
+            archived: expense.archived,
             transactions: transactions
                 .into_iter()
                 .map(|transaction| transaction.into())
                 .collect(),
+            pauses: pauses
+                .into_iter()
+                .map(|pause| (pause, date_format).into())
+                .collect(),
+            prices: prices.into_iter().map(|price| price.into()).collect(),
+            split: expense.split_method.map(|method| ExpenseSplitResponse {
+                method,
+                shares: shares.into_iter().map(|share| share.into()).collect(),
+            }),
         }
     }
 }
+
+/// Tag validation function, an expense or transaction can have a limited number of short tags.
+pub(super) fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > 20 {
+        return Err(ValidationError::new("there can be at most 20 tags"));
+    }
+    if tags
+        .iter()
+        .any(|tag| tag.trim().is_empty() || tag.chars().count() > 64)
+    {
+        return Err(ValidationError::new(
+            "tags must not be empty or longer than 64 characters",
+        ));
+    }
+    Ok(())
+}
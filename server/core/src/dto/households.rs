@@ -0,0 +1,55 @@
+use entity::household_member::HouseholdRole;
+use entity::{household, Id};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewHouseholdRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "household name must not be empty or longer than 255 characters"
+    ))]
+    pub name: String,
+}
+
+/// Invites the user registered with the email address to the household.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct HouseholdInvitationRequest {
+    #[validate(email(message = "email must be valid"))]
+    #[validate(length(
+        min = 1,
+        max = 320,
+        message = "email must be between 1 and 320 characters"
+    ))]
+    pub email: String,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HouseholdMemberResponse {
+    pub user_id: Id,
+    pub username: String,
+    pub role: HouseholdRole,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HouseholdResponse {
+    pub id: Id,
+    pub name: String,
+    pub members: Vec<HouseholdMemberResponse>,
+}
+
+pub type HouseholdResponseParts = (household::Model, Vec<HouseholdMemberResponse>);
+impl From<HouseholdResponseParts> for HouseholdResponse {
+    fn from((household, members): HouseholdResponseParts) -> Self {
+        Self {
+            id: household.id,
+            name: household.name,
+            members,
+        }
+    }
+}
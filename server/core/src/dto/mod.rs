@@ -1,5 +1,16 @@
+pub mod api_tokens;
+pub mod attachments;
+pub mod balances;
+pub mod categories;
 pub mod currencies;
 pub mod expenses;
+pub mod households;
+pub mod oidc;
+pub mod participants;
 pub mod recurrences;
+pub mod security_events;
+pub mod sessions;
 pub mod transactions;
+pub mod trash;
+pub mod two_factor;
 pub mod users;
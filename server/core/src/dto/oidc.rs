@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::OidcProviderConfig;
+
+/// The parameters the provider redirects the user back to the callback with.
+#[derive(Deserialize, Clone)]
+pub struct OidcCallbackQuery {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcProviderResponse {
+    pub name: String,
+    pub display_name: String,
+}
+
+impl From<&OidcProviderConfig> for OidcProviderResponse {
+    fn from(provider: &OidcProviderConfig) -> Self {
+        Self {
+            name: provider.name.clone(),
+            display_name: provider.display_name.clone(),
+        }
+    }
+}
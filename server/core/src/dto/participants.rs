@@ -0,0 +1,95 @@
+use entity::{currency, participant, Id, MoneyAmount};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::currencies::CurrencyResponse;
+
+/// The participant is shared in the household if it is given, the user must be a member of it.
+/// The linked account has to be the user or someone sharing a household with them.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewParticipantRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "participant name must not be empty or longer than 255 characters"
+    ))]
+    pub name: String,
+    pub household_id: Option<Id>,
+    pub account_id: Option<Id>,
+}
+
+/// Renames the participant and replaces its linked account, the owner can not be changed.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateParticipantRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "participant name must not be empty or longer than 255 characters"
+    ))]
+    pub name: String,
+    pub account_id: Option<Id>,
+}
+
+/// Narrows the contribution report to a period in the date format of the user, both ends are included.
+/// The report covers the expenses of the household if it is given, the own expenses of the user otherwise.
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub household_id: Option<Id>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantResponse {
+    pub id: Id,
+    pub name: String,
+    pub household_id: Option<Id>,
+    pub account_id: Option<Id>,
+}
+
+impl From<participant::Model> for ParticipantResponse {
+    fn from(participant: participant::Model) -> Self {
+        Self {
+            id: participant.id,
+            name: participant.name,
+            household_id: participant.household_id,
+            account_id: participant.account_id,
+        }
+    }
+}
+
+/// The payments of a participant in one currency, the percentage is their part of every payment in that currency.
+#[derive(Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionResponse {
+    pub participant: ParticipantResponse,
+    pub currency: CurrencyResponse,
+    pub total: MoneyAmount,
+    pub transactions: u64,
+    pub percentage: MoneyAmount,
+}
+
+pub type ContributionResponseParts = (
+    participant::Model,
+    currency::Model,
+    MoneyAmount,
+    u64,
+    MoneyAmount,
+);
+impl From<ContributionResponseParts> for ContributionResponse {
+    fn from(
+        (participant, currency, total, transactions, percentage): ContributionResponseParts,
+    ) -> Self {
+        Self {
+            participant: participant.into(),
+            currency: currency.into(),
+            total,
+            transactions,
+            percentage,
+        }
+    }
+}
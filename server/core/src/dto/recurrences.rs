@@ -1,5 +1,23 @@
 use entity::{recurrence, Id};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurrenceRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+    #[validate(range(
+        min = 0.01,
+        max = 366.0,
+        message = "per year must be between 0.01 and 366"
+    ))]
+    pub per_year: f64,
+}
 
 #[derive(Clone, Serialize, PartialEq)]
 pub struct RecurrenceResponse {
@@ -0,0 +1,28 @@
+use entity::security_event;
+use entity::Id;
+use sea_orm::prelude::DateTimeLocal;
+use serde::Serialize;
+
+pub use entity::security_event::SecurityEventType;
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityEventResponse {
+    pub id: Id,
+    pub event_type: SecurityEventType,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTimeLocal,
+}
+
+impl From<security_event::Model> for SecurityEventResponse {
+    fn from(security_event: security_event::Model) -> Self {
+        Self {
+            id: security_event.id,
+            event_type: security_event.event_type,
+            ip_address: security_event.ip_address,
+            user_agent: security_event.user_agent,
+            created_at: security_event.created_at,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use entity::{session, Id};
+use sea_orm::prelude::DateTimeLocal;
+use serde::Serialize;
+
+/// Information about the client a request originated from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct SessionResponse {
+    pub id: Id,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTimeLocal,
+    pub last_seen_at: DateTimeLocal,
+    pub expires_at: DateTimeLocal,
+    pub current: bool,
+}
+
+/// A session and whether it is the one the request was made with.
+pub type SessionResponseParts = (session::Model, bool);
+impl From<SessionResponseParts> for SessionResponse {
+    fn from((session, current): SessionResponseParts) -> Self {
+        Self {
+            id: session.id,
+            ip_address: session.ip_address,
+            user_agent: session.user_agent,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            expires_at: session.expires_at,
+            current,
+        }
+    }
+}
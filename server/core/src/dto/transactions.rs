@@ -1,19 +1,17 @@
-use entity::{currency, transaction, Id, MoneyAmount};
-use sea_orm::entity::prelude::*;
+use entity::user::DateFormat;
+use entity::{currency, participant, transaction, Id, MoneyAmount};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use super::currencies::CurrencyResponse;
+use super::expenses::validate_tags;
+use super::participants::ParticipantResponse;
 
 #[derive(Deserialize, Validate, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct NewTransactionRequest {
-    #[validate(length(
-        min = 1,
-        max = 255,
-        message = "donor name must not be empty or longer than 255 characters"
-    ))]
-    pub donor_name: String,
+    /// The participant who paid, who has to belong to the owner or the household of the expense.
+    pub participant_id: Id,
 
     pub currency_id: Id,
 
@@ -22,26 +20,45 @@ pub struct NewTransactionRequest {
     pub date: String,
 
     pub expense_id: Id,
+
+    pub category_id: Option<Id>,
+
+    #[serde(default)]
+    #[validate(custom = "validate_tags")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Clone, Serialize, PartialEq, Eq)]
 pub struct TransactionResponse {
     pub id: Id,
-    pub donor_name: String,
+    pub participant: ParticipantResponse,
     pub value: MoneyAmount,
-    pub date: Date,
+    pub date: String,
     pub currency: CurrencyResponse,
+    pub category_id: Option<Id>,
+    pub tags: Vec<String>,
 }
 
-pub type TransactionResponseParts = (transaction::Model, currency::Model);
+/// The date of the transaction is rendered in the format preferred by the user.
+pub type TransactionResponseParts = (
+    transaction::Model,
+    currency::Model,
+    participant::Model,
+    Vec<String>,
+    DateFormat,
+);
 impl From<TransactionResponseParts> for TransactionResponse {
-    fn from((transaction, currency): TransactionResponseParts) -> Self {
+    fn from(
+        (transaction, currency, participant, tags, date_format): TransactionResponseParts,
+    ) -> Self {
         Self {
             id: transaction.id,
-            donor_name: transaction.donor_name,
+            participant: participant.into(),
             value: transaction.value,
-            date: transaction.date,
+            date: transaction.date.format(date_format.pattern()).to_string(),
             currency: currency.into(),
+            category_id: transaction.category_id,
+            tags,
         }
     }
 }
@@ -0,0 +1,86 @@
+use chrono::Duration;
+use entity::user::DateFormat;
+use entity::{currency, expense, participant, transaction, Id, MoneyAmount};
+use sea_orm::prelude::DateTimeLocal;
+use serde::Serialize;
+
+use super::currencies::CurrencyResponse;
+use super::participants::ParticipantResponse;
+
+/// The deleted expenses and transactions of a user, which can still be restored.
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct TrashResponse {
+    pub expenses: Vec<TrashedExpenseResponse>,
+    pub transactions: Vec<TrashedTransactionResponse>,
+}
+
+#[derive(Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedExpenseResponse {
+    pub id: Id,
+    pub name: String,
+    pub value: MoneyAmount,
+    pub currency: CurrencyResponse,
+    pub deleted_at: DateTimeLocal,
+    pub purged_at: DateTimeLocal,
+}
+
+/// The expense is purged when the retention period passes after its deletion.
+pub type TrashedExpenseResponseParts = (expense::Model, currency::Model, Duration);
+impl From<TrashedExpenseResponseParts> for TrashedExpenseResponse {
+    fn from((expense, currency, retention): TrashedExpenseResponseParts) -> Self {
+        let deleted_at = expense
+            .deleted_at
+            .expect("only deleted expenses should be in the trash");
+        Self {
+            id: expense.id,
+            name: expense.name,
+            value: expense.value,
+            currency: currency.into(),
+            deleted_at,
+            purged_at: deleted_at + retention,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedTransactionResponse {
+    pub id: Id,
+    pub expense_id: Id,
+    pub participant: ParticipantResponse,
+    pub value: MoneyAmount,
+    pub currency: CurrencyResponse,
+    pub date: String,
+    pub deleted_at: DateTimeLocal,
+    pub purged_at: DateTimeLocal,
+}
+
+/// The transaction is purged when the retention period passes after its deletion,
+/// its date is rendered in the format preferred by the user.
+pub type TrashedTransactionResponseParts = (
+    transaction::Model,
+    currency::Model,
+    participant::Model,
+    Duration,
+    DateFormat,
+);
+impl From<TrashedTransactionResponseParts> for TrashedTransactionResponse {
+    fn from(
+        (transaction, currency, participant, retention, date_format): TrashedTransactionResponseParts,
+    ) -> Self {
+        let deleted_at = transaction
+            .deleted_at
+            .expect("only deleted transactions should be in the trash");
+        Self {
+            id: transaction.id,
+            expense_id: transaction.expense_id,
+            participant: participant.into(),
+            value: transaction.value,
+            currency: currency.into(),
+            date: transaction.date.format(date_format.pattern()).to_string(),
+            deleted_at,
+            purged_at: deleted_at + retention,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// A code proving the possession of the second factor, either a current TOTP code or a recovery code.
+#[derive(Clone, Deserialize, Validate)]
+pub struct TwoFactorCodeRequest {
+    #[validate(length(
+        min = 1,
+        max = 32,
+        message = "code must be between 1 and 32 characters"
+    ))]
+    pub code: String,
+}
+
+/// Finishes a login of a user with two-factor authentication enabled.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorLoginRequest {
+    #[validate(length(min = 1, message = "pending token must not be empty"))]
+    pub pending_token: String,
+    #[validate(length(
+        min = 1,
+        max = 32,
+        message = "code must be between 1 and 32 characters"
+    ))]
+    pub code: String,
+}
+
+/// The shared secret of a new TOTP enrollment, the uri can be shown to authenticator apps as a QR code.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct TwoFactorEnrollmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// Single-use recovery codes, they are only ever shown once.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// The result of the first login step. If two-factor authentication is required,
+/// no session is created yet, the pending token has to be sent back together with a code instead.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct LoginResponse {
+    pub two_factor_required: bool,
+    pub pending_token: Option<String>,
+}
@@ -1,14 +1,20 @@
-use entity::{user, Id};
+use entity::user::{self, DateFormat, FirstDayOfWeek, UserRole};
+use entity::Id;
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationError};
 
+use super::expenses::ExpenseResponse;
+
 lazy_static! {
     /// Password validation regex.
     static ref PASSWORD_REGEX: Regex =
         Regex::new(r"^(?=.*[a-z])(?=.*[A-Z])(?=.*\d)(?=.*[@$!%*?&])[A-Za-z\d@$!%*?&]{8,120}$")
             .expect("incorrect password regex");
+    /// Locale validation regex, accepting BCP 47 language tags like `en` or `hu-HU`.
+    static ref LOCALE_REGEX: Regex =
+        Regex::new(r"^[a-zA-Z]{2,3}(-[a-zA-Z0-9]{2,8})*$").expect("incorrect locale regex");
 }
 
 #[derive(Deserialize, Validate, Clone)]
@@ -52,10 +58,104 @@ pub struct NewUserRequest {
     pub confirm_password: String,
 }
 
-/// Password validation function supplied to `NewUserRequest`.
+#[derive(Deserialize, Validate, Clone)]
+pub struct DeleteAccountRequest {
+    #[validate(length(min = 1, message = "password must not be empty"))]
+    pub password: String,
+}
+
+#[derive(Deserialize, Validate, Clone)]
+pub struct ResendActivationRequest {
+    #[validate(length(
+        min = 1,
+        max = 320,
+        message = "email must be between 1 and 320 characters"
+    ))]
+    pub email: String,
+}
+
+#[derive(Deserialize, Validate, Clone)]
+pub struct ForgotPasswordRequest {
+    #[validate(length(
+        min = 1,
+        max = 320,
+        message = "email must be between 1 and 320 characters"
+    ))]
+    pub email: String,
+}
+
+#[derive(Deserialize, Validate, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordRequest {
+    #[validate(length(
+        min = 1,
+        max = 36,
+        message = "token must be between 1 and 36 characters"
+    ))]
+    pub token: String,
+    #[validate(custom = "validate_password")]
+    pub password: String,
+    #[validate(must_match(
+        other = "password",
+        message = "password and password confirmation must match"
+    ))]
+    pub confirm_password: String,
+}
+
+#[derive(Deserialize, Validate, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordRequest {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "current password must be between 1 and 255 characters"
+    ))]
+    pub current_password: String,
+    #[validate(custom = "validate_password")]
+    pub password: String,
+    #[validate(must_match(
+        other = "password",
+        message = "password and password confirmation must match"
+    ))]
+    pub confirm_password: String,
+}
+
+#[derive(Deserialize, Validate, Clone)]
+pub struct ChangeEmailRequest {
+    #[validate(email(message = "email must be valid"))]
+    #[validate(length(
+        min = 1,
+        max = 320,
+        message = "email must be between 1 and 320 characters"
+    ))]
+    pub email: String,
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "password must be between 1 and 255 characters"
+    ))]
+    pub password: String,
+}
+
+#[derive(Deserialize, Validate, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProfileRequest {
+    pub preferred_currency_id: Option<Id>,
+    #[validate(length(max = 35, message = "locale must not be longer than 35 characters"))]
+    #[validate(custom = "validate_locale")]
+    pub locale: String,
+    #[validate(custom = "validate_timezone")]
+    pub timezone: String,
+    pub date_format: DateFormat,
+    pub first_day_of_week: FirstDayOfWeek,
+}
+
+/// Password validation function supplied to requests which set a new password.
 fn validate_password(value: &str) -> Result<(), ValidationError> {
     let Ok(result) = PASSWORD_REGEX.is_match(value) else {
-        return Err(ValidationError::new("password cannot be matched against regex"));
+        return Err(ValidationError::new(
+            "password cannot be matched against regex",
+        ));
     };
     if !result {
         return Err(ValidationError::new("password must be at least 8 characters long and contain at least one uppercase letter, one lowercase letter, one number and one special character"));
@@ -63,11 +163,38 @@ fn validate_password(value: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Locale validation function, the locale has to be a well-formed language tag.
+fn validate_locale(value: &str) -> Result<(), ValidationError> {
+    match LOCALE_REGEX.is_match(value) {
+        Ok(true) => Ok(()),
+        _ => Err(ValidationError::new(
+            "locale must be a language tag like en or hu-HU",
+        )),
+    }
+}
+
+/// Timezone validation function, the timezone has to be a name from the IANA timezone database.
+fn validate_timezone(value: &str) -> Result<(), ValidationError> {
+    match value.parse::<chrono_tz::Tz>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ValidationError::new(
+            "timezone must be an IANA timezone like Europe/Budapest",
+        )),
+    }
+}
+
 #[derive(Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct UserResponse {
     pub id: Id,
     pub email: String,
     pub username: String,
+    pub role: UserRole,
+    pub preferred_currency_id: Option<Id>,
+    pub locale: String,
+    pub timezone: String,
+    pub date_format: DateFormat,
+    pub first_day_of_week: FirstDayOfWeek,
 }
 
 impl From<user::Model> for UserResponse {
@@ -76,6 +203,51 @@ impl From<user::Model> for UserResponse {
             id: user.id,
             email: user.email,
             username: user.username,
+            role: user.role,
+            preferred_currency_id: user.preferred_currency_id,
+            locale: user.locale,
+            timezone: user.timezone,
+            date_format: user.date_format,
+            first_day_of_week: user.first_day_of_week,
+        }
+    }
+}
+
+/// A user as seen by the administrators, including the state of the account.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct UserAccountResponse {
+    pub id: Id,
+    pub email: String,
+    pub username: String,
+    pub activated: bool,
+    pub role: UserRole,
+}
+
+impl From<user::Model> for UserAccountResponse {
+    fn from(user: user::Model) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            username: user.username,
+            activated: user.activated,
+            role: user.role,
+        }
+    }
+}
+
+/// Every personal data stored about a user, served as a downloadable archive.
+#[derive(Clone, Serialize, PartialEq)]
+pub struct UserDataExportResponse {
+    pub user: UserResponse,
+    pub expenses: Vec<ExpenseResponse>,
+}
+
+pub type UserDataExportResponseParts = (user::Model, Vec<ExpenseResponse>);
+impl From<UserDataExportResponseParts> for UserDataExportResponse {
+    fn from((user, expenses): UserDataExportResponseParts) -> Self {
+        Self {
+            user: user.into(),
+            expenses,
         }
     }
 }
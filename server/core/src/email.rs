@@ -20,6 +20,30 @@ pub const ACTIVATION_EMAIL_TEMPLATE: &str =
 #[cfg(doctest)]
 pub const ACTIVATION_EMAIL_TEMPLATE: &str =
     include_str!("../../resources/email/activation_email.html");
+#[cfg(not(doctest))]
+pub const PASSWORD_RESET_EMAIL_TEMPLATE: &str =
+    proc_macros::include_email_template!("./resources/email/password_reset_email.html");
+#[cfg(doctest)]
+pub const PASSWORD_RESET_EMAIL_TEMPLATE: &str =
+    include_str!("../../resources/email/password_reset_email.html");
+#[cfg(not(doctest))]
+pub const EMAIL_CHANGE_EMAIL_TEMPLATE: &str =
+    proc_macros::include_email_template!("./resources/email/email_change_email.html");
+#[cfg(doctest)]
+pub const EMAIL_CHANGE_EMAIL_TEMPLATE: &str =
+    include_str!("../../resources/email/email_change_email.html");
+#[cfg(not(doctest))]
+pub const CREDENTIALS_CHANGED_EMAIL_TEMPLATE: &str =
+    proc_macros::include_email_template!("./resources/email/credentials_changed_email.html");
+#[cfg(doctest)]
+pub const CREDENTIALS_CHANGED_EMAIL_TEMPLATE: &str =
+    include_str!("../../resources/email/credentials_changed_email.html");
+#[cfg(not(doctest))]
+pub const HOUSEHOLD_INVITATION_EMAIL_TEMPLATE: &str =
+    proc_macros::include_email_template!("./resources/email/household_invitation_email.html");
+#[cfg(doctest)]
+pub const HOUSEHOLD_INVITATION_EMAIL_TEMPLATE: &str =
+    include_str!("../../resources/email/household_invitation_email.html");
 
 pub type MailTransport = AsyncSmtpTransport<Tokio1Executor>;
 
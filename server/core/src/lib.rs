@@ -3,6 +3,8 @@ pub mod database;
 pub mod dto;
 pub mod email;
 pub mod logic;
+pub mod oidc;
+pub mod storage;
 
 pub use entity::Id;
 pub use sea_orm::DatabaseConnection;
@@ -0,0 +1,293 @@
+use self::errors::RevokeApiTokenError;
+
+use super::common::{find_entity_by_id, hash_token};
+use super::user_operations::authorize_user;
+use crate::dto::api_tokens::{ApiTokenResponse, NewApiTokenRequest, NewApiTokenResponse};
+
+use chrono::{Duration, Local};
+use entity::api_token::{self, Entity as ApiToken};
+use entity::Id;
+use sea_orm::prelude::Uuid;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+
+/// Every API token starts with this, so leaked tokens are easy to recognize.
+const API_TOKEN_PREFIX: &str = "vrz_";
+/// The last use of a token is only persisted if the previous one is older than this,
+/// so not every request results in a database write.
+const LAST_USED_REFRESH_INTERVAL_SECONDS: i64 = 60;
+
+/// Creates a new API token for the user. The token is returned only this once, only its hash is stored.
+pub async fn create_api_token(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    req: NewApiTokenRequest,
+) -> Result<NewApiTokenResponse, DbErr> {
+    let now = Local::now();
+    let token = generate_api_token();
+    let api_token = api_token::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        name: Set(req.name),
+        token_hash: Set(hash_token(&token)),
+        scope: Set(req.scope),
+        created_at: Set(now),
+        expires_at: Set(req.expires_in_days.map(|days| now + Duration::days(days))),
+        last_used_at: Set(None),
+    };
+    let api_token = api_token.insert(conn).await?;
+    Ok((api_token, token).into())
+}
+
+/// Lists the API tokens of the user, the newest first.
+pub async fn find_api_tokens_by_user_id(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<Vec<ApiTokenResponse>, DbErr> {
+    let api_tokens = ApiToken::find()
+        .filter(api_token::Column::UserId.eq(user_id))
+        .order_by_desc(api_token::Column::CreatedAt)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(ApiTokenResponse::from)
+        .collect();
+    Ok(api_tokens)
+}
+
+/// Revokes an API token of the user by its id.
+pub async fn revoke_api_token(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    api_token_id: Id,
+) -> Result<(), RevokeApiTokenError> {
+    let Some(api_token) = find_entity_by_id::<api_token::Entity>(conn, api_token_id).await? else {
+        return Err(RevokeApiTokenError::InvalidApiToken);
+    };
+    authorize_user(user_id, api_token.user_id)?;
+
+    ApiToken::delete_by_id(api_token_id).exec(conn).await?;
+    Ok(())
+}
+
+/// Revokes every API token of the user, used when the account is deactivated.
+/// Can be used inside a database transaction.
+pub async fn revoke_api_tokens_by_user_id<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+) -> Result<(), DbErr> {
+    ApiToken::delete_many()
+        .filter(api_token::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// Finds the API token matching the one sent by the client if it is not expired and marks it as used.
+/// An expired token is deleted and `None` is returned.
+pub async fn authenticate_api_token(
+    conn: &DatabaseConnection,
+    token: &str,
+) -> Result<Option<api_token::Model>, DbErr> {
+    if !token.starts_with(API_TOKEN_PREFIX) {
+        return Ok(None);
+    }
+    let Some(api_token) = ApiToken::find()
+        .filter(api_token::Column::TokenHash.eq(hash_token(token)))
+        .one(conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let now = Local::now();
+    if matches!(api_token.expires_at, Some(expires_at) if expires_at < now) {
+        ApiToken::delete_by_id(api_token.id).exec(conn).await?;
+        return Ok(None);
+    }
+
+    let refresh_due = match api_token.last_used_at {
+        Some(last_used_at) => {
+            now - last_used_at > Duration::seconds(LAST_USED_REFRESH_INTERVAL_SECONDS)
+        }
+        None => true,
+    };
+    if refresh_due {
+        ApiToken::update_many()
+            .col_expr(api_token::Column::LastUsedAt, Expr::value(now))
+            .filter(api_token::Column::Id.eq(api_token.id))
+            .exec(conn)
+            .await?;
+    }
+    Ok(Some(api_token))
+}
+
+fn generate_api_token() -> String {
+    format!(
+        "{}{}{}",
+        API_TOKEN_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use thiserror::Error;
+
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum RevokeApiTokenError {
+        #[error("api token id is invalid")]
+        InvalidApiToken,
+        #[error("{0}")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logic::{
+        common::tests::{exec_ok, test_api_token, test_db_error, TEST_ID, TEST_STR},
+        user_operations::errors::AuthorizeUserError,
+    };
+
+    use super::*;
+    use assert2::{check, let_assert};
+    use entity::api_token::ApiTokenScope;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn new_api_token_request() -> NewApiTokenRequest {
+        NewApiTokenRequest {
+            name: TEST_STR.to_string(),
+            scope: ApiTokenScope::ReadOnly,
+            expires_in_days: Some(30),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_api_token_all_cases() {
+        let api_token = test_api_token();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![api_token.clone()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, db_error) = tokio::join!(
+            create_api_token(&conn, TEST_ID, new_api_token_request()),
+            create_api_token(&conn, TEST_ID, new_api_token_request())
+        );
+
+        let_assert!(Ok(created) = happy_case);
+        check!(created.token.starts_with(API_TOKEN_PREFIX));
+        check!(created.api_token == api_token.into());
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn find_api_tokens_by_user_id_all_cases() {
+        let api_token = test_api_token();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![api_token.clone()], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (api_tokens, empty_vec, db_error) = tokio::join!(
+            find_api_tokens_by_user_id(&conn, TEST_ID),
+            find_api_tokens_by_user_id(&conn, TEST_ID),
+            find_api_tokens_by_user_id(&conn, TEST_ID)
+        );
+
+        check!(api_tokens == Ok(vec![api_token.into()]));
+        check!(empty_vec == Ok(vec![]));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn revoke_api_token_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_api_token()]])
+            .append_exec_results(vec![exec_ok()])
+            // api token not found
+            .append_query_results(vec![Vec::<api_token::Model>::new()])
+            // user unauthorized
+            .append_query_results(vec![vec![test_api_token()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, user_unauthorized, db_error) = tokio::join!(
+            revoke_api_token(&conn, TEST_ID, TEST_ID),
+            revoke_api_token(&conn, TEST_ID, TEST_ID),
+            revoke_api_token(&conn, TEST_ID + 1, TEST_ID),
+            revoke_api_token(&conn, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(RevokeApiTokenError::InvalidApiToken));
+        check!(user_unauthorized == Err(RevokeApiTokenError::UserUnauthorized(AuthorizeUserError)));
+        check!(db_error == Err(RevokeApiTokenError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn authenticate_api_token_all_cases() {
+        let token = generate_api_token();
+        let recent_api_token = api_token::Model {
+            last_used_at: Some(Local::now()),
+            ..test_api_token()
+        };
+        let unused_api_token = test_api_token();
+        let expired_api_token = api_token::Model {
+            expires_at: Some(Local::now() - Duration::minutes(1)),
+            ..test_api_token()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // recently used token
+            .append_query_results(vec![vec![recent_api_token.clone()]])
+            // token not used recently, last use gets updated
+            .append_query_results(vec![vec![unused_api_token.clone()]])
+            .append_exec_results(vec![exec_ok()])
+            // expired token gets deleted
+            .append_query_results(vec![vec![expired_api_token]])
+            .append_exec_results(vec![exec_ok()])
+            // token not found
+            .append_query_results(vec![Vec::<api_token::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (recent, unused, expired, not_found, db_error, malformed) = tokio::join!(
+            authenticate_api_token(&conn, &token),
+            authenticate_api_token(&conn, &token),
+            authenticate_api_token(&conn, &token),
+            authenticate_api_token(&conn, &token),
+            authenticate_api_token(&conn, &token),
+            authenticate_api_token(&conn, TEST_STR)
+        );
+
+        check!(recent == Ok(Some(recent_api_token)));
+        check!(unused == Ok(Some(unused_api_token)));
+        check!(expired == Ok(None));
+        check!(not_found == Ok(None));
+        check!(db_error == Err(test_db_error()));
+        check!(malformed == Ok(None));
+    }
+
+    #[test]
+    fn api_tokens_are_hashed_deterministically() {
+        let token = generate_api_token();
+
+        check!(token.len() == API_TOKEN_PREFIX.len() + 64);
+        check!(hash_token(&token) == hash_token(&token));
+        check!(hash_token(&token) != hash_token(&generate_api_token()));
+    }
+}
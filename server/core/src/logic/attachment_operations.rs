@@ -0,0 +1,682 @@
+use self::errors::{
+    DeleteAttachmentError, DownloadAttachmentError, FindAttachmentsError, OrphanAttachmentError,
+    UploadAttachmentError,
+};
+use super::common::find_entity_by_id;
+use super::expense_operations::find_expense_by_id;
+use super::household_operations::can_access_expense;
+use super::user_operations::errors::AuthorizeUserError;
+use crate::config::AttachmentConfig;
+use crate::dto::attachments::{AttachmentContent, AttachmentResponse, NewAttachmentRequest};
+use crate::storage::Storage;
+
+use chrono::Local;
+use entity::attachment::{self, Entity as Attachment};
+use entity::user::Entity as User;
+use entity::{expense, transaction, Id};
+
+use migration::DbErr;
+use sea_orm::sea_query::{Alias, Expr, Func, SimpleExpr};
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use tracing::error;
+use uuid::Uuid;
+
+/// File names are cut to this length, as the column does not allow longer ones.
+const MAX_FILE_NAME_LENGTH: usize = 255;
+/// The name of the attachment if the uploaded file had none.
+const DEFAULT_FILE_NAME: &str = "attachment";
+
+/// The expense or the transaction a file is attached to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachmentParent {
+    Expense(Id),
+    Transaction(Id),
+}
+
+impl TryFrom<&attachment::Model> for AttachmentParent {
+    type Error = OrphanAttachmentError;
+
+    fn try_from(attachment: &attachment::Model) -> Result<Self, Self::Error> {
+        match (attachment.expense_id, attachment.transaction_id) {
+            (Some(expense_id), _) => Ok(Self::Expense(expense_id)),
+            (None, Some(transaction_id)) => Ok(Self::Transaction(transaction_id)),
+            (None, None) => Err(OrphanAttachmentError),
+        }
+    }
+}
+
+/// Attaches the uploaded file to an expense or a transaction the user can access.
+/// Only the configured types of files are accepted, the type is recognized from the content of the file.
+/// The size of the file is counted against the quota of the uploader. The uploads of the same user are checked
+/// against the quota one after the other, so concurrent uploads can not exceed it together.
+pub async fn upload_attachment(
+    conn: &DatabaseConnection,
+    storage: &dyn Storage,
+    config: &AttachmentConfig,
+    user_id: Id,
+    parent: AttachmentParent,
+    req: NewAttachmentRequest,
+) -> Result<AttachmentResponse, UploadAttachmentError> {
+    let Some(expense) = find_parent_expense(conn, parent).await? else {
+        return Err(UploadAttachmentError::ParentNotFound);
+    };
+    if !can_access_expense(conn, user_id, &expense).await? {
+        return Err(UploadAttachmentError::UserUnauthorized(AuthorizeUserError));
+    }
+
+    let size = req.data.len() as u64;
+    if size > config.max_file_size_bytes {
+        return Err(UploadAttachmentError::FileTooLarge);
+    }
+    let content_type = detect_content_type(&req.data).filter(|content_type| {
+        config
+            .allowed_content_types
+            .iter()
+            .any(|allowed| allowed == content_type)
+    });
+    let Some(content_type) = content_type else {
+        return Err(UploadAttachmentError::UnsupportedFileType);
+    };
+
+    let storage_key = format!("{user_id}/{}", Uuid::new_v4());
+    storage.put(&storage_key, content_type, req.data).await?;
+    let (expense_id, transaction_id) = match parent {
+        AttachmentParent::Expense(expense_id) => (Some(expense_id), None),
+        AttachmentParent::Transaction(transaction_id) => (None, Some(transaction_id)),
+    };
+    let attachment = attachment::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        expense_id: Set(expense_id),
+        transaction_id: Set(transaction_id),
+        file_name: Set(sanitize_file_name(&req.file_name)),
+        content_type: Set(content_type.to_string()),
+        size: Set(size),
+        storage_key: Set(storage_key.clone()),
+        created_at: Set(Local::now()),
+    };
+    let user_quota_bytes = config.user_quota_bytes;
+    let inserted = conn
+        .transaction::<_, attachment::Model, UploadAttachmentError>(|txn| {
+            Box::pin(async move {
+                // The row of the uploader stays locked until the attachment is inserted
+                User::find_by_id(user_id).lock_exclusive().one(txn).await?;
+                if used_storage(txn, user_id).await? + size > user_quota_bytes {
+                    return Err(UploadAttachmentError::QuotaExceeded);
+                }
+                Ok(attachment.insert(txn).await?)
+            })
+        })
+        .await;
+    match inserted {
+        Ok(attachment) => Ok(attachment.into()),
+        Err(e) => {
+            delete_stored_files(storage, &[storage_key]).await;
+            Err(e.into())
+        }
+    }
+}
+
+/// Lists the attachments of an expense or a transaction the user can access, in the order of their upload.
+pub async fn find_attachments(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    parent: AttachmentParent,
+) -> Result<Vec<AttachmentResponse>, FindAttachmentsError> {
+    let Some(expense) = find_parent_expense(conn, parent).await? else {
+        return Err(FindAttachmentsError::ParentNotFound);
+    };
+    if !can_access_expense(conn, user_id, &expense).await? {
+        return Err(FindAttachmentsError::UserUnauthorized(AuthorizeUserError));
+    }
+
+    let parent_filter = match parent {
+        AttachmentParent::Expense(expense_id) => attachment::Column::ExpenseId.eq(expense_id),
+        AttachmentParent::Transaction(transaction_id) => {
+            attachment::Column::TransactionId.eq(transaction_id)
+        }
+    };
+    let attachments = Attachment::find()
+        .filter(parent_filter)
+        .order_by_asc(attachment::Column::CreatedAt)
+        .order_by_asc(attachment::Column::Id)
+        .all(conn)
+        .await?;
+    Ok(attachments
+        .into_iter()
+        .map(|attachment| attachment.into())
+        .collect())
+}
+
+/// Reads the content of an attachment, if the user can access the expense or the transaction it belongs to.
+pub async fn download_attachment(
+    conn: &DatabaseConnection,
+    storage: &dyn Storage,
+    user_id: Id,
+    attachment_id: Id,
+) -> Result<AttachmentContent, DownloadAttachmentError> {
+    let attachment = find_entity_by_id::<attachment::Entity>(conn, attachment_id).await?;
+    let Some(attachment) = attachment else {
+        return Err(DownloadAttachmentError::AttachmentNotFound);
+    };
+    let Ok(parent) = AttachmentParent::try_from(&attachment) else {
+        return Err(DownloadAttachmentError::AttachmentNotFound);
+    };
+    let Some(expense) = find_parent_expense(conn, parent).await? else {
+        return Err(DownloadAttachmentError::AttachmentNotFound);
+    };
+    if !can_access_expense(conn, user_id, &expense).await? {
+        return Err(DownloadAttachmentError::UserUnauthorized(
+            AuthorizeUserError,
+        ));
+    }
+
+    let data = storage.get(&attachment.storage_key).await?;
+    Ok(AttachmentContent {
+        file_name: attachment.file_name,
+        content_type: attachment.content_type,
+        data,
+    })
+}
+
+/// Deletes an attachment, if the user can access the expense or the transaction it belongs to.
+/// The file is removed from the storage first, so the attachment can be deleted again if that fails.
+pub async fn delete_attachment(
+    conn: &DatabaseConnection,
+    storage: &dyn Storage,
+    user_id: Id,
+    attachment_id: Id,
+) -> Result<(), DeleteAttachmentError> {
+    let attachment = find_entity_by_id::<attachment::Entity>(conn, attachment_id).await?;
+    let Some(attachment) = attachment else {
+        return Err(DeleteAttachmentError::AttachmentNotFound);
+    };
+    let Ok(parent) = AttachmentParent::try_from(&attachment) else {
+        return Err(DeleteAttachmentError::AttachmentNotFound);
+    };
+    let Some(expense) = find_parent_expense(conn, parent).await? else {
+        return Err(DeleteAttachmentError::AttachmentNotFound);
+    };
+    if !can_access_expense(conn, user_id, &expense).await? {
+        return Err(DeleteAttachmentError::UserUnauthorized(AuthorizeUserError));
+    }
+
+    storage.delete(&attachment.storage_key).await?;
+    Attachment::delete_by_id(attachment.id).exec(conn).await?;
+    Ok(())
+}
+
+/// Removes the files of deleted attachments from the storage.
+/// A failure is only logged, as the attachments are already gone and the file can not be reached anymore.
+pub(super) async fn delete_stored_files(storage: &dyn Storage, storage_keys: &[String]) {
+    for storage_key in storage_keys {
+        if let Err(e) = storage.delete(storage_key).await {
+            error!("Attachment file {storage_key} could not be deleted: {e}");
+        }
+    }
+}
+
+/// Sums the sizes of the attachments uploaded by the user.
+async fn used_storage<C: ConnectionTrait>(conn: &C, user_id: Id) -> Result<u64, DbErr> {
+    let used: SimpleExpr = Func::coalesce([
+        Expr::col(attachment::Column::Size).sum(),
+        Expr::val(0).into(),
+    ])
+    .into();
+    let used: SimpleExpr = Func::cast_as(used, Alias::new("UNSIGNED")).into();
+    let used = Attachment::find()
+        .select_only()
+        .column_as(used, "used")
+        .filter(attachment::Column::UserId.eq(user_id))
+        .into_tuple::<u64>()
+        .one(conn)
+        .await?;
+    Ok(used.unwrap_or_default())
+}
+
+/// Finds the expense the attachments of the parent are authorized by.
+/// Neither the expense nor the transaction can be in the trash.
+async fn find_parent_expense(
+    conn: &DatabaseConnection,
+    parent: AttachmentParent,
+) -> Result<Option<expense::Model>, DbErr> {
+    let expense_id = match parent {
+        AttachmentParent::Expense(expense_id) => expense_id,
+        AttachmentParent::Transaction(transaction_id) => {
+            let transaction =
+                find_entity_by_id::<transaction::Entity>(conn, transaction_id).await?;
+            match transaction.filter(|transaction| transaction.deleted_at.is_none()) {
+                Some(transaction) => transaction.expense_id,
+                None => return Ok(None),
+            }
+        }
+    };
+    find_expense_by_id(conn, expense_id).await
+}
+
+/// Recognizes the accepted types of files by their signature at the start of the content.
+fn detect_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Keeps only the last segment of the name if the client sent a path, without control characters.
+fn sanitize_file_name(file_name: &str) -> String {
+    let file_name: String = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect();
+    match file_name.trim() {
+        "" => DEFAULT_FILE_NAME.to_string(),
+        file_name => file_name.to_string(),
+    }
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+    use crate::storage::errors::StorageError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    #[error("attachment belongs to neither an expense nor a transaction")]
+    pub struct OrphanAttachmentError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UploadAttachmentError {
+        #[error("expense or transaction not found")]
+        ParentNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("file is too large")]
+        FileTooLarge,
+        #[error("type of the file is not supported")]
+        UnsupportedFileType,
+        #[error("attachment quota of the user is exceeded")]
+        QuotaExceeded,
+        #[error("storage error: '{0}'")]
+        StorageError(#[from] StorageError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<UploadAttachmentError>> for UploadAttachmentError {
+        fn from(e: TransactionError<UploadAttachmentError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum FindAttachmentsError {
+        #[error("expense or transaction not found")]
+        ParentNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DownloadAttachmentError {
+        #[error("attachment not found")]
+        AttachmentNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("storage error: '{0}'")]
+        StorageError(#[from] StorageError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteAttachmentError {
+        #[error("attachment not found")]
+        AttachmentNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("storage error: '{0}'")]
+        StorageError(#[from] StorageError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{
+        test_app_config, test_attachment, test_db_error, test_expense, test_transaction, test_user,
+        TEST_ID, TEST_STR,
+    };
+    use crate::storage::errors::StorageError;
+    use crate::storage::local::LocalStorage;
+    use assert2::check;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Value};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    const TEST_PDF: &[u8] = b"%PDF-1.7";
+
+    fn test_storage_root() -> PathBuf {
+        std::env::temp_dir().join(format!("veryrezsi-{}", Uuid::new_v4()))
+    }
+
+    fn test_upload(data: &[u8]) -> NewAttachmentRequest {
+        NewAttachmentRequest {
+            file_name: TEST_STR.to_string(),
+            data: data.to_vec(),
+        }
+    }
+
+    fn used_storage_row(used: u64) -> BTreeMap<&'static str, Value> {
+        BTreeMap::from([("used", used.into())])
+    }
+
+    fn insert_result() -> MockExecResult {
+        MockExecResult {
+            last_insert_id: TEST_ID,
+            rows_affected: 1,
+        }
+    }
+
+    // The storage does real file operations, which yield to the runtime,
+    // so the cases run one after the other to keep the order of the mocked results.
+    #[tokio::test]
+    async fn upload_attachment_all_cases() {
+        let other_users_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let trashed_transaction = transaction::Model {
+            deleted_at: Some(Local::now()),
+            ..test_transaction()
+        };
+        let attachment = test_attachment();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // attached to expense
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![used_storage_row(attachment.size)]])
+            .append_exec_results(vec![insert_result()])
+            .append_query_results(vec![vec![attachment.clone()]])
+            // attached to transaction
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![used_storage_row(0)]])
+            .append_exec_results(vec![insert_result()])
+            .append_query_results(vec![vec![attachment::Model {
+                expense_id: None,
+                transaction_id: Some(TEST_ID),
+                ..test_attachment()
+            }]])
+            // insert fails
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![used_storage_row(0)]])
+            .append_exec_errors(vec![test_db_error()])
+            // expense not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // transaction in the trash
+            .append_query_results(vec![vec![trashed_transaction]])
+            // unauthorized
+            .append_query_results(vec![vec![other_users_expense]])
+            // too large
+            .append_query_results(vec![vec![test_expense()]])
+            // unknown type
+            .append_query_results(vec![vec![test_expense()]])
+            // type not allowed
+            .append_query_results(vec![vec![test_expense()]])
+            // quota exceeded
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![used_storage_row(4090)]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let config = test_app_config().attachment_config;
+        let root = test_storage_root();
+        let storage = LocalStorage::new(&root);
+        let upload = |parent, data: &[u8]| {
+            upload_attachment(&conn, &storage, &config, TEST_ID, parent, test_upload(data))
+        };
+        let expense = AttachmentParent::Expense(TEST_ID);
+        let transaction = AttachmentParent::Transaction(TEST_ID);
+
+        let attached_to_expense = upload(expense, TEST_PDF).await;
+        let attached_to_transaction = upload(transaction, TEST_PDF).await;
+        let insert_fails = upload(expense, TEST_PDF).await;
+        let expense_not_found = upload(expense, TEST_PDF).await;
+        let transaction_in_trash = upload(transaction, TEST_PDF).await;
+        let unauthorized = upload(expense, TEST_PDF).await;
+        let too_large = upload(expense, &[b'%'; 1025]).await;
+        let unknown_type = upload(expense, b"plain text").await;
+        let type_not_allowed = upload(expense, &[0xFF, 0xD8, 0xFF, 0xE0]).await;
+        let quota_exceeded = upload(expense, TEST_PDF).await;
+        let db_error = upload(expense, TEST_PDF).await;
+        let log = format!("{:?}", conn.into_transaction_log());
+        let stored_files = std::fs::read_dir(root.join(TEST_ID.to_string()))
+            .unwrap()
+            .count();
+        let _ = tokio::fs::remove_dir_all(&root).await;
+
+        check!(attached_to_expense == Ok(attachment.into()));
+        check!(attached_to_transaction.unwrap().transaction_id == Some(TEST_ID));
+        check!(insert_fails == Err(UploadAttachmentError::DatabaseError(test_db_error())));
+        check!(expense_not_found == Err(UploadAttachmentError::ParentNotFound));
+        check!(transaction_in_trash == Err(UploadAttachmentError::ParentNotFound));
+        check!(unauthorized == Err(UploadAttachmentError::UserUnauthorized(AuthorizeUserError)));
+        check!(too_large == Err(UploadAttachmentError::FileTooLarge));
+        check!(unknown_type == Err(UploadAttachmentError::UnsupportedFileType));
+        check!(type_not_allowed == Err(UploadAttachmentError::UnsupportedFileType));
+        check!(quota_exceeded == Err(UploadAttachmentError::QuotaExceeded));
+        check!(db_error == Err(UploadAttachmentError::DatabaseError(test_db_error())));
+        check!(stored_files == 2);
+        check!(log.contains("FOR UPDATE"));
+        check!(log.contains("SUM(`size`)"));
+    }
+
+    #[tokio::test]
+    async fn find_attachments_all_cases() {
+        let attachment = test_attachment();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // attachments found
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![attachment.clone()]])
+            // parent not found
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            // unauthorized
+            .append_query_results(vec![vec![expense::Model {
+                user_id: TEST_ID + 1,
+                ..test_expense()
+            }]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (found, parent_not_found, unauthorized, db_error) = tokio::join!(
+            find_attachments(&conn, TEST_ID, AttachmentParent::Expense(TEST_ID)),
+            find_attachments(&conn, TEST_ID, AttachmentParent::Transaction(TEST_ID)),
+            find_attachments(&conn, TEST_ID, AttachmentParent::Expense(TEST_ID)),
+            find_attachments(&conn, TEST_ID, AttachmentParent::Expense(TEST_ID))
+        );
+
+        check!(found == Ok(vec![attachment.into()]));
+        check!(parent_not_found == Err(FindAttachmentsError::ParentNotFound));
+        check!(unauthorized == Err(FindAttachmentsError::UserUnauthorized(AuthorizeUserError)));
+        check!(db_error == Err(FindAttachmentsError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn download_attachment_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // downloaded
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_query_results(vec![vec![test_expense()]])
+            // file missing from the storage
+            .append_query_results(vec![vec![attachment::Model {
+                storage_key: format!("{TEST_ID}/missing"),
+                ..test_attachment()
+            }]])
+            .append_query_results(vec![vec![test_expense()]])
+            // attachment not found
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            // attachment without a parent
+            .append_query_results(vec![vec![attachment::Model {
+                expense_id: None,
+                ..test_attachment()
+            }]])
+            // expense in the trash
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_query_results(vec![vec![expense::Model {
+                deleted_at: Some(Local::now()),
+                ..test_expense()
+            }]])
+            // unauthorized
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_query_results(vec![vec![expense::Model {
+                user_id: TEST_ID + 1,
+                ..test_expense()
+            }]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let root = test_storage_root();
+        let storage = LocalStorage::new(&root);
+        storage
+            .put(
+                &test_attachment().storage_key,
+                "application/pdf",
+                TEST_PDF.to_vec(),
+            )
+            .await
+            .unwrap();
+
+        let downloaded = download_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let file_missing = download_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let not_found = download_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let without_parent = download_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let expense_in_trash = download_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let unauthorized = download_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let db_error = download_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let _ = tokio::fs::remove_dir_all(&root).await;
+
+        check!(
+            downloaded
+                == Ok(AttachmentContent {
+                    file_name: TEST_STR.to_string(),
+                    content_type: "application/pdf".to_string(),
+                    data: TEST_PDF.to_vec(),
+                })
+        );
+        check!(
+            file_missing
+                == Err(DownloadAttachmentError::StorageError(
+                    StorageError::NotFound
+                ))
+        );
+        check!(not_found == Err(DownloadAttachmentError::AttachmentNotFound));
+        check!(without_parent == Err(DownloadAttachmentError::AttachmentNotFound));
+        check!(expense_in_trash == Err(DownloadAttachmentError::AttachmentNotFound));
+        check!(
+            unauthorized
+                == Err(DownloadAttachmentError::UserUnauthorized(
+                    AuthorizeUserError
+                ))
+        );
+        check!(db_error == Err(DownloadAttachmentError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_attachment_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // deleted
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }])
+            // attachment not found
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            // unauthorized
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_query_results(vec![vec![expense::Model {
+                user_id: TEST_ID + 1,
+                ..test_expense()
+            }]])
+            // db error
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let root = test_storage_root();
+        let storage = LocalStorage::new(&root);
+        storage
+            .put(
+                &test_attachment().storage_key,
+                "application/pdf",
+                TEST_PDF.to_vec(),
+            )
+            .await
+            .unwrap();
+
+        let deleted = delete_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let stored_file = storage.get(&test_attachment().storage_key).await;
+        let not_found = delete_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let unauthorized = delete_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let db_error = delete_attachment(&conn, &storage, TEST_ID, TEST_ID).await;
+        let _ = tokio::fs::remove_dir_all(&root).await;
+
+        check!(deleted == Ok(()));
+        check!(stored_file == Err(StorageError::NotFound));
+        check!(not_found == Err(DeleteAttachmentError::AttachmentNotFound));
+        check!(unauthorized == Err(DeleteAttachmentError::UserUnauthorized(AuthorizeUserError)));
+        check!(db_error == Err(DeleteAttachmentError::DatabaseError(test_db_error())));
+    }
+
+    #[test]
+    fn detect_content_type_all_cases() {
+        check!(detect_content_type(TEST_PDF) == Some("application/pdf"));
+        check!(detect_content_type(&[0xFF, 0xD8, 0xFF, 0xE0]) == Some("image/jpeg"));
+        check!(
+            detect_content_type(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A])
+                == Some("image/png")
+        );
+        check!(detect_content_type(b"RIFF\x24\x00\x00\x00WEBPVP8 ") == Some("image/webp"));
+        check!(detect_content_type(b"RIFF\x24\x00\x00\x00WAVEfmt ").is_none());
+        check!(detect_content_type(b"").is_none());
+    }
+
+    #[test]
+    fn sanitize_file_name_all_cases() {
+        check!(sanitize_file_name("receipt.pdf") == "receipt.pdf");
+        check!(sanitize_file_name("C:\\Users\\test\\bill.png") == "bill.png");
+        check!(sanitize_file_name("../../etc/passwd") == "passwd");
+        check!(sanitize_file_name("bill\r\n.pdf") == "bill.pdf");
+        check!(sanitize_file_name("  ") == DEFAULT_FILE_NAME);
+        check!(sanitize_file_name(&"a".repeat(300)).len() == MAX_FILE_NAME_LENGTH);
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::{BTreeMap, HashSet};
+
+use self::errors::FindBalanceError;
+use super::common::parse_date;
+use super::expense_operations::{find_currency, find_participant};
+use super::expense_split::{expected_shares, settle_up};
+use super::household_operations::is_household_member;
+use super::participant_operations::find_participants_by_ids;
+use super::user_operations::errors::AuthorizeUserError;
+use super::user_operations::find_date_format;
+use crate::dto::balances::{BalanceQuery, BalanceResponse, SettlementResponse};
+
+use chrono::Duration;
+use entity::expense::{self, Entity as Expense};
+use entity::transaction::{self, Entity as Transaction};
+use entity::{
+    currency, expense_pause, expense_price, expense_share, participant, recurrence, Id, MoneyAmount,
+};
+
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, LoaderTrait, QueryFilter};
+
+/// Nets the transactions paid by each participant in the period against their share of the expected costs
+/// of the split expenses, and suggests the payments settling the balances of each currency.
+/// The expenses without a split are left out.
+pub async fn find_balance(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    query: BalanceQuery,
+) -> Result<BalanceResponse, FindBalanceError> {
+    if let Some(household_id) = query.household_id {
+        if !is_household_member(conn, household_id, user_id).await? {
+            return Err(FindBalanceError::UserUnauthorized(AuthorizeUserError));
+        }
+    }
+    let date_format = find_date_format(conn, user_id).await?;
+    let from = parse_date(&query.from, date_format)?;
+    let to = parse_date(&query.to, date_format)?;
+    if to < from {
+        return Err(FindBalanceError::InvalidPeriod);
+    }
+
+    let scope = match query.household_id {
+        Some(household_id) => expense::Column::HouseholdId.eq(household_id),
+        None => expense::Column::UserId.eq(user_id),
+    };
+    let expenses = Expense::find()
+        .filter(scope)
+        .filter(expense::Column::SplitMethod.is_not_null())
+        .filter(expense::Column::DeletedAt.is_null())
+        .all(conn)
+        .await?;
+    if expenses.is_empty() {
+        return Ok(BalanceResponse {
+            participants: Vec::new(),
+            settlements: Vec::new(),
+        });
+    }
+
+    let expense_ids: Vec<Id> = expenses.iter().map(|expense| expense.id).collect();
+    let recurrence_ids: HashSet<Id> = expenses
+        .iter()
+        .map(|expense| expense.recurrence_id)
+        .collect();
+    let (grouped_shares, grouped_pauses, grouped_prices, recurrences, transactions) = tokio::join!(
+        expenses.load_many(expense_share::Entity, conn),
+        expenses.load_many(expense_pause::Entity, conn),
+        expenses.load_many(expense_price::Entity, conn),
+        recurrence::Entity::find()
+            .filter(recurrence::Column::Id.is_in(recurrence_ids))
+            .all(conn),
+        Transaction::find()
+            .filter(transaction::Column::ExpenseId.is_in(expense_ids))
+            .filter(transaction::Column::DeletedAt.is_null())
+            .filter(transaction::Column::Date.between(from, to))
+            .all(conn)
+    );
+    let (grouped_shares, grouped_pauses, grouped_prices) =
+        (grouped_shares?, grouped_pauses?, grouped_prices?);
+    let recurrences = recurrences?;
+
+    // both sides are keyed by (participant id, currency id)
+    let mut paid: BTreeMap<(Id, Id), MoneyAmount> = BTreeMap::new();
+    for transaction in transactions? {
+        *paid
+            .entry((transaction.participant_id, transaction.currency_id))
+            .or_default() += transaction.value;
+    }
+    let mut owed: BTreeMap<(Id, Id), MoneyAmount> = BTreeMap::new();
+    for (((expense, shares), pauses), prices) in expenses
+        .iter()
+        .zip(grouped_shares)
+        .zip(grouped_pauses)
+        .zip(grouped_prices)
+    {
+        let Some(method) = expense.split_method else {
+            continue;
+        };
+        let per_year = recurrences
+            .iter()
+            .find(|recurrence| recurrence.id == expense.recurrence_id)
+            .map_or(0.0, |recurrence| recurrence.per_year);
+        let shares = expected_shares(
+            expense,
+            method,
+            &shares,
+            &pauses,
+            &prices,
+            per_year,
+            from,
+            to + Duration::days(1),
+        );
+        for (key, amount) in shares {
+            *owed.entry(key).or_default() += amount;
+        }
+    }
+
+    let keys: HashSet<(Id, Id)> = paid.keys().chain(owed.keys()).copied().collect();
+    if keys.is_empty() {
+        return Ok(BalanceResponse {
+            participants: Vec::new(),
+            settlements: Vec::new(),
+        });
+    }
+    let participant_ids: HashSet<Id> = keys
+        .iter()
+        .map(|(participant_id, _)| *participant_id)
+        .collect();
+    let currency_ids: HashSet<Id> = keys.iter().map(|(_, currency_id)| *currency_id).collect();
+    let (participants, currencies) = tokio::join!(
+        find_participants_by_ids(conn, participant_ids),
+        currency::Entity::find()
+            .filter(currency::Column::Id.is_in(currency_ids))
+            .all(conn)
+    );
+    let participants = participants?;
+    let currencies = currencies?;
+
+    let mut by_currency: BTreeMap<Id, Vec<(participant::Model, MoneyAmount, MoneyAmount)>> =
+        BTreeMap::new();
+    for key in keys {
+        let paid = paid.get(&key).copied().unwrap_or_default();
+        let owed = owed.get(&key).copied().unwrap_or_default();
+        by_currency.entry(key.1).or_default().push((
+            find_participant(&participants, key.0),
+            paid,
+            owed,
+        ));
+    }
+
+    let mut response = BalanceResponse {
+        participants: Vec::new(),
+        settlements: Vec::new(),
+    };
+    for (currency_id, mut balances) in by_currency {
+        balances.sort_by(|a, b| a.0.name.cmp(&b.0.name).then_with(|| a.0.id.cmp(&b.0.id)));
+        let currency = find_currency(&currencies, currency_id);
+        let net: Vec<(Id, MoneyAmount)> = balances
+            .iter()
+            .map(|(participant, paid, owed)| (participant.id, paid - owed))
+            .collect();
+        response
+            .settlements
+            .extend(
+                settle_up(&net)
+                    .into_iter()
+                    .map(|(from, to, amount)| SettlementResponse {
+                        from: find_participant(&participants, from).into(),
+                        to: find_participant(&participants, to).into(),
+                        amount,
+                        currency: currency.clone().into(),
+                    }),
+            );
+        response.participants.extend(
+            balances.into_iter().map(|(participant, paid, owed)| {
+                (participant, currency.clone(), paid, owed).into()
+            }),
+        );
+    }
+    Ok(response)
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use thiserror::Error;
+
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum FindBalanceError {
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("date could not be parsed")]
+        InvalidDate(#[from] chrono::ParseError),
+        #[error("the end of the period must not be before its start")]
+        InvalidPeriod,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::participants::ParticipantResponse;
+    use crate::logic::common::tests::{
+        test_currency, test_db_error, test_expense, test_participant, test_recurrence,
+        test_transaction, test_user, TEST_ID,
+    };
+    use assert2::check;
+    use chrono::NaiveDate;
+    use entity::expense::SplitMethod;
+    use entity::household_member;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn query(from: &str, to: &str, household_id: Option<Id>) -> BalanceQuery {
+        BalanceQuery {
+            from: from.to_string(),
+            to: to.to_string(),
+            household_id,
+        }
+    }
+
+    fn share(participant_id: Id) -> expense_share::Model {
+        expense_share::Model {
+            id: TEST_ID,
+            expense_id: TEST_ID,
+            participant_id,
+            value: None,
+        }
+    }
+
+    fn participant(id: Id, name: &str) -> participant::Model {
+        participant::Model {
+            id,
+            name: name.to_string(),
+            ..test_participant()
+        }
+    }
+
+    #[tokio::test]
+    async fn find_balance_all_cases() {
+        let split_expense = expense::Model {
+            value: MoneyAmount::from(100),
+            start_date: NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+            split_method: Some(SplitMethod::Equal),
+            ..test_expense()
+        };
+        let (anna, bob) = (participant(1, "Anna"), participant(2, "Bob"));
+        let payment = transaction::Model {
+            participant_id: anna.id,
+            value: MoneyAmount::from(1200),
+            ..test_transaction()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // balance of a split expense
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![split_expense]])
+            .append_query_results(vec![vec![share(anna.id), share(bob.id)]])
+            .append_query_results(vec![Vec::<expense_pause::Model>::new()])
+            .append_query_results(vec![Vec::<expense_price::Model>::new()])
+            .append_query_results(vec![vec![recurrence::Model {
+                per_year: 12.0,
+                ..test_recurrence()
+            }]])
+            .append_query_results(vec![vec![payment]])
+            .append_query_results(vec![vec![anna.clone(), bob.clone()]])
+            .append_query_results(vec![vec![test_currency()]])
+            // no split expenses
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // invalid date
+            .append_query_results(vec![vec![test_user()]])
+            // invalid period
+            .append_query_results(vec![vec![test_user()]])
+            // not a member of the household
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (balance, empty, invalid_date, invalid_period, unauthorized, db_error) = tokio::join!(
+            find_balance(&conn, TEST_ID, query("2023-01-01", "2023-12-31", None)),
+            find_balance(&conn, TEST_ID, query("2023-01-01", "2023-12-31", None)),
+            find_balance(&conn, TEST_ID, query("first of May", "2023-12-31", None)),
+            find_balance(&conn, TEST_ID, query("2023-12-31", "2023-01-01", None)),
+            find_balance(
+                &conn,
+                TEST_ID,
+                query("2023-01-01", "2023-12-31", Some(TEST_ID))
+            ),
+            find_balance(&conn, TEST_ID, query("2023-01-01", "2023-12-31", None))
+        );
+
+        let balance = balance.unwrap();
+        let owed: MoneyAmount = "599.59".parse().unwrap();
+        check!(balance.participants.len() == 2);
+        check!(balance.participants[0].participant == ParticipantResponse::from(anna.clone()));
+        check!(balance.participants[0].paid == MoneyAmount::from(1200));
+        check!(balance.participants[0].owed == owed);
+        check!(balance.participants[1].participant == ParticipantResponse::from(bob.clone()));
+        check!(balance.participants[1].balance == -owed);
+        check!(balance.settlements.len() == 1);
+        check!(balance.settlements[0].from == ParticipantResponse::from(bob));
+        check!(balance.settlements[0].to == ParticipantResponse::from(anna));
+        check!(balance.settlements[0].amount == owed);
+        let empty = empty.unwrap();
+        check!(empty.participants.is_empty());
+        check!(empty.settlements.is_empty());
+        check!(matches!(
+            invalid_date,
+            Err(FindBalanceError::InvalidDate(_))
+        ));
+        check!(invalid_period.err() == Some(FindBalanceError::InvalidPeriod));
+        check!(unauthorized.err() == Some(FindBalanceError::UserUnauthorized(AuthorizeUserError)));
+        check!(db_error.err() == Some(FindBalanceError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn find_balance_leaves_out_paused_and_ended_days() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2023, month, day).unwrap();
+        // a yearly payment of one unit per day
+        let cancelled_expense = expense::Model {
+            value: "365.25".parse().unwrap(),
+            start_date: date(1, 1),
+            end_date: Some(date(7, 1)),
+            split_method: Some(SplitMethod::Equal),
+            ..test_expense()
+        };
+        let pause = expense_pause::Model {
+            id: TEST_ID,
+            expense_id: TEST_ID,
+            start_date: date(3, 1),
+            end_date: Some(date(5, 1)),
+        };
+        let (anna, bob) = (participant(1, "Anna"), participant(2, "Bob"));
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![cancelled_expense]])
+            .append_query_results(vec![vec![share(anna.id), share(bob.id)]])
+            .append_query_results(vec![vec![pause]])
+            .append_query_results(vec![Vec::<expense_price::Model>::new()])
+            .append_query_results(vec![vec![recurrence::Model {
+                per_year: 1.0,
+                ..test_recurrence()
+            }]])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_query_results(vec![vec![anna, bob]])
+            .append_query_results(vec![vec![test_currency()]])
+            .into_connection();
+
+        let balance = find_balance(&conn, TEST_ID, query("2023-01-01", "2023-12-31", None)).await;
+
+        // 181 days until the cancellation, 61 of them paused
+        let balance = balance.unwrap();
+        check!(balance.participants.len() == 2);
+        check!(balance.participants[0].owed == MoneyAmount::from(60));
+        check!(balance.participants[1].owed == MoneyAmount::from(60));
+    }
+}
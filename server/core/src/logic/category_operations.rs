@@ -0,0 +1,421 @@
+use self::errors::{CreateCategoryError, DeleteCategoryError, UpdateCategoryError};
+
+use super::common::find_entity_by_id;
+use super::user_operations::authorize_user;
+use crate::dto::categories::{CategoryRequest, CategoryResponse};
+
+use entity::category::{self, Entity as Category};
+use entity::Id;
+
+use migration::DbErr;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+
+pub async fn find_categories(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<Vec<CategoryResponse>, DbErr> {
+    let categories = Category::find()
+        .filter(category::Column::UserId.eq(user_id))
+        .order_by_asc(category::Column::Name)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|category| category.into())
+        .collect();
+    Ok(categories)
+}
+
+/// Adds a category of the user, its name must not be used by another category with the same parent.
+pub async fn create_category(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    req: CategoryRequest,
+) -> Result<Id, CreateCategoryError> {
+    if let Some(parent_id) = req.parent_id {
+        if !is_own_category(conn, user_id, parent_id).await? {
+            return Err(CreateCategoryError::InvalidParent);
+        }
+    }
+    if find_sibling_by_name(conn, user_id, req.parent_id, &req.name)
+        .await?
+        .is_some()
+    {
+        return Err(CreateCategoryError::CategoryAlreadyExists);
+    }
+    let category = category::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        parent_id: Set(req.parent_id),
+        name: Set(req.name),
+    };
+    let category = category.insert(conn).await?;
+    Ok(category.id)
+}
+
+/// Renames or moves a category of the user, it cannot be moved under itself or one of its subcategories.
+pub async fn update_category(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    category_id: Id,
+    req: CategoryRequest,
+) -> Result<(), UpdateCategoryError> {
+    let Some(category) = find_entity_by_id::<category::Entity>(conn, category_id).await? else {
+        return Err(UpdateCategoryError::CategoryNotFound);
+    };
+    authorize_user(user_id, category.user_id)?;
+    if let Some(parent_id) = req.parent_id {
+        let categories = Category::find()
+            .filter(category::Column::UserId.eq(user_id))
+            .all(conn)
+            .await?;
+        let is_own_parent = categories.iter().any(|category| category.id == parent_id);
+        if !is_own_parent || descendant_ids(&categories, category_id).contains(&parent_id) {
+            return Err(UpdateCategoryError::InvalidParent);
+        }
+    }
+    if let Some(sibling) = find_sibling_by_name(conn, user_id, req.parent_id, &req.name).await? {
+        if sibling.id != category_id {
+            return Err(UpdateCategoryError::CategoryAlreadyExists);
+        }
+    }
+
+    let mut category = category.into_active_model();
+    category.parent_id = Set(req.parent_id);
+    category.name = Set(req.name);
+    category.update(conn).await?;
+    Ok(())
+}
+
+/// Deletes a category of the user. Its subcategories are moved to its parent,
+/// the expenses and transactions in it lose their category.
+pub async fn delete_category(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    category_id: Id,
+) -> Result<(), DeleteCategoryError> {
+    let Some(category) = find_entity_by_id::<category::Entity>(conn, category_id).await? else {
+        return Err(DeleteCategoryError::CategoryNotFound);
+    };
+    authorize_user(user_id, category.user_id)?;
+
+    conn.transaction::<_, (), DeleteCategoryError>(|txn| {
+        Box::pin(async move {
+            Category::update_many()
+                .col_expr(category::Column::ParentId, Expr::value(category.parent_id))
+                .filter(category::Column::ParentId.eq(category_id))
+                .exec(txn)
+                .await?;
+            Category::delete_by_id(category_id).exec(txn).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Tells whether the category exists and belongs to the user.
+pub(super) async fn is_own_category(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    category_id: Id,
+) -> Result<bool, DbErr> {
+    let category = find_entity_by_id::<category::Entity>(conn, category_id).await?;
+    Ok(category.is_some_and(|category| category.user_id == user_id))
+}
+
+/// Finds the top level category of the user with the given name, or creates it if the user has none.
+pub(super) async fn find_or_create_category(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    name: &str,
+) -> Result<Id, DbErr> {
+    if let Some(category) = find_sibling_by_name(conn, user_id, None, name).await? {
+        return Ok(category.id);
+    }
+    let category = category::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        parent_id: Set(None),
+        name: Set(name.to_string()),
+    };
+    let category = category.insert(conn).await?;
+    Ok(category.id)
+}
+
+/// The ids of the category and every category under it.
+pub(super) fn descendant_ids(categories: &[category::Model], category_id: Id) -> Vec<Id> {
+    let mut ids = vec![category_id];
+    let mut index = 0;
+    while index < ids.len() {
+        let parent_id = ids[index];
+        ids.extend(
+            categories
+                .iter()
+                .filter(|category| category.parent_id == Some(parent_id))
+                .map(|category| category.id),
+        );
+        index += 1;
+    }
+    ids
+}
+
+async fn find_sibling_by_name(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    parent_id: Option<Id>,
+    name: &str,
+) -> Result<Option<category::Model>, DbErr> {
+    let parent_condition = match parent_id {
+        Some(parent_id) => category::Column::ParentId.eq(parent_id),
+        None => category::Column::ParentId.is_null(),
+    };
+    Category::find()
+        .filter(category::Column::UserId.eq(user_id))
+        .filter(parent_condition)
+        .filter(category::Column::Name.eq(name))
+        .one(conn)
+        .await
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum CreateCategoryError {
+        #[error("parent category is invalid")]
+        InvalidParent,
+        #[error("category with the same name already exists under the parent")]
+        CategoryAlreadyExists,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UpdateCategoryError {
+        #[error("category not found")]
+        CategoryNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("parent category is invalid")]
+        InvalidParent,
+        #[error("category with the same name already exists under the parent")]
+        CategoryAlreadyExists,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteCategoryError {
+        #[error("category not found")]
+        CategoryNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DeleteCategoryError>> for DeleteCategoryError {
+        fn from(e: TransactionError<DeleteCategoryError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logic::common::tests::{exec_ok, test_category, test_db_error, TEST_ID, TEST_STR};
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+
+    use super::*;
+    use assert2::check;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn category_request(parent_id: Option<Id>) -> CategoryRequest {
+        CategoryRequest {
+            name: TEST_STR.to_string(),
+            parent_id,
+        }
+    }
+
+    fn subcategory(id: Id, parent_id: Id) -> category::Model {
+        category::Model {
+            id,
+            parent_id: Some(parent_id),
+            ..test_category()
+        }
+    }
+
+    #[tokio::test]
+    async fn find_categories_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_category()], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (categories, empty, db_error) = tokio::join!(
+            find_categories(&conn, TEST_ID),
+            find_categories(&conn, TEST_ID),
+            find_categories(&conn, TEST_ID)
+        );
+
+        check!(categories == Ok(vec![test_category().into()]));
+        check!(empty == Ok(vec![]));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn create_category_all_cases() {
+        let foreign_category = category::Model {
+            user_id: TEST_ID + 1,
+            ..test_category()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![Vec::<category::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_category()]])
+            // subcategory
+            .append_query_results(vec![vec![test_category()]])
+            .append_query_results(vec![Vec::<category::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![subcategory(TEST_ID + 1, TEST_ID)]])
+            // parent of another user
+            .append_query_results(vec![vec![foreign_category]])
+            // same name
+            .append_query_results(vec![vec![test_category()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, with_parent, foreign_parent, already_exists, db_error) = tokio::join!(
+            create_category(&conn, TEST_ID, category_request(None)),
+            create_category(&conn, TEST_ID, category_request(Some(TEST_ID))),
+            create_category(&conn, TEST_ID, category_request(Some(TEST_ID))),
+            create_category(&conn, TEST_ID, category_request(None)),
+            create_category(&conn, TEST_ID, category_request(None))
+        );
+
+        check!(happy_case == Ok(TEST_ID));
+        check!(with_parent == Ok(TEST_ID + 1));
+        check!(foreign_parent == Err(CreateCategoryError::InvalidParent));
+        check!(already_exists == Err(CreateCategoryError::CategoryAlreadyExists));
+        check!(db_error == Err(CreateCategoryError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn update_category_all_cases() {
+        let other_category = category::Model {
+            id: TEST_ID + 1,
+            ..test_category()
+        };
+        let foreign_category = category::Model {
+            user_id: TEST_ID + 1,
+            ..test_category()
+        };
+        let own_categories = vec![
+            test_category(),
+            other_category.clone(),
+            subcategory(TEST_ID + 2, TEST_ID),
+        ];
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // renamed
+            .append_query_results(vec![vec![test_category()]])
+            .append_query_results(vec![vec![test_category()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_category()]])
+            // moved under another category
+            .append_query_results(vec![vec![test_category()]])
+            .append_query_results(vec![own_categories.clone()])
+            .append_query_results(vec![Vec::<category::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_category()]])
+            // moved under its own subcategory
+            .append_query_results(vec![vec![test_category()]])
+            .append_query_results(vec![own_categories])
+            // name used by a sibling
+            .append_query_results(vec![vec![test_category()]])
+            .append_query_results(vec![vec![other_category]])
+            // category of another user
+            .append_query_results(vec![vec![foreign_category]])
+            // not found
+            .append_query_results(vec![Vec::<category::Model>::new()])
+            .into_connection();
+
+        let (renamed, moved, moved_under_itself, name_used, unauthorized, not_found) = tokio::join!(
+            update_category(&conn, TEST_ID, TEST_ID, category_request(None)),
+            update_category(&conn, TEST_ID, TEST_ID, category_request(Some(TEST_ID + 1))),
+            update_category(&conn, TEST_ID, TEST_ID, category_request(Some(TEST_ID + 2))),
+            update_category(&conn, TEST_ID, TEST_ID, category_request(None)),
+            update_category(&conn, TEST_ID, TEST_ID, category_request(None)),
+            update_category(&conn, TEST_ID, TEST_ID, category_request(None))
+        );
+
+        check!(renamed == Ok(()));
+        check!(moved == Ok(()));
+        check!(moved_under_itself == Err(UpdateCategoryError::InvalidParent));
+        check!(name_used == Err(UpdateCategoryError::CategoryAlreadyExists));
+        check!(unauthorized == Err(UpdateCategoryError::UserUnauthorized(AuthorizeUserError)));
+        check!(not_found == Err(UpdateCategoryError::CategoryNotFound));
+    }
+
+    #[tokio::test]
+    async fn delete_category_all_cases() {
+        let foreign_category = category::Model {
+            user_id: TEST_ID + 1,
+            ..test_category()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_category()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // category of another user
+            .append_query_results(vec![vec![foreign_category]])
+            // not found
+            .append_query_results(vec![Vec::<category::Model>::new()])
+            // db error
+            .append_query_results(vec![vec![test_category()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, unauthorized, not_found, db_error) = tokio::join!(
+            delete_category(&conn, TEST_ID, TEST_ID),
+            delete_category(&conn, TEST_ID, TEST_ID),
+            delete_category(&conn, TEST_ID, TEST_ID),
+            delete_category(&conn, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(unauthorized == Err(DeleteCategoryError::UserUnauthorized(AuthorizeUserError)));
+        check!(not_found == Err(DeleteCategoryError::CategoryNotFound));
+        check!(db_error == Err(DeleteCategoryError::DatabaseError(test_db_error())));
+    }
+
+    #[test]
+    fn descendant_ids_all_cases() {
+        let categories = vec![
+            test_category(),
+            subcategory(TEST_ID + 1, TEST_ID),
+            subcategory(TEST_ID + 2, TEST_ID + 1),
+            category::Model {
+                id: TEST_ID + 3,
+                ..test_category()
+            },
+        ];
+
+        check!(descendant_ids(&categories, TEST_ID) == vec![TEST_ID, TEST_ID + 1, TEST_ID + 2]);
+        check!(descendant_ids(&categories, TEST_ID + 2) == vec![TEST_ID + 2]);
+        check!(descendant_ids(&categories, TEST_ID + 3) == vec![TEST_ID + 3]);
+    }
+}
@@ -1,7 +1,34 @@
+use chrono::NaiveDate;
+use entity::user::DateFormat;
 use migration::DbErr;
 use sea_orm::{DatabaseConnection, EntityTrait, PrimaryKeyTrait};
+use sha2::{Digest, Sha256};
 
-pub static DATE_FORMAT: &str = "%d-%m-%Y";
+pub static ISO_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Parses a date sent by the user, which is always accepted in ISO 8601 format besides the preferred one.
+pub fn parse_date(value: &str, date_format: DateFormat) -> Result<NaiveDate, chrono::ParseError> {
+    NaiveDate::parse_from_str(value, ISO_DATE_FORMAT)
+        .or_else(|_| NaiveDate::parse_from_str(value, date_format.pattern()))
+}
+
+/// Trims the tags sent by the user and drops the empty and repeated ones, so every tag is stored once.
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|other| other == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// Only the hash of session, pending login and API tokens is stored, so a leaked database does not leak usable tokens.
+/// They are random enough, so a fast unsalted hash is sufficient and lets them be looked up by the hash.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
 
 pub async fn find_entity_by_id<E: EntityTrait>(
     conn: &DatabaseConnection,
@@ -15,120 +42,390 @@ pub mod tests {
     use assert2::check;
     use chrono::{Duration, NaiveDate};
     use entity::{
-        account_activation, currency, expense, predefined_expense, recurrence, transaction, user,
-        Id,
+        account_activation, api_token, attachment, category, currency, email_change, expense,
+        expense_pause, expense_price, household, household_invitation, household_member,
+        oidc_identity, participant, password_reset, pending_login, predefined_expense,
+        recovery_code, recurrence, security_event, session, transaction, two_factor_auth, user, Id,
     };
     use migration::DbErr;
     use sea_orm::entity::prelude::*;
-    use sea_orm::{DatabaseBackend, DeriveActiveModelBehavior, DeriveEntityModel, MockDatabase};
+    use sea_orm::{
+        DatabaseBackend, DeriveActiveModelBehavior, DeriveEntityModel, MockDatabase, MockExecResult,
+    };
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
-    use crate::config::{AppConfig, MailConfig};
-    use crate::logic::common::find_entity_by_id;
+    use crate::config::{
+        AccountConfig, AppConfig, AttachmentConfig, MailConfig, PasswordHashConfig,
+        RateLimitConfig, SessionConfig, StorageBackend, StorageConfig, TrashConfig,
+    };
+    use crate::logic::common::{find_entity_by_id, normalize_tags, parse_date};
+    use entity::user::DateFormat;
 
     pub const TEST_STR: &str = "test";
     pub const TEST_EMAIL: &str = "test@test.com";
     pub const TEST_ID: u64 = 1;
     pub const TEST_FLOAT: f64 = 1.0;
     pub const TEST_DATE: &str = "06-08-1998";
+    pub const TEST_TOTP_SECRET: &str = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP";
 
     pub fn test_db_error() -> DbErr {
         DbErr::Custom(TEST_STR.to_string())
     }
 
+    pub fn exec_ok() -> MockExecResult {
+        MockExecResult {
+            last_insert_id: TEST_ID,
+            rows_affected: 1,
+        }
+    }
+
     pub fn test_currency() -> currency::Model {
-        return currency::Model {
+        currency::Model {
             id: TEST_ID,
             abbreviation: TEST_STR.to_string(),
             name: TEST_STR.to_string(),
-        };
+        }
     }
 
     pub fn test_recurrence() -> recurrence::Model {
-        return recurrence::Model {
+        recurrence::Model {
             id: TEST_ID,
             name: TEST_STR.to_string(),
             per_year: TEST_FLOAT,
-        };
+        }
+    }
+
+    pub fn test_category() -> category::Model {
+        category::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            parent_id: None,
+            name: TEST_STR.to_string(),
+        }
     }
 
     pub fn test_expense() -> expense::Model {
-        return expense::Model {
+        expense::Model {
             id: TEST_ID,
             name: TEST_STR.to_string(),
             description: TEST_STR.to_string(),
             value: test_decimal(),
             start_date: NaiveDate::MIN,
+            end_date: None,
             user_id: TEST_ID,
             currency_id: TEST_ID,
             recurrence_id: TEST_ID,
             predefined_expense_id: Some(TEST_ID),
-        };
+            category_id: None,
+            household_id: None,
+            split_method: None,
+            archived: false,
+            deleted_at: None,
+        }
+    }
+
+    pub fn test_expense_pause() -> expense_pause::Model {
+        expense_pause::Model {
+            id: TEST_ID,
+            expense_id: TEST_ID,
+            start_date: NaiveDate::MIN,
+            end_date: None,
+        }
+    }
+
+    pub fn test_expense_price() -> expense_price::Model {
+        expense_price::Model {
+            id: TEST_ID,
+            expense_id: TEST_ID,
+            value: test_decimal(),
+            currency_id: TEST_ID,
+            effective_from: NaiveDate::MIN,
+        }
     }
 
     pub fn test_predefined_expense() -> predefined_expense::Model {
-        return predefined_expense::Model {
+        predefined_expense::Model {
             id: TEST_ID,
             name: TEST_STR.to_string(),
             description: TEST_STR.to_string(),
             value: test_decimal(),
             currency_id: TEST_ID,
             recurrence_id: TEST_ID,
-        };
+            default_category: None,
+            user_id: Some(TEST_ID),
+            household_id: None,
+            visibility: predefined_expense::PredefinedExpenseVisibility::Private,
+        }
     }
 
     pub fn test_transaction() -> transaction::Model {
-        return transaction::Model {
+        transaction::Model {
             id: TEST_ID,
-            donor_name: TEST_STR.to_string(),
+            participant_id: TEST_ID,
             value: test_decimal(),
             date: NaiveDate::MIN,
             currency_id: TEST_ID,
             expense_id: TEST_ID,
-        };
+            category_id: None,
+            deleted_at: None,
+        }
     }
 
     pub fn test_transaction_2() -> transaction::Model {
-        return transaction::Model {
+        transaction::Model {
             id: test_transaction().id + 1,
             ..test_transaction()
-        };
+        }
     }
 
     pub fn test_user() -> user::Model {
-        return user::Model {
+        user::Model {
             id: TEST_ID,
             email: TEST_EMAIL.to_string(),
             username: TEST_STR.to_string(),
             pw_hash: TEST_STR.to_string(),
             activated: true,
-        };
+            role: user::UserRole::User,
+            preferred_currency_id: None,
+            locale: "en".to_string(),
+            timezone: "UTC".to_string(),
+            date_format: user::DateFormat::DayMonthYear,
+            first_day_of_week: user::FirstDayOfWeek::Monday,
+        }
+    }
+
+    pub fn test_admin() -> user::Model {
+        user::Model {
+            role: user::UserRole::Admin,
+            ..test_user()
+        }
     }
 
     pub fn test_account_activation() -> account_activation::Model {
-        return account_activation::Model {
+        account_activation::Model {
             id: TEST_ID,
             user_id: TEST_ID,
             expiration: chrono::Local::now()
                 .checked_add_signed(Duration::days(1))
                 .unwrap(),
             token: TEST_STR.to_string(),
-        };
+        }
+    }
+
+    pub fn test_password_reset() -> password_reset::Model {
+        password_reset::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            expiration: chrono::Local::now()
+                .checked_add_signed(Duration::hours(1))
+                .unwrap(),
+            token: TEST_STR.to_string(),
+        }
+    }
+
+    pub fn test_email_change() -> email_change::Model {
+        email_change::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            new_email: TEST_EMAIL.to_string(),
+            expiration: chrono::Local::now()
+                .checked_add_signed(Duration::days(1))
+                .unwrap(),
+            token: TEST_STR.to_string(),
+        }
+    }
+
+    pub fn test_household() -> household::Model {
+        household::Model {
+            id: TEST_ID,
+            name: TEST_STR.to_string(),
+        }
+    }
+
+    pub fn test_household_member() -> household_member::Model {
+        household_member::Model {
+            id: TEST_ID,
+            household_id: TEST_ID,
+            user_id: TEST_ID,
+            role: household_member::HouseholdRole::Owner,
+        }
+    }
+
+    pub fn test_household_invitation() -> household_invitation::Model {
+        household_invitation::Model {
+            id: TEST_ID,
+            token: TEST_STR.to_string(),
+            household_id: TEST_ID,
+            email: TEST_EMAIL.to_string(),
+            invited_by: TEST_ID,
+            expiration: chrono::Local::now()
+                .checked_add_signed(Duration::days(1))
+                .unwrap(),
+        }
+    }
+
+    pub fn test_participant() -> participant::Model {
+        participant::Model {
+            id: TEST_ID,
+            name: TEST_STR.to_string(),
+            user_id: Some(TEST_ID),
+            household_id: None,
+            account_id: None,
+        }
+    }
+
+    pub fn test_session() -> session::Model {
+        let now = chrono::Local::now();
+        session::Model {
+            id: TEST_ID,
+            token_hash: TEST_STR.to_string(),
+            user_id: TEST_ID,
+            ip_address: Some(TEST_STR.to_string()),
+            user_agent: Some(TEST_STR.to_string()),
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now.checked_add_signed(Duration::days(1)).unwrap(),
+        }
+    }
+
+    pub fn test_two_factor_auth() -> two_factor_auth::Model {
+        two_factor_auth::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            secret: TEST_TOTP_SECRET.to_string(),
+            enabled: true,
+            last_used_step: None,
+            created_at: chrono::Local::now(),
+        }
+    }
+
+    pub fn test_recovery_code() -> recovery_code::Model {
+        recovery_code::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            code_hash: TEST_STR.to_string(),
+        }
+    }
+
+    pub fn test_pending_login() -> pending_login::Model {
+        pending_login::Model {
+            id: TEST_ID,
+            token_hash: TEST_STR.to_string(),
+            user_id: TEST_ID,
+            expires_at: chrono::Local::now()
+                .checked_add_signed(Duration::minutes(5))
+                .unwrap(),
+            failed_attempts: 0,
+        }
+    }
+
+    pub fn test_api_token() -> api_token::Model {
+        api_token::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            name: TEST_STR.to_string(),
+            token_hash: TEST_STR.to_string(),
+            scope: api_token::ApiTokenScope::ReadWrite,
+            created_at: chrono::Local::now(),
+            expires_at: None,
+            last_used_at: None,
+        }
+    }
+
+    pub fn test_oidc_identity() -> oidc_identity::Model {
+        oidc_identity::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            provider: TEST_STR.to_string(),
+            subject: TEST_STR.to_string(),
+            created_at: chrono::Local::now(),
+        }
+    }
+
+    pub fn test_security_event() -> security_event::Model {
+        security_event::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            event_type: security_event::SecurityEventType::Login,
+            ip_address: Some(TEST_STR.to_string()),
+            user_agent: Some(TEST_STR.to_string()),
+            created_at: chrono::Local::now(),
+        }
+    }
+
+    pub fn test_attachment() -> attachment::Model {
+        attachment::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            expense_id: Some(TEST_ID),
+            transaction_id: None,
+            file_name: TEST_STR.to_string(),
+            content_type: "application/pdf".to_string(),
+            size: 8,
+            storage_key: format!("{TEST_ID}/{TEST_STR}"),
+            created_at: chrono::Local::now(),
+        }
     }
 
     pub fn test_app_config() -> AppConfig {
-        return AppConfig {
+        AppConfig {
             server_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080),
+            client_url: TEST_STR.to_string(),
             database_url: TEST_STR.to_string(),
             cookie_key: TEST_STR.to_string(),
             log_level: crate::config::LogLevel::Trace,
+            trusted_proxies: vec![],
             mail_config: MailConfig {
                 smtp_address: TEST_STR.to_string(),
                 smtp_port: 7777,
                 smtp_username: TEST_STR.to_string(),
                 smtp_password: TEST_STR.to_string(),
             },
-        };
+            session_config: SessionConfig {
+                idle_timeout_minutes: 60,
+                absolute_timeout_hours: 24,
+                secure_cookie: true,
+            },
+            account_config: AccountConfig {
+                activation_resend_cooldown_minutes: 5,
+                unactivated_account_grace_period_hours: 72,
+                unactivated_account_sweep_interval_minutes: std::time::Duration::from_secs(60 * 60),
+                initial_admin_email: None,
+            },
+            rate_limit_config: RateLimitConfig {
+                login_max_failed_attempts_per_account: 5,
+                login_max_failed_attempts_per_ip: 20,
+                login_failure_window_minutes: 60,
+                login_lockout_minutes: 15,
+                login_backoff_base_seconds: 1,
+                registration_mails_per_ip_per_hour: 5,
+            },
+            // cheap parameters, so hashing in tests stays fast
+            password_hash_config: PasswordHashConfig {
+                argon2_memory_kib: 64,
+                argon2_iterations: 1,
+                argon2_parallelism: 1,
+            },
+            trash_config: TrashConfig {
+                retention_days: 30,
+                purge_interval_minutes: std::time::Duration::from_secs(60 * 60),
+            },
+            attachment_config: AttachmentConfig {
+                max_file_size_bytes: 1024,
+                user_quota_bytes: 4096,
+                allowed_content_types: vec!["application/pdf".to_string(), "image/png".to_string()],
+                storage: StorageConfig {
+                    backend: StorageBackend::Local,
+                    local_path: TEST_STR.to_string(),
+                    s3_endpoint: None,
+                    s3_bucket: None,
+                    s3_region: TEST_STR.to_string(),
+                    s3_access_key: None,
+                    s3_secret_key: None,
+                },
+            },
+            oidc_providers: vec![],
+        }
     }
 
     pub fn test_decimal() -> Decimal {
@@ -163,4 +460,29 @@ pub mod tests {
         check!(not_found == Ok(None));
         check!(db_error == Err(test_db_error()));
     }
+
+    #[test]
+    fn parse_date_all_cases() {
+        let expected = NaiveDate::from_ymd_opt(1998, 8, 6);
+
+        check!(parse_date(TEST_DATE, DateFormat::DayMonthYear).ok() == expected);
+        check!(parse_date("1998-08-06", DateFormat::MonthDayYear).ok() == expected);
+        check!(parse_date("08/06/1998", DateFormat::MonthDayYear).ok() == expected);
+        check!(parse_date("1998.08.06", DateFormat::YearMonthDayDotted).ok() == expected);
+        check!(parse_date(TEST_DATE, DateFormat::MonthDayYear).is_err());
+        check!(parse_date(TEST_STR, DateFormat::DayMonthYear).is_err());
+    }
+
+    #[test]
+    fn normalize_tags_all_cases() {
+        let tags = vec![
+            " utilities ".to_string(),
+            "home".to_string(),
+            "utilities".to_string(),
+            "  ".to_string(),
+        ];
+
+        check!(normalize_tags(tags) == vec!["utilities".to_string(), "home".to_string()]);
+        check!(normalize_tags(vec![]).is_empty());
+    }
 }
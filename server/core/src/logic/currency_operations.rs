@@ -1,9 +1,20 @@
-use entity::currency::Entity as Currency;
+use self::errors::{CreateCurrencyError, DeleteCurrencyError, UpdateCurrencyError};
+
+use entity::currency::{self, Entity as Currency};
+use entity::expense::{self, Entity as Expense};
+use entity::predefined_expense::{self, Entity as PredefinedExpense};
+use entity::transaction::{self, Entity as Transaction};
+use entity::Id;
 
 use migration::DbErr;
-use sea_orm::{DatabaseConnection, EntityTrait};
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, IntoActiveModel,
+    QueryFilter, Set,
+};
 
-use crate::dto::currencies::CurrencyResponse;
+use super::common::find_entity_by_id;
+use crate::dto::currencies::{CurrencyRequest, CurrencyResponse};
 
 pub async fn find_currencies(conn: &DatabaseConnection) -> Result<Vec<CurrencyResponse>, DbErr> {
     let currencies = Currency::find()
@@ -15,14 +26,136 @@ pub async fn find_currencies(conn: &DatabaseConnection) -> Result<Vec<CurrencyRe
     Ok(currencies)
 }
 
+/// Adds a currency to the reference data, its abbreviation and name must not be used by another currency.
+pub async fn create_currency(
+    conn: &DatabaseConnection,
+    req: CurrencyRequest,
+) -> Result<Id, CreateCurrencyError> {
+    if find_conflicting_currency(conn, &req, None).await?.is_some() {
+        return Err(CreateCurrencyError::CurrencyAlreadyExists);
+    }
+    let currency = currency::ActiveModel {
+        id: NotSet,
+        abbreviation: Set(req.abbreviation),
+        name: Set(req.name),
+    };
+    let currency = currency.insert(conn).await?;
+    Ok(currency.id)
+}
+
+pub async fn update_currency(
+    conn: &DatabaseConnection,
+    currency_id: Id,
+    req: CurrencyRequest,
+) -> Result<(), UpdateCurrencyError> {
+    let Some(currency) = find_entity_by_id::<currency::Entity>(conn, currency_id).await? else {
+        return Err(UpdateCurrencyError::CurrencyNotFound);
+    };
+    if find_conflicting_currency(conn, &req, Some(currency_id))
+        .await?
+        .is_some()
+    {
+        return Err(UpdateCurrencyError::CurrencyAlreadyExists);
+    }
+    let mut currency = currency.into_active_model();
+    currency.abbreviation = Set(req.abbreviation);
+    currency.name = Set(req.name);
+    currency.update(conn).await?;
+    Ok(())
+}
+
+/// Deletes a currency, which is only possible while no expense or transaction refers to it.
+pub async fn delete_currency(
+    conn: &DatabaseConnection,
+    currency_id: Id,
+) -> Result<(), DeleteCurrencyError> {
+    let Some(_) = find_entity_by_id::<currency::Entity>(conn, currency_id).await? else {
+        return Err(DeleteCurrencyError::CurrencyNotFound);
+    };
+    let (expense, predefined_expense, transaction) = tokio::join!(
+        Expense::find()
+            .filter(expense::Column::CurrencyId.eq(currency_id))
+            .one(conn),
+        PredefinedExpense::find()
+            .filter(predefined_expense::Column::CurrencyId.eq(currency_id))
+            .one(conn),
+        Transaction::find()
+            .filter(transaction::Column::CurrencyId.eq(currency_id))
+            .one(conn)
+    );
+    if expense?.is_some() || predefined_expense?.is_some() || transaction?.is_some() {
+        return Err(DeleteCurrencyError::CurrencyInUse);
+    }
+    Currency::delete_by_id(currency_id).exec(conn).await?;
+    Ok(())
+}
+
+async fn find_conflicting_currency(
+    conn: &DatabaseConnection,
+    req: &CurrencyRequest,
+    except_currency_id: Option<Id>,
+) -> Result<Option<currency::Model>, DbErr> {
+    let mut query = Currency::find().filter(
+        Condition::any()
+            .add(currency::Column::Abbreviation.eq(&req.abbreviation))
+            .add(currency::Column::Name.eq(&req.name)),
+    );
+    if let Some(except_currency_id) = except_currency_id {
+        query = query.filter(currency::Column::Id.ne(except_currency_id));
+    }
+    query.one(conn).await
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum CreateCurrencyError {
+        #[error("currency with the same abbreviation or name already exists")]
+        CurrencyAlreadyExists,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UpdateCurrencyError {
+        #[error("currency not found")]
+        CurrencyNotFound,
+        #[error("currency with the same abbreviation or name already exists")]
+        CurrencyAlreadyExists,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteCurrencyError {
+        #[error("currency not found")]
+        CurrencyNotFound,
+        #[error("currency is used by expenses or transactions")]
+        CurrencyInUse,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::logic::common::tests::{test_currency, test_db_error};
+    use crate::logic::common::tests::{
+        exec_ok, test_currency, test_db_error, test_expense, test_transaction, TEST_ID, TEST_STR,
+    };
 
     use super::*;
     use assert2::check;
     use sea_orm::{DatabaseBackend, MockDatabase};
 
+    fn currency_request() -> CurrencyRequest {
+        CurrencyRequest {
+            abbreviation: TEST_STR.to_string(),
+            name: TEST_STR.to_string(),
+        }
+    }
+
     #[tokio::test]
     async fn find_currencies_all_cases() {
         let expected_currencies: Vec<CurrencyResponse> =
@@ -44,4 +177,96 @@ mod tests {
         check!(empty_vec == Ok(vec![]));
         check!(db_error == Err(test_db_error()));
     }
+
+    #[tokio::test]
+    async fn create_currency_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![Vec::<currency::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_currency()]])
+            // already exists
+            .append_query_results(vec![vec![test_currency()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, already_exists, db_error) = tokio::join!(
+            create_currency(&conn, currency_request()),
+            create_currency(&conn, currency_request()),
+            create_currency(&conn, currency_request())
+        );
+
+        check!(happy_case == Ok(TEST_ID));
+        check!(already_exists == Err(CreateCurrencyError::CurrencyAlreadyExists));
+        check!(db_error == Err(CreateCurrencyError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn update_currency_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_currency()], vec![]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_currency()]])
+            // not found
+            .append_query_results(vec![Vec::<currency::Model>::new()])
+            // already exists
+            .append_query_results(vec![vec![test_currency()], vec![test_currency()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, already_exists, db_error) = tokio::join!(
+            update_currency(&conn, TEST_ID, currency_request()),
+            update_currency(&conn, TEST_ID, currency_request()),
+            update_currency(&conn, TEST_ID, currency_request()),
+            update_currency(&conn, TEST_ID, currency_request())
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(UpdateCurrencyError::CurrencyNotFound));
+        check!(already_exists == Err(UpdateCurrencyError::CurrencyAlreadyExists));
+        check!(db_error == Err(UpdateCurrencyError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_currency_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            // not found
+            .append_query_results(vec![Vec::<currency::Model>::new()])
+            // in use by an expense
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            // in use by a transaction
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            .append_query_results(vec![vec![test_transaction()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, used_by_expense, used_by_transaction, db_error) = tokio::join!(
+            delete_currency(&conn, TEST_ID),
+            delete_currency(&conn, TEST_ID),
+            delete_currency(&conn, TEST_ID),
+            delete_currency(&conn, TEST_ID),
+            delete_currency(&conn, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(DeleteCurrencyError::CurrencyNotFound));
+        check!(used_by_expense == Err(DeleteCurrencyError::CurrencyInUse));
+        check!(used_by_transaction == Err(DeleteCurrencyError::CurrencyInUse));
+        check!(db_error == Err(DeleteCurrencyError::DatabaseError(test_db_error())));
+    }
 }
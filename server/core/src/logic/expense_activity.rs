@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use crate::dto::expenses::ExpenseStatus;
+
+use chrono::NaiveDate;
+use entity::{expense, expense_pause, expense_price, Id, MoneyAmount};
+
+/// The average length of a year in days, the yearly occurrences of an expense are spread evenly over it.
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// Tells whether the expense is ended, paused or active on the given day.
+pub fn status_on(
+    expense: &expense::Model,
+    pauses: &[expense_pause::Model],
+    date: NaiveDate,
+) -> ExpenseStatus {
+    if expense.end_date.is_some_and(|end_date| end_date <= date) {
+        ExpenseStatus::Ended
+    } else if pauses.iter().any(|pause| {
+        pause.start_date <= date && pause.end_date.is_none_or(|end_date| date < end_date)
+    }) {
+        ExpenseStatus::Paused
+    } else {
+        ExpenseStatus::Active
+    }
+}
+
+/// Counts the days between `from` (inclusive) and `to` (exclusive) the expense is paid in:
+/// after it started, before it was cancelled and outside of its pauses.
+fn active_days(
+    expense: &expense::Model,
+    pauses: &[expense_pause::Model],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> i64 {
+    let from = from.max(expense.start_date);
+    let to = expense.end_date.map_or(to, |end_date| end_date.min(to));
+    if from >= to {
+        return 0;
+    }
+
+    let mut paused_intervals: Vec<(NaiveDate, NaiveDate)> = pauses
+        .iter()
+        .map(|pause| {
+            let end_date = pause.end_date.map_or(to, |end_date| end_date.min(to));
+            (pause.start_date.max(from), end_date)
+        })
+        .filter(|(start_date, end_date)| start_date < end_date)
+        .collect();
+    paused_intervals.sort();
+
+    // overlapping pauses are only subtracted once
+    let mut days = (to - from).num_days();
+    let mut paused_until = from;
+    for (start_date, end_date) in paused_intervals {
+        let start_date = start_date.max(paused_until);
+        if start_date < end_date {
+            days -= (end_date - start_date).num_days();
+            paused_until = end_date;
+        }
+    }
+    days
+}
+
+/// The number of payments expected between `from` (inclusive) and `to` (exclusive),
+/// the yearly occurrences of the recurrence spread over the days the expense is active.
+fn expected_occurrences(
+    expense: &expense::Model,
+    pauses: &[expense_pause::Model],
+    per_year: f64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> f64 {
+    per_year * active_days(expense, pauses, from, to) as f64 / DAYS_PER_YEAR
+}
+
+/// The value and currency id of the price effective on the given day,
+/// the price of the expense itself until its first price change.
+pub fn price_on(
+    expense: &expense::Model,
+    prices: &[expense_price::Model],
+    date: NaiveDate,
+) -> (MoneyAmount, Id) {
+    prices
+        .iter()
+        .filter(|price| price.effective_from <= date)
+        .max_by_key(|price| price.effective_from)
+        .map_or((expense.value, expense.currency_id), |price| {
+            (price.value, price.currency_id)
+        })
+}
+
+/// The payments expected between `from` (inclusive) and `to` (exclusive) as their value, currency id and number of occurrences.
+/// Every part of the period is counted with the price effective in it, so a price change splits the period.
+/// This is where the server counts with the yearly occurrences of the recurrence, the shares of the balance are
+/// calculated from it, so the days the expense is paused or ended are left out of them.
+pub fn expected_payments(
+    expense: &expense::Model,
+    pauses: &[expense_pause::Model],
+    prices: &[expense_price::Model],
+    per_year: f64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<(MoneyAmount, Id, f64)> {
+    let mut boundaries: Vec<NaiveDate> = prices
+        .iter()
+        .map(|price| price.effective_from)
+        .filter(|&effective_from| from < effective_from && effective_from < to)
+        .collect();
+    boundaries.push(from);
+    boundaries.push(to);
+    boundaries.sort();
+    boundaries.dedup();
+
+    boundaries
+        .windows(2)
+        .filter_map(|period| {
+            let occurrences = expected_occurrences(expense, pauses, per_year, period[0], period[1]);
+            if occurrences == 0.0 {
+                return None;
+            }
+            let (value, currency_id) = price_on(expense, prices, period[0]);
+            Some((value, currency_id, occurrences))
+        })
+        .collect()
+}
+
+/// The expected costs between `from` (inclusive) and `to` (exclusive) by currency id.
+pub fn expected_costs(
+    expense: &expense::Model,
+    pauses: &[expense_pause::Model],
+    prices: &[expense_price::Model],
+    per_year: f64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> BTreeMap<Id, MoneyAmount> {
+    let mut costs: BTreeMap<Id, MoneyAmount> = BTreeMap::new();
+    for (value, currency_id, occurrences) in
+        expected_payments(expense, pauses, prices, per_year, from, to)
+    {
+        let cost = MoneyAmount::from_f64_retain(occurrences).unwrap_or_default() * value;
+        *costs.entry(currency_id).or_default() += cost;
+    }
+    costs
+        .into_iter()
+        .map(|(currency_id, cost)| (currency_id, cost.round_dp(2)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{
+        test_expense, test_expense_pause, test_expense_price, TEST_ID,
+    };
+    use assert2::check;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+    }
+
+    fn pause(start_date: NaiveDate, end_date: Option<NaiveDate>) -> expense_pause::Model {
+        expense_pause::Model {
+            start_date,
+            end_date,
+            ..test_expense_pause()
+        }
+    }
+
+    #[test]
+    fn status_on_all_cases() {
+        let expense = expense::Model {
+            end_date: Some(date(6, 1)),
+            ..test_expense()
+        };
+        let pauses = vec![pause(date(2, 1), Some(date(3, 1))), pause(date(5, 1), None)];
+
+        check!(status_on(&expense, &pauses, date(1, 15)) == ExpenseStatus::Active);
+        check!(status_on(&expense, &pauses, date(2, 1)) == ExpenseStatus::Paused);
+        check!(status_on(&expense, &pauses, date(3, 1)) == ExpenseStatus::Active);
+        check!(status_on(&expense, &pauses, date(5, 20)) == ExpenseStatus::Paused);
+        check!(status_on(&expense, &pauses, date(6, 1)) == ExpenseStatus::Ended);
+        check!(status_on(&test_expense(), &[], date(6, 1)) == ExpenseStatus::Active);
+    }
+
+    #[test]
+    fn active_days_all_cases() {
+        let expense = expense::Model {
+            start_date: date(1, 11),
+            end_date: Some(date(3, 1)),
+            ..test_expense()
+        };
+        let overlapping_pauses = vec![
+            pause(date(2, 10), Some(date(2, 20))),
+            pause(date(2, 1), Some(date(2, 15))),
+        ];
+        let open_pause = vec![pause(date(2, 1), None)];
+
+        check!(active_days(&expense, &[], date(1, 1), date(2, 1)) == 21);
+        check!(active_days(&expense, &[], date(2, 1), date(4, 1)) == 28);
+        check!(active_days(&expense, &overlapping_pauses, date(2, 1), date(4, 1)) == 9);
+        check!(active_days(&expense, &open_pause, date(1, 1), date(4, 1)) == 21);
+        check!(active_days(&expense, &[], date(3, 1), date(4, 1)) == 0);
+        check!(active_days(&expense, &[], date(1, 1), date(1, 11)) == 0);
+    }
+
+    #[test]
+    fn expected_occurrences_all_cases() {
+        let expense = expense::Model {
+            start_date: date(1, 1),
+            end_date: None,
+            ..test_expense()
+        };
+        let half_year_pause = vec![pause(
+            date(1, 1),
+            Some(date(1, 1) + chrono::Duration::days(183)),
+        )];
+        let year_end = date(1, 1) + chrono::Duration::days(365);
+
+        let whole_year = expected_occurrences(&expense, &[], 12.0, date(1, 1), year_end);
+        let paused_half =
+            expected_occurrences(&expense, &half_year_pause, 12.0, date(1, 1), year_end);
+
+        check!((whole_year - 12.0).abs() < 0.01);
+        check!((paused_half - 6.0).abs() < 0.05);
+    }
+
+    fn price(value: i64, currency_id: Id, effective_from: NaiveDate) -> expense_price::Model {
+        expense_price::Model {
+            value: MoneyAmount::new(value, 0),
+            currency_id,
+            effective_from,
+            ..test_expense_price()
+        }
+    }
+
+    #[test]
+    fn price_on_all_cases() {
+        let expense = expense::Model {
+            start_date: date(1, 1),
+            value: MoneyAmount::new(10, 0),
+            currency_id: TEST_ID,
+            ..test_expense()
+        };
+        let prices = vec![
+            price(30, TEST_ID + 1, date(6, 1)),
+            price(20, TEST_ID, date(3, 1)),
+        ];
+
+        check!(price_on(&expense, &prices, date(2, 1)) == (MoneyAmount::new(10, 0), TEST_ID));
+        check!(price_on(&expense, &prices, date(3, 1)) == (MoneyAmount::new(20, 0), TEST_ID));
+        check!(price_on(&expense, &prices, date(5, 31)) == (MoneyAmount::new(20, 0), TEST_ID));
+        check!(price_on(&expense, &prices, date(7, 1)) == (MoneyAmount::new(30, 0), TEST_ID + 1));
+        check!(price_on(&expense, &[], date(7, 1)) == (MoneyAmount::new(10, 0), TEST_ID));
+    }
+
+    #[test]
+    fn expected_costs_all_cases() {
+        let expense = expense::Model {
+            start_date: date(1, 1),
+            end_date: None,
+            value: MoneyAmount::new(10, 0),
+            currency_id: TEST_ID,
+            ..test_expense()
+        };
+        let year_end = date(1, 1) + chrono::Duration::days(365);
+        let half_year = date(1, 1) + chrono::Duration::days(183);
+        let price_rise = vec![price(20, TEST_ID, half_year)];
+        let currency_change = vec![price(20, TEST_ID + 1, half_year)];
+
+        let unchanged = expected_costs(&expense, &[], &[], 12.0, date(1, 1), year_end);
+        let risen = expected_costs(&expense, &[], &price_rise, 12.0, date(1, 1), year_end);
+        let changed_currency =
+            expected_costs(&expense, &[], &currency_change, 12.0, date(1, 1), year_end);
+        let before_start = expected_costs(&expense, &[], &[], 12.0, date(1, 1), date(1, 1));
+
+        let cost_of = |costs: &BTreeMap<Id, MoneyAmount>, currency_id| {
+            costs.get(&currency_id).copied().unwrap_or_default()
+        };
+        check!((cost_of(&unchanged, TEST_ID) - MoneyAmount::new(120, 0)).abs() < MoneyAmount::ONE);
+        check!((cost_of(&risen, TEST_ID) - MoneyAmount::new(180, 0)).abs() < MoneyAmount::ONE);
+        check!(changed_currency.len() == 2);
+        check!(
+            (cost_of(&changed_currency, TEST_ID) - MoneyAmount::new(60, 0)).abs()
+                < MoneyAmount::ONE
+        );
+        check!(
+            (cost_of(&changed_currency, TEST_ID + 1) - MoneyAmount::new(120, 0)).abs()
+                < MoneyAmount::ONE
+        );
+        check!(before_start.is_empty());
+    }
+}
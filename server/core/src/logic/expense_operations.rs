@@ -1,59 +1,286 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use self::errors::{
-    CreateExpenseError, CreatePredefinedExpenseError, FindExpensesWithTransactionsByUserIdError,
-    ValidateRecurrenceAndCurrencyError,
+    AddPriceChangeError, CancelExpenseError, CreateExpenseError, CreatePredefinedExpenseError,
+    DeleteExpenseError, DeleteExpensePauseError, DeleteExpenseSplitError,
+    DeletePredefinedExpenseError, DeletePriceChangeError,
+    FindExpensesWithTransactionsByUserIdError, PauseExpenseError, RestoreExpenseError,
+    SetExpenseSplitError, UpdateExpenseError, UpdatePredefinedExpenseError,
+    ValidatePredefinedExpenseVisibilityError, ValidateRecurrenceAndCurrencyError,
 };
 
-use super::common;
-use super::user_operations::authorize_user;
+use super::category_operations::{descendant_ids, find_or_create_category, is_own_category};
+use super::common::{normalize_tags, parse_date};
+use super::expense_activity::{price_on, status_on};
+use super::household_operations::{
+    find_household_ids_by_user_id, find_shared_household_ids, is_household_member,
+};
+use super::participant_operations::{can_pay_for, can_payers_pay_for, find_participants_by_ids};
+use super::user_operations::errors::AuthorizeUserError;
+use super::user_operations::{authorize_user, find_date_format, is_admin};
 use crate::dto::expenses::{
-    ExpenseResponse, NewExpenseRequest, NewPredefinedExpenseRequest, PredefinedExpenseResponse,
+    CancelExpenseRequest, DeleteExpenseQuery, ExpenseListQuery, ExpenseResponse, ExpenseSort,
+    ExpenseSplitRequest, NewExpensePauseRequest, NewExpensePriceRequest, NewExpenseRequest,
+    NewPredefinedExpenseRequest, PatchExpenseRequest, PredefinedExpenseResponse, SortOrder,
 };
 use crate::logic::common::find_entity_by_id;
 
-use entity::expense::{self, Entity as Expense};
-use entity::predefined_expense::{self, Entity as PredefinedExpense};
-use entity::transaction;
-use entity::{currency, recurrence, Id};
+use chrono::{Local, NaiveDate};
+use entity::category::{self, Entity as Category};
+use entity::expense::{self, Entity as Expense, SplitMethod};
+use entity::expense_pause::{self, Entity as ExpensePause};
+use entity::expense_price::{self, Entity as ExpensePrice};
+use entity::expense_share::{self, Entity as ExpenseShare};
+use entity::expense_tag::{self, Entity as ExpenseTag};
+use entity::predefined_expense::{self, Entity as PredefinedExpense, PredefinedExpenseVisibility};
+use entity::transaction::{self, Entity as Transaction};
+use entity::transaction_tag::{self, Entity as TransactionTag};
+use entity::user::DateFormat;
+use entity::{currency, participant, recurrence, Id, MoneyAmount};
 
-use chrono::NaiveDate;
 use migration::DbErr;
+use sea_orm::sea_query::{Alias, ColumnRef, Expr, OrderedStatement, Query, WindowStatement};
 use sea_orm::ActiveValue::NotSet;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, LoaderTrait, QueryFilter, Set,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    FromQueryResult, IntoActiveModel, LoaderTrait, Order, QueryFilter, QueryOrder, QuerySelect,
+    Set, TransactionError, TransactionTrait,
 };
 
+/// The largest page of the expense listing.
+const MAX_EXPENSE_PAGE_SIZE: u64 = 100;
+
+/// Lists the expenses of the user together with the expenses of their households.
+/// Another member of a household can list the user too, but only sees the expenses of the households they share.
 pub async fn find_expenses_by_user_id(
     conn: &DatabaseConnection,
     authenticated_user_id: Id,
     user_id: Id,
+    query: ExpenseListQuery,
 ) -> Result<Vec<ExpenseResponse>, FindExpensesWithTransactionsByUserIdError> {
-    authorize_user(authenticated_user_id, user_id)?;
+    if query
+        .limit
+        .is_some_and(|limit| limit == 0 || limit > MAX_EXPENSE_PAGE_SIZE)
+    {
+        return Err(FindExpensesWithTransactionsByUserIdError::InvalidPageSize);
+    }
+    let household_ids = find_shared_household_ids(conn, authenticated_user_id, user_id).await?;
+    if authenticated_user_id != user_id && household_ids.is_empty() {
+        return Err(FindExpensesWithTransactionsByUserIdError::UnauthorizedUser(
+            AuthorizeUserError,
+        ));
+    }
+    let date_format = find_date_format(conn, authenticated_user_id).await?;
+
+    let mut visible = Condition::any().add(expense::Column::HouseholdId.is_in(household_ids));
+    if authenticated_user_id == user_id {
+        visible = visible.add(expense::Column::UserId.eq(user_id));
+    }
+    let mut select = Expense::find()
+        .filter(visible)
+        .filter(expense::Column::DeletedAt.is_null());
+    if !query.include_archived {
+        select = select.filter(expense::Column::Archived.eq(false));
+    }
+    if let Some(category_id) = query.category_id {
+        let categories = Category::find()
+            .filter(category::Column::UserId.eq(user_id))
+            .all(conn)
+            .await?;
+        select = select
+            .filter(expense::Column::CategoryId.is_in(descendant_ids(&categories, category_id)));
+    }
+    if let Some(tag) = &query.tag {
+        select = select.filter(
+            expense::Column::Id.in_subquery(
+                Query::select()
+                    .column(expense_tag::Column::ExpenseId)
+                    .from(expense_tag::Entity)
+                    .and_where(expense_tag::Column::Name.eq(tag.as_str()))
+                    .to_owned(),
+            ),
+        );
+    }
+    if let Some(name) = &query.name {
+        select = select.filter(expense::Column::Name.contains(name));
+    }
+    if let Some(household_id) = query.household_id {
+        select = select.filter(expense::Column::HouseholdId.eq(household_id));
+    }
+    if let Some(currency_id) = query.currency_id {
+        select = select.filter(expense::Column::CurrencyId.eq(currency_id));
+    }
+    if let Some(recurrence_id) = query.recurrence_id {
+        select = select.filter(expense::Column::RecurrenceId.eq(recurrence_id));
+    }
+    if let Some(predefined_expense_id) = query.predefined_expense_id {
+        select = select.filter(expense::Column::PredefinedExpenseId.eq(predefined_expense_id));
+    }
+    if let Some(from) = &query.from {
+        let from = parse_date(from, date_format)?;
+        select = select.filter(
+            Condition::any()
+                .add(expense::Column::EndDate.is_null())
+                .add(expense::Column::EndDate.gte(from)),
+        );
+    }
+    if let Some(to) = &query.to {
+        let to = parse_date(to, date_format)?;
+        select = select.filter(expense::Column::StartDate.lte(to));
+    }
+
+    let order = match query.order {
+        SortOrder::Asc => Order::Asc,
+        SortOrder::Desc => Order::Desc,
+    };
+    select = match query.sort {
+        ExpenseSort::Created => select.order_by(expense::Column::Id, order),
+        ExpenseSort::Name => select
+            .order_by(expense::Column::Name, order)
+            .order_by_asc(expense::Column::Id),
+        ExpenseSort::StartDate => select
+            .order_by(expense::Column::StartDate, order)
+            .order_by_asc(expense::Column::Id),
+        ExpenseSort::Value => select
+            .order_by(expense::Column::Value, order)
+            .order_by_asc(expense::Column::Id),
+    };
+    let expenses = select
+        .offset(query.offset)
+        .limit(query.limit)
+        .all(conn)
+        .await?;
+
+    Ok(load_expense_details(conn, expenses, query.transactions, date_format).await?)
+}
+
+/// Loads every expense of the user with all of their details, without any authorization.
+/// The archived expenses are included, the ones in the trash are not.
+pub(super) async fn load_expenses_by_user_id(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    date_format: DateFormat,
+) -> Result<Vec<ExpenseResponse>, DbErr> {
     let expenses = Expense::find()
         .filter(expense::Column::UserId.eq(user_id))
+        .filter(expense::Column::DeletedAt.is_null())
         .all(conn)
         .await?;
-    let (predefined_expenses, grouped_transactions, currencies, recurrences) = tokio::join!(
+    load_expense_details(conn, expenses, None, date_format).await
+}
+
+/// Loads the transactions, tags, pauses, price changes, shares and predefined expense of the expenses,
+/// with only the currencies, recurrences and participants they refer to. The dates are rendered in the given format,
+/// the status and price are the ones of the current day.
+async fn load_expense_details(
+    conn: &DatabaseConnection,
+    expenses: Vec<expense::Model>,
+    transaction_limit: Option<u64>,
+    date_format: DateFormat,
+) -> Result<Vec<ExpenseResponse>, DbErr> {
+    if expenses.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (
+        predefined_expenses,
+        grouped_tags,
+        grouped_transactions,
+        grouped_pauses,
+        grouped_prices,
+        grouped_shares,
+    ) = tokio::join!(
         expenses.load_one(predefined_expense::Entity, conn),
-        expenses.load_many(transaction::Entity, conn),
-        currency::Entity::find().all(conn),
-        recurrence::Entity::find().all(conn)
+        expenses.load_many(expense_tag::Entity, conn),
+        load_transactions(conn, &expenses, transaction_limit),
+        expenses.load_many(expense_pause::Entity, conn),
+        expenses.load_many(expense_price::Entity, conn),
+        expenses.load_many(expense_share::Entity, conn)
+    );
+    let predefined_expenses = predefined_expenses?;
+    let grouped_transactions = grouped_transactions?;
+    let grouped_prices = grouped_prices?;
+    let grouped_shares = grouped_shares?;
+
+    let mut currency_ids: HashSet<Id> =
+        expenses.iter().map(|expense| expense.currency_id).collect();
+    let mut recurrence_ids: HashSet<Id> = expenses
+        .iter()
+        .map(|expense| expense.recurrence_id)
+        .collect();
+    for predefined_expense in predefined_expenses.iter().flatten() {
+        currency_ids.insert(predefined_expense.currency_id);
+        recurrence_ids.insert(predefined_expense.recurrence_id);
+    }
+    currency_ids.extend(
+        grouped_transactions
+            .iter()
+            .flatten()
+            .map(|transaction| transaction.currency_id),
+    );
+    currency_ids.extend(
+        grouped_prices
+            .iter()
+            .flatten()
+            .map(|price| price.currency_id),
+    );
+    let transaction_ids: Vec<Id> = grouped_transactions
+        .iter()
+        .flatten()
+        .map(|transaction| transaction.id)
+        .collect();
+    let participant_ids: HashSet<Id> = grouped_transactions
+        .iter()
+        .flatten()
+        .map(|transaction| transaction.participant_id)
+        .chain(
+            grouped_shares
+                .iter()
+                .flatten()
+                .map(|share| share.participant_id),
+        )
+        .collect();
+    let (currencies, recurrences, transaction_tags, participants) = tokio::join!(
+        currency::Entity::find()
+            .filter(currency::Column::Id.is_in(currency_ids))
+            .all(conn),
+        recurrence::Entity::find()
+            .filter(recurrence::Column::Id.is_in(recurrence_ids))
+            .all(conn),
+        load_transaction_tags(conn, transaction_ids),
+        find_participants_by_ids(conn, participant_ids)
     );
-    let mut predefined_expenses: VecDeque<Option<predefined_expense::Model>> =
-        predefined_expenses?.into();
-    let mut grouped_transactions: VecDeque<Vec<transaction::Model>> =
-        grouped_transactions?.into_iter().collect();
     let currencies = currencies?;
     let recurrences = recurrences?;
+    let transaction_tags = transaction_tags?;
+    let participants = participants?;
+
+    let mut predefined_expenses: VecDeque<Option<predefined_expense::Model>> =
+        predefined_expenses.into();
+    let mut grouped_tags: VecDeque<Vec<expense_tag::Model>> = grouped_tags?.into_iter().collect();
+    let mut grouped_transactions: VecDeque<Vec<transaction::Model>> =
+        grouped_transactions.into_iter().collect();
+    let mut grouped_pauses: VecDeque<Vec<expense_pause::Model>> =
+        grouped_pauses?.into_iter().collect();
+    let mut grouped_prices: VecDeque<Vec<expense_price::Model>> =
+        grouped_prices.into_iter().collect();
+    let mut grouped_shares: VecDeque<Vec<expense_share::Model>> =
+        grouped_shares.into_iter().collect();
 
     assert!(
-        vec![predefined_expenses.len(), grouped_transactions.len()]
-            .iter()
-            .all(|&x| x == expenses.len()),
+        [
+            predefined_expenses.len(),
+            grouped_tags.len(),
+            grouped_transactions.len(),
+            grouped_pauses.len(),
+            grouped_prices.len(),
+            grouped_shares.len()
+        ]
+        .iter()
+        .all(|&x| x == expenses.len()),
         "the lengths of the fetched expense related lists should always be equal"
     );
 
+    let today = Local::now().date_naive();
     let expense_parts = expenses.into_iter().map(|expense| {
         let currency = find_currency(&currencies, expense.currency_id);
         let recurrence = find_recurrence(&recurrences, expense.recurrence_id);
@@ -70,6 +297,13 @@ pub async fn find_expenses_by_user_id(
             None => None,
         };
 
+        let tags = grouped_tags
+            .pop_front()
+            .expect("grouped tags queue should not be empty")
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+
         let transactions = grouped_transactions
             .pop_front()
             .expect("grouped transactions queue should not be empty");
@@ -77,7 +311,53 @@ pub async fn find_expenses_by_user_id(
             .into_iter()
             .map(|transaction| {
                 let transaction_currency = find_currency(&currencies, transaction.currency_id);
-                (transaction, transaction_currency)
+                let participant = find_participant(&participants, transaction.participant_id);
+                let tags = transaction_tags
+                    .iter()
+                    .filter(|tag| tag.transaction_id == transaction.id)
+                    .map(|tag| tag.name.clone())
+                    .collect();
+                (
+                    transaction,
+                    transaction_currency,
+                    participant,
+                    tags,
+                    date_format,
+                )
+            })
+            .collect();
+
+        let pauses = grouped_pauses
+            .pop_front()
+            .expect("grouped pauses queue should not be empty");
+        let status = status_on(&expense, &pauses, today);
+
+        let mut prices = grouped_prices
+            .pop_front()
+            .expect("grouped prices queue should not be empty");
+        prices.sort_by_key(|price| price.effective_from);
+        let (current_value, current_currency_id) = price_on(&expense, &prices, today);
+        let current_price = (
+            current_value,
+            find_currency(&currencies, current_currency_id),
+        );
+        let price_parts = prices
+            .into_iter()
+            .map(|price| {
+                let price_currency = find_currency(&currencies, price.currency_id);
+                (price, price_currency, date_format)
+            })
+            .collect();
+
+        let mut shares = grouped_shares
+            .pop_front()
+            .expect("grouped shares queue should not be empty");
+        shares.sort_by_key(|share| share.id);
+        let share_parts = shares
+            .into_iter()
+            .map(|share| {
+                let participant = find_participant(&participants, share.participant_id);
+                (share, participant)
             })
             .collect();
 
@@ -86,7 +366,14 @@ pub async fn find_expenses_by_user_id(
             currency,
             recurrence,
             predefined_expense,
+            tags,
             transaction_parts,
+            pauses,
+            price_parts,
+            share_parts,
+            status,
+            current_price,
+            date_format,
         )
     });
 
@@ -98,22 +385,120 @@ pub async fn find_expenses_by_user_id(
     Ok(expense_responses)
 }
 
+/// Loads the transactions of every expense which are not in the trash,
+/// only the latest ones of each expense if a limit is given.
+/// The limited transactions are numbered per expense by a window function, so they are loaded in a single query.
+async fn load_transactions(
+    conn: &DatabaseConnection,
+    expenses: &[expense::Model],
+    limit: Option<u64>,
+) -> Result<Vec<Vec<transaction::Model>>, DbErr> {
+    let Some(limit) = limit else {
+        let transactions = Transaction::find().filter(transaction::Column::DeletedAt.is_null());
+        return expenses.load_many(transactions, conn).await;
+    };
+    if limit == 0 {
+        return Ok(vec![Vec::new(); expenses.len()]);
+    }
+    let position = Alias::new("position");
+    let numbered_transactions = Query::select()
+        .expr(Expr::table_asterisk(Transaction))
+        .expr_window_as(
+            Expr::cust("ROW_NUMBER()"),
+            WindowStatement::partition_by(transaction::Column::ExpenseId)
+                .order_by_expr(Expr::col(transaction::Column::Date).into(), Order::Desc)
+                .order_by_expr(Expr::col(transaction::Column::Id).into(), Order::Desc)
+                .to_owned(),
+            position.clone(),
+        )
+        .from(Transaction)
+        .and_where(
+            Expr::col(transaction::Column::ExpenseId)
+                .is_in(expenses.iter().map(|expense| expense.id)),
+        )
+        .and_where(Expr::col(transaction::Column::DeletedAt).is_null())
+        .to_owned();
+    let query = Query::select()
+        .column(ColumnRef::Asterisk)
+        .from_subquery(numbered_transactions, Alias::new("numbered_transactions"))
+        .and_where(Expr::col(position.clone()).lte(limit))
+        .order_by(position, Order::Asc)
+        .to_owned();
+    let transactions =
+        transaction::Model::find_by_statement(conn.get_database_backend().build(&query))
+            .all(conn)
+            .await?;
+
+    let mut grouped_transactions: HashMap<Id, Vec<transaction::Model>> = HashMap::new();
+    for transaction in transactions {
+        grouped_transactions
+            .entry(transaction.expense_id)
+            .or_default()
+            .push(transaction);
+    }
+    Ok(expenses
+        .iter()
+        .map(|expense| grouped_transactions.remove(&expense.id).unwrap_or_default())
+        .collect())
+}
+
+async fn load_transaction_tags(
+    conn: &DatabaseConnection,
+    transaction_ids: Vec<Id>,
+) -> Result<Vec<transaction_tag::Model>, DbErr> {
+    if transaction_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    TransactionTag::find()
+        .filter(transaction_tag::Column::TransactionId.is_in(transaction_ids))
+        .all(conn)
+        .await
+}
+
 pub async fn create_expense(
     conn: &DatabaseConnection,
     user_id: Id,
     req: NewExpenseRequest,
 ) -> Result<Id, CreateExpenseError> {
-    if let Some(predefined_expense_id) = req.predefined_expense_id {
-        let opt = PredefinedExpense::find()
-            .filter(predefined_expense::Column::Id.eq(predefined_expense_id))
-            .one(conn)
-            .await?;
-        let Some(_) = opt else {
-            return Err(CreateExpenseError::InvalidPredefinedExpense);
-        };
-    }
+    let predefined_expense = match req.predefined_expense_id {
+        Some(predefined_expense_id) => {
+            let opt = find_visible_predefined_expense(conn, user_id, predefined_expense_id).await?;
+            let Some(predefined_expense) = opt else {
+                return Err(CreateExpenseError::InvalidPredefinedExpense);
+            };
+            Some(predefined_expense)
+        }
+        None => None,
+    };
     validate_recurrence_and_currency(conn, req.currency_id, req.recurrence_id).await?;
-    let parsed_date = NaiveDate::parse_from_str(&req.start_date, common::DATE_FORMAT)?;
+    let date_format = find_date_format(conn, user_id).await?;
+    let parsed_date = parse_date(&req.start_date, date_format)?;
+    let parsed_end_date = match req.end_date {
+        Some(end_date) => Some(parse_date(&end_date, date_format)?),
+        None => None,
+    };
+    if !is_valid_end_date(parsed_date, parsed_end_date) {
+        return Err(CreateExpenseError::InvalidEndDate);
+    }
+    let default_category =
+        predefined_expense.and_then(|predefined_expense| predefined_expense.default_category);
+    let category_id = match (req.category_id, default_category) {
+        (Some(category_id), _) => {
+            if !is_own_category(conn, user_id, category_id).await? {
+                return Err(CreateExpenseError::InvalidCategory);
+            }
+            Some(category_id)
+        }
+        (None, Some(default_category)) => {
+            Some(find_or_create_category(conn, user_id, &default_category).await?)
+        }
+        (None, None) => None,
+    };
+    if let Some(household_id) = req.household_id {
+        if !is_household_member(conn, household_id, user_id).await? {
+            return Err(CreateExpenseError::InvalidHousehold);
+        }
+    }
     let expense = expense::ActiveModel {
         id: NotSet,
         name: Set(req.name),
@@ -122,131 +507,1279 @@ pub async fn create_expense(
         currency_id: Set(req.currency_id),
         predefined_expense_id: Set(req.predefined_expense_id),
         start_date: Set(parsed_date),
+        end_date: Set(parsed_end_date),
         user_id: Set(user_id),
         value: Set(req.value),
+        category_id: Set(category_id),
+        household_id: Set(req.household_id),
+        split_method: Set(None),
+        archived: Set(false),
+        deleted_at: Set(None),
     };
-    let expense = expense.insert(conn).await?;
-    Ok(expense.id)
+    let tags = normalize_tags(req.tags);
+
+    let expense_id = conn
+        .transaction::<_, Id, CreateExpenseError>(|txn| {
+            Box::pin(async move {
+                let expense = expense.insert(txn).await?;
+                insert_expense_tags(txn, expense.id, tags).await?;
+                Ok(expense.id)
+            })
+        })
+        .await?;
+    Ok(expense_id)
 }
 
-pub async fn find_predefined_expenses(
+/// Replaces every field of an expense of the user.
+/// The value is the base of the price history, so it can only be changed by a price change once the expense has one.
+pub async fn update_expense(
     conn: &DatabaseConnection,
-) -> Result<Vec<PredefinedExpenseResponse>, DbErr> {
-    let predefined_expenses = PredefinedExpense::find().all(conn).await?;
-    let (currencies, recurrences) = tokio::join!(
-        predefined_expenses.load_one(currency::Entity, conn),
-        predefined_expenses.load_one(recurrence::Entity, conn)
-    );
-    let mut currencies: VecDeque<currency::Model> = currencies?.into_iter().flatten().collect();
-    let mut recurrences: VecDeque<recurrence::Model> = recurrences?.into_iter().flatten().collect();
+    user_id: Id,
+    expense_id: Id,
+    req: NewExpenseRequest,
+) -> Result<(), UpdateExpenseError> {
+    let expense = find_owned_expense(conn, user_id, expense_id).await?;
+    if req.value != expense.value && has_price_changes(conn, expense_id).await? {
+        return Err(UpdateExpenseError::PriceHistoryExists);
+    }
+    if let Some(predefined_expense_id) = req.predefined_expense_id {
+        validate_predefined_expense(conn, user_id, predefined_expense_id).await?;
+    }
+    if let Some(category_id) = req.category_id {
+        validate_category(conn, user_id, category_id).await?;
+    }
+    if let Some(household_id) = req.household_id {
+        validate_household(conn, user_id, household_id).await?;
+    }
+    if req.household_id != expense.household_id {
+        validate_payers_in_household(conn, &expense, req.household_id).await?;
+    }
+    validate_recurrence_and_currency(conn, req.currency_id, req.recurrence_id).await?;
+    let date_format = find_date_format(conn, user_id).await?;
+    let parsed_date = parse_date(&req.start_date, date_format)?;
+    let parsed_end_date = match req.end_date {
+        Some(end_date) => Some(parse_date(&end_date, date_format)?),
+        None => None,
+    };
+    if !is_valid_end_date(parsed_date, parsed_end_date) {
+        return Err(UpdateExpenseError::InvalidEndDate);
+    }
 
-    assert!(
-        vec![currencies.len(), recurrences.len()]
-            .iter()
-            .all(|&x| x == predefined_expenses.len()),
-        "the lengths of the fetched predefined expense related lists should be equal"
-    );
+    let mut expense = expense.into_active_model();
+    expense.name = Set(req.name);
+    expense.description = Set(req.description);
+    expense.recurrence_id = Set(req.recurrence_id);
+    expense.currency_id = Set(req.currency_id);
+    expense.predefined_expense_id = Set(req.predefined_expense_id);
+    expense.start_date = Set(parsed_date);
+    expense.end_date = Set(parsed_end_date);
+    expense.value = Set(req.value);
+    expense.category_id = Set(req.category_id);
+    expense.household_id = Set(req.household_id);
+    let tags = normalize_tags(req.tags);
 
-    let predefined_expense_response = predefined_expenses
-        .into_iter()
-        .map(|predefined_expense| {
-            (
-                predefined_expense,
-                currencies
-                    .pop_front()
-                    .expect("currency queue should not be empty"),
-                recurrences
-                    .pop_front()
-                    .expect("recurrence queue should not be empty"),
-            )
+    conn.transaction::<_, (), UpdateExpenseError>(|txn| {
+        Box::pin(async move {
+            expense.update(txn).await?;
+            replace_expense_tags(txn, expense_id, tags).await?;
+            Ok(())
         })
-        .map(|parts| parts.into())
-        .collect();
-
-    Ok(predefined_expense_response)
+    })
+    .await?;
+    Ok(())
 }
 
-pub async fn create_predefined_expense(
+/// Changes the given fields of an expense of the user, validated the same way as on update.
+pub async fn patch_expense(
     conn: &DatabaseConnection,
-    req: NewPredefinedExpenseRequest,
-) -> Result<Id, CreatePredefinedExpenseError> {
-    validate_recurrence_and_currency(conn, req.currency_id, req.recurrence_id).await?;
-    let predefined_expense = predefined_expense::ActiveModel {
-        id: NotSet,
-        name: Set(req.name),
-        description: Set(req.description),
-        value: Set(req.value),
-        currency_id: Set(req.currency_id),
-        recurrence_id: Set(req.recurrence_id),
+    user_id: Id,
+    expense_id: Id,
+    req: PatchExpenseRequest,
+) -> Result<(), UpdateExpenseError> {
+    let expense = find_owned_expense(conn, user_id, expense_id).await?;
+    if req.value.is_some_and(|value| value != expense.value)
+        && has_price_changes(conn, expense_id).await?
+    {
+        return Err(UpdateExpenseError::PriceHistoryExists);
+    }
+    if let Some(Some(predefined_expense_id)) = req.predefined_expense_id {
+        validate_predefined_expense(conn, user_id, predefined_expense_id).await?;
+    }
+    if let Some(Some(category_id)) = req.category_id {
+        validate_category(conn, user_id, category_id).await?;
+    }
+    if let Some(Some(household_id)) = req.household_id {
+        validate_household(conn, user_id, household_id).await?;
+    }
+    if let Some(household_id) = req
+        .household_id
+        .filter(|household_id| *household_id != expense.household_id)
+    {
+        validate_payers_in_household(conn, &expense, household_id).await?;
+    }
+    if req.currency_id.is_some() || req.recurrence_id.is_some() {
+        validate_recurrence_and_currency(
+            conn,
+            req.currency_id.unwrap_or(expense.currency_id),
+            req.recurrence_id.unwrap_or(expense.recurrence_id),
+        )
+        .await?;
+    }
+    let (parsed_date, parsed_end_date) = match (req.start_date, req.end_date) {
+        (None, None) => (None, None),
+        (None, Some(None)) => (None, Some(None)),
+        (start_date, end_date) => {
+            let date_format = find_date_format(conn, user_id).await?;
+            let parsed_date = match start_date {
+                Some(start_date) => Some(parse_date(&start_date, date_format)?),
+                None => None,
+            };
+            let parsed_end_date = match end_date {
+                Some(Some(end_date)) => Some(Some(parse_date(&end_date, date_format)?)),
+                Some(None) => Some(None),
+                None => None,
+            };
+            (parsed_date, parsed_end_date)
+        }
     };
-    let predefined_expense = predefined_expense.insert(conn).await?;
-    Ok(predefined_expense.id)
+    if !is_valid_end_date(
+        parsed_date.unwrap_or(expense.start_date),
+        parsed_end_date.unwrap_or(expense.end_date),
+    ) {
+        return Err(UpdateExpenseError::InvalidEndDate);
+    }
+
+    let mut expense = expense.into_active_model();
+    if let Some(name) = req.name {
+        expense.name = Set(name);
+    }
+    if let Some(description) = req.description {
+        expense.description = Set(description);
+    }
+    if let Some(recurrence_id) = req.recurrence_id {
+        expense.recurrence_id = Set(recurrence_id);
+    }
+    if let Some(currency_id) = req.currency_id {
+        expense.currency_id = Set(currency_id);
+    }
+    if let Some(predefined_expense_id) = req.predefined_expense_id {
+        expense.predefined_expense_id = Set(predefined_expense_id);
+    }
+    if let Some(start_date) = parsed_date {
+        expense.start_date = Set(start_date);
+    }
+    if let Some(end_date) = parsed_end_date {
+        expense.end_date = Set(end_date);
+    }
+    if let Some(value) = req.value {
+        expense.value = Set(value);
+    }
+    if let Some(category_id) = req.category_id {
+        expense.category_id = Set(category_id);
+    }
+    if let Some(household_id) = req.household_id {
+        expense.household_id = Set(household_id);
+    }
+    if let Some(archived) = req.archived {
+        expense.archived = Set(archived);
+    }
+    let tags = req.tags.map(normalize_tags);
+    if !expense.is_changed() && tags.is_none() {
+        return Ok(());
+    }
+
+    conn.transaction::<_, (), UpdateExpenseError>(|txn| {
+        Box::pin(async move {
+            if expense.is_changed() {
+                expense.update(txn).await?;
+            }
+            if let Some(tags) = tags {
+                replace_expense_tags(txn, expense_id, tags).await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
 }
 
-async fn validate_recurrence_and_currency(
+/// Moves an expense of the user to the trash. Its transactions are moved to another expense of the user if one is given
+/// and their participants can pay for it, otherwise they stay with the expense and are restored together with it.
+pub async fn delete_expense(
     conn: &DatabaseConnection,
-    currency_id: Id,
-    recurrence_id: Id,
-) -> Result<(), ValidateRecurrenceAndCurrencyError> {
-    let (referred_recurrence, referred_currency) = tokio::join!(
-        find_entity_by_id::<recurrence::Entity>(conn, recurrence_id),
-        find_entity_by_id::<currency::Entity>(conn, currency_id)
-    );
-    let Some(_) = referred_currency? else {
-        return Err(ValidateRecurrenceAndCurrencyError::InvalidCurrency)
-    };
-    let Some(_) = referred_recurrence? else {
-        return Err(ValidateRecurrenceAndCurrencyError::InvalidRecurrence)
+    user_id: Id,
+    expense_id: Id,
+    query: DeleteExpenseQuery,
+) -> Result<(), DeleteExpenseError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(DeleteExpenseError::ExpenseNotFound);
     };
+    authorize_user(user_id, expense.user_id)?;
+    if let Some(target_expense_id) = query.reassign_to {
+        let Some(target_expense) = find_expense_by_id(conn, target_expense_id).await? else {
+            return Err(DeleteExpenseError::InvalidReassignTarget);
+        };
+        if target_expense.id == expense.id || target_expense.user_id != user_id {
+            return Err(DeleteExpenseError::InvalidReassignTarget);
+        }
+        let payer_ids = Transaction::find()
+            .filter(transaction::Column::ExpenseId.eq(expense.id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|transaction| transaction.participant_id)
+            .collect();
+        let payers = find_participants_by_ids(conn, payer_ids).await?;
+        if !payers
+            .iter()
+            .all(|payer| can_pay_for(payer, &target_expense))
+        {
+            return Err(DeleteExpenseError::ForeignPayers);
+        }
+    }
+
+    let deleted_at = Local::now();
+    conn.transaction::<_, (), DeleteExpenseError>(|txn| {
+        Box::pin(async move {
+            if let Some(target_expense_id) = query.reassign_to {
+                Transaction::update_many()
+                    .col_expr(
+                        transaction::Column::ExpenseId,
+                        Expr::value(target_expense_id),
+                    )
+                    .filter(transaction::Column::ExpenseId.eq(expense_id))
+                    .exec(txn)
+                    .await?;
+            }
+            Expense::update_many()
+                .col_expr(expense::Column::DeletedAt, Expr::value(deleted_at))
+                .filter(expense::Column::Id.eq(expense_id))
+                .exec(txn)
+                .await?;
+            Ok(())
+        })
+    })
+    .await?;
     Ok(())
 }
 
-fn find_currency(currencies: &[currency::Model], id: Id) -> currency::Model {
-    currencies
-        .iter()
-        .find(|currency| currency.id == id)
-        .expect("the currency should be in the database")
-        .clone()
-}
+/// Takes an expense of the user out of the trash, its transactions reappear together with it.
+pub async fn restore_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+) -> Result<(), RestoreExpenseError> {
+    let Some(expense) = find_entity_by_id::<expense::Entity>(conn, expense_id).await? else {
+        return Err(RestoreExpenseError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    if expense.deleted_at.is_none() {
+        return Err(RestoreExpenseError::ExpenseNotInTrash);
+    }
 
-fn find_recurrence(recurrences: &[recurrence::Model], id: Id) -> recurrence::Model {
-    recurrences
-        .iter()
-        .find(|recurrence| recurrence.id == id)
-        .expect("the currency should be in the database")
-        .clone()
+    let mut expense = expense.into_active_model();
+    expense.deleted_at = Set(None);
+    expense.update(conn).await?;
+    Ok(())
 }
 
-pub mod errors {
-    use migration::DbErr;
-    use thiserror::Error;
+/// Cancels an expense of the user from the given date, it can be moved earlier or later by cancelling it again.
+pub async fn cancel_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+    req: CancelExpenseRequest,
+) -> Result<(), CancelExpenseError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(CancelExpenseError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    let date_format = find_date_format(conn, user_id).await?;
+    let end_date = parse_date(&req.date, date_format)?;
+    if !is_valid_end_date(expense.start_date, Some(end_date)) {
+        return Err(CancelExpenseError::InvalidEndDate);
+    }
 
-    use crate::logic::user_operations::errors::AuthorizeUserError;
+    let mut expense = expense.into_active_model();
+    expense.end_date = Set(Some(end_date));
+    expense.update(conn).await?;
+    Ok(())
+}
 
-    #[derive(Error, Debug, PartialEq, Eq)]
-    pub enum FindExpensesWithTransactionsByUserIdError {
-        #[error("{0}")]
-        UnauthorizedUser(#[from] AuthorizeUserError),
-        #[error("database error: '{0}'")]
-        DatabaseError(#[from] DbErr),
+/// Pauses an expense of the user for an interval, which must not overlap its other pauses.
+pub async fn pause_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+    req: NewExpensePauseRequest,
+) -> Result<Id, PauseExpenseError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(PauseExpenseError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    let date_format = find_date_format(conn, user_id).await?;
+    let start_date = parse_date(&req.start_date, date_format)?;
+    let end_date = match req.end_date {
+        Some(end_date) => Some(parse_date(&end_date, date_format)?),
+        None => None,
+    };
+    if !is_valid_end_date(start_date, end_date) {
+        return Err(PauseExpenseError::InvalidInterval);
     }
 
-    #[derive(Error, Debug, PartialEq, Eq)]
-    pub enum CreateExpenseError {
-        #[error("predefined expense is invalid")]
-        InvalidPredefinedExpense,
-        #[error("start_date could not be parsed")]
-        InvalidStartDate(#[from] chrono::ParseError),
-        #[error("invalid related type: '{0}'")]
-        InvalidRelatedType(#[from] ValidateRecurrenceAndCurrencyError),
-        #[error("database error: '{0}'")]
-        DatabaseError(#[from] DbErr),
+    let pauses = ExpensePause::find()
+        .filter(expense_pause::Column::ExpenseId.eq(expense_id))
+        .all(conn)
+        .await?;
+    let overlaps = pauses.iter().any(|pause| {
+        pause
+            .end_date
+            .is_none_or(|pause_end| start_date < pause_end)
+            && end_date.is_none_or(|end_date| pause.start_date < end_date)
+    });
+    if overlaps {
+        return Err(PauseExpenseError::OverlappingPause);
     }
 
-    #[derive(Error, Debug, PartialEq, Eq)]
+    let pause = expense_pause::ActiveModel {
+        id: NotSet,
+        expense_id: Set(expense_id),
+        start_date: Set(start_date),
+        end_date: Set(end_date),
+    };
+    let pause = pause.insert(conn).await?;
+    Ok(pause.id)
+}
+
+/// Deletes a pause of an expense of the user, the expense is paid again in that interval.
+pub async fn delete_expense_pause(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+    pause_id: Id,
+) -> Result<(), DeleteExpensePauseError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(DeleteExpensePauseError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    let Some(pause) = find_entity_by_id::<expense_pause::Entity>(conn, pause_id).await? else {
+        return Err(DeleteExpensePauseError::PauseNotFound);
+    };
+    if pause.expense_id != expense.id {
+        return Err(DeleteExpensePauseError::PauseNotFound);
+    }
+    ExpensePause::delete_by_id(pause.id).exec(conn).await?;
+    Ok(())
+}
+
+/// Changes the price of an expense of the user from the effective date, which has to be after the start date
+/// of the expense. A change with a future effective date is scheduled, it takes effect on that day.
+pub async fn add_price_change(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+    req: NewExpensePriceRequest,
+) -> Result<Id, AddPriceChangeError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(AddPriceChangeError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    let Some(_) = find_entity_by_id::<currency::Entity>(conn, req.currency_id).await? else {
+        return Err(AddPriceChangeError::InvalidCurrency);
+    };
+    let date_format = find_date_format(conn, user_id).await?;
+    let effective_from = parse_date(&req.effective_from, date_format)?;
+    if !is_valid_end_date(expense.start_date, Some(effective_from)) {
+        return Err(AddPriceChangeError::InvalidEffectiveDate);
+    }
+    let same_day_change = ExpensePrice::find()
+        .filter(expense_price::Column::ExpenseId.eq(expense_id))
+        .filter(expense_price::Column::EffectiveFrom.eq(effective_from))
+        .one(conn)
+        .await?;
+    if same_day_change.is_some() {
+        return Err(AddPriceChangeError::PriceChangeExists);
+    }
+
+    let price = expense_price::ActiveModel {
+        id: NotSet,
+        expense_id: Set(expense_id),
+        value: Set(req.value),
+        currency_id: Set(req.currency_id),
+        effective_from: Set(effective_from),
+    };
+    let price = price.insert(conn).await?;
+    Ok(price.id)
+}
+
+/// Deletes a price change of an expense of the user, the previous price stays effective instead.
+pub async fn delete_price_change(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+    price_id: Id,
+) -> Result<(), DeletePriceChangeError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(DeletePriceChangeError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    let Some(price) = find_entity_by_id::<expense_price::Entity>(conn, price_id).await? else {
+        return Err(DeletePriceChangeError::PriceChangeNotFound);
+    };
+    if price.expense_id != expense.id {
+        return Err(DeletePriceChangeError::PriceChangeNotFound);
+    }
+    ExpensePrice::delete_by_id(price.id).exec(conn).await?;
+    Ok(())
+}
+
+/// Splits the costs of an expense of the user between the participants, replacing its previous split.
+pub async fn set_expense_split(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+    req: ExpenseSplitRequest,
+) -> Result<(), SetExpenseSplitError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(SetExpenseSplitError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    validate_split(&req)?;
+    let participant_ids: HashSet<Id> = req
+        .shares
+        .iter()
+        .map(|share| share.participant_id)
+        .collect();
+    let participants = find_participants_by_ids(conn, participant_ids).await?;
+    if participants.len() != req.shares.len()
+        || !participants
+            .iter()
+            .all(|participant| can_pay_for(participant, &expense))
+    {
+        return Err(SetExpenseSplitError::InvalidParticipant);
+    }
+
+    let shares: Vec<expense_share::ActiveModel> = req
+        .shares
+        .into_iter()
+        .map(|share| expense_share::ActiveModel {
+            id: NotSet,
+            expense_id: Set(expense_id),
+            participant_id: Set(share.participant_id),
+            value: Set(match req.method {
+                SplitMethod::Equal => None,
+                _ => share.value,
+            }),
+        })
+        .collect();
+    let mut expense = expense.into_active_model();
+    expense.split_method = Set(Some(req.method));
+
+    conn.transaction::<_, (), SetExpenseSplitError>(|txn| {
+        Box::pin(async move {
+            expense.update(txn).await?;
+            ExpenseShare::delete_many()
+                .filter(expense_share::Column::ExpenseId.eq(expense_id))
+                .exec(txn)
+                .await?;
+            ExpenseShare::insert_many(shares).exec(txn).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Removes the split of an expense of the user, it is left out of the balances from then on.
+pub async fn delete_expense_split(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+) -> Result<(), DeleteExpenseSplitError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(DeleteExpenseSplitError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+
+    let mut expense = expense.into_active_model();
+    expense.split_method = Set(None);
+    conn.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            expense.update(txn).await?;
+            ExpenseShare::delete_many()
+                .filter(expense_share::Column::ExpenseId.eq(expense_id))
+                .exec(txn)
+                .await?;
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| match e {
+        TransactionError::Connection(e) | TransactionError::Transaction(e) => e,
+    })?;
+    Ok(())
+}
+
+/// Every participant can have only one share. Percentages have to add up to 100,
+/// fixed amounts can not be negative and weights have to be positive.
+fn validate_split(req: &ExpenseSplitRequest) -> Result<(), SetExpenseSplitError> {
+    let mut participants = HashSet::new();
+    if !req
+        .shares
+        .iter()
+        .all(|share| participants.insert(share.participant_id))
+    {
+        return Err(SetExpenseSplitError::DuplicateParticipant);
+    }
+    let mut values = req.shares.iter().map(|share| share.value);
+    match req.method {
+        SplitMethod::Equal => {}
+        SplitMethod::Percentage => {
+            if values
+                .clone()
+                .any(|value| value.is_none_or(|value| value < MoneyAmount::ZERO))
+            {
+                return Err(SetExpenseSplitError::InvalidShareValue);
+            }
+            if values.flatten().sum::<MoneyAmount>() != MoneyAmount::ONE_HUNDRED {
+                return Err(SetExpenseSplitError::InvalidPercentages);
+            }
+        }
+        SplitMethod::Fixed => {
+            if values.any(|value| value.is_none_or(|value| value < MoneyAmount::ZERO)) {
+                return Err(SetExpenseSplitError::InvalidShareValue);
+            }
+        }
+        SplitMethod::Weight => {
+            if values.any(|value| value.is_none_or(|value| value <= MoneyAmount::ZERO)) {
+                return Err(SetExpenseSplitError::InvalidShareValue);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The end of an interval is exclusive, so it has to be after its start.
+fn is_valid_end_date(start_date: NaiveDate, end_date: Option<NaiveDate>) -> bool {
+    end_date.is_none_or(|end_date| start_date < end_date)
+}
+
+/// Finds an expense by its id, the expenses in the trash are treated as missing.
+pub(super) async fn find_expense_by_id(
+    conn: &DatabaseConnection,
+    expense_id: Id,
+) -> Result<Option<expense::Model>, DbErr> {
+    let expense = find_entity_by_id::<expense::Entity>(conn, expense_id).await?;
+    Ok(expense.filter(|expense| expense.deleted_at.is_none()))
+}
+
+async fn find_owned_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    expense_id: Id,
+) -> Result<expense::Model, UpdateExpenseError> {
+    let Some(expense) = find_expense_by_id(conn, expense_id).await? else {
+        return Err(UpdateExpenseError::ExpenseNotFound);
+    };
+    authorize_user(user_id, expense.user_id)?;
+    Ok(expense)
+}
+
+async fn has_price_changes(conn: &DatabaseConnection, expense_id: Id) -> Result<bool, DbErr> {
+    let price_change = ExpensePrice::find()
+        .filter(expense_price::Column::ExpenseId.eq(expense_id))
+        .one(conn)
+        .await?;
+    Ok(price_change.is_some())
+}
+
+async fn validate_predefined_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    predefined_expense_id: Id,
+) -> Result<(), UpdateExpenseError> {
+    let Some(_) = find_visible_predefined_expense(conn, user_id, predefined_expense_id).await?
+    else {
+        return Err(UpdateExpenseError::InvalidPredefinedExpense);
+    };
+    Ok(())
+}
+
+async fn validate_category(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    category_id: Id,
+) -> Result<(), UpdateExpenseError> {
+    if !is_own_category(conn, user_id, category_id).await? {
+        return Err(UpdateExpenseError::InvalidCategory);
+    }
+    Ok(())
+}
+
+async fn validate_household(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    household_id: Id,
+) -> Result<(), UpdateExpenseError> {
+    if !is_household_member(conn, household_id, user_id).await? {
+        return Err(UpdateExpenseError::InvalidHousehold);
+    }
+    Ok(())
+}
+
+/// An expense cannot be moved to another household while it has transactions or shares of participants who could not pay for it there.
+async fn validate_payers_in_household(
+    conn: &DatabaseConnection,
+    expense: &expense::Model,
+    household_id: Option<Id>,
+) -> Result<(), UpdateExpenseError> {
+    let moved_expense = expense::Model {
+        household_id,
+        ..expense.clone()
+    };
+    if !can_payers_pay_for(conn, &moved_expense).await? {
+        return Err(UpdateExpenseError::ForeignPayers);
+    }
+    Ok(())
+}
+
+async fn insert_expense_tags<C: ConnectionTrait>(
+    conn: &C,
+    expense_id: Id,
+    tags: Vec<String>,
+) -> Result<(), DbErr> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+    let tags = tags.into_iter().map(|name| expense_tag::ActiveModel {
+        id: NotSet,
+        expense_id: Set(expense_id),
+        name: Set(name),
+    });
+    ExpenseTag::insert_many(tags).exec(conn).await?;
+    Ok(())
+}
+
+async fn replace_expense_tags<C: ConnectionTrait>(
+    conn: &C,
+    expense_id: Id,
+    tags: Vec<String>,
+) -> Result<(), DbErr> {
+    ExpenseTag::delete_many()
+        .filter(expense_tag::Column::ExpenseId.eq(expense_id))
+        .exec(conn)
+        .await?;
+    insert_expense_tags(conn, expense_id, tags).await
+}
+
+/// Lists the predefined expenses the user can see: the public catalog, their own ones and the ones of their households.
+pub async fn find_predefined_expenses(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<Vec<PredefinedExpenseResponse>, DbErr> {
+    let household_ids = find_household_ids_by_user_id(conn, user_id).await?;
+    let predefined_expenses = PredefinedExpense::find()
+        .filter(
+            Condition::any()
+                .add(predefined_expense::Column::Visibility.eq(PredefinedExpenseVisibility::Public))
+                .add(
+                    Condition::all()
+                        .add(
+                            predefined_expense::Column::Visibility
+                                .eq(PredefinedExpenseVisibility::Private),
+                        )
+                        .add(predefined_expense::Column::UserId.eq(user_id)),
+                )
+                .add(
+                    Condition::all()
+                        .add(
+                            predefined_expense::Column::Visibility
+                                .eq(PredefinedExpenseVisibility::Household),
+                        )
+                        .add(predefined_expense::Column::HouseholdId.is_in(household_ids)),
+                ),
+        )
+        .order_by_asc(predefined_expense::Column::Name)
+        .all(conn)
+        .await?;
+    let (currencies, recurrences) = tokio::join!(
+        predefined_expenses.load_one(currency::Entity, conn),
+        predefined_expenses.load_one(recurrence::Entity, conn)
+    );
+    let mut currencies: VecDeque<currency::Model> = currencies?.into_iter().flatten().collect();
+    let mut recurrences: VecDeque<recurrence::Model> = recurrences?.into_iter().flatten().collect();
+
+    assert!(
+        [currencies.len(), recurrences.len()]
+            .iter()
+            .all(|&x| x == predefined_expenses.len()),
+        "the lengths of the fetched predefined expense related lists should be equal"
+    );
+
+    let predefined_expense_response = predefined_expenses
+        .into_iter()
+        .map(|predefined_expense| {
+            (
+                predefined_expense,
+                currencies
+                    .pop_front()
+                    .expect("currency queue should not be empty"),
+                recurrences
+                    .pop_front()
+                    .expect("recurrence queue should not be empty"),
+            )
+        })
+        .map(|parts| parts.into())
+        .collect();
+
+    Ok(predefined_expense_response)
+}
+
+/// Creates a predefined expense owned by the user.
+pub async fn create_predefined_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    req: NewPredefinedExpenseRequest,
+) -> Result<Id, CreatePredefinedExpenseError> {
+    let household_id =
+        validate_predefined_expense_visibility(conn, user_id, req.visibility, req.household_id)
+            .await?;
+    validate_recurrence_and_currency(conn, req.currency_id, req.recurrence_id).await?;
+    let predefined_expense = predefined_expense::ActiveModel {
+        id: NotSet,
+        name: Set(req.name),
+        description: Set(req.description),
+        value: Set(req.value),
+        currency_id: Set(req.currency_id),
+        recurrence_id: Set(req.recurrence_id),
+        default_category: Set(req.default_category),
+        user_id: Set(Some(user_id)),
+        household_id: Set(household_id),
+        visibility: Set(req.visibility),
+    };
+    let predefined_expense = predefined_expense.insert(conn).await?;
+    Ok(predefined_expense.id)
+}
+
+/// Replaces every field of a predefined expense the user can manage, the owner stays the same.
+/// The visibility can not be narrowed while expenses of other users refer to it, as they could not see it anymore.
+pub async fn update_predefined_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    predefined_expense_id: Id,
+    req: NewPredefinedExpenseRequest,
+) -> Result<(), UpdatePredefinedExpenseError> {
+    let Some(predefined_expense) =
+        find_visible_predefined_expense(conn, user_id, predefined_expense_id).await?
+    else {
+        return Err(UpdatePredefinedExpenseError::PredefinedExpenseNotFound);
+    };
+    if !can_manage_predefined_expense(conn, user_id, &predefined_expense).await? {
+        return Err(UpdatePredefinedExpenseError::UserUnauthorized(
+            AuthorizeUserError,
+        ));
+    }
+    let household_id =
+        validate_predefined_expense_visibility(conn, user_id, req.visibility, req.household_id)
+            .await?;
+    validate_recurrence_and_currency(conn, req.currency_id, req.recurrence_id).await?;
+    let narrowed = req.visibility != PredefinedExpenseVisibility::Public
+        && (req.visibility, household_id)
+            != (
+                predefined_expense.visibility,
+                predefined_expense.household_id,
+            );
+    if narrowed
+        && is_predefined_expense_used_by_others(conn, user_id, predefined_expense_id).await?
+    {
+        return Err(UpdatePredefinedExpenseError::PredefinedExpenseInUse);
+    }
+
+    let mut predefined_expense = predefined_expense.into_active_model();
+    predefined_expense.name = Set(req.name);
+    predefined_expense.description = Set(req.description);
+    predefined_expense.value = Set(req.value);
+    predefined_expense.currency_id = Set(req.currency_id);
+    predefined_expense.recurrence_id = Set(req.recurrence_id);
+    predefined_expense.default_category = Set(req.default_category);
+    predefined_expense.household_id = Set(household_id);
+    predefined_expense.visibility = Set(req.visibility);
+    predefined_expense.update(conn).await?;
+    Ok(())
+}
+
+/// Deletes a predefined expense the user can manage, which is only possible while no expense of another user refers to it.
+/// The expenses of the user referring to it are kept without a predefined expense.
+pub async fn delete_predefined_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    predefined_expense_id: Id,
+) -> Result<(), DeletePredefinedExpenseError> {
+    let Some(predefined_expense) =
+        find_visible_predefined_expense(conn, user_id, predefined_expense_id).await?
+    else {
+        return Err(DeletePredefinedExpenseError::PredefinedExpenseNotFound);
+    };
+    if !can_manage_predefined_expense(conn, user_id, &predefined_expense).await? {
+        return Err(DeletePredefinedExpenseError::UserUnauthorized(
+            AuthorizeUserError,
+        ));
+    }
+    if is_predefined_expense_used_by_others(conn, user_id, predefined_expense_id).await? {
+        return Err(DeletePredefinedExpenseError::PredefinedExpenseInUse);
+    }
+
+    conn.transaction::<_, (), DeletePredefinedExpenseError>(|txn| {
+        Box::pin(async move {
+            Expense::update_many()
+                .col_expr(
+                    expense::Column::PredefinedExpenseId,
+                    Expr::value(Option::<Id>::None),
+                )
+                .filter(expense::Column::PredefinedExpenseId.eq(predefined_expense_id))
+                .exec(txn)
+                .await?;
+            PredefinedExpense::delete_by_id(predefined_expense_id)
+                .exec(txn)
+                .await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Deletes the predefined expenses a user who is being deleted still owns, should be run inside a database transaction.
+/// The ones of the households the user is a member of are handed over to the successors before, so only the private ones
+/// and the ones of households the user already left are deleted. The public ones stay in the catalog without an owner.
+/// The expenses created from the deleted ones keep their values without the reference.
+pub(super) async fn delete_own_predefined_expenses<C: ConnectionTrait>(
+    txn: &C,
+    user_id: Id,
+) -> Result<(), DbErr> {
+    let own_ids = Query::select()
+        .column(predefined_expense::Column::Id)
+        .from(PredefinedExpense)
+        .and_where(Expr::col(predefined_expense::Column::UserId).eq(user_id))
+        .and_where(
+            Expr::col(predefined_expense::Column::Visibility)
+                .ne(PredefinedExpenseVisibility::Public),
+        )
+        .to_owned();
+    Expense::update_many()
+        .col_expr(
+            expense::Column::PredefinedExpenseId,
+            Expr::value(Option::<Id>::None),
+        )
+        .filter(expense::Column::PredefinedExpenseId.in_subquery(own_ids))
+        .exec(txn)
+        .await?;
+    PredefinedExpense::delete_many()
+        .filter(predefined_expense::Column::UserId.eq(user_id))
+        .filter(predefined_expense::Column::Visibility.ne(PredefinedExpenseVisibility::Public))
+        .exec(txn)
+        .await?;
+    Ok(())
+}
+
+/// Finds the predefined expense if the user can see it.
+/// A household one is only visible to the current members of its household, even to its owner.
+async fn find_visible_predefined_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    predefined_expense_id: Id,
+) -> Result<Option<predefined_expense::Model>, DbErr> {
+    let predefined_expense =
+        find_entity_by_id::<predefined_expense::Entity>(conn, predefined_expense_id).await?;
+    let Some(predefined_expense) = predefined_expense else {
+        return Ok(None);
+    };
+    let visible = match predefined_expense.visibility {
+        PredefinedExpenseVisibility::Public => true,
+        PredefinedExpenseVisibility::Household => match predefined_expense.household_id {
+            Some(household_id) => is_household_member(conn, household_id, user_id).await?,
+            None => false,
+        },
+        PredefinedExpenseVisibility::Private => predefined_expense.user_id == Some(user_id),
+    };
+    Ok(visible.then_some(predefined_expense))
+}
+
+/// The public catalog is managed by the administrators, the other predefined expenses by their owners.
+async fn can_manage_predefined_expense(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    predefined_expense: &predefined_expense::Model,
+) -> Result<bool, DbErr> {
+    match predefined_expense.visibility {
+        PredefinedExpenseVisibility::Public => is_admin(conn, user_id).await,
+        _ => Ok(predefined_expense.user_id == Some(user_id)),
+    }
+}
+
+/// Checks whether the user can give the visibility to a predefined expense.
+/// Returns the household to store, which is only kept for household visibility.
+async fn validate_predefined_expense_visibility(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    visibility: PredefinedExpenseVisibility,
+    household_id: Option<Id>,
+) -> Result<Option<Id>, ValidatePredefinedExpenseVisibilityError> {
+    match visibility {
+        PredefinedExpenseVisibility::Private => Ok(None),
+        PredefinedExpenseVisibility::Household => {
+            let Some(household_id) = household_id else {
+                return Err(ValidatePredefinedExpenseVisibilityError::InvalidHousehold);
+            };
+            if !is_household_member(conn, household_id, user_id).await? {
+                return Err(ValidatePredefinedExpenseVisibilityError::InvalidHousehold);
+            }
+            Ok(Some(household_id))
+        }
+        PredefinedExpenseVisibility::Public => {
+            if !is_admin(conn, user_id).await? {
+                return Err(ValidatePredefinedExpenseVisibilityError::PublicationUnauthorized);
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Tells whether an expense of another user, even in the trash, refers to the predefined expense.
+async fn is_predefined_expense_used_by_others(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    predefined_expense_id: Id,
+) -> Result<bool, DbErr> {
+    let expense = Expense::find()
+        .filter(expense::Column::PredefinedExpenseId.eq(predefined_expense_id))
+        .filter(expense::Column::UserId.ne(user_id))
+        .one(conn)
+        .await?;
+    Ok(expense.is_some())
+}
+
+async fn validate_recurrence_and_currency(
+    conn: &DatabaseConnection,
+    currency_id: Id,
+    recurrence_id: Id,
+) -> Result<(), ValidateRecurrenceAndCurrencyError> {
+    let (referred_recurrence, referred_currency) = tokio::join!(
+        find_entity_by_id::<recurrence::Entity>(conn, recurrence_id),
+        find_entity_by_id::<currency::Entity>(conn, currency_id)
+    );
+    let Some(_) = referred_currency? else {
+        return Err(ValidateRecurrenceAndCurrencyError::InvalidCurrency);
+    };
+    let Some(_) = referred_recurrence? else {
+        return Err(ValidateRecurrenceAndCurrencyError::InvalidRecurrence);
+    };
+    Ok(())
+}
+
+pub(super) fn find_currency(currencies: &[currency::Model], id: Id) -> currency::Model {
+    currencies
+        .iter()
+        .find(|currency| currency.id == id)
+        .expect("the currency should be in the database")
+        .clone()
+}
+
+pub(super) fn find_participant(participants: &[participant::Model], id: Id) -> participant::Model {
+    participants
+        .iter()
+        .find(|participant| participant.id == id)
+        .expect("the participant should be in the database")
+        .clone()
+}
+
+fn find_recurrence(recurrences: &[recurrence::Model], id: Id) -> recurrence::Model {
+    recurrences
+        .iter()
+        .find(|recurrence| recurrence.id == id)
+        .expect("the currency should be in the database")
+        .clone()
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum FindExpensesWithTransactionsByUserIdError {
+        #[error("{0}")]
+        UnauthorizedUser(#[from] AuthorizeUserError),
+        #[error("limit must be between 1 and 100")]
+        InvalidPageSize,
+        #[error("date could not be parsed")]
+        InvalidDate(#[from] chrono::ParseError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum CreateExpenseError {
+        #[error("predefined expense is invalid")]
+        InvalidPredefinedExpense,
+        #[error("category is invalid")]
+        InvalidCategory,
+        #[error("household is invalid")]
+        InvalidHousehold,
+        #[error("start_date could not be parsed")]
+        InvalidStartDate(#[from] chrono::ParseError),
+        #[error("end_date must be after start_date")]
+        InvalidEndDate,
+        #[error("invalid related type: '{0}'")]
+        InvalidRelatedType(#[from] ValidateRecurrenceAndCurrencyError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<CreateExpenseError>> for CreateExpenseError {
+        fn from(e: TransactionError<CreateExpenseError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UpdateExpenseError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("predefined expense is invalid")]
+        InvalidPredefinedExpense,
+        #[error("category is invalid")]
+        InvalidCategory,
+        #[error("household is invalid")]
+        InvalidHousehold,
+        #[error("start_date could not be parsed")]
+        InvalidStartDate(#[from] chrono::ParseError),
+        #[error("end_date must be after start_date")]
+        InvalidEndDate,
+        #[error(
+            "the value of an expense with price changes can only be changed by a new price change"
+        )]
+        PriceHistoryExists,
+        #[error("the expense has transactions or shares of participants who could not pay for it in the new household")]
+        ForeignPayers,
+        #[error("invalid related type: '{0}'")]
+        InvalidRelatedType(#[from] ValidateRecurrenceAndCurrencyError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<UpdateExpenseError>> for UpdateExpenseError {
+        fn from(e: TransactionError<UpdateExpenseError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteExpenseError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("transactions can only be moved to another expense of the user")]
+        InvalidReassignTarget,
+        #[error("the transactions have participants who could not pay for the target expense")]
+        ForeignPayers,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DeleteExpenseError>> for DeleteExpenseError {
+        fn from(e: TransactionError<DeleteExpenseError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum RestoreExpenseError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("expense is not in the trash")]
+        ExpenseNotInTrash,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum CancelExpenseError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("date could not be parsed")]
+        InvalidDate(#[from] chrono::ParseError),
+        #[error("expense can only be cancelled after its start_date")]
+        InvalidEndDate,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum PauseExpenseError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("date could not be parsed")]
+        InvalidDate(#[from] chrono::ParseError),
+        #[error("end_date of the pause must be after its start_date")]
+        InvalidInterval,
+        #[error("pause overlaps another pause of the expense")]
+        OverlappingPause,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteExpensePauseError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("expense pause not found")]
+        PauseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum AddPriceChangeError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("currency type is invalid")]
+        InvalidCurrency,
+        #[error("effective_from could not be parsed")]
+        InvalidDate(#[from] chrono::ParseError),
+        #[error("effective_from must be after the start_date of the expense")]
+        InvalidEffectiveDate,
+        #[error("the price of the expense already changes on this date")]
+        PriceChangeExists,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeletePriceChangeError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("price change not found")]
+        PriceChangeNotFound,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum SetExpenseSplitError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("a participant can only have one share of the expense")]
+        DuplicateParticipant,
+        #[error("participant can not share the costs of this expense")]
+        InvalidParticipant,
+        #[error("share value is missing or out of range for the split method")]
+        InvalidShareValue,
+        #[error("percentages of the shares must add up to 100")]
+        InvalidPercentages,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+    impl From<TransactionError<SetExpenseSplitError>> for SetExpenseSplitError {
+        fn from(e: TransactionError<SetExpenseSplitError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteExpenseSplitError {
+        #[error("expense not found")]
+        ExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
     pub enum CreatePredefinedExpenseError {
+        #[error("invalid visibility: '{0}'")]
+        InvalidVisibility(#[from] ValidatePredefinedExpenseVisibilityError),
+        #[error("invalid related type: '{0}'")]
+        InvalidRelatedType(#[from] ValidateRecurrenceAndCurrencyError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UpdatePredefinedExpenseError {
+        #[error("predefined expense not found")]
+        PredefinedExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("invalid visibility: '{0}'")]
+        InvalidVisibility(#[from] ValidatePredefinedExpenseVisibilityError),
         #[error("invalid related type: '{0}'")]
         InvalidRelatedType(#[from] ValidateRecurrenceAndCurrencyError),
+        #[error("predefined expense is used by expenses of other users")]
+        PredefinedExpenseInUse,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeletePredefinedExpenseError {
+        #[error("predefined expense not found")]
+        PredefinedExpenseNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("predefined expense is used by expenses of other users")]
+        PredefinedExpenseInUse,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DeletePredefinedExpenseError>> for DeletePredefinedExpenseError {
+        fn from(e: TransactionError<DeletePredefinedExpenseError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ValidatePredefinedExpenseVisibilityError {
+        #[error("only administrators can make predefined expenses public")]
+        PublicationUnauthorized,
+        #[error("household not found")]
+        InvalidHousehold,
         #[error("database error: '{0}'")]
         DatabaseError(#[from] DbErr),
     }
@@ -266,14 +1799,17 @@ pub mod errors {
 mod tests {
     use crate::{
         dto::{
-            currencies::CurrencyResponse, expenses::PredefinedExpenseResponse,
-            recurrences::RecurrenceResponse, transactions::TransactionResponse,
+            currencies::CurrencyResponse,
+            expenses::{ExpenseShareRequest, ExpenseStatus, PredefinedExpenseResponse},
+            recurrences::RecurrenceResponse,
+            transactions::TransactionResponse,
         },
         logic::{
             common::tests::{
-                test_currency, test_db_error, test_decimal, test_expense, test_predefined_expense,
-                test_recurrence, test_transaction, test_transaction_2, TEST_DATE, TEST_ID,
-                TEST_STR,
+                exec_ok, test_admin, test_category, test_currency, test_db_error, test_decimal,
+                test_expense, test_expense_pause, test_expense_price, test_household_member,
+                test_participant, test_predefined_expense, test_recurrence, test_transaction,
+                test_transaction_2, test_user, TEST_DATE, TEST_ID, TEST_STR,
             },
             user_operations::errors::AuthorizeUserError,
         },
@@ -281,7 +1817,9 @@ mod tests {
 
     use super::*;
     use assert2::check;
-    use entity::{currency, recurrence};
+    use chrono::NaiveDate;
+    use entity::household_member::{self, HouseholdRole};
+    use entity::{currency, recurrence, user};
     use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
 
     #[tokio::test]
@@ -294,50 +1832,96 @@ mod tests {
             test_recurrence(),
         )
             .into();
-        let expected_transaction: TransactionResponse =
-            (test_transaction(), test_currency()).into();
-        let expected_transaction_2: TransactionResponse =
-            (test_transaction_2(), test_currency()).into();
+        let date_format = DateFormat::YearMonthDay;
+        let expected_transaction: TransactionResponse = (
+            test_transaction(),
+            test_currency(),
+            test_participant(),
+            vec![TEST_STR.to_string()],
+            date_format,
+        )
+            .into();
+        let expected_transaction_2: TransactionResponse = (
+            test_transaction_2(),
+            test_currency(),
+            test_participant(),
+            vec![],
+            date_format,
+        )
+            .into();
         let expected_expenses = vec![ExpenseResponse {
             id: TEST_ID,
             name: TEST_STR.to_string(),
             description: TEST_STR.to_string(),
             value: test_decimal(),
-            start_date: NaiveDate::MIN.to_string(),
+            start_date: NaiveDate::MIN.format("%Y-%m-%d").to_string(),
+            end_date: None,
+            status: ExpenseStatus::Paused,
+            current_value: test_decimal(),
+            current_currency: expected_currency.clone(),
             user_id: TEST_ID,
             currency: expected_currency,
             recurrence: expected_recurrence,
             predefined_expense: Some(expected_predefined_expense),
+            category_id: None,
+            household_id: None,
+            tags: vec![TEST_STR.to_string()],
+            archived: false,
             transactions: vec![expected_transaction, expected_transaction_2],
+            pauses: vec![(test_expense_pause(), date_format).into()],
+            prices: vec![(test_expense_price(), test_currency(), date_format).into()],
+            split: None,
         }];
 
         let expenses_stub = vec![test_expense()];
         let predefined_expenses_stub = vec![test_predefined_expense()];
         let transactions_stub = vec![test_transaction(), test_transaction_2()];
+        let expense_tags_stub = vec![expense_tag::Model {
+            id: TEST_ID,
+            expense_id: TEST_ID,
+            name: TEST_STR.to_string(),
+        }];
+        let transaction_tags_stub = vec![transaction_tag::Model {
+            id: TEST_ID,
+            transaction_id: test_transaction().id,
+            name: TEST_STR.to_string(),
+        }];
         let currencies_stub = vec![test_currency()];
         let recurrences_stub = vec![test_recurrence()];
+        let user_stub = user::Model {
+            date_format,
+            ..test_user()
+        };
         let conn = MockDatabase::new(DatabaseBackend::MySql)
             // expenses
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![user_stub]])
             .append_query_results(vec![expenses_stub])
             .append_query_results(vec![predefined_expenses_stub])
+            .append_query_results(vec![expense_tags_stub])
             .append_query_results(vec![transactions_stub])
-            .append_query_results(vec![currencies_stub.clone()])
-            .append_query_results(vec![recurrences_stub.clone()])
-            // empty_expenses
-            .append_query_results(vec![Vec::<expense::Model>::new()])
-            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
-            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_query_results(vec![vec![test_expense_pause()]])
+            .append_query_results(vec![vec![test_expense_price()]])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
             .append_query_results(vec![currencies_stub])
             .append_query_results(vec![recurrences_stub])
+            .append_query_results(vec![transaction_tags_stub])
+            .append_query_results(vec![vec![test_participant()]])
+            // empty_expenses
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // unauthorized_error
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
             // db_error
             .append_query_errors(vec![test_db_error()])
             .into_connection();
 
         let (expenses, empty_expenses, unauthorized_error, db_error) = tokio::join!(
-            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID),
-            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID),
-            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID + 1),
-            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID)
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, ExpenseListQuery::default()),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, ExpenseListQuery::default()),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID + 1, ExpenseListQuery::default()),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, ExpenseListQuery::default())
         );
 
         check!(expenses == Ok(expected_expenses));
@@ -356,11 +1940,178 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn find_expenses_by_user_id_query_cases() {
+        let child_category = category::Model {
+            id: TEST_ID + 1,
+            parent_id: Some(TEST_ID),
+            ..test_category()
+        };
+        let user_stub = user::Model {
+            date_format: DateFormat::YearMonthDay,
+            ..test_user()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // by category
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![user_stub.clone()]])
+            .append_query_results(vec![vec![test_category(), child_category]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // filtered, sorted and paged
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![user_stub.clone()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // latest transaction only
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![user_stub.clone()]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            .append_query_results(vec![Vec::<expense_tag::Model>::new()])
+            .append_query_results(vec![vec![test_transaction_2()]])
+            .append_query_results(vec![Vec::<expense_pause::Model>::new()])
+            .append_query_results(vec![Vec::<expense_price::Model>::new()])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![Vec::<transaction_tag::Model>::new()])
+            .append_query_results(vec![vec![test_participant()]])
+            // without transactions
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![user_stub.clone()]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            .append_query_results(vec![Vec::<expense_tag::Model>::new()])
+            .append_query_results(vec![Vec::<expense_pause::Model>::new()])
+            .append_query_results(vec![Vec::<expense_price::Model>::new()])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            // invalid date
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![user_stub]])
+            // db error on category query
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_errors(vec![test_db_error()])
+            // expenses of another household member
+            .append_query_results(vec![vec![
+                test_household_member(),
+                household_member::Model {
+                    id: TEST_ID + 1,
+                    user_id: TEST_ID + 1,
+                    role: HouseholdRole::Member,
+                    ..test_household_member()
+                },
+            ]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .into_connection();
+        let by_category = ExpenseListQuery {
+            category_id: Some(TEST_ID),
+            ..Default::default()
+        };
+        let filtered = ExpenseListQuery {
+            tag: Some(TEST_STR.to_string()),
+            name: Some(TEST_STR.to_string()),
+            currency_id: Some(TEST_ID),
+            recurrence_id: Some(TEST_ID),
+            predefined_expense_id: Some(TEST_ID),
+            from: Some("2000-01-01".to_string()),
+            to: Some("2000-12-31".to_string()),
+            sort: ExpenseSort::StartDate,
+            order: SortOrder::Desc,
+            offset: Some(20),
+            limit: Some(10),
+            ..Default::default()
+        };
+        let latest_transaction = ExpenseListQuery {
+            transactions: Some(1),
+            ..Default::default()
+        };
+        let without_transactions = ExpenseListQuery {
+            transactions: Some(0),
+            ..Default::default()
+        };
+        let invalid_date = ExpenseListQuery {
+            from: Some(TEST_STR.to_string()),
+            ..Default::default()
+        };
+        let too_large_page = ExpenseListQuery {
+            limit: Some(MAX_EXPENSE_PAGE_SIZE + 1),
+            ..Default::default()
+        };
+
+        let (
+            by_category_result,
+            filtered_result,
+            latest_transaction_result,
+            without_transactions_result,
+            invalid_date_result,
+            too_large_page_result,
+            db_error,
+            household_member_result,
+        ) = tokio::join!(
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, by_category.clone()),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, filtered),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, latest_transaction),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, without_transactions),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, invalid_date),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, too_large_page),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID, by_category),
+            find_expenses_by_user_id(&conn, TEST_ID, TEST_ID + 1, ExpenseListQuery::default())
+        );
+
+        check!(by_category_result == Ok(vec![]));
+        check!(filtered_result == Ok(vec![]));
+        let latest_transaction_result = latest_transaction_result.unwrap();
+        check!(latest_transaction_result.len() == 1);
+        check!(latest_transaction_result[0].transactions.len() == 1);
+        check!(latest_transaction_result[0].transactions[0].id == test_transaction_2().id);
+        let without_transactions_result = without_transactions_result.unwrap();
+        check!(without_transactions_result.len() == 1);
+        check!(without_transactions_result[0].transactions.is_empty());
+        check!(let Err(FindExpensesWithTransactionsByUserIdError::InvalidDate(_)) = invalid_date_result);
+        check!(
+            too_large_page_result
+                == Err(FindExpensesWithTransactionsByUserIdError::InvalidPageSize)
+        );
+        check!(
+            db_error
+                == Err(FindExpensesWithTransactionsByUserIdError::DatabaseError(
+                    test_db_error()
+                ))
+        );
+
+        check!(household_member_result == Ok(vec![]));
+
+        let log = conn.into_transaction_log();
+        let filtered_sql = format!("{:?}", log[6]);
+        check!(filtered_sql.contains("LIKE"));
+        check!(filtered_sql.contains("`expenses`.`end_date` IS NULL"));
+        check!(filtered_sql.contains("`expenses`.`deleted_at` IS NULL"));
+        check!(filtered_sql.contains("`expenses`.`archived` = ?"));
+        check!(filtered_sql.contains("ORDER BY `expenses`.`start_date` DESC, `expenses`.`id` ASC"));
+        check!(filtered_sql.contains("LIMIT ? OFFSET ?"));
+        let transactions_sql = log
+            .iter()
+            .map(|statement| format!("{statement:?}"))
+            .filter(|statement| statement.contains("ROW_NUMBER()"))
+            .collect::<Vec<_>>();
+        check!(transactions_sql.len() == 1);
+        check!(transactions_sql[0].contains(
+            "ROW_NUMBER() OVER ( PARTITION BY `expense_id` ORDER BY `date` DESC, `id` DESC )"
+        ));
+        let household_member_sql = format!("{:?}", log[log.len() - 1]);
+        check!(household_member_sql.contains("`expenses`.`household_id` IN (?)"));
+        check!(!household_member_sql.contains("`expenses`.`user_id` = ?"));
+    }
+
     #[tokio::test]
     async fn create_expense_happy_path() {
         let conn = MockDatabase::new(DatabaseBackend::MySql)
             .append_query_results(vec![vec![test_recurrence()]])
             .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
             .append_exec_results(vec![MockExecResult {
                 last_insert_id: TEST_ID,
                 rows_affected: 1,
@@ -376,9 +2127,13 @@ mod tests {
                 description: TEST_STR.to_string(),
                 value: test_decimal(),
                 start_date: TEST_DATE.to_string(),
+                end_date: None,
                 currency_id: TEST_ID,
                 recurrence_id: TEST_ID,
                 predefined_expense_id: None,
+                category_id: None,
+                tags: vec![],
+                household_id: None,
             },
         )
         .await;
@@ -392,6 +2147,7 @@ mod tests {
             .append_query_results(vec![vec![test_predefined_expense()]])
             .append_query_results(vec![vec![test_recurrence()]])
             .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
             .append_exec_results(vec![MockExecResult {
                 last_insert_id: TEST_ID,
                 rows_affected: 1,
@@ -407,9 +2163,13 @@ mod tests {
                 description: TEST_STR.to_string(),
                 value: test_decimal(),
                 start_date: TEST_DATE.to_string(),
+                end_date: None,
                 currency_id: TEST_ID,
                 recurrence_id: TEST_ID,
                 predefined_expense_id: Some(TEST_ID),
+                category_id: None,
+                tags: vec![],
+                household_id: None,
             },
         )
         .await;
@@ -417,6 +2177,63 @@ mod tests {
         check!(saved_expense_id == Ok(TEST_ID));
     }
 
+    #[tokio::test]
+    async fn create_expense_with_category_and_tags_all_cases() {
+        let predefined_expense_with_category = predefined_expense::Model {
+            default_category: Some(TEST_STR.to_string()),
+            ..test_predefined_expense()
+        };
+        let foreign_category = category::Model {
+            user_id: TEST_ID + 1,
+            ..test_category()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // own category and tags
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_category()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // default category of the predefined expense
+            .append_query_results(vec![vec![predefined_expense_with_category]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_category()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // category of another user
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![foreign_category]])
+            .into_connection();
+        let with_category_and_tags = NewExpenseRequest {
+            category_id: Some(TEST_ID),
+            tags: vec![TEST_STR.to_string(), format!(" {TEST_STR} ")],
+            ..test_expense_request()
+        };
+        let with_predefined_expense = NewExpenseRequest {
+            predefined_expense_id: Some(TEST_ID),
+            ..test_expense_request()
+        };
+        let with_foreign_category = NewExpenseRequest {
+            category_id: Some(TEST_ID),
+            ..test_expense_request()
+        };
+
+        let (with_category_and_tags, with_default_category, invalid_category) = tokio::join!(
+            create_expense(&conn, TEST_ID, with_category_and_tags),
+            create_expense(&conn, TEST_ID, with_predefined_expense),
+            create_expense(&conn, TEST_ID, with_foreign_category)
+        );
+
+        check!(with_category_and_tags == Ok(TEST_ID));
+        check!(with_default_category == Ok(TEST_ID));
+        check!(invalid_category == Err(CreateExpenseError::InvalidCategory));
+    }
+
     #[tokio::test]
     async fn create_expense_db_error_cases() {
         let conn = MockDatabase::new(DatabaseBackend::MySql)
@@ -444,20 +2261,31 @@ mod tests {
             .append_query_results(vec![vec![test_predefined_expense()]])
             .append_query_results(vec![vec![test_recurrence()]])
             .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
             .append_exec_errors(vec![test_db_error()])
             // date cannot be parsed
             .append_query_results(vec![vec![test_predefined_expense()]])
             .append_query_results(vec![vec![test_recurrence()]])
             .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            // end date before start date
+            .append_query_results(vec![vec![test_predefined_expense()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
             .into_connection();
         let mut req = NewExpenseRequest {
             name: TEST_STR.to_string(),
             description: TEST_STR.to_string(),
             value: test_decimal(),
             start_date: TEST_DATE.to_string(),
+            end_date: None,
             currency_id: TEST_ID,
             recurrence_id: TEST_ID,
             predefined_expense_id: Some(TEST_ID),
+            category_id: None,
+            tags: vec![],
+            household_id: None,
         };
 
         let (
@@ -478,7 +2306,10 @@ mod tests {
             create_expense(&conn, TEST_ID, req.clone()),
         );
         req.start_date = "wrong_date".to_string();
-        let parse_date_error = create_expense(&conn, TEST_ID, req).await;
+        let parse_date_error = create_expense(&conn, TEST_ID, req.clone()).await;
+        req.start_date = TEST_DATE.to_string();
+        req.end_date = Some("01-01-1990".to_string());
+        let end_date_error = create_expense(&conn, TEST_ID, req).await;
 
         check!(predefined_expense_not_found == Err(CreateExpenseError::InvalidPredefinedExpense));
         check!(
@@ -494,6 +2325,7 @@ mod tests {
                 ))
         );
         check!(let Err(CreateExpenseError::InvalidStartDate(_)) = parse_date_error);
+        check!(end_date_error == Err(CreateExpenseError::InvalidEndDate));
         check!(
             predefined_expense_db_error == Err(CreateExpenseError::DatabaseError(test_db_error()))
         );
@@ -512,6 +2344,35 @@ mod tests {
         check!(expense_insert_db_error == Err(CreateExpenseError::DatabaseError(test_db_error())));
     }
 
+    fn test_predefined_expense_request() -> NewPredefinedExpenseRequest {
+        NewPredefinedExpenseRequest {
+            name: TEST_STR.to_string(),
+            description: TEST_STR.to_string(),
+            value: test_decimal(),
+            currency_id: TEST_ID,
+            recurrence_id: TEST_ID,
+            default_category: None,
+            visibility: PredefinedExpenseVisibility::Private,
+            household_id: None,
+        }
+    }
+
+    fn test_public_predefined_expense() -> predefined_expense::Model {
+        predefined_expense::Model {
+            user_id: None,
+            visibility: PredefinedExpenseVisibility::Public,
+            ..test_predefined_expense()
+        }
+    }
+
+    fn test_household_predefined_expense() -> predefined_expense::Model {
+        predefined_expense::Model {
+            household_id: Some(TEST_ID),
+            visibility: PredefinedExpenseVisibility::Household,
+            ..test_predefined_expense()
+        }
+    }
+
     #[tokio::test]
     async fn find_predefined_expenses_all_cases() {
         let expected_predefined_expenses: Vec<PredefinedExpenseResponse> = vec![
@@ -522,22 +2383,25 @@ mod tests {
             )
                 .into(),
             (
-                test_predefined_expense(),
+                test_public_predefined_expense(),
                 test_currency(),
                 test_recurrence(),
             )
                 .into(),
         ];
 
-        let predefined_expenses_stub = vec![test_predefined_expense(), test_predefined_expense()];
+        let predefined_expenses_stub =
+            vec![test_predefined_expense(), test_public_predefined_expense()];
         let currencies_stub = vec![test_currency()];
         let recurrences_stub = vec![test_recurrence()];
         let conn = MockDatabase::new(DatabaseBackend::MySql)
             // predefined_expenses
+            .append_query_results(vec![vec![test_household_member()]])
             .append_query_results(vec![predefined_expenses_stub])
             .append_query_results(vec![currencies_stub])
             .append_query_results(vec![recurrences_stub])
             // empty_predefined_expenses
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
             .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
             .append_query_results(vec![Vec::<currency::Model>::new()])
             .append_query_results(vec![Vec::<recurrence::Model>::new()])
@@ -546,9 +2410,9 @@ mod tests {
             .into_connection();
 
         let (predefined_expenses, empty_predefined_expenses, db_error) = tokio::join!(
-            find_predefined_expenses(&conn),
-            find_predefined_expenses(&conn),
-            find_predefined_expenses(&conn)
+            find_predefined_expenses(&conn, TEST_ID),
+            find_predefined_expenses(&conn, TEST_ID),
+            find_predefined_expenses(&conn, TEST_ID)
         );
 
         check!(predefined_expenses == Ok(expected_predefined_expenses));
@@ -568,21 +2432,82 @@ mod tests {
             .append_query_results(vec![vec![test_predefined_expense()]])
             .into_connection();
 
-        let saved_predefined_expense_id = create_predefined_expense(
-            &conn,
-            NewPredefinedExpenseRequest {
-                name: TEST_STR.to_string(),
-                description: TEST_STR.to_string(),
-                value: test_decimal(),
-                currency_id: TEST_ID,
-                recurrence_id: TEST_ID,
-            },
-        )
-        .await;
+        let saved_predefined_expense_id =
+            create_predefined_expense(&conn, TEST_ID, test_predefined_expense_request()).await;
 
         check!(saved_predefined_expense_id == Ok(TEST_ID));
     }
 
+    #[tokio::test]
+    async fn create_predefined_expense_visibility_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // shared with household
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: TEST_ID,
+                rows_affected: 1,
+            }])
+            .append_query_results(vec![vec![test_household_predefined_expense()]])
+            // published by admin
+            .append_query_results(vec![vec![test_admin()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: TEST_ID,
+                rows_affected: 1,
+            }])
+            .append_query_results(vec![vec![test_public_predefined_expense()]])
+            // not a member of the household
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // published by user
+            .append_query_results(vec![vec![test_user()]])
+            .into_connection();
+        let household_req = NewPredefinedExpenseRequest {
+            visibility: PredefinedExpenseVisibility::Household,
+            household_id: Some(TEST_ID),
+            ..test_predefined_expense_request()
+        };
+        let public_req = NewPredefinedExpenseRequest {
+            visibility: PredefinedExpenseVisibility::Public,
+            ..test_predefined_expense_request()
+        };
+        let missing_household_req = NewPredefinedExpenseRequest {
+            household_id: None,
+            ..household_req.clone()
+        };
+
+        let (household, published, missing_household, not_member, unauthorized_publication) = tokio::join!(
+            create_predefined_expense(&conn, TEST_ID, household_req.clone()),
+            create_predefined_expense(&conn, TEST_ID, public_req.clone()),
+            create_predefined_expense(&conn, TEST_ID, missing_household_req),
+            create_predefined_expense(&conn, TEST_ID, household_req),
+            create_predefined_expense(&conn, TEST_ID, public_req)
+        );
+
+        check!(household == Ok(TEST_ID));
+        check!(published == Ok(TEST_ID));
+        check!(
+            missing_household
+                == Err(CreatePredefinedExpenseError::InvalidVisibility(
+                    ValidatePredefinedExpenseVisibilityError::InvalidHousehold
+                ))
+        );
+        check!(
+            not_member
+                == Err(CreatePredefinedExpenseError::InvalidVisibility(
+                    ValidatePredefinedExpenseVisibilityError::InvalidHousehold
+                ))
+        );
+        check!(
+            unauthorized_publication
+                == Err(CreatePredefinedExpenseError::InvalidVisibility(
+                    ValidatePredefinedExpenseVisibilityError::PublicationUnauthorized
+                ))
+        );
+    }
+
     #[tokio::test]
     async fn create_predefined_expense_error_cases() {
         let conn = MockDatabase::new(DatabaseBackend::MySql)
@@ -603,13 +2528,7 @@ mod tests {
             .append_query_results(vec![vec![test_currency()]])
             .append_exec_errors(vec![test_db_error()])
             .into_connection();
-        let req = NewPredefinedExpenseRequest {
-            name: TEST_STR.to_string(),
-            description: TEST_STR.to_string(),
-            value: test_decimal(),
-            currency_id: TEST_ID,
-            recurrence_id: TEST_ID,
-        };
+        let req = test_predefined_expense_request();
 
         let (
             recurrence_not_found,
@@ -618,11 +2537,11 @@ mod tests {
             currency_db_error,
             insertion_db_error,
         ) = tokio::join!(
-            create_predefined_expense(&conn, req.clone()),
-            create_predefined_expense(&conn, req.clone()),
-            create_predefined_expense(&conn, req.clone()),
-            create_predefined_expense(&conn, req.clone()),
-            create_predefined_expense(&conn, req)
+            create_predefined_expense(&conn, TEST_ID, req.clone()),
+            create_predefined_expense(&conn, TEST_ID, req.clone()),
+            create_predefined_expense(&conn, TEST_ID, req.clone()),
+            create_predefined_expense(&conn, TEST_ID, req.clone()),
+            create_predefined_expense(&conn, TEST_ID, req)
         );
 
         check!(
@@ -644,14 +2563,184 @@ mod tests {
                 ))
         );
         check!(
-            currency_db_error
-                == Err(CreatePredefinedExpenseError::InvalidRelatedType(
-                    ValidateRecurrenceAndCurrencyError::DatabaseError(test_db_error())
+            currency_db_error
+                == Err(CreatePredefinedExpenseError::InvalidRelatedType(
+                    ValidateRecurrenceAndCurrencyError::DatabaseError(test_db_error())
+                ))
+        );
+        check!(
+            insertion_db_error == Err(CreatePredefinedExpenseError::DatabaseError(test_db_error()))
+        );
+    }
+
+    #[tokio::test]
+    async fn update_predefined_expense_all_cases() {
+        let other_users_private = predefined_expense::Model {
+            user_id: Some(TEST_ID + 1),
+            ..test_predefined_expense()
+        };
+        let other_users_household = predefined_expense::Model {
+            user_id: Some(TEST_ID + 1),
+            ..test_household_predefined_expense()
+        };
+        let other_users_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_predefined_expense()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: TEST_ID,
+                rows_affected: 1,
+            }])
+            .append_query_results(vec![vec![test_predefined_expense()]])
+            // published by admin
+            .append_query_results(vec![vec![test_household_predefined_expense()]])
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_admin()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: TEST_ID,
+                rows_affected: 1,
+            }])
+            .append_query_results(vec![vec![test_public_predefined_expense()]])
+            // not found
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            // private predefined expense of another user
+            .append_query_results(vec![vec![other_users_private]])
+            // household predefined expense of another user
+            .append_query_results(vec![vec![other_users_household]])
+            .append_query_results(vec![vec![test_household_member()]])
+            // own household predefined expense after leaving the household
+            .append_query_results(vec![vec![test_household_predefined_expense()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // public predefined expense edited by user
+            .append_query_results(vec![vec![test_public_predefined_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            // narrowed while used by another user
+            .append_query_results(vec![vec![test_household_predefined_expense()]])
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![other_users_expense]])
+            // invalid currency
+            .append_query_results(vec![vec![test_predefined_expense()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![Vec::<currency::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let req = test_predefined_expense_request();
+        let public_req = NewPredefinedExpenseRequest {
+            visibility: PredefinedExpenseVisibility::Public,
+            ..test_predefined_expense_request()
+        };
+
+        let (
+            happy_case,
+            published,
+            not_found,
+            hidden,
+            unauthorized,
+            left_household,
+            unauthorized_curation,
+            narrowed_in_use,
+            invalid_currency,
+            db_error,
+        ) = tokio::join!(
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, public_req),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_predefined_expense(&conn, TEST_ID, TEST_ID, req)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(published == Ok(()));
+        check!(not_found == Err(UpdatePredefinedExpenseError::PredefinedExpenseNotFound));
+        check!(hidden == Err(UpdatePredefinedExpenseError::PredefinedExpenseNotFound));
+        check!(
+            unauthorized
+                == Err(UpdatePredefinedExpenseError::UserUnauthorized(
+                    AuthorizeUserError
+                ))
+        );
+        check!(left_household == Err(UpdatePredefinedExpenseError::PredefinedExpenseNotFound));
+        check!(
+            unauthorized_curation
+                == Err(UpdatePredefinedExpenseError::UserUnauthorized(
+                    AuthorizeUserError
+                ))
+        );
+        check!(narrowed_in_use == Err(UpdatePredefinedExpenseError::PredefinedExpenseInUse));
+        check!(
+            invalid_currency
+                == Err(UpdatePredefinedExpenseError::InvalidRelatedType(
+                    ValidateRecurrenceAndCurrencyError::InvalidCurrency
+                ))
+        );
+        check!(db_error == Err(UpdatePredefinedExpenseError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_predefined_expense_all_cases() {
+        let other_users_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_predefined_expense()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_exec_results(vec![
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                },
+            ])
+            // not found
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            // public predefined expense deleted by user
+            .append_query_results(vec![vec![test_public_predefined_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            // in use by another user
+            .append_query_results(vec![vec![test_predefined_expense()]])
+            .append_query_results(vec![vec![other_users_expense]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, unauthorized, in_use, db_error) = tokio::join!(
+            delete_predefined_expense(&conn, TEST_ID, TEST_ID),
+            delete_predefined_expense(&conn, TEST_ID, TEST_ID),
+            delete_predefined_expense(&conn, TEST_ID, TEST_ID),
+            delete_predefined_expense(&conn, TEST_ID, TEST_ID),
+            delete_predefined_expense(&conn, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(DeletePredefinedExpenseError::PredefinedExpenseNotFound));
+        check!(
+            unauthorized
+                == Err(DeletePredefinedExpenseError::UserUnauthorized(
+                    AuthorizeUserError
                 ))
         );
-        check!(
-            insertion_db_error == Err(CreatePredefinedExpenseError::DatabaseError(test_db_error()))
-        );
+        check!(in_use == Err(DeletePredefinedExpenseError::PredefinedExpenseInUse));
+        check!(db_error == Err(DeletePredefinedExpenseError::DatabaseError(test_db_error())));
     }
 
     #[tokio::test]
@@ -704,4 +2793,790 @@ mod tests {
                 ))
         );
     }
+
+    fn test_expense_request() -> NewExpenseRequest {
+        NewExpenseRequest {
+            name: TEST_STR.to_string(),
+            description: TEST_STR.to_string(),
+            value: test_decimal(),
+            start_date: TEST_DATE.to_string(),
+            end_date: None,
+            currency_id: TEST_ID,
+            recurrence_id: TEST_ID,
+            predefined_expense_id: None,
+            category_id: None,
+            tags: vec![],
+            household_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn update_expense_all_cases() {
+        let foreign_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let household_expense = expense::Model {
+            household_id: Some(TEST_ID),
+            ..test_expense()
+        };
+        let household_participant = participant::Model {
+            user_id: None,
+            household_id: Some(TEST_ID),
+            ..test_participant()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // expense of another user
+            .append_query_results(vec![vec![foreign_expense]])
+            // invalid predefined expense
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            // private predefined expense of another user
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![predefined_expense::Model {
+                user_id: Some(TEST_ID + 1),
+                ..test_predefined_expense()
+            }]])
+            // invalid start date
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            // value changed with price changes
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_expense_price()]])
+            // left the household while a participant of the household pays for it
+            .append_query_results(vec![vec![household_expense]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![vec![household_participant]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let with_new_value = NewExpenseRequest {
+            value: MoneyAmount::ONE_HUNDRED,
+            ..test_expense_request()
+        };
+        let with_predefined_expense = NewExpenseRequest {
+            predefined_expense_id: Some(TEST_ID),
+            ..test_expense_request()
+        };
+        let with_invalid_date = NewExpenseRequest {
+            start_date: TEST_STR.to_string(),
+            ..test_expense_request()
+        };
+
+        let (
+            happy_case,
+            not_found,
+            unauthorized,
+            invalid_predefined_expense,
+            hidden_predefined_expense,
+            invalid_start_date,
+            price_history_exists,
+            foreign_payers,
+            db_error,
+        ) = tokio::join!(
+            update_expense(&conn, TEST_ID, TEST_ID, test_expense_request()),
+            update_expense(&conn, TEST_ID, TEST_ID, test_expense_request()),
+            update_expense(&conn, TEST_ID, TEST_ID, test_expense_request()),
+            update_expense(&conn, TEST_ID, TEST_ID, with_predefined_expense.clone()),
+            update_expense(&conn, TEST_ID, TEST_ID, with_predefined_expense),
+            update_expense(&conn, TEST_ID, TEST_ID, with_invalid_date),
+            update_expense(&conn, TEST_ID, TEST_ID, with_new_value),
+            update_expense(&conn, TEST_ID, TEST_ID, test_expense_request()),
+            update_expense(&conn, TEST_ID, TEST_ID, test_expense_request())
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(UpdateExpenseError::ExpenseNotFound));
+        check!(unauthorized == Err(UpdateExpenseError::UserUnauthorized(AuthorizeUserError)));
+        check!(invalid_predefined_expense == Err(UpdateExpenseError::InvalidPredefinedExpense));
+        check!(hidden_predefined_expense == Err(UpdateExpenseError::InvalidPredefinedExpense));
+        check!(let Err(UpdateExpenseError::InvalidStartDate(_)) = invalid_start_date);
+        check!(price_history_exists == Err(UpdateExpenseError::PriceHistoryExists));
+        check!(foreign_payers == Err(UpdateExpenseError::ForeignPayers));
+        check!(db_error == Err(UpdateExpenseError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn patch_expense_all_cases() {
+        let later_expense = expense::Model {
+            start_date: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            ..test_expense()
+        };
+        let household_expense = expense::Model {
+            household_id: Some(TEST_ID),
+            ..test_expense()
+        };
+        let household_participant = participant::Model {
+            user_id: None,
+            household_id: Some(TEST_ID),
+            ..test_participant()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // nothing to change
+            .append_query_results(vec![vec![test_expense()]])
+            // name changed
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // currency and start date changed
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // invalid currency
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![Vec::<currency::Model>::new()])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // end date before the current start date
+            .append_query_results(vec![vec![later_expense]])
+            .append_query_results(vec![vec![test_user()]])
+            // value changed without price changes
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<expense_price::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // value changed with price changes
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_expense_price()]])
+            // left the household while a participant of the household pays for it
+            .append_query_results(vec![vec![household_expense]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![vec![household_participant]])
+            .into_connection();
+        let leave_household = PatchExpenseRequest {
+            household_id: Some(None),
+            ..Default::default()
+        };
+        let rename = PatchExpenseRequest {
+            name: Some(TEST_STR.to_string()),
+            ..Default::default()
+        };
+        let change_currency_and_date = PatchExpenseRequest {
+            currency_id: Some(TEST_ID),
+            start_date: Some(TEST_DATE.to_string()),
+            ..Default::default()
+        };
+        let change_currency = PatchExpenseRequest {
+            currency_id: Some(TEST_ID + 1),
+            ..Default::default()
+        };
+        let end_early = PatchExpenseRequest {
+            end_date: Some(Some(TEST_DATE.to_string())),
+            ..Default::default()
+        };
+        let change_value = PatchExpenseRequest {
+            value: Some(MoneyAmount::ONE_HUNDRED),
+            ..Default::default()
+        };
+
+        let (
+            nothing_changed,
+            renamed,
+            currency_and_date_changed,
+            invalid_currency,
+            not_found,
+            invalid_end_date,
+            value_changed,
+            price_history_exists,
+            foreign_payers,
+        ) = tokio::join!(
+            patch_expense(&conn, TEST_ID, TEST_ID, PatchExpenseRequest::default()),
+            patch_expense(&conn, TEST_ID, TEST_ID, rename),
+            patch_expense(&conn, TEST_ID, TEST_ID, change_currency_and_date),
+            patch_expense(&conn, TEST_ID, TEST_ID, change_currency),
+            patch_expense(&conn, TEST_ID, TEST_ID, PatchExpenseRequest::default()),
+            patch_expense(&conn, TEST_ID, TEST_ID, end_early),
+            patch_expense(&conn, TEST_ID, TEST_ID, change_value.clone()),
+            patch_expense(&conn, TEST_ID, TEST_ID, change_value),
+            patch_expense(&conn, TEST_ID, TEST_ID, leave_household)
+        );
+
+        check!(nothing_changed == Ok(()));
+        check!(renamed == Ok(()));
+        check!(currency_and_date_changed == Ok(()));
+        check!(
+            invalid_currency
+                == Err(UpdateExpenseError::InvalidRelatedType(
+                    ValidateRecurrenceAndCurrencyError::InvalidCurrency
+                ))
+        );
+        check!(not_found == Err(UpdateExpenseError::ExpenseNotFound));
+        check!(invalid_end_date == Err(UpdateExpenseError::InvalidEndDate));
+        check!(value_changed == Ok(()));
+        check!(price_history_exists == Err(UpdateExpenseError::PriceHistoryExists));
+        check!(foreign_payers == Err(UpdateExpenseError::ForeignPayers));
+    }
+
+    #[tokio::test]
+    async fn patch_expense_category_and_tags_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // category changed
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_category()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // tags replaced
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // unknown category
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<category::Model>::new()])
+            // category and end date cleared
+            .append_query_results(vec![vec![expense::Model {
+                category_id: Some(TEST_ID),
+                end_date: Some(NaiveDate::MAX),
+                ..test_expense()
+            }]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            .into_connection();
+        let change_category = PatchExpenseRequest {
+            category_id: Some(Some(TEST_ID)),
+            ..Default::default()
+        };
+        let replace_tags = PatchExpenseRequest {
+            tags: Some(vec![TEST_STR.to_string()]),
+            ..Default::default()
+        };
+        let unknown_category = PatchExpenseRequest {
+            category_id: Some(Some(TEST_ID + 1)),
+            ..Default::default()
+        };
+        let clear_category_and_end_date = PatchExpenseRequest {
+            category_id: Some(None),
+            end_date: Some(None),
+            ..Default::default()
+        };
+
+        let (category_changed, tags_replaced, invalid_category, cleared) = tokio::join!(
+            patch_expense(&conn, TEST_ID, TEST_ID, change_category),
+            patch_expense(&conn, TEST_ID, TEST_ID, replace_tags),
+            patch_expense(&conn, TEST_ID, TEST_ID, unknown_category),
+            patch_expense(&conn, TEST_ID, TEST_ID, clear_category_and_end_date)
+        );
+
+        check!(category_changed == Ok(()));
+        check!(tags_replaced == Ok(()));
+        check!(invalid_category == Err(UpdateExpenseError::InvalidCategory));
+        check!(cleared == Ok(()));
+    }
+
+    #[tokio::test]
+    async fn delete_expense_all_cases() {
+        let other_expense = expense::Model {
+            id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let foreign_expense = expense::Model {
+            id: TEST_ID + 1,
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let trashed_expense = expense::Model {
+            deleted_at: Some(Local::now()),
+            ..test_expense()
+        };
+        let household_expense = expense::Model {
+            household_id: Some(TEST_ID),
+            ..test_expense()
+        };
+        let household_participant = participant::Model {
+            user_id: None,
+            household_id: Some(TEST_ID),
+            ..test_participant()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // moved to the trash with its transactions
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_results(vec![exec_ok()])
+            // transactions moved to another expense
+            .append_query_results(vec![vec![test_expense()], vec![other_expense.clone()]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // transactions paid by a household participant moved to a private expense
+            .append_query_results(vec![vec![household_expense], vec![other_expense]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![vec![household_participant]])
+            // transactions moved to the deleted expense itself
+            .append_query_results(vec![vec![test_expense()], vec![test_expense()]])
+            // transactions moved to the expense of another user
+            .append_query_results(vec![vec![test_expense()], vec![foreign_expense.clone()]])
+            // transactions moved to a missing expense
+            .append_query_results(vec![vec![test_expense()], vec![]])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // expense of another user
+            .append_query_results(vec![vec![foreign_expense]])
+            // already in the trash
+            .append_query_results(vec![vec![trashed_expense]])
+            // db error
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let keep_transactions = || DeleteExpenseQuery { reassign_to: None };
+        let reassign_to = |expense_id| DeleteExpenseQuery {
+            reassign_to: Some(expense_id),
+        };
+
+        let (
+            deleted,
+            reassigned,
+            reassigned_with_foreign_payers,
+            reassigned_to_itself,
+            reassigned_to_foreign,
+            reassigned_to_missing,
+            not_found,
+            unauthorized,
+            already_trashed,
+            db_error,
+        ) = tokio::join!(
+            delete_expense(&conn, TEST_ID, TEST_ID, keep_transactions()),
+            delete_expense(&conn, TEST_ID, TEST_ID, reassign_to(TEST_ID + 1)),
+            delete_expense(&conn, TEST_ID, TEST_ID, reassign_to(TEST_ID + 1)),
+            delete_expense(&conn, TEST_ID, TEST_ID, reassign_to(TEST_ID)),
+            delete_expense(&conn, TEST_ID, TEST_ID, reassign_to(TEST_ID + 1)),
+            delete_expense(&conn, TEST_ID, TEST_ID, reassign_to(TEST_ID + 1)),
+            delete_expense(&conn, TEST_ID, TEST_ID, keep_transactions()),
+            delete_expense(&conn, TEST_ID, TEST_ID, keep_transactions()),
+            delete_expense(&conn, TEST_ID, TEST_ID, keep_transactions()),
+            delete_expense(&conn, TEST_ID, TEST_ID, keep_transactions())
+        );
+
+        let invalid_target = Err(DeleteExpenseError::InvalidReassignTarget);
+        check!(deleted == Ok(()));
+        check!(reassigned == Ok(()));
+        check!(reassigned_with_foreign_payers == Err(DeleteExpenseError::ForeignPayers));
+        check!(reassigned_to_itself == invalid_target);
+        check!(reassigned_to_foreign == invalid_target);
+        check!(reassigned_to_missing == invalid_target);
+        check!(not_found == Err(DeleteExpenseError::ExpenseNotFound));
+        check!(unauthorized == Err(DeleteExpenseError::UserUnauthorized(AuthorizeUserError)));
+        check!(already_trashed == Err(DeleteExpenseError::ExpenseNotFound));
+        check!(db_error == Err(DeleteExpenseError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn restore_expense_all_cases() {
+        let trashed_expense = expense::Model {
+            deleted_at: Some(Local::now()),
+            ..test_expense()
+        };
+        let foreign_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..trashed_expense.clone()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![trashed_expense.clone()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // not in the trash
+            .append_query_results(vec![vec![test_expense()]])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // expense of another user
+            .append_query_results(vec![vec![foreign_expense]])
+            // db error
+            .append_query_results(vec![vec![trashed_expense]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_in_trash, not_found, unauthorized, db_error) = tokio::join!(
+            restore_expense(&conn, TEST_ID, TEST_ID),
+            restore_expense(&conn, TEST_ID, TEST_ID),
+            restore_expense(&conn, TEST_ID, TEST_ID),
+            restore_expense(&conn, TEST_ID, TEST_ID),
+            restore_expense(&conn, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_in_trash == Err(RestoreExpenseError::ExpenseNotInTrash));
+        check!(not_found == Err(RestoreExpenseError::ExpenseNotFound));
+        check!(unauthorized == Err(RestoreExpenseError::UserUnauthorized(AuthorizeUserError)));
+        check!(db_error == Err(RestoreExpenseError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn cancel_expense_all_cases() {
+        let later_expense = expense::Model {
+            start_date: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            ..test_expense()
+        };
+        let foreign_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // expense of another user
+            .append_query_results(vec![vec![foreign_expense]])
+            // invalid date
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            // cancelled before its start date
+            .append_query_results(vec![vec![later_expense]])
+            .append_query_results(vec![vec![test_user()]])
+            .into_connection();
+        let cancel_on = |date: &str| CancelExpenseRequest {
+            date: date.to_string(),
+        };
+
+        let (happy_case, not_found, unauthorized, invalid_date, invalid_end_date) = tokio::join!(
+            cancel_expense(&conn, TEST_ID, TEST_ID, cancel_on(TEST_DATE)),
+            cancel_expense(&conn, TEST_ID, TEST_ID, cancel_on(TEST_DATE)),
+            cancel_expense(&conn, TEST_ID, TEST_ID, cancel_on(TEST_DATE)),
+            cancel_expense(&conn, TEST_ID, TEST_ID, cancel_on(TEST_STR)),
+            cancel_expense(&conn, TEST_ID, TEST_ID, cancel_on(TEST_DATE))
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(CancelExpenseError::ExpenseNotFound));
+        check!(unauthorized == Err(CancelExpenseError::UserUnauthorized(AuthorizeUserError)));
+        check!(let Err(CancelExpenseError::InvalidDate(_)) = invalid_date);
+        check!(invalid_end_date == Err(CancelExpenseError::InvalidEndDate));
+    }
+
+    #[tokio::test]
+    async fn pause_expense_all_cases() {
+        let finished_pause = expense_pause::Model {
+            end_date: NaiveDate::from_ymd_opt(1990, 1, 1),
+            ..test_expense_pause()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![finished_pause]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense_pause()]])
+            // overlapping an open ended pause
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_expense_pause()]])
+            // ends before it starts
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // db error
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let pause_from = |start_date: &str, end_date: Option<&str>| NewExpensePauseRequest {
+            start_date: start_date.to_string(),
+            end_date: end_date.map(|end_date| end_date.to_string()),
+        };
+
+        let (happy_case, overlapping, invalid_interval, not_found, db_error) = tokio::join!(
+            pause_expense(&conn, TEST_ID, TEST_ID, pause_from(TEST_DATE, None)),
+            pause_expense(&conn, TEST_ID, TEST_ID, pause_from(TEST_DATE, None)),
+            pause_expense(
+                &conn,
+                TEST_ID,
+                TEST_ID,
+                pause_from(TEST_DATE, Some(TEST_DATE))
+            ),
+            pause_expense(&conn, TEST_ID, TEST_ID, pause_from(TEST_DATE, None)),
+            pause_expense(&conn, TEST_ID, TEST_ID, pause_from(TEST_DATE, None))
+        );
+
+        check!(happy_case == Ok(TEST_ID));
+        check!(overlapping == Err(PauseExpenseError::OverlappingPause));
+        check!(invalid_interval == Err(PauseExpenseError::InvalidInterval));
+        check!(not_found == Err(PauseExpenseError::ExpenseNotFound));
+        check!(db_error == Err(PauseExpenseError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_expense_pause_all_cases() {
+        let foreign_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let pause_of_other_expense = expense_pause::Model {
+            expense_id: TEST_ID + 1,
+            ..test_expense_pause()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_expense_pause()]])
+            .append_exec_results(vec![exec_ok()])
+            // pause of another expense
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![pause_of_other_expense]])
+            // pause not found
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<expense_pause::Model>::new()])
+            // expense not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // expense of another user
+            .append_query_results(vec![vec![foreign_expense]])
+            .into_connection();
+
+        let (happy_case, other_expense, pause_not_found, expense_not_found, unauthorized) = tokio::join!(
+            delete_expense_pause(&conn, TEST_ID, TEST_ID, TEST_ID),
+            delete_expense_pause(&conn, TEST_ID, TEST_ID, TEST_ID),
+            delete_expense_pause(&conn, TEST_ID, TEST_ID, TEST_ID),
+            delete_expense_pause(&conn, TEST_ID, TEST_ID, TEST_ID),
+            delete_expense_pause(&conn, TEST_ID, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(other_expense == Err(DeleteExpensePauseError::PauseNotFound));
+        check!(pause_not_found == Err(DeleteExpensePauseError::PauseNotFound));
+        check!(expense_not_found == Err(DeleteExpensePauseError::ExpenseNotFound));
+        check!(
+            unauthorized
+                == Err(DeleteExpensePauseError::UserUnauthorized(
+                    AuthorizeUserError
+                ))
+        );
+    }
+
+    #[tokio::test]
+    async fn add_price_change_all_cases() {
+        let later_expense = expense::Model {
+            start_date: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            ..test_expense()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<expense_price::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense_price()]])
+            // price already changes on that day
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_expense_price()]])
+            // effective before the expense starts
+            .append_query_results(vec![vec![later_expense]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_user()]])
+            // invalid currency
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<currency::Model>::new()])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .into_connection();
+        let change_on = |effective_from: &str| NewExpensePriceRequest {
+            value: test_decimal(),
+            currency_id: TEST_ID,
+            effective_from: effective_from.to_string(),
+        };
+
+        let (happy_case, same_day, before_start, invalid_currency, not_found) = tokio::join!(
+            add_price_change(&conn, TEST_ID, TEST_ID, change_on(TEST_DATE)),
+            add_price_change(&conn, TEST_ID, TEST_ID, change_on(TEST_DATE)),
+            add_price_change(&conn, TEST_ID, TEST_ID, change_on(TEST_DATE)),
+            add_price_change(&conn, TEST_ID, TEST_ID, change_on(TEST_DATE)),
+            add_price_change(&conn, TEST_ID, TEST_ID, change_on(TEST_DATE))
+        );
+
+        check!(happy_case == Ok(TEST_ID));
+        check!(same_day == Err(AddPriceChangeError::PriceChangeExists));
+        check!(before_start == Err(AddPriceChangeError::InvalidEffectiveDate));
+        check!(invalid_currency == Err(AddPriceChangeError::InvalidCurrency));
+        check!(not_found == Err(AddPriceChangeError::ExpenseNotFound));
+    }
+
+    #[tokio::test]
+    async fn delete_price_change_all_cases() {
+        let foreign_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let price_of_other_expense = expense_price::Model {
+            expense_id: TEST_ID + 1,
+            ..test_expense_price()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_expense_price()]])
+            .append_exec_results(vec![exec_ok()])
+            // price change of another expense
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![price_of_other_expense]])
+            // expense of another user
+            .append_query_results(vec![vec![foreign_expense]])
+            // db error
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_expense_price()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, other_expense, unauthorized, db_error) = tokio::join!(
+            delete_price_change(&conn, TEST_ID, TEST_ID, TEST_ID),
+            delete_price_change(&conn, TEST_ID, TEST_ID, TEST_ID),
+            delete_price_change(&conn, TEST_ID, TEST_ID, TEST_ID),
+            delete_price_change(&conn, TEST_ID, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(other_expense == Err(DeletePriceChangeError::PriceChangeNotFound));
+        check!(unauthorized == Err(DeletePriceChangeError::UserUnauthorized(AuthorizeUserError)));
+        check!(db_error == Err(DeletePriceChangeError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn set_expense_split_all_cases() {
+        let foreign_expense = expense::Model {
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let split_expense = expense::Model {
+            split_method: Some(SplitMethod::Percentage),
+            ..test_expense()
+        };
+        let participants = vec![
+            test_participant(),
+            participant::Model {
+                id: TEST_ID + 1,
+                ..test_participant()
+            },
+        ];
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![participants.clone()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![split_expense]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // participant of another user
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![
+                test_participant(),
+                participant::Model {
+                    id: TEST_ID + 1,
+                    user_id: Some(TEST_ID + 1),
+                    ..test_participant()
+                },
+            ]])
+            // duplicate participant
+            .append_query_results(vec![vec![test_expense()]])
+            // percentages not adding up to 100
+            .append_query_results(vec![vec![test_expense()]])
+            // missing weight
+            .append_query_results(vec![vec![test_expense()]])
+            // expense of another user
+            .append_query_results(vec![vec![foreign_expense]])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .into_connection();
+        let split = |method: SplitMethod, shares: &[(Id, Option<i64>)]| ExpenseSplitRequest {
+            method,
+            shares: shares
+                .iter()
+                .map(|(participant_id, value)| ExpenseShareRequest {
+                    participant_id: *participant_id,
+                    value: value.map(MoneyAmount::from),
+                })
+                .collect(),
+        };
+        let (anna, bob) = (TEST_ID, TEST_ID + 1);
+        let percentages = split(
+            SplitMethod::Percentage,
+            &[(anna, Some(60)), (bob, Some(40))],
+        );
+
+        let (
+            happy_case,
+            invalid_participant,
+            duplicate,
+            invalid_percentages,
+            missing_weight,
+            unauthorized,
+            not_found,
+        ) = tokio::join!(
+            set_expense_split(&conn, TEST_ID, TEST_ID, percentages.clone()),
+            set_expense_split(&conn, TEST_ID, TEST_ID, percentages.clone()),
+            set_expense_split(
+                &conn,
+                TEST_ID,
+                TEST_ID,
+                split(SplitMethod::Equal, &[(anna, None), (anna, None)])
+            ),
+            set_expense_split(
+                &conn,
+                TEST_ID,
+                TEST_ID,
+                split(
+                    SplitMethod::Percentage,
+                    &[(anna, Some(60)), (bob, Some(30))]
+                )
+            ),
+            set_expense_split(
+                &conn,
+                TEST_ID,
+                TEST_ID,
+                split(SplitMethod::Weight, &[(anna, Some(2)), (bob, None)])
+            ),
+            set_expense_split(&conn, TEST_ID, TEST_ID, percentages.clone()),
+            set_expense_split(&conn, TEST_ID, TEST_ID, percentages)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(invalid_participant == Err(SetExpenseSplitError::InvalidParticipant));
+        check!(duplicate == Err(SetExpenseSplitError::DuplicateParticipant));
+        check!(invalid_percentages == Err(SetExpenseSplitError::InvalidPercentages));
+        check!(missing_weight == Err(SetExpenseSplitError::InvalidShareValue));
+        check!(unauthorized == Err(SetExpenseSplitError::UserUnauthorized(AuthorizeUserError)));
+        check!(not_found == Err(SetExpenseSplitError::ExpenseNotFound));
+    }
+
+    #[tokio::test]
+    async fn delete_expense_split_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_results(vec![exec_ok()])
+            // not found
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // db error
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, db_error) = tokio::join!(
+            delete_expense_split(&conn, TEST_ID, TEST_ID),
+            delete_expense_split(&conn, TEST_ID, TEST_ID),
+            delete_expense_split(&conn, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(DeleteExpenseSplitError::ExpenseNotFound));
+        check!(db_error == Err(DeleteExpenseSplitError::DatabaseError(test_db_error())));
+    }
 }
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use super::expense_activity::expected_payments;
+
+use chrono::NaiveDate;
+use entity::expense::SplitMethod;
+use entity::{expense, expense_pause, expense_price, expense_share, Id, MoneyAmount};
+
+/// Divides one payment between the shares by the split method, the parts are in the order of the shares.
+/// A fixed split charges the fixed amounts first and divides the rest of the payment equally.
+pub fn split_payment(
+    method: SplitMethod,
+    shares: &[expense_share::Model],
+    value: MoneyAmount,
+) -> Vec<MoneyAmount> {
+    if shares.is_empty() {
+        return Vec::new();
+    }
+    let participants = MoneyAmount::from(shares.len());
+    let share_value = |share: &expense_share::Model| share.value.unwrap_or_default();
+
+    match method {
+        SplitMethod::Equal => vec![value / participants; shares.len()],
+        SplitMethod::Percentage => shares
+            .iter()
+            .map(|share| value * share_value(share) / MoneyAmount::ONE_HUNDRED)
+            .collect(),
+        SplitMethod::Fixed => {
+            let rest = (value - shares.iter().map(share_value).sum::<MoneyAmount>()) / participants;
+            shares
+                .iter()
+                .map(|share| share_value(share) + rest)
+                .collect()
+        }
+        SplitMethod::Weight => {
+            let total_weight: MoneyAmount = shares.iter().map(share_value).sum();
+            if total_weight.is_zero() {
+                return split_payment(SplitMethod::Equal, shares, value);
+            }
+            shares
+                .iter()
+                .map(|share| value * share_value(share) / total_weight)
+                .collect()
+        }
+    }
+}
+
+/// The share of each participant in the expected costs between `from` (inclusive) and `to` (exclusive),
+/// by participant id and currency id.
+#[allow(clippy::too_many_arguments)]
+pub fn expected_shares(
+    expense: &expense::Model,
+    method: SplitMethod,
+    shares: &[expense_share::Model],
+    pauses: &[expense_pause::Model],
+    prices: &[expense_price::Model],
+    per_year: f64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> BTreeMap<(Id, Id), MoneyAmount> {
+    let mut expected: BTreeMap<(Id, Id), MoneyAmount> = BTreeMap::new();
+    for (value, currency_id, occurrences) in
+        expected_payments(expense, pauses, prices, per_year, from, to)
+    {
+        let occurrences = MoneyAmount::from_f64_retain(occurrences).unwrap_or_default();
+        for (share, part) in shares.iter().zip(split_payment(method, shares, value)) {
+            *expected
+                .entry((share.participant_id, currency_id))
+                .or_default() += part * occurrences;
+        }
+    }
+    expected
+        .into_iter()
+        .map(|(key, amount)| (key, amount.round_dp(2)))
+        .collect()
+}
+
+/// Suggests the payments settling the balances of one currency as (debtor id, creditor id, amount),
+/// the largest debts are paid to the largest credits first, so it takes fewer payments than participants.
+/// Rounding leftovers below a cent are not settled.
+pub fn settle_up(balances: &[(Id, MoneyAmount)]) -> Vec<(Id, Id, MoneyAmount)> {
+    let by_amount = |side: Vec<(Id, MoneyAmount)>| {
+        let mut side: Vec<(Id, MoneyAmount)> = side
+            .into_iter()
+            .map(|(participant_id, amount)| (participant_id, amount.round_dp(2)))
+            .filter(|(_, amount)| amount.is_sign_positive() && !amount.is_zero())
+            .collect();
+        side.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        side
+    };
+    let mut creditors = by_amount(balances.to_vec());
+    let mut debtors = by_amount(
+        balances
+            .iter()
+            .map(|(participant_id, balance)| (*participant_id, -balance))
+            .collect(),
+    );
+
+    let mut settlements = Vec::new();
+    let (mut creditor, mut debtor) = (0, 0);
+    while creditor < creditors.len() && debtor < debtors.len() {
+        let amount = creditors[creditor].1.min(debtors[debtor].1);
+        settlements.push((debtors[debtor].0, creditors[creditor].0, amount));
+        creditors[creditor].1 -= amount;
+        debtors[debtor].1 -= amount;
+        if creditors[creditor].1.is_zero() {
+            creditor += 1;
+        }
+        if debtors[debtor].1.is_zero() {
+            debtor += 1;
+        }
+    }
+    settlements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{test_expense, TEST_ID};
+    use assert2::check;
+
+    const ANNA: Id = 1;
+    const BOB: Id = 2;
+    const CECIL: Id = 3;
+    const DORA: Id = 4;
+
+    fn share(participant_id: Id, value: Option<i64>) -> expense_share::Model {
+        expense_share::Model {
+            id: TEST_ID,
+            expense_id: TEST_ID,
+            participant_id,
+            value: value.map(MoneyAmount::from),
+        }
+    }
+
+    fn amount(value: &str) -> MoneyAmount {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn split_payment_all_cases() {
+        let shares = vec![
+            share(ANNA, Some(50)),
+            share(BOB, Some(30)),
+            share(CECIL, Some(20)),
+        ];
+
+        check!(split_payment(SplitMethod::Equal, &[], amount("90")) == vec![]);
+        check!(
+            split_payment(SplitMethod::Equal, &shares, amount("90"))
+                == vec![amount("30"), amount("30"), amount("30")]
+        );
+        check!(
+            split_payment(SplitMethod::Percentage, &shares, amount("90"))
+                == vec![amount("45"), amount("27"), amount("18")]
+        );
+        check!(
+            split_payment(SplitMethod::Fixed, &shares, amount("130"))
+                == vec![amount("60"), amount("40"), amount("30")]
+        );
+        check!(
+            split_payment(SplitMethod::Weight, &shares, amount("90"))
+                == vec![amount("45"), amount("27"), amount("18")]
+        );
+        check!(
+            split_payment(
+                SplitMethod::Weight,
+                &[share(ANNA, None), share(BOB, None)],
+                amount("90")
+            ) == vec![amount("45"), amount("45")]
+        );
+    }
+
+    #[test]
+    fn expected_shares_all_cases() {
+        let expense = expense::Model {
+            start_date: NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+            end_date: None,
+            value: amount("100"),
+            ..test_expense()
+        };
+        let shares = vec![share(ANNA, Some(3)), share(BOB, Some(1))];
+        let from = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        let expected = expected_shares(
+            &expense,
+            SplitMethod::Weight,
+            &shares,
+            &[],
+            &[],
+            12.0,
+            from,
+            to,
+        );
+
+        check!(
+            expected
+                == BTreeMap::from([
+                    ((ANNA, expense.currency_id), amount("899.38")),
+                    ((BOB, expense.currency_id), amount("299.79")),
+                ])
+        );
+    }
+
+    #[test]
+    fn settle_up_all_cases() {
+        check!(settle_up(&[]) == vec![]);
+        check!(settle_up(&[(ANNA, amount("0.004"))]) == vec![]);
+
+        let settlements = settle_up(&[
+            (ANNA, amount("60")),
+            (BOB, amount("-45")),
+            (CECIL, amount("-15")),
+            (DORA, amount("0")),
+        ]);
+        check!(settlements == vec![(BOB, ANNA, amount("45")), (CECIL, ANNA, amount("15"))]);
+
+        let settlements = settle_up(&[
+            (ANNA, amount("30")),
+            (BOB, amount("20")),
+            (CECIL, amount("-50")),
+        ]);
+        check!(settlements == vec![(CECIL, ANNA, amount("30")), (CECIL, BOB, amount("20"))]);
+    }
+}
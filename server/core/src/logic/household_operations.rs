@@ -0,0 +1,1044 @@
+use self::errors::{
+    AcceptHouseholdInvitationError, DeleteHouseholdError, InviteToHouseholdError,
+    RemoveHouseholdMemberError,
+};
+
+use super::common::find_entity_by_id;
+use super::participant_operations::adopt_payers;
+use super::user_operations::errors::AuthorizeUserError;
+use crate::config::AppConfig;
+use crate::dto::households::{
+    HouseholdInvitationRequest, HouseholdMemberResponse, HouseholdResponse, NewHouseholdRequest,
+};
+use crate::email::{render_template, send_mail, HOUSEHOLD_INVITATION_EMAIL_TEMPLATE};
+
+use chrono::Duration;
+use entity::expense::{self, Entity as Expense};
+use entity::household::{self, Entity as Household};
+use entity::household_invitation::{self, Entity as HouseholdInvitation};
+use entity::household_member::{self, Entity as HouseholdMember, HouseholdRole};
+use entity::predefined_expense::{self, Entity as PredefinedExpense, PredefinedExpenseVisibility};
+use entity::user::{self, Entity as User};
+use entity::Id;
+use lettre::AsyncTransport;
+use migration::DbErr;
+use sea_orm::prelude::Uuid;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, LoaderTrait,
+    ModelTrait, QueryFilter, QueryOrder, Set, TransactionError, TransactionTrait,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Household invitations can be accepted for this many days after they were sent.
+const INVITATION_VALIDITY_DAYS: i64 = 7;
+
+/// Lists the households the user is a member of, with all of their members.
+pub async fn find_households(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<Vec<HouseholdResponse>, DbErr> {
+    let household_ids = find_household_ids_by_user_id(conn, user_id).await?;
+    if household_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let (households, members) = tokio::join!(
+        Household::find()
+            .filter(household::Column::Id.is_in(household_ids.clone()))
+            .order_by_asc(household::Column::Name)
+            .all(conn),
+        HouseholdMember::find()
+            .filter(household_member::Column::HouseholdId.is_in(household_ids))
+            .order_by_asc(household_member::Column::Id)
+            .all(conn)
+    );
+    let members = members?;
+    let users = members.load_one(User, conn).await?;
+
+    let households = households?
+        .into_iter()
+        .map(|household| {
+            let household_members = members
+                .iter()
+                .zip(users.iter())
+                .filter(|(member, _)| member.household_id == household.id)
+                .filter_map(|(member, user)| {
+                    user.as_ref().map(|user| HouseholdMemberResponse {
+                        user_id: member.user_id,
+                        username: user.username.clone(),
+                        role: member.role,
+                    })
+                })
+                .collect();
+            (household, household_members).into()
+        })
+        .collect();
+    Ok(households)
+}
+
+/// Creates a household with the user as its owner.
+pub async fn create_household(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    req: NewHouseholdRequest,
+) -> Result<Id, DbErr> {
+    conn.transaction::<_, Id, DbErr>(|txn| {
+        Box::pin(async move {
+            let household = household::ActiveModel {
+                id: NotSet,
+                name: Set(req.name),
+            };
+            let household = household.insert(txn).await?;
+            let owner = household_member::ActiveModel {
+                id: NotSet,
+                household_id: Set(household.id),
+                user_id: Set(user_id),
+                role: Set(HouseholdRole::Owner),
+            };
+            owner.insert(txn).await?;
+            Ok(household.id)
+        })
+    })
+    .await
+    .map_err(|e| match e {
+        TransactionError::Connection(e) | TransactionError::Transaction(e) => e,
+    })
+}
+
+/// Deletes a household of the owner, its expenses stay with the members who created them.
+/// The participants of the household are deleted with it, see `delete_household_with_participants`.
+pub async fn delete_household(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    household_id: Id,
+) -> Result<(), DeleteHouseholdError> {
+    let Some(membership) = find_membership(conn, household_id, user_id).await? else {
+        return Err(DeleteHouseholdError::HouseholdNotFound);
+    };
+    if membership.role != HouseholdRole::Owner {
+        return Err(DeleteHouseholdError::UserUnauthorized(AuthorizeUserError));
+    }
+    conn.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move { delete_household_with_participants(txn, household_id).await })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Sends an invitation to the email address, which replaces the earlier invitations sent to it.
+/// Only the owner can invite, and the invitation is only stored if the email could be sent.
+pub async fn invite_to_household<M>(
+    config: &AppConfig,
+    conn: &DatabaseConnection,
+    mail_transport: Arc<M>,
+    user_id: Id,
+    household_id: Id,
+    req: HouseholdInvitationRequest,
+) -> Result<(), InviteToHouseholdError>
+where
+    M: AsyncTransport + Send + Sync + 'static,
+    <M as AsyncTransport>::Error: std::fmt::Debug,
+{
+    let Some(membership) = find_membership(conn, household_id, user_id).await? else {
+        return Err(InviteToHouseholdError::HouseholdNotFound);
+    };
+    if membership.role != HouseholdRole::Owner {
+        return Err(InviteToHouseholdError::UserUnauthorized(AuthorizeUserError));
+    }
+    let (household, inviter, existing_member) = tokio::join!(
+        find_entity_by_id::<household::Entity>(conn, household_id),
+        find_entity_by_id::<user::Entity>(conn, user_id),
+        HouseholdMember::find()
+            .inner_join(User)
+            .filter(household_member::Column::HouseholdId.eq(household_id))
+            .filter(user::Column::Email.eq(req.email.as_str()))
+            .one(conn)
+    );
+    let (Some(household), Some(inviter)) = (household?, inviter?) else {
+        return Err(InviteToHouseholdError::HouseholdNotFound);
+    };
+    if existing_member?.is_some() {
+        return Err(InviteToHouseholdError::AlreadyMember);
+    }
+
+    let client_url = config.client_url.clone();
+    conn.transaction::<_, (), InviteToHouseholdError>(|txn| {
+        Box::pin(async move {
+            HouseholdInvitation::delete_many()
+                .filter(household_invitation::Column::HouseholdId.eq(household_id))
+                .filter(household_invitation::Column::Email.eq(req.email.as_str()))
+                .exec(txn)
+                .await?;
+            let invitation = household_invitation::ActiveModel {
+                id: NotSet,
+                token: Set(Uuid::new_v4().to_string()),
+                household_id: Set(household_id),
+                email: Set(req.email),
+                invited_by: Set(user_id),
+                expiration: Set(chrono::Local::now()
+                    .checked_add_signed(Duration::days(INVITATION_VALIDITY_DAYS))
+                    .expect("we should not be this far ahead into the future Marty, the date overflowed the bounds")),
+            };
+            let invitation = invitation.insert(txn).await?;
+
+            let invitation_link = format!("{}/household/join/{}", client_url, invitation.token);
+            let validity_days = INVITATION_VALIDITY_DAYS.to_string();
+            let mut data = HashMap::new();
+            data.insert("username", &inviter.username);
+            data.insert("household_name", &household.name);
+            data.insert("invitation_link", &invitation_link);
+            data.insert("validity_days", &validity_days);
+            let body = render_template(HOUSEHOLD_INVITATION_EMAIL_TEMPLATE, &data);
+            match send_mail(mail_transport, invitation.email, "Veryrezsi household invitation", body).await {
+                Ok(_) => Ok(()),
+                Err(reason) => Err(InviteToHouseholdError::EmailCannotBeSent(reason)),
+            }
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Makes the user a member of the household they were invited to, the invitation must have been sent to their email address.
+pub async fn accept_household_invitation(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    token: String,
+) -> Result<Id, AcceptHouseholdInvitationError> {
+    let Some(invitation) = HouseholdInvitation::find()
+        .filter(household_invitation::Column::Token.eq(token))
+        .one(conn)
+        .await? else {
+        return Err(AcceptHouseholdInvitationError::InvalidToken);
+    };
+    if invitation.expiration < chrono::Local::now() {
+        return Err(AcceptHouseholdInvitationError::InvalidToken);
+    }
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(AcceptHouseholdInvitationError::InvalidToken);
+    };
+    if !user.email.eq_ignore_ascii_case(&invitation.email) {
+        return Err(AcceptHouseholdInvitationError::InvalidToken);
+    }
+    let household_id = invitation.household_id;
+    if find_membership(conn, household_id, user_id)
+        .await?
+        .is_some()
+    {
+        return Err(AcceptHouseholdInvitationError::AlreadyMember);
+    }
+
+    conn.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            let member = household_member::ActiveModel {
+                id: NotSet,
+                household_id: Set(household_id),
+                user_id: Set(user_id),
+                role: Set(HouseholdRole::Member),
+            };
+            member.insert(txn).await?;
+            invitation.delete(txn).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(household_id)
+}
+
+/// Removes a member from the household, the owner can remove anyone and the others can leave.
+/// The owner cannot leave, they can delete the household instead.
+pub async fn remove_household_member(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    household_id: Id,
+    member_user_id: Id,
+) -> Result<(), RemoveHouseholdMemberError> {
+    let Some(membership) = find_membership(conn, household_id, user_id).await? else {
+        return Err(RemoveHouseholdMemberError::HouseholdNotFound);
+    };
+    if user_id != member_user_id && membership.role != HouseholdRole::Owner {
+        return Err(RemoveHouseholdMemberError::UserUnauthorized(
+            AuthorizeUserError,
+        ));
+    }
+    let member = if user_id == member_user_id {
+        membership
+    } else {
+        let Some(member) = find_membership(conn, household_id, member_user_id).await? else {
+            return Err(RemoveHouseholdMemberError::MemberNotFound);
+        };
+        member
+    };
+    if member.role == HouseholdRole::Owner {
+        return Err(RemoveHouseholdMemberError::OwnerCannotLeave);
+    }
+    member.delete(conn).await?;
+    Ok(())
+}
+
+/// Whether the user can use the expense: its creator always can, the members of its household can too.
+pub(super) async fn can_access_expense<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+    expense: &expense::Model,
+) -> Result<bool, DbErr> {
+    if expense.user_id == user_id {
+        return Ok(true);
+    }
+    match expense.household_id {
+        Some(household_id) => is_household_member(conn, household_id, user_id).await,
+        None => Ok(false),
+    }
+}
+
+pub(super) async fn is_household_member<C: ConnectionTrait>(
+    conn: &C,
+    household_id: Id,
+    user_id: Id,
+) -> Result<bool, DbErr> {
+    Ok(find_membership(conn, household_id, user_id)
+        .await?
+        .is_some())
+}
+
+/// The households both users are members of, which are all households of the user if the two are the same.
+pub(super) async fn find_shared_household_ids<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+    other_user_id: Id,
+) -> Result<Vec<Id>, DbErr> {
+    let memberships = HouseholdMember::find()
+        .filter(household_member::Column::UserId.is_in([user_id, other_user_id]))
+        .all(conn)
+        .await?;
+    let household_ids = memberships
+        .iter()
+        .filter(|membership| membership.user_id == user_id)
+        .map(|membership| membership.household_id)
+        .filter(|household_id| {
+            memberships.iter().any(|membership| {
+                membership.user_id == other_user_id && membership.household_id == *household_id
+            })
+        })
+        .collect();
+    Ok(household_ids)
+}
+
+pub(super) async fn find_household_ids_by_user_id<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+) -> Result<Vec<Id>, DbErr> {
+    find_shared_household_ids(conn, user_id, user_id).await
+}
+
+/// Hands the household expenses of a user who is being deleted over to a remaining member of each household,
+/// who also becomes the owner if the user owned it. The successor is the owner of the household, or the earliest joined member if the user was the owner.
+/// A household without remaining members is deleted, so its expenses are detached and deleted with the rest of the user's data.
+/// The own participants of the user paying for the handed over expenses are replaced by participants of the household,
+/// as they are deleted together with the user.
+pub(super) async fn hand_over_household_expenses<C: ConnectionTrait>(
+    txn: &C,
+    user_id: Id,
+) -> Result<(), DbErr> {
+    let memberships = HouseholdMember::find()
+        .filter(household_member::Column::UserId.eq(user_id))
+        .all(txn)
+        .await?;
+    let expenses = Expense::find()
+        .filter(expense::Column::UserId.eq(user_id))
+        .filter(expense::Column::HouseholdId.is_not_null())
+        .all(txn)
+        .await?;
+    let household_ids: BTreeSet<Id> = memberships
+        .iter()
+        .map(|membership| membership.household_id)
+        .chain(expenses.iter().filter_map(|expense| expense.household_id))
+        .collect();
+    if household_ids.is_empty() {
+        return Ok(());
+    }
+
+    let other_members = HouseholdMember::find()
+        .filter(household_member::Column::HouseholdId.is_in(household_ids.iter().copied()))
+        .filter(household_member::Column::UserId.ne(user_id))
+        .order_by_asc(household_member::Column::Id)
+        .all(txn)
+        .await?;
+    let mut handed_over = Vec::new();
+    for household_id in household_ids {
+        let mut members = other_members
+            .iter()
+            .filter(|member| member.household_id == household_id);
+        let successor = members
+            .clone()
+            .find(|member| member.role == HouseholdRole::Owner)
+            .or_else(|| members.next());
+        let Some(successor) = successor else {
+            delete_household_with_participants(txn, household_id).await?;
+            continue;
+        };
+        let owned = memberships.iter().any(|membership| {
+            membership.household_id == household_id && membership.role == HouseholdRole::Owner
+        });
+        if owned && successor.role != HouseholdRole::Owner {
+            HouseholdMember::update_many()
+                .col_expr(
+                    household_member::Column::Role,
+                    Expr::value(HouseholdRole::Owner),
+                )
+                .filter(household_member::Column::Id.eq(successor.id))
+                .exec(txn)
+                .await?;
+        }
+        Expense::update_many()
+            .col_expr(expense::Column::UserId, Expr::value(successor.user_id))
+            .filter(expense::Column::UserId.eq(user_id))
+            .filter(expense::Column::HouseholdId.eq(household_id))
+            .exec(txn)
+            .await?;
+        PredefinedExpense::update_many()
+            .col_expr(
+                predefined_expense::Column::UserId,
+                Expr::value(successor.user_id),
+            )
+            .filter(predefined_expense::Column::UserId.eq(user_id))
+            .filter(predefined_expense::Column::HouseholdId.eq(household_id))
+            .exec(txn)
+            .await?;
+        handed_over.extend(
+            expenses
+                .iter()
+                .filter(|expense| expense.household_id == Some(household_id))
+                .map(|expense| expense::Model {
+                    user_id: successor.user_id,
+                    ..expense.clone()
+                }),
+        );
+    }
+    adopt_payers(txn, &handed_over).await
+}
+
+/// Deletes a household together with its participants, so no participant is left without an owner.
+/// The expenses of the household become the own expenses of their creators, and the participants of the household
+/// paying for them are replaced by own participants of the creators first.
+/// The predefined expenses of the household become the private ones of their owners.
+async fn delete_household_with_participants<C: ConnectionTrait>(
+    txn: &C,
+    household_id: Id,
+) -> Result<(), DbErr> {
+    let detached: Vec<expense::Model> = Expense::find()
+        .filter(expense::Column::HouseholdId.eq(household_id))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|expense| expense::Model {
+            household_id: None,
+            ..expense
+        })
+        .collect();
+    adopt_payers(txn, &detached).await?;
+    PredefinedExpense::update_many()
+        .col_expr(
+            predefined_expense::Column::Visibility,
+            Expr::value(PredefinedExpenseVisibility::Private),
+        )
+        .col_expr(
+            predefined_expense::Column::HouseholdId,
+            Expr::value(Option::<Id>::None),
+        )
+        .filter(predefined_expense::Column::HouseholdId.eq(household_id))
+        .exec(txn)
+        .await?;
+    Household::delete_by_id(household_id).exec(txn).await?;
+    Ok(())
+}
+
+async fn find_membership<C: ConnectionTrait>(
+    conn: &C,
+    household_id: Id,
+    user_id: Id,
+) -> Result<Option<household_member::Model>, DbErr> {
+    HouseholdMember::find()
+        .filter(household_member::Column::HouseholdId.eq(household_id))
+        .filter(household_member::Column::UserId.eq(user_id))
+        .one(conn)
+        .await
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteHouseholdError {
+        #[error("household not found")]
+        HouseholdNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DbErr>> for DeleteHouseholdError {
+        fn from(e: TransactionError<DbErr>) -> Self {
+            match e {
+                TransactionError::Connection(e) | TransactionError::Transaction(e) => e.into(),
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum InviteToHouseholdError {
+        #[error("household not found")]
+        HouseholdNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("user is already a member of the household")]
+        AlreadyMember,
+        #[error("{0}")]
+        EmailCannotBeSent(String),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<InviteToHouseholdError>> for InviteToHouseholdError {
+        fn from(e: TransactionError<InviteToHouseholdError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum AcceptHouseholdInvitationError {
+        #[error("invitation is invalid or expired")]
+        InvalidToken,
+        #[error("user is already a member of the household")]
+        AlreadyMember,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DbErr>> for AcceptHouseholdInvitationError {
+        fn from(e: TransactionError<DbErr>) -> Self {
+            match e {
+                TransactionError::Connection(e) | TransactionError::Transaction(e) => e.into(),
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum RemoveHouseholdMemberError {
+        #[error("household not found")]
+        HouseholdNotFound,
+        #[error("member not found")]
+        MemberNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("the owner cannot leave the household")]
+        OwnerCannotLeave,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logic::common::tests::{
+        exec_ok, test_app_config, test_db_error, test_expense, test_household,
+        test_household_invitation, test_household_member, test_participant, test_transaction,
+        test_user, TEST_EMAIL, TEST_ID, TEST_STR,
+    };
+    use entity::{expense_share, participant, transaction};
+
+    use super::*;
+    use assert2::check;
+    use lettre::transport::stub::AsyncStubTransport;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+    fn test_member() -> household_member::Model {
+        household_member::Model {
+            id: TEST_ID + 1,
+            user_id: TEST_ID + 1,
+            role: HouseholdRole::Member,
+            ..test_household_member()
+        }
+    }
+
+    #[tokio::test]
+    async fn find_households_all_cases() {
+        let member_user = user::Model {
+            id: TEST_ID + 1,
+            username: TEST_EMAIL.to_string(),
+            ..test_user()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_household()]])
+            .append_query_results(vec![vec![test_household_member(), test_member()]])
+            .append_query_results(vec![vec![test_user(), member_user]])
+            // without households
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, without_households, db_error) = tokio::join!(
+            find_households(&conn, TEST_ID),
+            find_households(&conn, TEST_ID),
+            find_households(&conn, TEST_ID)
+        );
+
+        let expected = HouseholdResponse {
+            id: TEST_ID,
+            name: TEST_STR.to_string(),
+            members: vec![
+                HouseholdMemberResponse {
+                    user_id: TEST_ID,
+                    username: TEST_STR.to_string(),
+                    role: HouseholdRole::Owner,
+                },
+                HouseholdMemberResponse {
+                    user_id: TEST_ID + 1,
+                    username: TEST_EMAIL.to_string(),
+                    role: HouseholdRole::Member,
+                },
+            ],
+        };
+        check!(happy_case == Ok(vec![expected]));
+        check!(without_households == Ok(vec![]));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn create_household_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_household()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_household_member()]])
+            // db error
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let req = || NewHouseholdRequest {
+            name: TEST_STR.to_string(),
+        };
+
+        let (happy_case, db_error) = tokio::join!(
+            create_household(&conn, TEST_ID, req()),
+            create_household(&conn, TEST_ID, req())
+        );
+
+        check!(happy_case == Ok(TEST_ID));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn delete_household_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // not a member
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // not the owner
+            .append_query_results(vec![vec![test_member()]])
+            // db error
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, unauthorized, db_error) = tokio::join!(
+            delete_household(&conn, TEST_ID, TEST_ID),
+            delete_household(&conn, TEST_ID, TEST_ID),
+            delete_household(&conn, TEST_ID + 1, TEST_ID),
+            delete_household(&conn, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(DeleteHouseholdError::HouseholdNotFound));
+        check!(unauthorized == Err(DeleteHouseholdError::UserUnauthorized(AuthorizeUserError)));
+        check!(db_error == Err(DeleteHouseholdError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn invite_to_household_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_household()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_household_invitation()]])
+            // not a member
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // not the owner
+            .append_query_results(vec![vec![test_member()]])
+            // already a member
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_household()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_member()]])
+            // email cannot be sent
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_household()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_household_invitation()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let ok_mail_transport = Arc::new(AsyncStubTransport::new_ok());
+        let error_mail_transport = Arc::new(AsyncStubTransport::new_error());
+        let app_config = &test_app_config();
+        let req = || HouseholdInvitationRequest {
+            email: TEST_EMAIL.to_string(),
+        };
+
+        let (happy_case, not_found, unauthorized, already_member, email_error, db_error) = tokio::join!(
+            invite_to_household(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                TEST_ID,
+                TEST_ID,
+                req()
+            ),
+            invite_to_household(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                TEST_ID,
+                TEST_ID,
+                req()
+            ),
+            invite_to_household(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                TEST_ID + 1,
+                TEST_ID,
+                req()
+            ),
+            invite_to_household(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                TEST_ID,
+                TEST_ID,
+                req()
+            ),
+            invite_to_household(
+                app_config,
+                &conn,
+                error_mail_transport,
+                TEST_ID,
+                TEST_ID,
+                req()
+            ),
+            invite_to_household(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                TEST_ID,
+                TEST_ID,
+                req()
+            )
+        );
+
+        check!(happy_case == Ok(()));
+        check!(ok_mail_transport.messages().await.len() == 1);
+        check!(not_found == Err(InviteToHouseholdError::HouseholdNotFound));
+        check!(unauthorized == Err(InviteToHouseholdError::UserUnauthorized(AuthorizeUserError)));
+        check!(already_member == Err(InviteToHouseholdError::AlreadyMember));
+        check!(let Err(InviteToHouseholdError::EmailCannotBeSent(_)) = email_error);
+        check!(db_error == Err(InviteToHouseholdError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn accept_household_invitation_all_cases() {
+        let expired_invitation = household_invitation::Model {
+            expiration: chrono::Local::now() - Duration::days(1),
+            ..test_household_invitation()
+        };
+        let other_user = user::Model {
+            email: "other@test.com".to_string(),
+            ..test_user()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_household_invitation()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_member()]])
+            .append_exec_results(vec![exec_ok()])
+            // unknown token
+            .append_query_results(vec![Vec::<household_invitation::Model>::new()])
+            // expired token
+            .append_query_results(vec![vec![expired_invitation]])
+            // invitation sent to another email
+            .append_query_results(vec![vec![test_household_invitation()]])
+            .append_query_results(vec![vec![other_user]])
+            // already a member
+            .append_query_results(vec![vec![test_household_invitation()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_member()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let token = || TEST_STR.to_string();
+
+        let (happy_case, unknown, expired, other_email, already_member, db_error) = tokio::join!(
+            accept_household_invitation(&conn, TEST_ID + 1, token()),
+            accept_household_invitation(&conn, TEST_ID + 1, token()),
+            accept_household_invitation(&conn, TEST_ID + 1, token()),
+            accept_household_invitation(&conn, TEST_ID + 1, token()),
+            accept_household_invitation(&conn, TEST_ID + 1, token()),
+            accept_household_invitation(&conn, TEST_ID + 1, token())
+        );
+
+        check!(happy_case == Ok(TEST_ID));
+        check!(unknown == Err(AcceptHouseholdInvitationError::InvalidToken));
+        check!(expired == Err(AcceptHouseholdInvitationError::InvalidToken));
+        check!(other_email == Err(AcceptHouseholdInvitationError::InvalidToken));
+        check!(already_member == Err(AcceptHouseholdInvitationError::AlreadyMember));
+        check!(
+            db_error
+                == Err(AcceptHouseholdInvitationError::DatabaseError(
+                    test_db_error()
+                ))
+        );
+    }
+
+    #[tokio::test]
+    async fn remove_household_member_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // removed by the owner
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_member()]])
+            .append_exec_results(vec![exec_ok()])
+            // member leaves
+            .append_query_results(vec![vec![test_member()]])
+            .append_exec_results(vec![exec_ok()])
+            // not a member
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // member removes another member
+            .append_query_results(vec![vec![test_member()]])
+            // member to remove not found
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // owner leaves
+            .append_query_results(vec![vec![test_household_member()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (removed, left, not_found, unauthorized, member_not_found, owner_leaves, db_error) = tokio::join!(
+            remove_household_member(&conn, TEST_ID, TEST_ID, TEST_ID + 1),
+            remove_household_member(&conn, TEST_ID + 1, TEST_ID, TEST_ID + 1),
+            remove_household_member(&conn, TEST_ID + 2, TEST_ID, TEST_ID + 1),
+            remove_household_member(&conn, TEST_ID + 1, TEST_ID, TEST_ID + 2),
+            remove_household_member(&conn, TEST_ID, TEST_ID, TEST_ID + 2),
+            remove_household_member(&conn, TEST_ID, TEST_ID, TEST_ID),
+            remove_household_member(&conn, TEST_ID, TEST_ID, TEST_ID + 1)
+        );
+
+        check!(removed == Ok(()));
+        check!(left == Ok(()));
+        check!(not_found == Err(RemoveHouseholdMemberError::HouseholdNotFound));
+        check!(
+            unauthorized
+                == Err(RemoveHouseholdMemberError::UserUnauthorized(
+                    AuthorizeUserError
+                ))
+        );
+        check!(member_not_found == Err(RemoveHouseholdMemberError::MemberNotFound));
+        check!(owner_leaves == Err(RemoveHouseholdMemberError::OwnerCannotLeave));
+        check!(db_error == Err(RemoveHouseholdMemberError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn can_access_expense_all_cases() {
+        let household_expense = expense::Model {
+            household_id: Some(TEST_ID),
+            ..test_expense()
+        };
+        let private_expense = test_expense();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // household member
+            .append_query_results(vec![vec![test_member()]])
+            // not a household member
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .into_connection();
+
+        let (creator, member, not_member, not_shared) = tokio::join!(
+            can_access_expense(&conn, TEST_ID, &household_expense),
+            can_access_expense(&conn, TEST_ID + 1, &household_expense),
+            can_access_expense(&conn, TEST_ID + 2, &household_expense),
+            can_access_expense(&conn, TEST_ID + 1, &private_expense)
+        );
+
+        check!(creator == Ok(true));
+        check!(member == Ok(true));
+        check!(not_member == Ok(false));
+        check!(not_shared == Ok(false));
+    }
+
+    #[tokio::test]
+    async fn hand_over_household_expenses_all_cases() {
+        let household_expense = expense::Model {
+            household_id: Some(TEST_ID),
+            ..test_expense()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // without households
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            // member is deleted, the owner gets the expenses
+            .append_query_results(vec![vec![test_member()]])
+            .append_query_results(vec![vec![household_expense.clone()]])
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            // owner is deleted, the other member becomes the owner
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![household_expense]])
+            .append_query_results(vec![vec![test_member()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            // last member is deleted, the household is deleted too
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (without_households, member, owner, last_member, db_error) = tokio::join!(
+            hand_over_household_expenses(&conn, TEST_ID),
+            hand_over_household_expenses(&conn, TEST_ID + 1),
+            hand_over_household_expenses(&conn, TEST_ID),
+            hand_over_household_expenses(&conn, TEST_ID),
+            hand_over_household_expenses(&conn, TEST_ID),
+        );
+
+        check!(without_households == Ok(()));
+        check!(member == Ok(()));
+        check!(owner == Ok(()));
+        check!(last_member == Ok(()));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn participants_of_a_deleted_household_are_replaced_by_own_participants() {
+        let household_expense = expense::Model {
+            household_id: Some(TEST_ID),
+            ..test_expense()
+        };
+        let household_participant = participant::Model {
+            user_id: None,
+            household_id: Some(TEST_ID),
+            ..test_participant()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![household_expense]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![vec![household_participant]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            .into_connection();
+
+        let deleted = delete_household(&conn, TEST_ID, TEST_ID).await;
+
+        check!(deleted == Ok(()));
+        // every payer has an owner before the participants of the household are deleted
+        let log = format!("{:?}", conn.into_transaction_log());
+        let replaced = log.find("UPDATE `transactions` SET `participant_id`");
+        let household_deleted = log.find("DELETE FROM `households`");
+        check!(replaced.is_some());
+        check!(household_deleted.is_some());
+        check!(replaced < household_deleted);
+        // the predefined expenses of the household become private ones of their owners
+        let made_private = log.find("UPDATE `predefined_expenses` SET `visibility`");
+        check!(made_private.is_some());
+        check!(made_private < household_deleted);
+    }
+
+    #[tokio::test]
+    async fn hand_over_replaces_the_own_participants_of_the_user() {
+        let household_expense = expense::Model {
+            household_id: Some(TEST_ID),
+            user_id: TEST_ID + 1,
+            ..test_expense()
+        };
+        let own_participant = participant::Model {
+            user_id: Some(TEST_ID + 1),
+            ..test_participant()
+        };
+        let share = expense_share::Model {
+            id: TEST_ID,
+            expense_id: TEST_ID,
+            participant_id: TEST_ID,
+            value: None,
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_member()]])
+            .append_query_results(vec![vec![household_expense]])
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![vec![share]])
+            .append_query_results(vec![vec![own_participant]])
+            .append_query_results(vec![Vec::<participant::Model>::new()])
+            .append_exec_results(vec![
+                exec_ok(),
+                exec_ok(),
+                MockExecResult {
+                    last_insert_id: TEST_ID + 1,
+                    rows_affected: 1,
+                },
+                exec_ok(),
+                exec_ok(),
+            ])
+            .into_connection();
+
+        let handed_over = hand_over_household_expenses(&conn, TEST_ID + 1).await;
+
+        check!(handed_over == Ok(()));
+        let log: Vec<String> = conn
+            .into_transaction_log()
+            .iter()
+            .map(|statement| format!("{statement:?}"))
+            .collect();
+        check!(log
+            .iter()
+            .any(|sql| sql.contains("INSERT INTO `participants`")));
+        check!(log
+            .iter()
+            .any(|sql| sql.contains("UPDATE `transactions` SET `participant_id`")));
+        check!(log
+            .iter()
+            .any(|sql| sql.contains("UPDATE `expense_shares` SET `participant_id`")));
+        check!(log
+            .iter()
+            .any(|sql| sql.contains("UPDATE `predefined_expenses` SET `user_id`")));
+    }
+}
@@ -1,6 +1,21 @@
+pub mod api_token_operations;
+pub mod attachment_operations;
+pub mod balance_operations;
+pub mod category_operations;
 pub mod common;
 pub mod currency_operations;
+pub mod expense_activity;
 pub mod expense_operations;
+pub mod expense_split;
+pub mod household_operations;
+pub mod oidc_operations;
+pub mod participant_operations;
+pub mod password_hashing;
+pub mod rate_limit_operations;
 pub mod recurrence_operations;
+pub mod security_event_operations;
+pub mod session_operations;
 pub mod transaction_operations;
+pub mod trash_operations;
+pub mod two_factor_operations;
 pub mod user_operations;
@@ -0,0 +1,245 @@
+use self::errors::OidcLoginError;
+
+use super::common::find_entity_by_id;
+use super::security_event_operations::record_security_event;
+use crate::dto::sessions::ClientInfo;
+use crate::oidc::OidcIdentity;
+
+use chrono::Local;
+use entity::account_activation::{self, Entity as AccountActivation};
+use entity::oidc_identity::{self, Entity as OidcIdentityEntity};
+use entity::security_event::SecurityEventType;
+use entity::user::{self, Entity as User, UserRole};
+use entity::Id;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+
+/// Users registered by a provider have no local password, this never matches any password.
+/// They can set one with a password reset.
+const NO_PASSWORD_HASH: &str = "";
+
+/// Finds the user signing in with the identity of a provider and returns the id of the user.
+/// An unknown identity is linked to the user with the same email, or a new user is registered for it.
+/// The email is verified by the provider, so these users need no activation.
+/// Accounts deactivated by an administrator cannot sign in this way either.
+/// Registrations and activations done this way are recorded into the audit log of the user.
+/// Signing in this way only replaces the password, the second factor of the user is still required afterwards.
+pub async fn login_with_oidc_identity(
+    conn: &DatabaseConnection,
+    client: &ClientInfo,
+    identity: OidcIdentity,
+) -> Result<Id, OidcLoginError> {
+    let linked_identity = OidcIdentityEntity::find()
+        .filter(oidc_identity::Column::Provider.eq(identity.provider.clone()))
+        .filter(oidc_identity::Column::Subject.eq(identity.subject.clone()))
+        .one(conn)
+        .await?;
+    if let Some(linked_identity) = linked_identity {
+        let Some(user) = find_entity_by_id::<user::Entity>(conn, linked_identity.user_id).await?
+        else {
+            return Err(DbErr::RecordNotFound("user of the identity".to_string()).into());
+        };
+        if !user.activated {
+            return Err(OidcLoginError::AccountDeactivated);
+        }
+        return Ok(user.id);
+    }
+
+    let existing_user = User::find()
+        .filter(user::Column::Email.eq(identity.email.clone()))
+        .one(conn)
+        .await?;
+    if let Some(user) = &existing_user {
+        // an account awaiting activation can be activated by the provider, a deactivated one cannot
+        if !user.activated
+            && AccountActivation::find()
+                .filter(account_activation::Column::UserId.eq(user.id))
+                .one(conn)
+                .await?
+                .is_none()
+        {
+            return Err(OidcLoginError::AccountDeactivated);
+        }
+    }
+
+    let client = client.clone();
+    let user_id = conn
+        .transaction::<_, Id, OidcLoginError>(|txn| {
+            Box::pin(async move {
+                let user_id = match existing_user {
+                    Some(user) => {
+                        if !user.activated {
+                            User::update_many()
+                                .col_expr(user::Column::Activated, Expr::value(true))
+                                .filter(user::Column::Id.eq(user.id))
+                                .exec(txn)
+                                .await?;
+                            AccountActivation::delete_many()
+                                .filter(account_activation::Column::UserId.eq(user.id))
+                                .exec(txn)
+                                .await?;
+                            record_security_event(
+                                txn,
+                                user.id,
+                                SecurityEventType::Activation,
+                                &client,
+                            )
+                            .await?;
+                        }
+                        user.id
+                    }
+                    None => {
+                        let user = user::ActiveModel {
+                            id: NotSet,
+                            email: Set(identity.email),
+                            username: Set(identity.username),
+                            pw_hash: Set(NO_PASSWORD_HASH.to_string()),
+                            activated: Set(true),
+                            role: Set(UserRole::User),
+                            preferred_currency_id: NotSet,
+                            locale: NotSet,
+                            timezone: NotSet,
+                            date_format: NotSet,
+                            first_day_of_week: NotSet,
+                        };
+                        let user = user.insert(txn).await?;
+                        record_security_event(
+                            txn,
+                            user.id,
+                            SecurityEventType::Registration,
+                            &client,
+                        )
+                        .await?;
+                        user.id
+                    }
+                };
+
+                let oidc_identity = oidc_identity::ActiveModel {
+                    id: NotSet,
+                    user_id: Set(user_id),
+                    provider: Set(identity.provider),
+                    subject: Set(identity.subject),
+                    created_at: Set(Local::now()),
+                };
+                oidc_identity.insert(txn).await?;
+                Ok(user_id)
+            })
+        })
+        .await?;
+    Ok(user_id)
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum OidcLoginError {
+        #[error("account is deactivated")]
+        AccountDeactivated,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<OidcLoginError>> for OidcLoginError {
+        fn from(e: TransactionError<OidcLoginError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{
+        exec_ok, test_account_activation, test_db_error, test_oidc_identity, test_user, TEST_EMAIL,
+        TEST_ID, TEST_STR,
+    };
+    use assert2::check;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn test_identity() -> OidcIdentity {
+        OidcIdentity {
+            provider: TEST_STR.to_string(),
+            subject: TEST_STR.to_string(),
+            email: TEST_EMAIL.to_string(),
+            username: TEST_STR.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn login_with_oidc_identity_all_cases() {
+        let oidc_identity = test_oidc_identity();
+        let inactive_user = user::Model {
+            activated: false,
+            ..test_user()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // identity already linked
+            .append_query_results(vec![vec![oidc_identity.clone()]])
+            .append_query_results(vec![vec![test_user()]])
+            // linked to a deactivated user
+            .append_query_results(vec![vec![oidc_identity.clone()]])
+            .append_query_results(vec![vec![inactive_user.clone()]])
+            // linked to the activated user with the same email
+            .append_query_results(vec![Vec::<oidc_identity::Model>::new()])
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![oidc_identity.clone()]])
+            // linked to the user awaiting activation, who gets activated
+            .append_query_results(vec![Vec::<oidc_identity::Model>::new()])
+            .append_query_results(vec![vec![inactive_user.clone()]])
+            .append_query_results(vec![vec![test_account_activation()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![oidc_identity.clone()]])
+            // same email belongs to a deactivated user
+            .append_query_results(vec![Vec::<oidc_identity::Model>::new()])
+            .append_query_results(vec![vec![inactive_user]])
+            .append_query_results(vec![Vec::<account_activation::Model>::new()])
+            // new user gets registered
+            .append_query_results(vec![Vec::<oidc_identity::Model>::new()])
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![oidc_identity]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let client = ClientInfo::default();
+
+        let (
+            linked,
+            linked_deactivated,
+            linked_by_email,
+            activated_by_email,
+            deactivated_by_email,
+            registered,
+            db_error,
+        ) = tokio::join!(
+            login_with_oidc_identity(&conn, &client, test_identity()),
+            login_with_oidc_identity(&conn, &client, test_identity()),
+            login_with_oidc_identity(&conn, &client, test_identity()),
+            login_with_oidc_identity(&conn, &client, test_identity()),
+            login_with_oidc_identity(&conn, &client, test_identity()),
+            login_with_oidc_identity(&conn, &client, test_identity()),
+            login_with_oidc_identity(&conn, &client, test_identity())
+        );
+
+        check!(linked == Ok(TEST_ID));
+        check!(linked_deactivated == Err(OidcLoginError::AccountDeactivated));
+        check!(linked_by_email == Ok(TEST_ID));
+        check!(activated_by_email == Ok(TEST_ID));
+        check!(deactivated_by_email == Err(OidcLoginError::AccountDeactivated));
+        check!(registered == Ok(TEST_ID));
+        check!(db_error == Err(OidcLoginError::DatabaseError(test_db_error())));
+    }
+}
@@ -0,0 +1,907 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use self::errors::{
+    CreateParticipantError, DeleteParticipantError, FindContributionsError, MergeParticipantsError,
+    UpdateParticipantError,
+};
+use super::common::{find_entity_by_id, parse_date};
+use super::household_operations::{
+    find_household_ids_by_user_id, find_shared_household_ids, is_household_member,
+};
+use super::user_operations::errors::AuthorizeUserError;
+use super::user_operations::find_date_format;
+use crate::dto::participants::{
+    ContributionQuery, ContributionResponse, NewParticipantRequest, ParticipantResponse,
+    UpdateParticipantRequest,
+};
+
+use entity::expense::{self, Entity as Expense};
+use entity::expense_share::{self, Entity as ExpenseShare};
+use entity::participant::{self, Entity as Participant};
+use entity::transaction::{self, Entity as Transaction};
+use entity::{currency, Id, MoneyAmount};
+
+use migration::DbErr;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+/// Lists the participants of the user together with the participants of their households.
+pub async fn find_participants(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<Vec<ParticipantResponse>, DbErr> {
+    let household_ids = find_household_ids_by_user_id(conn, user_id).await?;
+    let participants = Participant::find()
+        .filter(
+            Condition::any()
+                .add(participant::Column::UserId.eq(user_id))
+                .add(participant::Column::HouseholdId.is_in(household_ids)),
+        )
+        .order_by_asc(participant::Column::Name)
+        .all(conn)
+        .await?;
+    Ok(participants
+        .into_iter()
+        .map(|participant| participant.into())
+        .collect())
+}
+
+/// Creates a participant of the user, or of the household if it is given.
+/// The names of the participants of the same owner are unique.
+pub async fn create_participant(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    req: NewParticipantRequest,
+) -> Result<Id, CreateParticipantError> {
+    if let Some(household_id) = req.household_id {
+        if !is_household_member(conn, household_id, user_id).await? {
+            return Err(CreateParticipantError::InvalidHousehold);
+        }
+    }
+    if let Some(account_id) = req.account_id {
+        if !is_linkable_account(conn, user_id, req.household_id, account_id).await? {
+            return Err(CreateParticipantError::InvalidAccount);
+        }
+    }
+    let owner_id = match req.household_id {
+        Some(_) => None,
+        None => Some(user_id),
+    };
+    let name = req.name.trim().to_string();
+    if find_participant_by_name(conn, owner_id, req.household_id, &name)
+        .await?
+        .is_some()
+    {
+        return Err(CreateParticipantError::NameTaken);
+    }
+
+    let participant = participant::ActiveModel {
+        id: NotSet,
+        name: Set(name),
+        user_id: Set(owner_id),
+        household_id: Set(req.household_id),
+        account_id: Set(req.account_id),
+    };
+    let participant = participant.insert(conn).await?;
+    Ok(participant.id)
+}
+
+pub async fn update_participant(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    participant_id: Id,
+    req: UpdateParticipantRequest,
+) -> Result<(), UpdateParticipantError> {
+    let Some(participant) = find_entity_by_id::<participant::Entity>(conn, participant_id).await? else {
+        return Err(UpdateParticipantError::ParticipantNotFound);
+    };
+    if !can_manage_participant(conn, user_id, &participant).await? {
+        return Err(UpdateParticipantError::UserUnauthorized(AuthorizeUserError));
+    }
+    if let Some(account_id) = req.account_id {
+        if !is_linkable_account(conn, user_id, participant.household_id, account_id).await? {
+            return Err(UpdateParticipantError::InvalidAccount);
+        }
+    }
+    let name = req.name.trim().to_string();
+    let same_name =
+        find_participant_by_name(conn, participant.user_id, participant.household_id, &name)
+            .await?;
+    if same_name.is_some_and(|same_name| same_name.id != participant.id) {
+        return Err(UpdateParticipantError::NameTaken);
+    }
+
+    let mut participant = participant.into_active_model();
+    participant.name = Set(name);
+    participant.account_id = Set(req.account_id);
+    participant.update(conn).await?;
+    Ok(())
+}
+
+/// Deletes a participant, which is only possible while it has no transactions and no shares of expenses.
+pub async fn delete_participant(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    participant_id: Id,
+) -> Result<(), DeleteParticipantError> {
+    let Some(participant) = find_entity_by_id::<participant::Entity>(conn, participant_id).await? else {
+        return Err(DeleteParticipantError::ParticipantNotFound);
+    };
+    if !can_manage_participant(conn, user_id, &participant).await? {
+        return Err(DeleteParticipantError::UserUnauthorized(AuthorizeUserError));
+    }
+    let (transaction, share) = tokio::join!(
+        Transaction::find()
+            .filter(transaction::Column::ParticipantId.eq(participant_id))
+            .one(conn),
+        ExpenseShare::find()
+            .filter(expense_share::Column::ParticipantId.eq(participant_id))
+            .one(conn)
+    );
+    if transaction?.is_some() || share?.is_some() {
+        return Err(DeleteParticipantError::ParticipantInUse);
+    }
+    Participant::delete_by_id(participant_id).exec(conn).await?;
+    Ok(())
+}
+
+/// Moves the transactions and shares of a participant to another participant of the same owner,
+/// then deletes it. This is how the different spellings of the same payer are merged.
+pub async fn merge_participants(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    participant_id: Id,
+    target_id: Id,
+) -> Result<(), MergeParticipantsError> {
+    if participant_id == target_id {
+        return Err(MergeParticipantsError::SameParticipant);
+    }
+    let (participant, target) = tokio::join!(
+        find_entity_by_id::<participant::Entity>(conn, participant_id),
+        find_entity_by_id::<participant::Entity>(conn, target_id)
+    );
+    let (Some(participant), Some(target)) = (participant?, target?) else {
+        return Err(MergeParticipantsError::ParticipantNotFound);
+    };
+    if !can_manage_participant(conn, user_id, &participant).await? {
+        return Err(MergeParticipantsError::UserUnauthorized(AuthorizeUserError));
+    }
+    if (participant.user_id, participant.household_id) != (target.user_id, target.household_id) {
+        return Err(MergeParticipantsError::DifferentOwners);
+    }
+    let shares = ExpenseShare::find()
+        .filter(expense_share::Column::ParticipantId.is_in([participant_id, target_id]))
+        .all(conn)
+        .await?;
+    let mut split_expense_ids = HashSet::new();
+    if !shares
+        .iter()
+        .all(|share| split_expense_ids.insert(share.expense_id))
+    {
+        return Err(MergeParticipantsError::ConflictingShares);
+    }
+
+    conn.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            Transaction::update_many()
+                .col_expr(transaction::Column::ParticipantId, Expr::value(target_id))
+                .filter(transaction::Column::ParticipantId.eq(participant_id))
+                .exec(txn)
+                .await?;
+            ExpenseShare::update_many()
+                .col_expr(expense_share::Column::ParticipantId, Expr::value(target_id))
+                .filter(expense_share::Column::ParticipantId.eq(participant_id))
+                .exec(txn)
+                .await?;
+            Participant::delete_by_id(participant_id).exec(txn).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Sums the transactions paid by each participant per currency, largest contribution first.
+/// The transactions and expenses in the trash are left out.
+pub async fn find_contributions(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    query: ContributionQuery,
+) -> Result<Vec<ContributionResponse>, FindContributionsError> {
+    if let Some(household_id) = query.household_id {
+        if !is_household_member(conn, household_id, user_id).await? {
+            return Err(FindContributionsError::UserUnauthorized(AuthorizeUserError));
+        }
+    }
+    let date_format = find_date_format(conn, user_id).await?;
+    let from = match query.from {
+        Some(from) => Some(parse_date(&from, date_format)?),
+        None => None,
+    };
+    let to = match query.to {
+        Some(to) => Some(parse_date(&to, date_format)?),
+        None => None,
+    };
+    if from.is_some_and(|from| to.is_some_and(|to| to < from)) {
+        return Err(FindContributionsError::InvalidPeriod);
+    }
+
+    let scope = match query.household_id {
+        Some(household_id) => expense::Column::HouseholdId.eq(household_id),
+        None => expense::Column::UserId.eq(user_id),
+    };
+    let mut select = Transaction::find()
+        .inner_join(Expense)
+        .filter(scope)
+        .filter(expense::Column::DeletedAt.is_null())
+        .filter(transaction::Column::DeletedAt.is_null());
+    if let Some(from) = from {
+        select = select.filter(transaction::Column::Date.gte(from));
+    }
+    if let Some(to) = to {
+        select = select.filter(transaction::Column::Date.lte(to));
+    }
+    let transactions = select.all(conn).await?;
+    if transactions.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // the totals are keyed by (currency id, participant id)
+    let mut totals: BTreeMap<(Id, Id), (MoneyAmount, u64)> = BTreeMap::new();
+    let mut currency_totals: BTreeMap<Id, MoneyAmount> = BTreeMap::new();
+    for transaction in &transactions {
+        let total = totals
+            .entry((transaction.currency_id, transaction.participant_id))
+            .or_default();
+        total.0 += transaction.value;
+        total.1 += 1;
+        *currency_totals.entry(transaction.currency_id).or_default() += transaction.value;
+    }
+    let participant_ids: HashSet<Id> = totals.keys().map(|(_, id)| *id).collect();
+    let (participants, currencies) = tokio::join!(
+        find_participants_by_ids(conn, participant_ids),
+        currency::Entity::find()
+            .filter(currency::Column::Id.is_in(currency_totals.keys().copied()))
+            .all(conn)
+    );
+    let participants = participants?;
+    let currencies = currencies?;
+
+    let mut contributions: Vec<(Id, ContributionResponse)> = totals
+        .into_iter()
+        .filter_map(|((currency_id, participant_id), (total, count))| {
+            let participant = participants.iter().find(|p| p.id == participant_id)?;
+            let currency = currencies.iter().find(|c| c.id == currency_id)?;
+            let currency_total = currency_totals[&currency_id];
+            let percentage = if currency_total.is_zero() {
+                MoneyAmount::ZERO
+            } else {
+                (total * MoneyAmount::ONE_HUNDRED / currency_total).round_dp(2)
+            };
+            let parts = (
+                participant.clone(),
+                currency.clone(),
+                total,
+                count,
+                percentage,
+            );
+            Some((currency_id, parts.into()))
+        })
+        .collect();
+    contributions.sort_by(|(a_currency, a), (b_currency, b)| {
+        a_currency
+            .cmp(b_currency)
+            .then_with(|| b.total.cmp(&a.total))
+            .then_with(|| a.participant.name.cmp(&b.participant.name))
+    });
+    Ok(contributions
+        .into_iter()
+        .map(|(_, contribution)| contribution)
+        .collect())
+}
+
+/// The user can manage their own participants and the participants of their households.
+pub(super) async fn can_manage_participant<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+    participant: &participant::Model,
+) -> Result<bool, DbErr> {
+    if participant.user_id == Some(user_id) {
+        return Ok(true);
+    }
+    match participant.household_id {
+        Some(household_id) => is_household_member(conn, household_id, user_id).await,
+        None => Ok(false),
+    }
+}
+
+/// An expense can be paid by the participants of its owner, or of its household if it is shared.
+pub(super) fn can_pay_for(participant: &participant::Model, expense: &expense::Model) -> bool {
+    participant.user_id == Some(expense.user_id)
+        || participant
+            .household_id
+            .is_some_and(|household_id| expense.household_id == Some(household_id))
+}
+
+/// Tells whether every payer of the transactions and the split of an expense can pay for it.
+/// It is checked with the changed expense before the expense is moved to another household.
+pub(super) async fn can_payers_pay_for<C: ConnectionTrait>(
+    conn: &C,
+    expense: &expense::Model,
+) -> Result<bool, DbErr> {
+    let (transactions, shares) = find_payments(conn, vec![expense.id]).await?;
+    let payer_ids: HashSet<Id> = transactions
+        .iter()
+        .map(|transaction| transaction.participant_id)
+        .chain(shares.iter().map(|share| share.participant_id))
+        .collect();
+    let payers = find_participants_by_ids(conn, payer_ids).await?;
+    Ok(payers.iter().all(|payer| can_pay_for(payer, expense)))
+}
+
+/// Replaces the payers who cannot pay for the given expenses any more, after the expenses got a new owner or left their household.
+/// Each of them is replaced by the participant of the same name of the new owner of the expense, which is created if it does not exist yet.
+/// The created participants are not linked to accounts. A share of a replaced payer is dropped if the replacement already has one.
+pub(super) async fn adopt_payers<C: ConnectionTrait>(
+    txn: &C,
+    expenses: &[expense::Model],
+) -> Result<(), DbErr> {
+    if expenses.is_empty() {
+        return Ok(());
+    }
+    let (transactions, shares) =
+        find_payments(txn, expenses.iter().map(|expense| expense.id).collect()).await?;
+    let payer_ids: HashSet<Id> = transactions
+        .iter()
+        .map(|transaction| transaction.participant_id)
+        .chain(shares.iter().map(|share| share.participant_id))
+        .collect();
+    let payers = find_participants_by_ids(txn, payer_ids).await?;
+    let mut shareholders: HashSet<(Id, Id)> = shares
+        .iter()
+        .map(|share| (share.expense_id, share.participant_id))
+        .collect();
+
+    // the replacements are keyed by (owner user id, owner household id, name)
+    let mut replacements: HashMap<(Option<Id>, Option<Id>, String), Id> = HashMap::new();
+    for expense in expenses {
+        for payer in payers.iter().filter(|payer| !can_pay_for(payer, expense)) {
+            let has_transactions = transactions.iter().any(|transaction| {
+                transaction.expense_id == expense.id && transaction.participant_id == payer.id
+            });
+            let has_share = shareholders.contains(&(expense.id, payer.id));
+            if !has_transactions && !has_share {
+                continue;
+            }
+            let (user_id, household_id) = match expense.household_id {
+                Some(household_id) => (None, Some(household_id)),
+                None => (Some(expense.user_id), None),
+            };
+            let key = (user_id, household_id, payer.name.clone());
+            let replacement_id = match replacements.get(&key) {
+                Some(replacement_id) => *replacement_id,
+                None => {
+                    let replacement_id =
+                        find_or_create_participant(txn, user_id, household_id, &payer.name).await?;
+                    replacements.insert(key, replacement_id);
+                    replacement_id
+                }
+            };
+
+            if has_transactions {
+                Transaction::update_many()
+                    .col_expr(
+                        transaction::Column::ParticipantId,
+                        Expr::value(replacement_id),
+                    )
+                    .filter(transaction::Column::ExpenseId.eq(expense.id))
+                    .filter(transaction::Column::ParticipantId.eq(payer.id))
+                    .exec(txn)
+                    .await?;
+            }
+            if !has_share {
+                continue;
+            }
+            shareholders.remove(&(expense.id, payer.id));
+            if shareholders.insert((expense.id, replacement_id)) {
+                ExpenseShare::update_many()
+                    .col_expr(
+                        expense_share::Column::ParticipantId,
+                        Expr::value(replacement_id),
+                    )
+                    .filter(expense_share::Column::ExpenseId.eq(expense.id))
+                    .filter(expense_share::Column::ParticipantId.eq(payer.id))
+                    .exec(txn)
+                    .await?;
+            } else {
+                ExpenseShare::delete_many()
+                    .filter(expense_share::Column::ExpenseId.eq(expense.id))
+                    .filter(expense_share::Column::ParticipantId.eq(payer.id))
+                    .exec(txn)
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds the participants by their ids, an id without a participant is left out.
+pub(super) async fn find_participants_by_ids<C: ConnectionTrait>(
+    conn: &C,
+    participant_ids: HashSet<Id>,
+) -> Result<Vec<participant::Model>, DbErr> {
+    if participant_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    Participant::find()
+        .filter(participant::Column::Id.is_in(participant_ids))
+        .all(conn)
+        .await
+}
+
+/// A participant of a household can be linked to the account of a member,
+/// an own participant to the user themselves or to someone sharing a household with them.
+async fn is_linkable_account(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    household_id: Option<Id>,
+    account_id: Id,
+) -> Result<bool, DbErr> {
+    match household_id {
+        Some(household_id) => is_household_member(conn, household_id, account_id).await,
+        None if account_id == user_id => Ok(true),
+        None => Ok(!find_shared_household_ids(conn, user_id, account_id)
+            .await?
+            .is_empty()),
+    }
+}
+
+/// The names are compared by the collation of the database, so the names only differing in case are the same.
+/// Finds the transactions and the shares of the expenses, including the ones in the trash.
+async fn find_payments<C: ConnectionTrait>(
+    conn: &C,
+    expense_ids: Vec<Id>,
+) -> Result<(Vec<transaction::Model>, Vec<expense_share::Model>), DbErr> {
+    let transactions = Transaction::find()
+        .filter(transaction::Column::ExpenseId.is_in(expense_ids.clone()))
+        .all(conn)
+        .await?;
+    let shares = ExpenseShare::find()
+        .filter(expense_share::Column::ExpenseId.is_in(expense_ids))
+        .all(conn)
+        .await?;
+    Ok((transactions, shares))
+}
+
+async fn find_or_create_participant<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Option<Id>,
+    household_id: Option<Id>,
+    name: &str,
+) -> Result<Id, DbErr> {
+    if let Some(participant) = find_participant_by_name(conn, user_id, household_id, name).await? {
+        return Ok(participant.id);
+    }
+    let participant = participant::ActiveModel {
+        id: NotSet,
+        name: Set(name.to_string()),
+        user_id: Set(user_id),
+        household_id: Set(household_id),
+        account_id: Set(None),
+    };
+    Ok(Participant::insert(participant)
+        .exec(conn)
+        .await?
+        .last_insert_id)
+}
+
+async fn find_participant_by_name<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Option<Id>,
+    household_id: Option<Id>,
+    name: &str,
+) -> Result<Option<participant::Model>, DbErr> {
+    let owner = match household_id {
+        Some(household_id) => participant::Column::HouseholdId.eq(household_id),
+        None => participant::Column::UserId.eq(user_id),
+    };
+    Participant::find()
+        .filter(owner)
+        .filter(participant::Column::Name.eq(name))
+        .one(conn)
+        .await
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    use crate::logic::user_operations::errors::AuthorizeUserError;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum CreateParticipantError {
+        #[error("household not found")]
+        InvalidHousehold,
+        #[error("account can not be linked to the participant")]
+        InvalidAccount,
+        #[error("a participant with this name already exists")]
+        NameTaken,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UpdateParticipantError {
+        #[error("participant not found")]
+        ParticipantNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("account can not be linked to the participant")]
+        InvalidAccount,
+        #[error("a participant with this name already exists")]
+        NameTaken,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteParticipantError {
+        #[error("participant not found")]
+        ParticipantNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("participant still has transactions or shares of expenses")]
+        ParticipantInUse,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum MergeParticipantsError {
+        #[error("participant not found")]
+        ParticipantNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("a participant can not be merged into itself")]
+        SameParticipant,
+        #[error("only participants of the same owner can be merged")]
+        DifferentOwners,
+        #[error("both participants have a share of the same expense")]
+        ConflictingShares,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+    impl From<TransactionError<DbErr>> for MergeParticipantsError {
+        fn from(e: TransactionError<DbErr>) -> Self {
+            match e {
+                TransactionError::Connection(e) | TransactionError::Transaction(e) => e.into(),
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum FindContributionsError {
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("date could not be parsed")]
+        InvalidDate(#[from] chrono::ParseError),
+        #[error("the end of the period must not be before its start")]
+        InvalidPeriod,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{
+        exec_ok, test_currency, test_db_error, test_household_member, test_participant,
+        test_transaction, test_user, TEST_ID, TEST_STR,
+    };
+    use assert2::check;
+    use entity::household_member;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn household_participant() -> participant::Model {
+        participant::Model {
+            id: TEST_ID + 1,
+            user_id: None,
+            household_id: Some(TEST_ID),
+            ..test_participant()
+        }
+    }
+
+    fn new_request(household_id: Option<Id>, account_id: Option<Id>) -> NewParticipantRequest {
+        NewParticipantRequest {
+            name: format!(" {TEST_STR} "),
+            household_id,
+            account_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn find_participants_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_participant(), household_participant()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (participants, db_error) = tokio::join!(
+            find_participants(&conn, TEST_ID),
+            find_participants(&conn, TEST_ID)
+        );
+
+        check!(
+            participants
+                == Ok(vec![
+                    test_participant().into(),
+                    household_participant().into()
+                ])
+        );
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn create_participant_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // own participant
+            .append_query_results(vec![Vec::<participant::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_participant()]])
+            // participant of the household linked to a member
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![Vec::<participant::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![household_participant()]])
+            // not a member of the household
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // account of a stranger
+            .append_query_results(vec![vec![test_household_member()]])
+            // name taken
+            .append_query_results(vec![vec![test_participant()]])
+            .into_connection();
+
+        let (own, shared, invalid_household, invalid_account, name_taken) = tokio::join!(
+            create_participant(&conn, TEST_ID, new_request(None, None)),
+            create_participant(&conn, TEST_ID, new_request(Some(TEST_ID), Some(TEST_ID))),
+            create_participant(&conn, TEST_ID, new_request(Some(TEST_ID), None)),
+            create_participant(&conn, TEST_ID, new_request(None, Some(TEST_ID + 1))),
+            create_participant(&conn, TEST_ID, new_request(None, Some(TEST_ID)))
+        );
+
+        check!(own == Ok(TEST_ID));
+        check!(shared == Ok(TEST_ID + 1));
+        check!(invalid_household == Err(CreateParticipantError::InvalidHousehold));
+        check!(invalid_account == Err(CreateParticipantError::InvalidAccount));
+        check!(name_taken == Err(CreateParticipantError::NameTaken));
+    }
+
+    #[tokio::test]
+    async fn update_participant_all_cases() {
+        let renamed = participant::Model {
+            name: "renamed".to_string(),
+            ..test_participant()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case, keeping its own name
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![renamed]])
+            // name taken by another participant
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![participant::Model {
+                id: TEST_ID + 1,
+                ..test_participant()
+            }]])
+            // participant of a household the user is not a member of
+            .append_query_results(vec![vec![household_participant()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            // not found
+            .append_query_results(vec![Vec::<participant::Model>::new()])
+            .into_connection();
+        let req = UpdateParticipantRequest {
+            name: TEST_STR.to_string(),
+            account_id: None,
+        };
+
+        let (happy_case, name_taken, unauthorized, not_found) = tokio::join!(
+            update_participant(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_participant(&conn, TEST_ID, TEST_ID, req.clone()),
+            update_participant(&conn, TEST_ID, TEST_ID + 1, req.clone()),
+            update_participant(&conn, TEST_ID, TEST_ID, req)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(name_taken == Err(UpdateParticipantError::NameTaken));
+        check!(unauthorized == Err(UpdateParticipantError::UserUnauthorized(AuthorizeUserError)));
+        check!(not_found == Err(UpdateParticipantError::ParticipantNotFound));
+    }
+
+    #[tokio::test]
+    async fn delete_participant_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            // still has transactions
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            // participant of another user
+            .append_query_results(vec![vec![participant::Model {
+                user_id: Some(TEST_ID + 1),
+                ..test_participant()
+            }]])
+            // not found
+            .append_query_results(vec![Vec::<participant::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, in_use, unauthorized, not_found, db_error) = tokio::join!(
+            delete_participant(&conn, TEST_ID, TEST_ID),
+            delete_participant(&conn, TEST_ID, TEST_ID),
+            delete_participant(&conn, TEST_ID, TEST_ID),
+            delete_participant(&conn, TEST_ID, TEST_ID),
+            delete_participant(&conn, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(in_use == Err(DeleteParticipantError::ParticipantInUse));
+        check!(unauthorized == Err(DeleteParticipantError::UserUnauthorized(AuthorizeUserError)));
+        check!(not_found == Err(DeleteParticipantError::ParticipantNotFound));
+        check!(db_error == Err(DeleteParticipantError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn merge_participants_all_cases() {
+        let target = participant::Model {
+            id: TEST_ID + 1,
+            ..test_participant()
+        };
+        let share = |id: Id, participant_id: Id| expense_share::Model {
+            id,
+            expense_id: TEST_ID,
+            participant_id,
+            value: None,
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![target.clone()]])
+            .append_query_results(vec![vec![share(TEST_ID, TEST_ID)]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // both have a share of the same expense
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![target.clone()]])
+            .append_query_results(vec![vec![
+                share(TEST_ID, TEST_ID),
+                share(TEST_ID + 1, TEST_ID + 1),
+            ]])
+            // participants of different owners
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![household_participant()]])
+            // participant of another user
+            .append_query_results(vec![vec![participant::Model {
+                user_id: Some(TEST_ID + 1),
+                ..test_participant()
+            }]])
+            .append_query_results(vec![vec![target]])
+            // target not found
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![Vec::<participant::Model>::new()])
+            .into_connection();
+
+        let (happy_case, conflicting, different_owners, unauthorized, not_found, same) = tokio::join!(
+            merge_participants(&conn, TEST_ID, TEST_ID, TEST_ID + 1),
+            merge_participants(&conn, TEST_ID, TEST_ID, TEST_ID + 1),
+            merge_participants(&conn, TEST_ID, TEST_ID, TEST_ID + 1),
+            merge_participants(&conn, TEST_ID, TEST_ID, TEST_ID + 1),
+            merge_participants(&conn, TEST_ID, TEST_ID, TEST_ID + 1),
+            merge_participants(&conn, TEST_ID, TEST_ID, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(conflicting == Err(MergeParticipantsError::ConflictingShares));
+        check!(different_owners == Err(MergeParticipantsError::DifferentOwners));
+        check!(unauthorized == Err(MergeParticipantsError::UserUnauthorized(AuthorizeUserError)));
+        check!(not_found == Err(MergeParticipantsError::ParticipantNotFound));
+        check!(same == Err(MergeParticipantsError::SameParticipant));
+    }
+
+    #[tokio::test]
+    async fn find_contributions_all_cases() {
+        let (anna, bob) = (
+            participant::Model {
+                name: "Anna".to_string(),
+                ..test_participant()
+            },
+            participant::Model {
+                id: TEST_ID + 1,
+                name: "Bob".to_string(),
+                ..test_participant()
+            },
+        );
+        let payment = |id: Id, participant_id: Id, value: i64| transaction::Model {
+            id,
+            participant_id,
+            value: MoneyAmount::from(value),
+            ..test_transaction()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // contributions
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![
+                payment(1, bob.id, 100),
+                payment(2, anna.id, 200),
+                payment(3, anna.id, 100),
+            ]])
+            .append_query_results(vec![vec![anna.clone(), bob.clone()]])
+            .append_query_results(vec![vec![test_currency()]])
+            // no transactions in the period
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            // invalid period
+            .append_query_results(vec![vec![test_user()]])
+            // not a member of the household
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .into_connection();
+        let period = |from: &str, to: &str| ContributionQuery {
+            from: Some(from.to_string()),
+            to: Some(to.to_string()),
+            household_id: None,
+        };
+
+        let (contributions, empty, invalid_period, unauthorized) = tokio::join!(
+            find_contributions(&conn, TEST_ID, ContributionQuery::default()),
+            find_contributions(&conn, TEST_ID, period("2023-01-01", "2023-12-31")),
+            find_contributions(&conn, TEST_ID, period("2023-12-31", "2023-01-01")),
+            find_contributions(
+                &conn,
+                TEST_ID,
+                ContributionQuery {
+                    household_id: Some(TEST_ID),
+                    ..Default::default()
+                }
+            )
+        );
+
+        check!(
+            contributions
+                == Ok(vec![
+                    (
+                        anna,
+                        test_currency(),
+                        MoneyAmount::from(300),
+                        2,
+                        MoneyAmount::from(75)
+                    )
+                        .into(),
+                    (
+                        bob,
+                        test_currency(),
+                        MoneyAmount::from(100),
+                        1,
+                        MoneyAmount::from(25)
+                    )
+                        .into(),
+                ])
+        );
+        check!(empty == Ok(vec![]));
+        check!(invalid_period == Err(FindContributionsError::InvalidPeriod));
+        check!(unauthorized == Err(FindContributionsError::UserUnauthorized(AuthorizeUserError)));
+    }
+}
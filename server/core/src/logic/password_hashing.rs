@@ -0,0 +1,106 @@
+use crate::config::PasswordHashConfig;
+
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version, ARGON2ID_IDENT};
+use pwhash::bcrypt;
+use sea_orm::prelude::Uuid;
+
+/// Every Argon2 hash in PHC string format starts with this, anything else is a legacy bcrypt hash.
+const ARGON2_HASH_PREFIX: &str = "$argon2";
+
+/// Hashes the password with Argon2id, using the configured cost parameters.
+pub fn hash_password(
+    config: &PasswordHashConfig,
+    password: &str,
+) -> Result<String, password_hash::Error> {
+    let params = Params::new(
+        config.argon2_memory_kib,
+        config.argon2_iterations,
+        config.argon2_parallelism,
+        None,
+    )?;
+    let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes())?;
+    let hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(password.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
+/// Checks the password against either an Argon2 or a legacy bcrypt hash.
+/// Argon2 hashes are verified with the parameters stored in them, not the configured ones.
+pub fn verify_password(password: &str, pw_hash: &str) -> bool {
+    if !pw_hash.starts_with(ARGON2_HASH_PREFIX) {
+        return bcrypt::verify(password, pw_hash);
+    }
+    match PasswordHash::new(pw_hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Tells whether the hash should be replaced on the next successful login,
+/// because it is not an Argon2id hash or it is weaker than the configured parameters.
+pub fn needs_rehash(config: &PasswordHashConfig, pw_hash: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(pw_hash) else {
+        return true;
+    };
+    if hash.algorithm != ARGON2ID_IDENT || hash.version != Some(Version::V0x13.into()) {
+        return true;
+    }
+    let Ok(params) = Params::try_from(&hash) else {
+        return true;
+    };
+    params.m_cost() < config.argon2_memory_kib
+        || params.t_cost() < config.argon2_iterations
+        || params.p_cost() < config.argon2_parallelism
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{test_app_config, TEST_STR};
+    use assert2::check;
+
+    fn weaker_config() -> PasswordHashConfig {
+        PasswordHashConfig {
+            argon2_memory_kib: 32,
+            ..test_app_config().password_hash_config
+        }
+    }
+
+    #[test]
+    fn hash_password_creates_argon2id_hashes() {
+        let config = test_app_config().password_hash_config;
+
+        let hash = hash_password(&config, TEST_STR).unwrap();
+
+        check!(hash.starts_with("$argon2id$"));
+        check!(hash != hash_password(&config, TEST_STR).unwrap());
+        check!(verify_password(TEST_STR, &hash));
+        check!(!verify_password("wrong", &hash));
+    }
+
+    #[test]
+    fn verify_password_accepts_legacy_bcrypt_hashes() {
+        let hash = bcrypt::hash(TEST_STR).unwrap();
+
+        check!(verify_password(TEST_STR, &hash));
+        check!(!verify_password("wrong", &hash));
+        check!(!verify_password(TEST_STR, "$argon2id$malformed"));
+    }
+
+    #[test]
+    fn needs_rehash_all_cases() {
+        let config = test_app_config().password_hash_config;
+        let current_hash = hash_password(&config, TEST_STR).unwrap();
+        let weaker_hash = hash_password(&weaker_config(), TEST_STR).unwrap();
+        let bcrypt_hash = bcrypt::hash(TEST_STR).unwrap();
+
+        check!(!needs_rehash(&config, &current_hash));
+        check!(needs_rehash(&config, &weaker_hash));
+        check!(!needs_rehash(&weaker_config(), &current_hash));
+        check!(needs_rehash(&config, &bcrypt_hash));
+        check!(needs_rehash(&config, TEST_STR));
+    }
+}
@@ -0,0 +1,384 @@
+use crate::config::RateLimitConfig;
+
+use chrono::{DateTime, Duration, Local};
+use entity::rate_limit::{self, Entity as RateLimit};
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set};
+
+/// The window in which the registration emails sent to an ip address are counted.
+const REGISTRATION_WINDOW_HOURS: i64 = 1;
+
+/// Why a login attempt is refused before the credentials are even checked.
+/// Both variants hold the number of seconds after which a new attempt can be made.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoginBlock {
+    /// Too many failed logins in short succession, the wait grows exponentially with every failure.
+    Backoff(i64),
+    /// The maximum number of failed logins was reached.
+    Locked(i64),
+}
+
+/// Finds the longest block among the failure counters of the account and the ip address, if any of them is blocked.
+pub async fn find_login_block(
+    conn: &DatabaseConnection,
+    config: &RateLimitConfig,
+    email: &str,
+    ip_address: Option<&str>,
+) -> Result<Option<LoginBlock>, DbErr> {
+    let limits = login_limits(config, email, ip_address);
+    let keys: Vec<String> = limits.iter().map(|(key, _)| key.clone()).collect();
+    let rate_limits = RateLimit::find()
+        .filter(rate_limit::Column::Key.is_in(keys))
+        .all(conn)
+        .await?;
+
+    let now = Local::now();
+    let block = rate_limits
+        .into_iter()
+        .filter_map(|rate_limit| {
+            let blocked_until = rate_limit.blocked_until.filter(|until| *until > now)?;
+            let max_failures = max_failures_of(&limits, &rate_limit.key);
+            Some((
+                rate_limit.count >= max_failures,
+                seconds_until(now, blocked_until),
+            ))
+        })
+        .max_by_key(|(_, seconds)| *seconds)
+        .map(|(locked, seconds)| {
+            if locked {
+                LoginBlock::Locked(seconds)
+            } else {
+                LoginBlock::Backoff(seconds)
+            }
+        });
+    Ok(block)
+}
+
+/// Counts a failed login for both the account and the ip address, and blocks them for a while.
+/// The block grows exponentially with the number of failures, and turns into a lockout when the maximum is reached.
+pub async fn record_login_failure(
+    conn: &DatabaseConnection,
+    config: &RateLimitConfig,
+    email: &str,
+    ip_address: Option<&str>,
+) -> Result<(), DbErr> {
+    let limits = login_limits(config, email, ip_address);
+    let keys: Vec<String> = limits.iter().map(|(key, _)| key.clone()).collect();
+    let rate_limits = RateLimit::find()
+        .filter(rate_limit::Column::Key.is_in(keys))
+        .all(conn)
+        .await?;
+
+    let now = Local::now();
+    let window = Duration::minutes(config.login_failure_window_minutes);
+    let lockout = Duration::minutes(config.login_lockout_minutes);
+    for (key, max_failures) in limits {
+        let existing = rate_limits
+            .iter()
+            .find(|rate_limit| rate_limit.key == key)
+            .filter(|rate_limit| rate_limit.window_started_at + window > now);
+        let count = existing.map_or(0, |rate_limit| rate_limit.count) + 1;
+        let window_started_at = existing.map_or(now, |rate_limit| rate_limit.window_started_at);
+        let blocked_for = if count >= max_failures {
+            lockout
+        } else {
+            backoff(config.login_backoff_base_seconds, count, lockout)
+        };
+        save_rate_limit(
+            conn,
+            &rate_limits,
+            key,
+            count,
+            window_started_at,
+            Some(now + blocked_for),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Forgets the failed logins of the account after a successful login.
+/// The counter of the ip address is kept, so one known password cannot be used to keep guessing others.
+pub async fn reset_login_failures(conn: &DatabaseConnection, email: &str) -> Result<(), DbErr> {
+    RateLimit::delete_many()
+        .filter(rate_limit::Column::Key.eq(login_account_key(email)))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// Counts a registration email sent to a request from the ip address.
+/// Returns false without counting if the hourly limit of the ip address is already reached.
+pub async fn try_consume_registration_mail(
+    conn: &DatabaseConnection,
+    config: &RateLimitConfig,
+    ip_address: Option<&str>,
+) -> Result<bool, DbErr> {
+    let Some(ip_address) = ip_address else {
+        return Ok(true);
+    };
+    let key = registration_ip_key(ip_address);
+    let rate_limits = RateLimit::find()
+        .filter(rate_limit::Column::Key.eq(key.clone()))
+        .all(conn)
+        .await?;
+
+    let now = Local::now();
+    let existing = rate_limits.first().filter(|rate_limit| {
+        rate_limit.window_started_at + Duration::hours(REGISTRATION_WINDOW_HOURS) > now
+    });
+    let count = existing.map_or(0, |rate_limit| rate_limit.count);
+    if count >= config.registration_mails_per_ip_per_hour {
+        return Ok(false);
+    }
+    let window_started_at = existing.map_or(now, |rate_limit| rate_limit.window_started_at);
+    save_rate_limit(conn, &rate_limits, key, count + 1, window_started_at, None).await?;
+    Ok(true)
+}
+
+/// Updates the counter of the key if it is already stored, otherwise inserts it.
+async fn save_rate_limit(
+    conn: &DatabaseConnection,
+    stored: &[rate_limit::Model],
+    key: String,
+    count: i32,
+    window_started_at: DateTime<Local>,
+    blocked_until: Option<DateTime<Local>>,
+) -> Result<(), DbErr> {
+    match stored.iter().find(|rate_limit| rate_limit.key == key) {
+        Some(rate_limit) => {
+            RateLimit::update_many()
+                .col_expr(rate_limit::Column::Count, Expr::value(count))
+                .col_expr(
+                    rate_limit::Column::WindowStartedAt,
+                    Expr::value(window_started_at),
+                )
+                .col_expr(rate_limit::Column::BlockedUntil, Expr::value(blocked_until))
+                .filter(rate_limit::Column::Id.eq(rate_limit.id))
+                .exec(conn)
+                .await?;
+        }
+        None => {
+            RateLimit::insert(rate_limit::ActiveModel {
+                id: NotSet,
+                key: Set(key),
+                count: Set(count),
+                window_started_at: Set(window_started_at),
+                blocked_until: Set(blocked_until),
+            })
+            .exec(conn)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The counter keys of a login attempt together with the number of failures allowed for them.
+fn login_limits(
+    config: &RateLimitConfig,
+    email: &str,
+    ip_address: Option<&str>,
+) -> Vec<(String, i32)> {
+    let mut limits = vec![(
+        login_account_key(email),
+        config.login_max_failed_attempts_per_account,
+    )];
+    if let Some(ip_address) = ip_address {
+        limits.push((
+            format!("login:ip:{ip_address}"),
+            config.login_max_failed_attempts_per_ip,
+        ));
+    }
+    limits
+}
+
+fn max_failures_of(limits: &[(String, i32)], key: &str) -> i32 {
+    limits
+        .iter()
+        .find(|(limit_key, _)| limit_key == key)
+        .map_or(i32::MAX, |(_, max_failures)| *max_failures)
+}
+
+fn login_account_key(email: &str) -> String {
+    format!("login:account:{}", email.to_lowercase())
+}
+
+fn registration_ip_key(ip_address: &str) -> String {
+    format!("registration:ip:{ip_address}")
+}
+
+/// The wait doubles with every failure, but never exceeds the lockout.
+/// It is capped before the conversion, as chrono panics on durations out of its range.
+fn backoff(base_seconds: i64, failures: i32, lockout: Duration) -> Duration {
+    let exponent = (failures - 1).clamp(0, 30) as u32;
+    let seconds = base_seconds.saturating_mul(2_i64.pow(exponent));
+    Duration::seconds(seconds.min(lockout.num_seconds()))
+}
+
+fn seconds_until(now: DateTime<Local>, until: DateTime<Local>) -> i64 {
+    (until - now).num_seconds().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use chrono::{Duration, Local};
+    use entity::rate_limit;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use super::{
+        backoff, find_login_block, record_login_failure, reset_login_failures,
+        try_consume_registration_mail, LoginBlock,
+    };
+    use crate::logic::common::tests::{
+        exec_ok, test_app_config, test_db_error, TEST_EMAIL, TEST_ID, TEST_STR,
+    };
+
+    fn test_rate_limit(key: String, count: i32, blocked_minutes: i64) -> rate_limit::Model {
+        let now = Local::now();
+        rate_limit::Model {
+            id: TEST_ID,
+            key,
+            count,
+            window_started_at: now,
+            blocked_until: Some(now + Duration::minutes(blocked_minutes)),
+        }
+    }
+
+    fn account_key() -> String {
+        format!("login:account:{TEST_EMAIL}")
+    }
+
+    #[tokio::test]
+    async fn find_login_block_all_cases() {
+        let config = &test_app_config().rate_limit_config;
+        let ip_key = format!("login:ip:{TEST_STR}");
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // no failures
+            .append_query_results(vec![Vec::<rate_limit::Model>::new()])
+            // block expired
+            .append_query_results(vec![vec![test_rate_limit(account_key(), 2, -1)]])
+            // backoff
+            .append_query_results(vec![vec![test_rate_limit(account_key(), 2, 1)]])
+            // account locked, the longer block wins
+            .append_query_results(vec![vec![
+                test_rate_limit(ip_key.clone(), 3, 1),
+                test_rate_limit(account_key(), 5, 15),
+            ]])
+            // ip locked
+            .append_query_results(vec![vec![test_rate_limit(ip_key, 20, 15)]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (no_failures, expired, backoff, account_locked, ip_locked, db_error) = tokio::join!(
+            find_login_block(&conn, config, TEST_EMAIL, Some(TEST_STR)),
+            find_login_block(&conn, config, TEST_EMAIL, Some(TEST_STR)),
+            find_login_block(&conn, config, TEST_EMAIL, Some(TEST_STR)),
+            find_login_block(&conn, config, TEST_EMAIL, Some(TEST_STR)),
+            find_login_block(&conn, config, TEST_EMAIL, Some(TEST_STR)),
+            find_login_block(&conn, config, TEST_EMAIL, Some(TEST_STR)),
+        );
+
+        check!(no_failures == Ok(None));
+        check!(expired == Ok(None));
+        check!(let Ok(Some(LoginBlock::Backoff(_))) = backoff);
+        let_assert!(Ok(Some(LoginBlock::Locked(seconds))) = account_locked);
+        check!(seconds > 60);
+        check!(let Ok(Some(LoginBlock::Locked(_))) = ip_locked);
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn record_login_failure_all_cases() {
+        let config = &test_app_config().rate_limit_config;
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // first failures are inserted
+            .append_query_results(vec![Vec::<rate_limit::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // further failure is updated
+            .append_query_results(vec![vec![test_rate_limit(account_key(), 1, 0)]])
+            .append_exec_results(vec![exec_ok()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (first, further, db_error) = tokio::join!(
+            record_login_failure(&conn, config, TEST_EMAIL, Some(TEST_STR)),
+            record_login_failure(&conn, config, TEST_EMAIL, None),
+            record_login_failure(&conn, config, TEST_EMAIL, None),
+        );
+
+        check!(first == Ok(()));
+        check!(further == Ok(()));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn reset_login_failures_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_exec_results(vec![exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (reset, db_error) = tokio::join!(
+            reset_login_failures(&conn, TEST_EMAIL),
+            reset_login_failures(&conn, TEST_EMAIL),
+        );
+
+        check!(reset == Ok(()));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn try_consume_registration_mail_all_cases() {
+        let config = &test_app_config().rate_limit_config;
+        let key = format!("registration:ip:{TEST_STR}");
+        let old_window = rate_limit::Model {
+            window_started_at: Local::now() - Duration::hours(2),
+            ..test_rate_limit(key.clone(), 5, 0)
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // first registration
+            .append_query_results(vec![Vec::<rate_limit::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            // limit reached
+            .append_query_results(vec![vec![test_rate_limit(key, 5, 0)]])
+            // limit reached in an earlier window
+            .append_query_results(vec![vec![old_window]])
+            .append_exec_results(vec![exec_ok()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (first, limit_reached, new_window, unknown_ip, db_error) = tokio::join!(
+            try_consume_registration_mail(&conn, config, Some(TEST_STR)),
+            try_consume_registration_mail(&conn, config, Some(TEST_STR)),
+            try_consume_registration_mail(&conn, config, Some(TEST_STR)),
+            try_consume_registration_mail(&conn, config, None),
+            try_consume_registration_mail(&conn, config, Some(TEST_STR)),
+        );
+
+        check!(first == Ok(true));
+        check!(limit_reached == Ok(false));
+        check!(new_window == Ok(true));
+        check!(unknown_ip == Ok(true));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[test]
+    fn backoff_doubles_with_every_failure() {
+        let lockout = Duration::minutes(15);
+        check!(backoff(1, 1, lockout) == Duration::seconds(1));
+        check!(backoff(1, 2, lockout) == Duration::seconds(2));
+        check!(backoff(2, 4, lockout) == Duration::seconds(16));
+    }
+
+    #[test]
+    fn backoff_is_capped_at_the_lockout() {
+        let lockout = Duration::minutes(15);
+        check!(backoff(600, 3, lockout) == lockout);
+        check!(backoff(i64::MAX, 31, lockout) == lockout);
+    }
+}
@@ -1,9 +1,19 @@
-use entity::recurrence::Entity as Recurrence;
+use self::errors::{CreateRecurrenceError, DeleteRecurrenceError, UpdateRecurrenceError};
+
+use entity::expense::{self, Entity as Expense};
+use entity::predefined_expense::{self, Entity as PredefinedExpense};
+use entity::recurrence::{self, Entity as Recurrence};
+use entity::Id;
 
 use migration::DbErr;
-use sea_orm::{DatabaseConnection, EntityTrait};
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    Set,
+};
 
-use crate::dto::recurrences::RecurrenceResponse;
+use super::common::find_entity_by_id;
+use crate::dto::recurrences::{RecurrenceRequest, RecurrenceResponse};
 
 pub async fn find_recurrences(conn: &DatabaseConnection) -> Result<Vec<RecurrenceResponse>, DbErr> {
     let recurrences = Recurrence::find()
@@ -15,16 +25,136 @@ pub async fn find_recurrences(conn: &DatabaseConnection) -> Result<Vec<Recurrenc
     Ok(recurrences)
 }
 
+/// Adds a recurrence to the reference data, its name must not be used by another recurrence.
+pub async fn create_recurrence(
+    conn: &DatabaseConnection,
+    req: RecurrenceRequest,
+) -> Result<Id, CreateRecurrenceError> {
+    if find_conflicting_recurrence(conn, &req, None)
+        .await?
+        .is_some()
+    {
+        return Err(CreateRecurrenceError::RecurrenceAlreadyExists);
+    }
+    let recurrence = recurrence::ActiveModel {
+        id: NotSet,
+        name: Set(req.name),
+        per_year: Set(req.per_year),
+    };
+    let recurrence = recurrence.insert(conn).await?;
+    Ok(recurrence.id)
+}
+
+pub async fn update_recurrence(
+    conn: &DatabaseConnection,
+    recurrence_id: Id,
+    req: RecurrenceRequest,
+) -> Result<(), UpdateRecurrenceError> {
+    let Some(recurrence) = find_entity_by_id::<recurrence::Entity>(conn, recurrence_id).await?
+    else {
+        return Err(UpdateRecurrenceError::RecurrenceNotFound);
+    };
+    if find_conflicting_recurrence(conn, &req, Some(recurrence_id))
+        .await?
+        .is_some()
+    {
+        return Err(UpdateRecurrenceError::RecurrenceAlreadyExists);
+    }
+    let mut recurrence = recurrence.into_active_model();
+    recurrence.name = Set(req.name);
+    recurrence.per_year = Set(req.per_year);
+    recurrence.update(conn).await?;
+    Ok(())
+}
+
+/// Deletes a recurrence, which is only possible while no expense refers to it.
+pub async fn delete_recurrence(
+    conn: &DatabaseConnection,
+    recurrence_id: Id,
+) -> Result<(), DeleteRecurrenceError> {
+    let Some(_) = find_entity_by_id::<recurrence::Entity>(conn, recurrence_id).await? else {
+        return Err(DeleteRecurrenceError::RecurrenceNotFound);
+    };
+    let (expense, predefined_expense) = tokio::join!(
+        Expense::find()
+            .filter(expense::Column::RecurrenceId.eq(recurrence_id))
+            .one(conn),
+        PredefinedExpense::find()
+            .filter(predefined_expense::Column::RecurrenceId.eq(recurrence_id))
+            .one(conn)
+    );
+    if expense?.is_some() || predefined_expense?.is_some() {
+        return Err(DeleteRecurrenceError::RecurrenceInUse);
+    }
+    Recurrence::delete_by_id(recurrence_id).exec(conn).await?;
+    Ok(())
+}
+
+async fn find_conflicting_recurrence(
+    conn: &DatabaseConnection,
+    req: &RecurrenceRequest,
+    except_recurrence_id: Option<Id>,
+) -> Result<Option<recurrence::Model>, DbErr> {
+    let mut query = Recurrence::find().filter(recurrence::Column::Name.eq(&req.name));
+    if let Some(except_recurrence_id) = except_recurrence_id {
+        query = query.filter(recurrence::Column::Id.ne(except_recurrence_id));
+    }
+    query.one(conn).await
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum CreateRecurrenceError {
+        #[error("recurrence with the same name already exists")]
+        RecurrenceAlreadyExists,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UpdateRecurrenceError {
+        #[error("recurrence not found")]
+        RecurrenceNotFound,
+        #[error("recurrence with the same name already exists")]
+        RecurrenceAlreadyExists,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteRecurrenceError {
+        #[error("recurrence not found")]
+        RecurrenceNotFound,
+        #[error("recurrence is used by expenses")]
+        RecurrenceInUse,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::vec;
 
-    use crate::logic::common::tests::{test_db_error, test_recurrence};
+    use crate::logic::common::tests::{
+        exec_ok, test_db_error, test_predefined_expense, test_recurrence, TEST_FLOAT, TEST_ID,
+        TEST_STR,
+    };
 
     use super::*;
     use assert2::check;
     use sea_orm::{DatabaseBackend, MockDatabase};
 
+    fn recurrence_request() -> RecurrenceRequest {
+        RecurrenceRequest {
+            name: TEST_STR.to_string(),
+            per_year: TEST_FLOAT,
+        }
+    }
+
     #[tokio::test]
     async fn find_recurrences_all_cases() {
         let expected_recurrences: Vec<RecurrenceResponse> =
@@ -46,4 +176,87 @@ mod tests {
         check!(empty_vec == Ok(vec![]));
         check!(db_error == Err(test_db_error()));
     }
+
+    #[tokio::test]
+    async fn create_recurrence_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![Vec::<recurrence::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_recurrence()]])
+            // already exists
+            .append_query_results(vec![vec![test_recurrence()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, already_exists, db_error) = tokio::join!(
+            create_recurrence(&conn, recurrence_request()),
+            create_recurrence(&conn, recurrence_request()),
+            create_recurrence(&conn, recurrence_request())
+        );
+
+        check!(happy_case == Ok(TEST_ID));
+        check!(already_exists == Err(CreateRecurrenceError::RecurrenceAlreadyExists));
+        check!(db_error == Err(CreateRecurrenceError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn update_recurrence_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_recurrence()], vec![]])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![test_recurrence()]])
+            // not found
+            .append_query_results(vec![Vec::<recurrence::Model>::new()])
+            // already exists
+            .append_query_results(vec![vec![test_recurrence()], vec![test_recurrence()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, already_exists, db_error) = tokio::join!(
+            update_recurrence(&conn, TEST_ID, recurrence_request()),
+            update_recurrence(&conn, TEST_ID, recurrence_request()),
+            update_recurrence(&conn, TEST_ID, recurrence_request()),
+            update_recurrence(&conn, TEST_ID, recurrence_request())
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(UpdateRecurrenceError::RecurrenceNotFound));
+        check!(already_exists == Err(UpdateRecurrenceError::RecurrenceAlreadyExists));
+        check!(db_error == Err(UpdateRecurrenceError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_recurrence_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            // not found
+            .append_query_results(vec![Vec::<recurrence::Model>::new()])
+            // in use by a predefined expense
+            .append_query_results(vec![vec![test_recurrence()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![vec![test_predefined_expense()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, in_use, db_error) = tokio::join!(
+            delete_recurrence(&conn, TEST_ID),
+            delete_recurrence(&conn, TEST_ID),
+            delete_recurrence(&conn, TEST_ID),
+            delete_recurrence(&conn, TEST_ID)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(DeleteRecurrenceError::RecurrenceNotFound));
+        check!(in_use == Err(DeleteRecurrenceError::RecurrenceInUse));
+        check!(db_error == Err(DeleteRecurrenceError::DatabaseError(test_db_error())));
+    }
 }
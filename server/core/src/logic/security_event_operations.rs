@@ -0,0 +1,94 @@
+use crate::dto::security_events::SecurityEventResponse;
+use crate::dto::sessions::ClientInfo;
+
+use chrono::Local;
+use entity::security_event::{self, Entity as SecurityEvent, SecurityEventType};
+use entity::Id;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+
+/// Appends an event to the audit log of the user, together with the client it originated from.
+/// Events are never updated or deleted, only together with the account itself.
+pub async fn record_security_event<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+    event_type: SecurityEventType,
+    client: &ClientInfo,
+) -> Result<(), DbErr> {
+    let security_event = security_event::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        event_type: Set(event_type),
+        ip_address: Set(client.ip_address.clone()),
+        user_agent: Set(client.user_agent.clone()),
+        created_at: Set(Local::now()),
+    };
+    SecurityEvent::insert(security_event).exec(conn).await?;
+    Ok(())
+}
+
+/// Lists the recorded events of the user, the newest first.
+pub async fn find_security_events_by_user_id(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<Vec<SecurityEventResponse>, DbErr> {
+    let security_events = SecurityEvent::find()
+        .filter(security_event::Column::UserId.eq(user_id))
+        .order_by_desc(security_event::Column::CreatedAt)
+        .order_by_desc(security_event::Column::Id)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(SecurityEventResponse::from)
+        .collect();
+    Ok(security_events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{exec_ok, test_db_error, test_security_event, TEST_ID};
+    use assert2::check;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    #[tokio::test]
+    async fn record_security_event_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_exec_results(vec![exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let client = ClientInfo::default();
+
+        let (happy_case, db_error) = tokio::join!(
+            record_security_event(&conn, TEST_ID, SecurityEventType::Login, &client),
+            record_security_event(&conn, TEST_ID, SecurityEventType::Login, &client)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn find_security_events_by_user_id_all_cases() {
+        let security_event = test_security_event();
+        let expected_security_events: Vec<SecurityEventResponse> =
+            vec![security_event.clone().into()];
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![security_event], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (security_events, empty_vec, db_error) = tokio::join!(
+            find_security_events_by_user_id(&conn, TEST_ID),
+            find_security_events_by_user_id(&conn, TEST_ID),
+            find_security_events_by_user_id(&conn, TEST_ID)
+        );
+
+        check!(security_events == Ok(expected_security_events));
+        check!(empty_vec == Ok(vec![]));
+        check!(db_error == Err(test_db_error()));
+    }
+}
@@ -0,0 +1,363 @@
+use self::errors::RevokeSessionByIdError;
+
+use super::common::{find_entity_by_id, hash_token};
+use crate::config::SessionConfig;
+use crate::dto::sessions::{ClientInfo, SessionResponse};
+
+use chrono::{DateTime, Duration, Local};
+use entity::session::{self, Entity as Session};
+use entity::Id;
+use sea_orm::prelude::Uuid;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+/// The last activity of a session is only persisted if the previous one is older than this,
+/// so not every request results in a database write.
+const LAST_SEEN_REFRESH_INTERVAL_SECONDS: i64 = 60;
+
+/// Creates a new session for the user and returns its token, which identifies the session in the cookie.
+/// Only the hash of the token is stored. Expired sessions of the user are deleted along the way.
+pub async fn create_session(
+    conn: &DatabaseConnection,
+    config: &SessionConfig,
+    user_id: Id,
+    client: ClientInfo,
+) -> Result<String, DbErr> {
+    let now = Local::now();
+    Session::delete_many()
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(expired_condition(config, now))
+        .exec(conn)
+        .await?;
+
+    let token = Uuid::new_v4().to_string();
+    let session = session::ActiveModel {
+        id: NotSet,
+        token_hash: Set(hash_token(&token)),
+        user_id: Set(user_id),
+        ip_address: Set(client.ip_address),
+        user_agent: Set(client.user_agent),
+        created_at: Set(now),
+        last_seen_at: Set(now),
+        expires_at: Set(now + Duration::hours(config.absolute_timeout_hours)),
+    };
+    session.insert(conn).await?;
+    Ok(token)
+}
+
+/// Finds the session belonging to the token if it is still valid and marks it as used.
+/// An expired session is deleted and `None` is returned.
+pub async fn authenticate_session(
+    conn: &DatabaseConnection,
+    config: &SessionConfig,
+    token: &str,
+) -> Result<Option<session::Model>, DbErr> {
+    let Some(session) = Session::find()
+        .filter(session::Column::TokenHash.eq(hash_token(token)))
+        .one(conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let now = Local::now();
+    if is_expired(config, &session, now) {
+        Session::delete_by_id(session.id).exec(conn).await?;
+        return Ok(None);
+    }
+
+    if now - session.last_seen_at > Duration::seconds(LAST_SEEN_REFRESH_INTERVAL_SECONDS) {
+        Session::update_many()
+            .col_expr(session::Column::LastSeenAt, Expr::value(now))
+            .filter(session::Column::Id.eq(session.id))
+            .exec(conn)
+            .await?;
+    }
+    Ok(Some(session))
+}
+
+/// Revokes the session identified by the token, used on logout.
+/// Returns the id of the user the session belonged to, if it still existed.
+pub async fn revoke_session(conn: &DatabaseConnection, token: &str) -> Result<Option<Id>, DbErr> {
+    let Some(session) = Session::find()
+        .filter(session::Column::TokenHash.eq(hash_token(token)))
+        .one(conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+    Session::delete_by_id(session.id).exec(conn).await?;
+    Ok(Some(session.user_id))
+}
+
+/// Lists the still valid sessions of the user, marking the one the request was made with.
+pub async fn find_sessions_by_user_id(
+    conn: &DatabaseConnection,
+    config: &SessionConfig,
+    user_id: Id,
+    current_session_id: Id,
+) -> Result<Vec<SessionResponse>, DbErr> {
+    let sessions = Session::find()
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(expired_condition(config, Local::now()).not())
+        .order_by_desc(session::Column::LastSeenAt)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|session| {
+            let current = session.id == current_session_id;
+            (session, current).into()
+        })
+        .collect();
+    Ok(sessions)
+}
+
+/// Revokes a session of the user by its id, so the user can log out other devices.
+/// Sessions of other users are reported as missing, so their existence is not revealed.
+pub async fn revoke_session_by_id(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    session_id: Id,
+) -> Result<(), RevokeSessionByIdError> {
+    let session = find_entity_by_id::<session::Entity>(conn, session_id).await?;
+    if !matches!(session, Some(session) if session.user_id == user_id) {
+        return Err(RevokeSessionByIdError::InvalidSession);
+    }
+
+    Session::delete_by_id(session_id).exec(conn).await?;
+    Ok(())
+}
+
+/// Revokes every session of the user, except the one given, used when the credentials of the user change.
+/// Can be used inside a database transaction.
+pub async fn revoke_sessions_by_user_id<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+    except_session_id: Option<Id>,
+) -> Result<(), DbErr> {
+    let mut delete = Session::delete_many().filter(session::Column::UserId.eq(user_id));
+    if let Some(except_session_id) = except_session_id {
+        delete = delete.filter(session::Column::Id.ne(except_session_id));
+    }
+    delete.exec(conn).await?;
+    Ok(())
+}
+
+fn is_expired(config: &SessionConfig, session: &session::Model, now: DateTime<Local>) -> bool {
+    session.expires_at < now
+        || session.last_seen_at < now - Duration::minutes(config.idle_timeout_minutes)
+}
+
+fn expired_condition(config: &SessionConfig, now: DateTime<Local>) -> Condition {
+    Condition::any()
+        .add(session::Column::ExpiresAt.lt(now))
+        .add(session::Column::LastSeenAt.lt(now - Duration::minutes(config.idle_timeout_minutes)))
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum RevokeSessionByIdError {
+        #[error("session id is invalid")]
+        InvalidSession,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logic::common::tests::{
+        exec_ok, test_app_config, test_db_error, test_session, TEST_ID, TEST_STR,
+    };
+
+    use super::*;
+    use assert2::{check, let_assert};
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    #[tokio::test]
+    async fn create_session_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_session()]])
+            // db error on expired session cleanup
+            .append_exec_errors(vec![test_db_error()])
+            // db error on session insert
+            .append_exec_results(vec![exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let config = test_app_config().session_config;
+
+        let (happy_case, cleanup_db_error, insert_db_error) = tokio::join!(
+            create_session(&conn, &config, TEST_ID, ClientInfo::default()),
+            create_session(&conn, &config, TEST_ID, ClientInfo::default()),
+            create_session(&conn, &config, TEST_ID, ClientInfo::default()),
+        );
+
+        let_assert!(Ok(token) = happy_case);
+        check!(token != TEST_STR);
+        check!(cleanup_db_error == Err(test_db_error()));
+        check!(insert_db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn authenticate_session_all_cases() {
+        let recent_session = test_session();
+        let stale_session = session::Model {
+            last_seen_at: Local::now() - Duration::minutes(10),
+            ..test_session()
+        };
+        let idle_session = session::Model {
+            last_seen_at: Local::now() - Duration::days(1),
+            ..test_session()
+        };
+        let expired_session = session::Model {
+            expires_at: Local::now() - Duration::minutes(1),
+            ..test_session()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // recently used session
+            .append_query_results(vec![vec![recent_session.clone()]])
+            // session used a while ago, last seen gets updated
+            .append_query_results(vec![vec![stale_session.clone()]])
+            .append_exec_results(vec![exec_ok()])
+            // idle session gets deleted
+            .append_query_results(vec![vec![idle_session]])
+            .append_exec_results(vec![exec_ok()])
+            // expired session gets deleted
+            .append_query_results(vec![vec![expired_session]])
+            .append_exec_results(vec![exec_ok()])
+            // session not found
+            .append_query_results(vec![Vec::<session::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let config = test_app_config().session_config;
+
+        let (recent, stale, idle, expired, not_found, db_error) = tokio::join!(
+            authenticate_session(&conn, &config, TEST_STR),
+            authenticate_session(&conn, &config, TEST_STR),
+            authenticate_session(&conn, &config, TEST_STR),
+            authenticate_session(&conn, &config, TEST_STR),
+            authenticate_session(&conn, &config, TEST_STR),
+            authenticate_session(&conn, &config, TEST_STR),
+        );
+
+        check!(recent == Ok(Some(recent_session)));
+        check!(stale == Ok(Some(stale_session)));
+        check!(idle == Ok(None));
+        check!(expired == Ok(None));
+        check!(not_found == Ok(None));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn revoke_session_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_session()]])
+            .append_exec_results(vec![exec_ok()])
+            // session not found
+            .append_query_results(vec![Vec::<session::Model>::new()])
+            // db error
+            .append_query_results(vec![vec![test_session()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, db_error) = tokio::join!(
+            revoke_session(&conn, TEST_STR),
+            revoke_session(&conn, TEST_STR),
+            revoke_session(&conn, TEST_STR)
+        );
+
+        check!(happy_case == Ok(Some(TEST_ID)));
+        check!(not_found == Ok(None));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn revoke_sessions_by_user_id_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (every_session, other_sessions, db_error) = tokio::join!(
+            revoke_sessions_by_user_id(&conn, TEST_ID, None),
+            revoke_sessions_by_user_id(&conn, TEST_ID, Some(TEST_ID)),
+            revoke_sessions_by_user_id(&conn, TEST_ID, None)
+        );
+
+        check!(every_session == Ok(()));
+        check!(other_sessions == Ok(()));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn find_sessions_by_user_id_all_cases() {
+        let current_session = test_session();
+        let other_session = session::Model {
+            id: TEST_ID + 1,
+            ..current_session.clone()
+        };
+        let expected_sessions: Vec<SessionResponse> = vec![
+            (current_session.clone(), true).into(),
+            (other_session.clone(), false).into(),
+        ];
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![current_session, other_session], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let config = test_app_config().session_config;
+
+        let (sessions, empty_vec, db_error) = tokio::join!(
+            find_sessions_by_user_id(&conn, &config, TEST_ID, TEST_ID),
+            find_sessions_by_user_id(&conn, &config, TEST_ID, TEST_ID),
+            find_sessions_by_user_id(&conn, &config, TEST_ID, TEST_ID)
+        );
+
+        check!(sessions == Ok(expected_sessions));
+        check!(empty_vec == Ok(vec![]));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn revoke_session_by_id_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_session()]])
+            .append_exec_results(vec![exec_ok()])
+            // session not found
+            .append_query_results(vec![Vec::<session::Model>::new()])
+            // session of another user
+            .append_query_results(vec![vec![test_session()]])
+            // session query db error
+            .append_query_errors(vec![test_db_error()])
+            // session delete db error
+            .append_query_results(vec![vec![test_session()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, other_users_session, query_db_error, delete_db_error) = tokio::join!(
+            revoke_session_by_id(&conn, TEST_ID, TEST_ID),
+            revoke_session_by_id(&conn, TEST_ID, TEST_ID),
+            revoke_session_by_id(&conn, TEST_ID + 1, TEST_ID),
+            revoke_session_by_id(&conn, TEST_ID, TEST_ID),
+            revoke_session_by_id(&conn, TEST_ID, TEST_ID),
+        );
+
+        let db_error = Err(RevokeSessionByIdError::DatabaseError(test_db_error()));
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(RevokeSessionByIdError::InvalidSession));
+        check!(other_users_session == Err(RevokeSessionByIdError::InvalidSession));
+        check!(query_db_error == db_error);
+        check!(delete_db_error == db_error);
+    }
+}
@@ -1,25 +1,36 @@
-use self::errors::{CreateTransactionError, DeleteTransactionByIdError};
+use self::errors::{CreateTransactionError, DeleteTransactionByIdError, RestoreTransactionError};
 
-use super::common;
-use super::user_operations::authorize_user;
+use super::category_operations::is_own_category;
+use super::common::{normalize_tags, parse_date};
+use super::expense_operations::find_expense_by_id;
+use super::household_operations::can_access_expense;
+use super::participant_operations::can_pay_for;
+use super::user_operations::errors::AuthorizeUserError;
+use super::user_operations::find_date_format;
 use crate::dto::transactions::NewTransactionRequest;
 use crate::logic::common::find_entity_by_id;
 
+use chrono::Local;
 use entity::transaction::{self, Entity as Transaction};
-use entity::{currency, expense, Id};
+use entity::transaction_tag::{self, Entity as TransactionTag};
+use entity::{currency, expense, participant, Id};
 
-use chrono::NaiveDate;
+use sea_orm::sea_query::Expr;
 use sea_orm::ActiveValue::NotSet;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    Set, TransactionTrait,
+};
 
 pub async fn create_transaction(
     conn: &DatabaseConnection,
     user_id: Id,
     req: NewTransactionRequest,
 ) -> Result<Id, CreateTransactionError> {
-    let (expense_result, currency_result) = tokio::join!(
-        find_entity_by_id::<expense::Entity>(conn, req.expense_id),
-        find_entity_by_id::<currency::Entity>(conn, req.currency_id)
+    let (expense_result, currency_result, participant_result) = tokio::join!(
+        find_expense_by_id(conn, req.expense_id),
+        find_entity_by_id::<currency::Entity>(conn, req.currency_id),
+        find_entity_by_id::<participant::Entity>(conn, req.participant_id)
     );
     let Some(expense) = expense_result? else {
         return Err(CreateTransactionError::InvalidExpenseId);
@@ -27,40 +38,114 @@ pub async fn create_transaction(
     let Some(_) = currency_result? else {
         return Err(CreateTransactionError::InvalidCurrency);
     };
-    authorize_user(user_id, expense.user_id)?;
+    if !can_access_expense(conn, user_id, &expense).await? {
+        return Err(CreateTransactionError::UserUnauthorized(AuthorizeUserError));
+    }
+    let Some(participant) = participant_result? else {
+        return Err(CreateTransactionError::InvalidParticipant);
+    };
+    if !can_pay_for(&participant, &expense) {
+        return Err(CreateTransactionError::InvalidParticipant);
+    }
+    if let Some(category_id) = req.category_id {
+        if !is_own_category(conn, user_id, category_id).await? {
+            return Err(CreateTransactionError::InvalidCategory);
+        }
+    }
 
-    let parsed_date = NaiveDate::parse_from_str(&req.date, common::DATE_FORMAT)?;
+    let date_format = find_date_format(conn, user_id).await?;
+    let parsed_date = parse_date(&req.date, date_format)?;
     let transaction = transaction::ActiveModel {
         id: NotSet,
-        donor_name: Set(req.donor_name),
+        participant_id: Set(req.participant_id),
         currency_id: Set(req.currency_id),
         value: Set(req.value),
         date: Set(parsed_date),
         expense_id: Set(req.expense_id),
+        category_id: Set(req.category_id),
+        deleted_at: Set(None),
     };
-    let transaction = transaction.insert(conn).await?;
-    Ok(transaction.id)
+    let tags = normalize_tags(req.tags);
+
+    let transaction_id = conn
+        .transaction::<_, Id, CreateTransactionError>(|txn| {
+            Box::pin(async move {
+                let transaction = transaction.insert(txn).await?;
+                if !tags.is_empty() {
+                    let tags = tags.into_iter().map(|name| transaction_tag::ActiveModel {
+                        id: NotSet,
+                        transaction_id: Set(transaction.id),
+                        name: Set(name),
+                    });
+                    TransactionTag::insert_many(tags).exec(txn).await?;
+                }
+                Ok(transaction.id)
+            })
+        })
+        .await?;
+    Ok(transaction_id)
 }
 
+/// Moves a transaction of the user to the trash, it can be restored until it is purged.
 pub async fn delete_transaction_by_id(
     conn: &DatabaseConnection,
     user_id: Id,
     transaction_id: Id,
 ) -> Result<(), DeleteTransactionByIdError> {
-    let Some(transaction) = find_entity_by_id::<transaction::Entity>(conn, transaction_id).await? else {
+    let transaction = find_entity_by_id::<transaction::Entity>(conn, transaction_id).await?;
+    let Some(transaction) = transaction.filter(|transaction| transaction.deleted_at.is_none()) else {
         return Err(DeleteTransactionByIdError::InvalidTransaction);
     };
-    let Some(expense) = find_entity_by_id::<expense::Entity>(conn, transaction.expense_id).await? else {
+    let Some(expense) = find_expense_by_id(conn, transaction.expense_id).await? else {
         return Err(DeleteTransactionByIdError::InvalidTransaction);
     };
-    authorize_user(user_id, expense.user_id)?;
+    if !can_access_expense(conn, user_id, &expense).await? {
+        return Err(DeleteTransactionByIdError::UserUnauthorized(
+            AuthorizeUserError,
+        ));
+    }
+
+    Transaction::update_many()
+        .col_expr(transaction::Column::DeletedAt, Expr::value(Local::now()))
+        .filter(transaction::Column::Id.eq(transaction_id))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// Takes a transaction of the user out of the trash, which is only possible while its expense is not in the trash.
+pub async fn restore_transaction(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    transaction_id: Id,
+) -> Result<(), RestoreTransactionError> {
+    let Some(transaction) = find_entity_by_id::<transaction::Entity>(conn, transaction_id).await? else {
+        return Err(RestoreTransactionError::TransactionNotFound);
+    };
+    let Some(expense) = find_entity_by_id::<expense::Entity>(conn, transaction.expense_id).await? else {
+        return Err(RestoreTransactionError::TransactionNotFound);
+    };
+    if !can_access_expense(conn, user_id, &expense).await? {
+        return Err(RestoreTransactionError::UserUnauthorized(
+            AuthorizeUserError,
+        ));
+    }
+    if transaction.deleted_at.is_none() {
+        return Err(RestoreTransactionError::TransactionNotInTrash);
+    }
+    if expense.deleted_at.is_some() {
+        return Err(RestoreTransactionError::ExpenseInTrash);
+    }
 
-    Transaction::delete_by_id(transaction_id).exec(conn).await?;
+    let mut transaction = transaction.into_active_model();
+    transaction.deleted_at = Set(None);
+    transaction.update(conn).await?;
     Ok(())
 }
 
 pub mod errors {
     use migration::DbErr;
+    use sea_orm::TransactionError;
     use thiserror::Error;
 
     use crate::logic::user_operations::errors::AuthorizeUserError;
@@ -75,10 +160,23 @@ pub mod errors {
         UserUnauthorized(#[from] AuthorizeUserError),
         #[error("start_date could not be parsed")]
         InvalidStartDate(#[from] chrono::ParseError),
+        #[error("category is invalid")]
+        InvalidCategory,
+        #[error("participant can not pay for this expense")]
+        InvalidParticipant,
         #[error("database error: '{0}'")]
         DatabaseError(#[from] DbErr),
     }
 
+    impl From<TransactionError<CreateTransactionError>> for CreateTransactionError {
+        fn from(e: TransactionError<CreateTransactionError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
     #[derive(Error, Debug, PartialEq, Eq)]
     pub enum DeleteTransactionByIdError {
         #[error("transaction id is invalid")]
@@ -88,6 +186,20 @@ pub mod errors {
         #[error("database error: '{0}'")]
         DatabaseError(#[from] DbErr),
     }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum RestoreTransactionError {
+        #[error("transaction not found")]
+        TransactionNotFound,
+        #[error("user is not authorized")]
+        UserUnauthorized(#[from] AuthorizeUserError),
+        #[error("transaction is not in the trash")]
+        TransactionNotInTrash,
+        #[error("the expense of the transaction is in the trash, it has to be restored first")]
+        ExpenseInTrash,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
 }
 
 #[cfg(test)]
@@ -95,13 +207,17 @@ mod tests {
     use std::vec;
 
     use crate::logic::{
-        common::tests::{test_db_error, test_decimal, TEST_DATE, TEST_ID, TEST_STR},
+        common::tests::{
+            test_category, test_currency, test_db_error, test_decimal, test_expense,
+            test_participant, test_transaction, test_user, TEST_DATE, TEST_ID, TEST_STR,
+        },
         user_operations::errors::AuthorizeUserError,
     };
 
     use super::*;
     use assert2::check;
-    use entity::{currency, expense};
+    use chrono::NaiveDate;
+    use entity::{category, currency, expense};
     use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
 
     #[tokio::test]
@@ -112,10 +228,16 @@ mod tests {
             description: TEST_STR.to_string(),
             value: test_decimal(),
             start_date: NaiveDate::MIN,
+            end_date: None,
             user_id: TEST_ID,
             currency_id: TEST_ID,
             recurrence_id: TEST_ID,
             predefined_expense_id: None,
+            category_id: None,
+            household_id: None,
+            split_method: None,
+            archived: false,
+            deleted_at: None,
         };
         let mock_currency = currency::Model {
             id: TEST_ID,
@@ -124,15 +246,19 @@ mod tests {
         };
         let mock_transaction = transaction::Model {
             id: TEST_ID,
-            donor_name: TEST_STR.to_string(),
+            participant_id: TEST_ID,
             value: test_decimal(),
             date: NaiveDate::MIN,
             currency_id: TEST_ID,
             expense_id: TEST_ID,
+            category_id: None,
+            deleted_at: None,
         };
         let conn = MockDatabase::new(DatabaseBackend::MySql)
             .append_query_results(vec![vec![mock_expense.clone()]])
             .append_query_results(vec![vec![mock_currency.clone()]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![test_user()]])
             .append_exec_results(vec![MockExecResult {
                 last_insert_id: TEST_ID,
                 rows_affected: 1,
@@ -144,11 +270,13 @@ mod tests {
             &conn,
             TEST_ID,
             NewTransactionRequest {
-                donor_name: TEST_STR.to_string(),
+                participant_id: TEST_ID,
                 currency_id: TEST_ID,
                 value: test_decimal(),
                 date: TEST_DATE.to_string(),
                 expense_id: TEST_ID,
+                category_id: None,
+                tags: vec![],
             },
         )
         .await;
@@ -156,6 +284,53 @@ mod tests {
         check!(saved_transaction_id == Ok(TEST_ID));
     }
 
+    #[tokio::test]
+    async fn create_transaction_with_category_and_tags_all_cases() {
+        let foreign_category = category::Model {
+            user_id: TEST_ID + 1,
+            ..test_category()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // categorized and tagged
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![test_category()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: TEST_ID,
+                rows_affected: 1,
+            }])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: TEST_ID,
+                rows_affected: 2,
+            }])
+            // category of another user
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![foreign_category]])
+            .into_connection();
+        let req = NewTransactionRequest {
+            participant_id: TEST_ID,
+            currency_id: TEST_ID,
+            value: test_decimal(),
+            date: TEST_DATE.to_string(),
+            expense_id: TEST_ID,
+            category_id: Some(TEST_ID),
+            tags: vec![TEST_STR.to_string(), "shared".to_string()],
+        };
+
+        let (categorized, foreign_category) = tokio::join!(
+            create_transaction(&conn, TEST_ID, req.clone()),
+            create_transaction(&conn, TEST_ID, req)
+        );
+
+        check!(categorized == Ok(TEST_ID));
+        check!(foreign_category == Err(CreateTransactionError::InvalidCategory));
+    }
+
     #[tokio::test]
     async fn create_transaction_error_cases() {
         let mock_expense = expense::Model {
@@ -164,10 +339,16 @@ mod tests {
             description: TEST_STR.to_string(),
             value: test_decimal(),
             start_date: NaiveDate::MIN,
+            end_date: None,
             user_id: TEST_ID,
             currency_id: TEST_ID,
             recurrence_id: TEST_ID,
             predefined_expense_id: None,
+            category_id: None,
+            household_id: None,
+            split_method: None,
+            archived: false,
+            deleted_at: None,
         };
         let mock_currency = currency::Model {
             id: TEST_ID,
@@ -178,28 +359,49 @@ mod tests {
             // invalid expense id
             .append_query_results(vec![Vec::<expense::Model>::new()])
             .append_query_results(vec![vec![mock_currency.clone()]])
+            .append_query_results(vec![vec![test_participant()]])
             // invalid currency type id
             .append_query_results(vec![vec![mock_expense.clone()]])
             .append_query_results(vec![Vec::<currency::Model>::new()])
+            .append_query_results(vec![vec![test_participant()]])
             // unauthorized
             .append_query_results(vec![vec![mock_expense.clone()]])
             .append_query_results(vec![vec![mock_currency.clone()]])
+            .append_query_results(vec![vec![test_participant()]])
             // date cannot be parsed
             .append_query_results(vec![vec![mock_expense.clone()]])
             .append_query_results(vec![vec![mock_currency.clone()]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![test_user()]])
+            // participant of another user
+            .append_query_results(vec![vec![mock_expense.clone()]])
+            .append_query_results(vec![vec![mock_currency.clone()]])
+            .append_query_results(vec![vec![participant::Model {
+                user_id: Some(TEST_ID + 1),
+                ..test_participant()
+            }]])
             .into_connection();
 
         let req = NewTransactionRequest {
-            donor_name: TEST_STR.to_string(),
+            participant_id: TEST_ID,
             currency_id: TEST_ID,
             value: test_decimal(),
             date: "wrong_date".to_string(),
             expense_id: TEST_ID,
+            category_id: None,
+            tags: vec![],
         };
-        let (invalid_expense_id, invalid_currency_id, user_unauthorized, invalid_start_date) = tokio::join!(
+        let (
+            invalid_expense_id,
+            invalid_currency_id,
+            user_unauthorized,
+            invalid_start_date,
+            invalid_participant,
+        ) = tokio::join!(
             create_transaction(&conn, TEST_ID, req.clone()),
             create_transaction(&conn, TEST_ID, req.clone()),
             create_transaction(&conn, TEST_ID - 1, req.clone()),
+            create_transaction(&conn, TEST_ID, req.clone()),
             create_transaction(&conn, TEST_ID, req),
         );
 
@@ -209,6 +411,7 @@ mod tests {
             user_unauthorized == Err(CreateTransactionError::UserUnauthorized(AuthorizeUserError))
         );
         check!(let Err(CreateTransactionError::InvalidStartDate(_)) = invalid_start_date);
+        check!(invalid_participant == Err(CreateTransactionError::InvalidParticipant));
     }
 
     #[tokio::test]
@@ -219,10 +422,16 @@ mod tests {
             description: TEST_STR.to_string(),
             value: test_decimal(),
             start_date: NaiveDate::MIN,
+            end_date: None,
             user_id: TEST_ID,
             currency_id: TEST_ID,
             recurrence_id: TEST_ID,
             predefined_expense_id: None,
+            category_id: None,
+            household_id: None,
+            split_method: None,
+            archived: false,
+            deleted_at: None,
         };
         let mock_currency = currency::Model {
             id: TEST_ID,
@@ -233,21 +442,27 @@ mod tests {
             // expense query db error
             .append_query_errors(vec![test_db_error()])
             .append_query_results(vec![vec![mock_currency.clone()]])
+            .append_query_results(vec![vec![test_participant()]])
             // currency type query db error
             .append_query_results(vec![vec![mock_expense.clone()]])
             .append_query_errors(vec![test_db_error()])
+            .append_query_results(vec![vec![test_participant()]])
             // transaction insert db error
             .append_query_results(vec![vec![mock_expense.clone()]])
             .append_query_results(vec![vec![mock_currency.clone()]])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![vec![test_user()]])
             .append_exec_errors(vec![test_db_error()])
             .into_connection();
 
         let req = NewTransactionRequest {
-            donor_name: TEST_STR.to_string(),
+            participant_id: TEST_ID,
             currency_id: TEST_ID,
             value: test_decimal(),
             date: TEST_DATE.to_string(),
             expense_id: TEST_ID,
+            category_id: None,
+            tags: vec![],
         };
         let (expense_db_error, currency_db_error, transaction_insert_db_error) = tokio::join!(
             create_transaction(&conn, TEST_ID, req.clone()),
@@ -269,7 +484,9 @@ mod tests {
             currency_id: TEST_ID,
             expense_id: TEST_ID,
             date: NaiveDate::MIN,
-            donor_name: TEST_STR.to_string(),
+            participant_id: TEST_ID,
+            category_id: None,
+            deleted_at: None,
         };
         let mock_expense = expense::Model {
             id: TEST_ID,
@@ -277,10 +494,16 @@ mod tests {
             description: TEST_STR.to_string(),
             value: test_decimal(),
             start_date: NaiveDate::MIN,
+            end_date: None,
             user_id: TEST_ID,
             currency_id: TEST_ID,
             recurrence_id: TEST_ID,
             predefined_expense_id: None,
+            category_id: None,
+            household_id: None,
+            split_method: None,
+            archived: false,
+            deleted_at: None,
         };
         let conn = MockDatabase::new(DatabaseBackend::MySql)
             // happy case
@@ -341,4 +564,62 @@ mod tests {
         check!(expense_query_db_error == db_error);
         check!(transaction_delete_db_error == db_error);
     }
+
+    #[tokio::test]
+    async fn restore_transaction_all_cases() {
+        let trashed_transaction = transaction::Model {
+            deleted_at: Some(Local::now()),
+            ..test_transaction()
+        };
+        let trashed_expense = expense::Model {
+            deleted_at: Some(Local::now()),
+            ..test_expense()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![trashed_transaction.clone()]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: TEST_ID,
+                rows_affected: 1,
+            }])
+            .append_query_results(vec![vec![test_transaction()]])
+            // transaction not found
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            // user unauthorized
+            .append_query_results(vec![vec![trashed_transaction.clone()]])
+            .append_query_results(vec![vec![test_expense()]])
+            // transaction not in the trash
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![vec![test_expense()]])
+            // expense in the trash
+            .append_query_results(vec![vec![trashed_transaction.clone()]])
+            .append_query_results(vec![vec![trashed_expense]])
+            // db error
+            .append_query_results(vec![vec![trashed_transaction]])
+            .append_query_results(vec![vec![test_expense()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (happy_case, not_found, unauthorized, not_in_trash, expense_in_trash, db_error) = tokio::join!(
+            restore_transaction(&conn, TEST_ID, TEST_ID),
+            restore_transaction(&conn, TEST_ID, TEST_ID),
+            restore_transaction(&conn, TEST_ID + 1, TEST_ID),
+            restore_transaction(&conn, TEST_ID, TEST_ID),
+            restore_transaction(&conn, TEST_ID, TEST_ID),
+            restore_transaction(&conn, TEST_ID, TEST_ID),
+        );
+
+        check!(happy_case == Ok(()));
+        check!(not_found == Err(RestoreTransactionError::TransactionNotFound));
+        check!(
+            unauthorized
+                == Err(RestoreTransactionError::UserUnauthorized(
+                    AuthorizeUserError
+                ))
+        );
+        check!(not_in_trash == Err(RestoreTransactionError::TransactionNotInTrash));
+        check!(expense_in_trash == Err(RestoreTransactionError::ExpenseInTrash));
+        check!(db_error == Err(RestoreTransactionError::DatabaseError(test_db_error())));
+    }
 }
@@ -0,0 +1,278 @@
+use std::collections::HashSet;
+
+use super::attachment_operations::delete_stored_files;
+use super::expense_operations::{find_currency, find_participant};
+use super::participant_operations::find_participants_by_ids;
+use super::user_operations::find_date_format;
+use crate::config::TrashConfig;
+use crate::dto::trash::TrashResponse;
+use crate::storage::Storage;
+
+use chrono::{Duration, Local};
+use entity::attachment::{self, Entity as Attachment};
+use entity::expense::{self, Entity as Expense};
+use entity::transaction::{self, Entity as Transaction};
+use entity::{currency, Id};
+
+use migration::DbErr;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionError, TransactionTrait,
+};
+
+/// Lists the expenses and transactions of the user in the trash, latest deletion first.
+/// The transactions of the expenses in the trash are not listed on their own, they are restored together with the expense.
+pub async fn find_trash(
+    conn: &DatabaseConnection,
+    config: &TrashConfig,
+    user_id: Id,
+) -> Result<TrashResponse, DbErr> {
+    let date_format = find_date_format(conn, user_id).await?;
+    let (expenses, transactions) = tokio::join!(
+        Expense::find()
+            .filter(expense::Column::UserId.eq(user_id))
+            .filter(expense::Column::DeletedAt.is_not_null())
+            .order_by_desc(expense::Column::DeletedAt)
+            .all(conn),
+        Transaction::find()
+            .inner_join(expense::Entity)
+            .filter(expense::Column::UserId.eq(user_id))
+            .filter(expense::Column::DeletedAt.is_null())
+            .filter(transaction::Column::DeletedAt.is_not_null())
+            .order_by_desc(transaction::Column::DeletedAt)
+            .all(conn)
+    );
+    let expenses = expenses?;
+    let transactions = transactions?;
+
+    let currency_ids: HashSet<Id> = expenses
+        .iter()
+        .map(|expense| expense.currency_id)
+        .chain(
+            transactions
+                .iter()
+                .map(|transaction| transaction.currency_id),
+        )
+        .collect();
+    let participant_ids: HashSet<Id> = transactions
+        .iter()
+        .map(|transaction| transaction.participant_id)
+        .collect();
+    let currencies = if currency_ids.is_empty() {
+        Vec::new()
+    } else {
+        currency::Entity::find()
+            .filter(currency::Column::Id.is_in(currency_ids))
+            .all(conn)
+            .await?
+    };
+    let participants = find_participants_by_ids(conn, participant_ids).await?;
+
+    let retention = Duration::days(config.retention_days);
+    let expenses = expenses
+        .into_iter()
+        .map(|expense| {
+            let currency = find_currency(&currencies, expense.currency_id);
+            (expense, currency, retention).into()
+        })
+        .collect();
+    let transactions = transactions
+        .into_iter()
+        .map(|transaction| {
+            let currency = find_currency(&currencies, transaction.currency_id);
+            let participant = find_participant(&participants, transaction.participant_id);
+            (transaction, currency, participant, retention, date_format).into()
+        })
+        .collect();
+    Ok(TrashResponse {
+        expenses,
+        transactions,
+    })
+}
+
+/// Permanently deletes the expenses and transactions which have been in the trash for longer than the retention period,
+/// the transactions of the purged expenses are deleted with them. Returns the number of deleted expenses and transactions.
+/// The attachments of the purged items are deleted by the database, their files are removed from the storage afterwards.
+pub async fn purge_trash(
+    conn: &DatabaseConnection,
+    storage: &dyn Storage,
+    config: &TrashConfig,
+) -> Result<u64, DbErr> {
+    let cutoff = Local::now() - Duration::days(config.retention_days);
+    let expense_ids: Vec<Id> = Expense::find()
+        .filter(expense::Column::DeletedAt.lt(cutoff))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|expense| expense.id)
+        .collect();
+    let purged_transactions = Condition::any()
+        .add(transaction::Column::DeletedAt.lt(cutoff))
+        .add(transaction::Column::ExpenseId.is_in(expense_ids.clone()));
+    let transaction_ids: Vec<Id> = Transaction::find()
+        .filter(purged_transactions.clone())
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|transaction| transaction.id)
+        .collect();
+    let storage_keys: Vec<String> = if expense_ids.is_empty() && transaction_ids.is_empty() {
+        Vec::new()
+    } else {
+        Attachment::find()
+            .filter(
+                Condition::any()
+                    .add(attachment::Column::ExpenseId.is_in(expense_ids.clone()))
+                    .add(attachment::Column::TransactionId.is_in(transaction_ids)),
+            )
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|attachment| attachment.storage_key)
+            .collect()
+    };
+
+    let purged = conn
+        .transaction::<_, u64, DbErr>(|txn| {
+            Box::pin(async move {
+                let transactions = Transaction::delete_many()
+                    .filter(purged_transactions)
+                    .exec(txn)
+                    .await?;
+                if expense_ids.is_empty() {
+                    return Ok(transactions.rows_affected);
+                }
+                let expenses = Expense::delete_many()
+                    .filter(expense::Column::Id.is_in(expense_ids))
+                    .exec(txn)
+                    .await?;
+                Ok(transactions.rows_affected + expenses.rows_affected)
+            })
+        })
+        .await
+        .map_err(|e| match e {
+            TransactionError::Connection(e) | TransactionError::Transaction(e) => e,
+        })?;
+    delete_stored_files(storage, &storage_keys).await;
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{
+        test_app_config, test_attachment, test_currency, test_db_error, test_expense,
+        test_participant, test_transaction, test_user, TEST_ID,
+    };
+    use crate::storage::errors::StorageError;
+    use crate::storage::local::LocalStorage;
+    use assert2::check;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+    #[tokio::test]
+    async fn find_trash_all_cases() {
+        let deleted_at = Local::now();
+        let trashed_expense = expense::Model {
+            deleted_at: Some(deleted_at),
+            ..test_expense()
+        };
+        let trashed_transaction = transaction::Model {
+            deleted_at: Some(deleted_at),
+            ..test_transaction()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // trash with items
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![trashed_expense]])
+            .append_query_results(vec![vec![trashed_transaction]])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_participant()]])
+            // empty trash
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            // db error
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_errors(vec![test_db_error()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .into_connection();
+        let config = test_app_config().trash_config;
+
+        let (trash, empty_trash, db_error) = tokio::join!(
+            find_trash(&conn, &config, TEST_ID),
+            find_trash(&conn, &config, TEST_ID),
+            find_trash(&conn, &config, TEST_ID)
+        );
+
+        let trash = trash.unwrap();
+        let purged_at = deleted_at + Duration::days(config.retention_days);
+        check!(trash.expenses.len() == 1);
+        check!(trash.expenses[0].purged_at == purged_at);
+        check!(trash.transactions.len() == 1);
+        check!(trash.transactions[0].deleted_at == deleted_at);
+        let empty_trash = empty_trash.unwrap();
+        check!(empty_trash.expenses.is_empty());
+        check!(empty_trash.transactions.is_empty());
+        check!(db_error.err() == Some(test_db_error()));
+    }
+
+    // The storage does real file operations, which yield to the runtime,
+    // so the cases run one after the other to keep the order of the mocked results.
+    #[tokio::test]
+    async fn purge_trash_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // expired expense and transactions
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_exec_results(vec![
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 3,
+                },
+                MockExecResult {
+                    last_insert_id: 0,
+                    rows_affected: 1,
+                },
+            ])
+            // expired transactions only
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 2,
+            }])
+            // nothing expired
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_exec_results(vec![MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 0,
+            }])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let config = test_app_config().trash_config;
+        let root = std::env::temp_dir().join(format!("veryrezsi-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+        let storage_key = test_attachment().storage_key;
+        storage
+            .put(&storage_key, "application/pdf", b"%PDF-1.7".to_vec())
+            .await
+            .unwrap();
+
+        let with_expense = purge_trash(&conn, &storage, &config).await;
+        let stored_file = storage.get(&storage_key).await;
+        let transactions_only = purge_trash(&conn, &storage, &config).await;
+        let nothing_expired = purge_trash(&conn, &storage, &config).await;
+        let db_error = purge_trash(&conn, &storage, &config).await;
+        let _ = tokio::fs::remove_dir_all(&root).await;
+
+        check!(with_expense == Ok(4));
+        check!(stored_file == Err(StorageError::NotFound));
+        check!(transactions_only == Ok(2));
+        check!(nothing_expired == Ok(0));
+        check!(db_error == Err(test_db_error()));
+    }
+}
@@ -0,0 +1,730 @@
+use self::errors::{
+    CompleteTwoFactorLoginError, ConfirmTwoFactorError, DisableTwoFactorError, EnrollTwoFactorError,
+};
+
+use super::common::{find_entity_by_id, hash_token};
+use super::rate_limit_operations::{
+    find_login_block, record_login_failure, reset_login_failures, LoginBlock,
+};
+use super::security_event_operations::record_security_event;
+use crate::config::AppConfig;
+use crate::dto::sessions::ClientInfo;
+use crate::dto::two_factor::{
+    RecoveryCodesResponse, TwoFactorCodeRequest, TwoFactorEnrollmentResponse, TwoFactorLoginRequest,
+};
+
+use chrono::{Duration, Local};
+use entity::pending_login::{self, Entity as PendingLogin};
+use entity::recovery_code::{self, Entity as RecoveryCode};
+use entity::security_event::SecurityEventType;
+use entity::two_factor_auth::{self, Entity as TwoFactorAuth};
+use entity::user;
+use entity::Id;
+use rand::Rng;
+use sea_orm::prelude::Uuid;
+use sea_orm::sea_query::Expr;
+use sea_orm::ActiveValue::NotSet;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, QueryFilter, Set, TransactionTrait,
+};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// The issuer shown in authenticator apps next to the account name.
+const TOTP_ISSUER: &str = "Veryrezsi";
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECONDS: u64 = 30;
+/// Codes of this many neighbouring time steps are accepted as well, to tolerate clock drift.
+const TOTP_SKEW_STEPS: u64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+/// The second login step has to be completed within this many minutes after the password was verified.
+const PENDING_LOGIN_VALIDITY_MINUTES: i64 = 5;
+/// A pending login is dropped after this many wrong codes, the password has to be entered again.
+const PENDING_LOGIN_MAX_FAILED_ATTEMPTS: i32 = 5;
+
+/// Starts a TOTP enrollment by generating a new secret for the user.
+/// The enrollment only takes effect after it is confirmed with a code, an unconfirmed one is simply replaced.
+pub async fn enroll_two_factor(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<TwoFactorEnrollmentResponse, EnrollTwoFactorError> {
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(EnrollTwoFactorError::UserNotFound);
+    };
+    if let Some(two_factor_auth) = find_two_factor_auth(conn, user_id).await? {
+        if two_factor_auth.enabled {
+            return Err(EnrollTwoFactorError::AlreadyEnabled);
+        }
+        two_factor_auth.into_active_model().delete(conn).await?;
+    }
+
+    let Secret::Encoded(secret) = Secret::generate_secret().to_encoded() else {
+        unreachable!("an encoded secret should always be returned")
+    };
+    let two_factor_auth = two_factor_auth::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        secret: Set(secret),
+        enabled: Set(false),
+        last_used_step: Set(None),
+        created_at: Set(Local::now()),
+    };
+    let two_factor_auth = two_factor_auth.insert(conn).await?;
+
+    let otpauth_uri = build_totp(&two_factor_auth.secret, user.email).get_url();
+    Ok(TwoFactorEnrollmentResponse {
+        secret: two_factor_auth.secret,
+        otpauth_uri,
+    })
+}
+
+/// Enables two-factor authentication if the code is valid for the enrolled secret.
+/// Returns a new set of recovery codes, only their hashes are stored.
+pub async fn confirm_two_factor(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    req: TwoFactorCodeRequest,
+) -> Result<RecoveryCodesResponse, ConfirmTwoFactorError> {
+    let Some(two_factor_auth) = find_two_factor_auth(conn, user_id).await? else {
+        return Err(ConfirmTwoFactorError::NotEnrolled);
+    };
+    if two_factor_auth.enabled {
+        return Err(ConfirmTwoFactorError::AlreadyEnabled);
+    }
+    let Some(step) = verify_totp_code(&two_factor_auth, &req.code) else {
+        return Err(ConfirmTwoFactorError::InvalidCode);
+    };
+
+    let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+    let recovery_code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| hash_recovery_code(code))
+        .collect();
+    conn.transaction::<_, (), ConfirmTwoFactorError>(|txn| {
+        Box::pin(async move {
+            let mut two_factor_auth = two_factor_auth.into_active_model();
+            two_factor_auth.enabled = Set(true);
+            two_factor_auth.last_used_step = Set(Some(step));
+            two_factor_auth.update(txn).await?;
+            RecoveryCode::delete_many()
+                .filter(recovery_code::Column::UserId.eq(user_id))
+                .exec(txn)
+                .await?;
+            RecoveryCode::insert_many(recovery_code_hashes.into_iter().map(|code_hash| {
+                recovery_code::ActiveModel {
+                    id: NotSet,
+                    user_id: Set(user_id),
+                    code_hash: Set(code_hash),
+                }
+            }))
+            .exec(txn)
+            .await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(RecoveryCodesResponse { recovery_codes })
+}
+
+/// Turns off two-factor authentication, which requires either a valid TOTP code or a recovery code.
+pub async fn disable_two_factor(
+    conn: &DatabaseConnection,
+    client: &ClientInfo,
+    user_id: Id,
+    req: TwoFactorCodeRequest,
+) -> Result<(), DisableTwoFactorError> {
+    let Some(two_factor_auth) = find_two_factor_auth(conn, user_id)
+        .await?
+        .filter(|two_factor_auth| two_factor_auth.enabled) else {
+        return Err(DisableTwoFactorError::NotEnabled);
+    };
+    if !verify_second_factor(conn, client, &two_factor_auth, &req.code).await? {
+        return Err(DisableTwoFactorError::InvalidCode);
+    }
+
+    conn.transaction::<_, (), DisableTwoFactorError>(|txn| {
+        Box::pin(async move {
+            RecoveryCode::delete_many()
+                .filter(recovery_code::Column::UserId.eq(user_id))
+                .exec(txn)
+                .await?;
+            two_factor_auth.into_active_model().delete(txn).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Decides whether a login with a verified password needs a second step.
+/// If the user has two-factor authentication enabled, a pending login is created and its token is returned,
+/// which has to be completed with a code before a session is issued.
+pub async fn start_pending_login(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<Option<String>, DbErr> {
+    if !is_two_factor_enabled(conn, user_id).await? {
+        return Ok(None);
+    }
+
+    PendingLogin::delete_many()
+        .filter(pending_login::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await?;
+    let token = Uuid::new_v4().to_string();
+    let pending_login = pending_login::ActiveModel {
+        id: NotSet,
+        token_hash: Set(hash_token(&token)),
+        user_id: Set(user_id),
+        expires_at: Set(Local::now() + Duration::minutes(PENDING_LOGIN_VALIDITY_MINUTES)),
+        failed_attempts: Set(0),
+    };
+    pending_login.insert(conn).await?;
+    Ok(Some(token))
+}
+
+/// Completes the second login step with a TOTP code or a recovery code and returns the id of the user to log in.
+/// The pending login is used up on success, and dropped after too many wrong codes.
+/// Wrong codes are counted as failed logins of the account and the ip address, so new pending logins cannot be used to keep guessing.
+/// The failed logins of the account are only forgotten when this step succeeds.
+pub async fn complete_pending_login(
+    config: &AppConfig,
+    conn: &DatabaseConnection,
+    client: &ClientInfo,
+    req: TwoFactorLoginRequest,
+) -> Result<Id, CompleteTwoFactorLoginError> {
+    let Some(pending_login) = PendingLogin::find()
+        .filter(pending_login::Column::TokenHash.eq(hash_token(&req.pending_token)))
+        .one(conn)
+        .await? else {
+        return Err(CompleteTwoFactorLoginError::InvalidPendingLogin);
+    };
+    if pending_login.expires_at < Local::now() {
+        pending_login.into_active_model().delete(conn).await?;
+        return Err(CompleteTwoFactorLoginError::InvalidPendingLogin);
+    }
+    let Some(two_factor_auth) = find_two_factor_auth(conn, pending_login.user_id)
+        .await?
+        .filter(|two_factor_auth| two_factor_auth.enabled) else {
+        return Err(CompleteTwoFactorLoginError::InvalidPendingLogin);
+    };
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, pending_login.user_id).await? else {
+        return Err(CompleteTwoFactorLoginError::InvalidPendingLogin);
+    };
+    let rate_limit_config = &config.rate_limit_config;
+    let ip_address = client.ip_address.as_deref();
+    match find_login_block(conn, rate_limit_config, &user.email, ip_address).await? {
+        Some(LoginBlock::Backoff(seconds)) => {
+            return Err(CompleteTwoFactorLoginError::TooManyAttempts(seconds))
+        }
+        Some(LoginBlock::Locked(seconds)) => {
+            return Err(CompleteTwoFactorLoginError::TemporarilyLocked(seconds))
+        }
+        None => {}
+    }
+
+    if !verify_second_factor(conn, client, &two_factor_auth, &req.code).await? {
+        record_login_failure(conn, rate_limit_config, &user.email, ip_address).await?;
+        if pending_login.failed_attempts + 1 >= PENDING_LOGIN_MAX_FAILED_ATTEMPTS {
+            pending_login.into_active_model().delete(conn).await?;
+        } else {
+            PendingLogin::update_many()
+                .col_expr(
+                    pending_login::Column::FailedAttempts,
+                    Expr::col(pending_login::Column::FailedAttempts).add(1),
+                )
+                .filter(pending_login::Column::Id.eq(pending_login.id))
+                .exec(conn)
+                .await?;
+        }
+        return Err(CompleteTwoFactorLoginError::InvalidCode);
+    }
+
+    let user_id = pending_login.user_id;
+    pending_login.into_active_model().delete(conn).await?;
+    reset_login_failures(conn, &user.email).await?;
+    Ok(user_id)
+}
+
+/// Whether the user has to complete a second login step after the password.
+pub(super) async fn is_two_factor_enabled(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<bool, DbErr> {
+    let two_factor_auth = find_two_factor_auth(conn, user_id).await?;
+    Ok(matches!(two_factor_auth, Some(two_factor_auth) if two_factor_auth.enabled))
+}
+
+async fn find_two_factor_auth<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Id,
+) -> Result<Option<two_factor_auth::Model>, DbErr> {
+    TwoFactorAuth::find()
+        .filter(two_factor_auth::Column::UserId.eq(user_id))
+        .one(conn)
+        .await
+}
+
+/// Checks the code as a TOTP code first, then as a recovery code.
+/// A matching TOTP code cannot be reused, and a matching recovery code is deleted.
+/// A wrong code is recorded in the audit log of the user, like a failed password login.
+async fn verify_second_factor(
+    conn: &DatabaseConnection,
+    client: &ClientInfo,
+    two_factor_auth: &two_factor_auth::Model,
+    code: &str,
+) -> Result<bool, DbErr> {
+    if let Some(step) = verify_totp_code(two_factor_auth, code) {
+        TwoFactorAuth::update_many()
+            .col_expr(
+                two_factor_auth::Column::LastUsedStep,
+                Expr::value(Some(step)),
+            )
+            .filter(two_factor_auth::Column::Id.eq(two_factor_auth.id))
+            .exec(conn)
+            .await?;
+        return Ok(true);
+    }
+
+    let Some(recovery_code) = RecoveryCode::find()
+        .filter(recovery_code::Column::UserId.eq(two_factor_auth.user_id))
+        .filter(recovery_code::Column::CodeHash.eq(hash_recovery_code(code)))
+        .one(conn)
+        .await? else {
+        record_security_event(
+            conn,
+            two_factor_auth.user_id,
+            SecurityEventType::TwoFactorFailed,
+            client,
+        )
+        .await?;
+        return Ok(false);
+    };
+    recovery_code.into_active_model().delete(conn).await?;
+    Ok(true)
+}
+
+/// Returns the time step the code belongs to, if it is valid around the current time and newer than the last used one.
+fn verify_totp_code(two_factor_auth: &two_factor_auth::Model, code: &str) -> Option<i64> {
+    let totp = build_totp(&two_factor_auth.secret, String::new());
+    let current_step = Local::now().timestamp() as u64 / TOTP_STEP_SECONDS;
+    (current_step.saturating_sub(TOTP_SKEW_STEPS)..=current_step + TOTP_SKEW_STEPS)
+        .map(|step| step as i64)
+        .filter(|&step| Some(step) > two_factor_auth.last_used_step)
+        .find(|&step| totp.generate(step as u64 * TOTP_STEP_SECONDS) == code.trim())
+}
+
+fn build_totp(secret: &str, account_name: String) -> TOTP {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .expect("stored totp secret should be valid base32");
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        0,
+        TOTP_STEP_SECONDS,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account_name,
+    )
+    .expect("totp parameters should be valid")
+}
+
+/// Generates a random recovery code in the form of `xxxx-xxxx-xxxx-xxxx`, made of 64 random bits.
+fn generate_recovery_code() -> String {
+    let random = hex::encode(rand::thread_rng().gen::<[u8; 8]>());
+    random
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Recovery codes are random enough, so a fast hash is sufficient. Dashes and letter case do not matter.
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    hash_token(&normalized)
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum EnrollTwoFactorError {
+        #[error("user not found")]
+        UserNotFound,
+        #[error("two-factor authentication is already enabled")]
+        AlreadyEnabled,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ConfirmTwoFactorError {
+        #[error("two-factor authentication enrollment was not started")]
+        NotEnrolled,
+        #[error("two-factor authentication is already enabled")]
+        AlreadyEnabled,
+        #[error("invalid code")]
+        InvalidCode,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<ConfirmTwoFactorError>> for ConfirmTwoFactorError {
+        fn from(e: TransactionError<ConfirmTwoFactorError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DisableTwoFactorError {
+        #[error("two-factor authentication is not enabled")]
+        NotEnabled,
+        #[error("invalid code")]
+        InvalidCode,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DisableTwoFactorError>> for DisableTwoFactorError {
+        fn from(e: TransactionError<DisableTwoFactorError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum CompleteTwoFactorLoginError {
+        #[error("login expired, please log in again")]
+        InvalidPendingLogin,
+        #[error("invalid code")]
+        InvalidCode,
+        #[error("too many failed login attempts, try again in {0} seconds")]
+        TooManyAttempts(i64),
+        #[error("login is temporarily locked because of too many failed attempts, try again in {0} seconds")]
+        TemporarilyLocked(i64),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use chrono::{Duration, Local};
+    use entity::{pending_login, rate_limit, recovery_code, two_factor_auth, user};
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    use super::errors::{
+        CompleteTwoFactorLoginError, ConfirmTwoFactorError, DisableTwoFactorError,
+        EnrollTwoFactorError,
+    };
+    use super::{
+        build_totp, complete_pending_login, confirm_two_factor, disable_two_factor,
+        enroll_two_factor, generate_recovery_code, hash_recovery_code, start_pending_login,
+        RECOVERY_CODE_COUNT, TOTP_STEP_SECONDS,
+    };
+    use crate::dto::sessions::ClientInfo;
+    use crate::dto::two_factor::{TwoFactorCodeRequest, TwoFactorLoginRequest};
+    use crate::logic::common::tests::{
+        exec_ok, test_app_config, test_db_error, test_pending_login, test_recovery_code,
+        test_two_factor_auth, test_user, TEST_EMAIL, TEST_ID, TEST_STR, TEST_TOTP_SECRET,
+    };
+
+    fn current_code() -> String {
+        build_totp(TEST_TOTP_SECRET, String::new()).generate(Local::now().timestamp() as u64)
+    }
+
+    fn code_request(code: &str) -> TwoFactorCodeRequest {
+        TwoFactorCodeRequest {
+            code: code.to_string(),
+        }
+    }
+
+    fn not_enabled_two_factor_auth() -> two_factor_auth::Model {
+        two_factor_auth::Model {
+            enabled: false,
+            ..test_two_factor_auth()
+        }
+    }
+
+    #[tokio::test]
+    async fn enroll_two_factor_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<two_factor_auth::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_query_results(vec![vec![not_enabled_two_factor_auth()]])
+            // unconfirmed enrollment is replaced
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![not_enabled_two_factor_auth()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![not_enabled_two_factor_auth()]])
+            // already enabled
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            // user not found
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (enrolled, re_enrolled, already_enabled, user_not_found, db_error) = tokio::join!(
+            enroll_two_factor(&conn, TEST_ID),
+            enroll_two_factor(&conn, TEST_ID),
+            enroll_two_factor(&conn, TEST_ID),
+            enroll_two_factor(&conn, TEST_ID),
+            enroll_two_factor(&conn, TEST_ID),
+        );
+
+        let enrolled = enrolled.unwrap();
+        check!(enrolled.secret == TEST_TOTP_SECRET);
+        check!(enrolled
+            .otpauth_uri
+            .starts_with("otpauth://totp/Veryrezsi:"));
+        check!(enrolled.otpauth_uri.contains(TEST_TOTP_SECRET));
+        check!(re_enrolled.is_ok());
+        check!(already_enabled == Err(EnrollTwoFactorError::AlreadyEnabled));
+        check!(user_not_found == Err(EnrollTwoFactorError::UserNotFound));
+        check!(db_error == Err(EnrollTwoFactorError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn confirm_two_factor_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![
+                vec![not_enabled_two_factor_auth()],
+                vec![test_two_factor_auth()],
+            ])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // invalid code
+            .append_query_results(vec![vec![not_enabled_two_factor_auth()]])
+            // not enrolled
+            .append_query_results(vec![Vec::<two_factor_auth::Model>::new()])
+            // already enabled
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            // db error - on update
+            .append_query_results(vec![vec![not_enabled_two_factor_auth()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (confirmed, invalid_code, not_enrolled, already_enabled, db_error) = tokio::join!(
+            confirm_two_factor(&conn, TEST_ID, code_request(&current_code())),
+            confirm_two_factor(&conn, TEST_ID, code_request("000000x")),
+            confirm_two_factor(&conn, TEST_ID, code_request(&current_code())),
+            confirm_two_factor(&conn, TEST_ID, code_request(&current_code())),
+            confirm_two_factor(&conn, TEST_ID, code_request(&current_code())),
+        );
+
+        let recovery_codes = confirmed.unwrap().recovery_codes;
+        check!(recovery_codes.len() == RECOVERY_CODE_COUNT);
+        check!(invalid_code == Err(ConfirmTwoFactorError::InvalidCode));
+        check!(not_enrolled == Err(ConfirmTwoFactorError::NotEnrolled));
+        check!(already_enabled == Err(ConfirmTwoFactorError::AlreadyEnabled));
+        check!(db_error == Err(ConfirmTwoFactorError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn disable_two_factor_all_cases() {
+        let client = ClientInfo::default();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case - with totp code
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // happy case - with recovery code
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_query_results(vec![vec![test_recovery_code()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // invalid code
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_query_results(vec![Vec::<recovery_code::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            // not enabled
+            .append_query_results(vec![vec![not_enabled_two_factor_auth()]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (with_totp, with_recovery_code, invalid_code, not_enabled, db_error) = tokio::join!(
+            disable_two_factor(&conn, &client, TEST_ID, code_request(&current_code())),
+            disable_two_factor(&conn, &client, TEST_ID, code_request("abcd-abcd-abcd-abcd")),
+            disable_two_factor(&conn, &client, TEST_ID, code_request("abcd-abcd-abcd-abcd")),
+            disable_two_factor(&conn, &client, TEST_ID, code_request(&current_code())),
+            disable_two_factor(&conn, &client, TEST_ID, code_request(&current_code())),
+        );
+
+        check!(with_totp == Ok(()));
+        check!(with_recovery_code == Ok(()));
+        check!(invalid_code == Err(DisableTwoFactorError::InvalidCode));
+        check!(not_enabled == Err(DisableTwoFactorError::NotEnabled));
+        check!(db_error == Err(DisableTwoFactorError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn start_pending_login_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // two-factor authentication enabled
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_pending_login()]])
+            // only enrolled
+            .append_query_results(vec![vec![not_enabled_two_factor_auth()]])
+            // not enrolled
+            .append_query_results(vec![Vec::<two_factor_auth::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (pending, only_enrolled, not_enrolled, db_error) = tokio::join!(
+            start_pending_login(&conn, TEST_ID),
+            start_pending_login(&conn, TEST_ID),
+            start_pending_login(&conn, TEST_ID),
+            start_pending_login(&conn, TEST_ID),
+        );
+
+        let_assert!(Ok(Some(pending_token)) = pending);
+        check!(pending_token != TEST_STR);
+        check!(only_enrolled == Ok(None));
+        check!(not_enrolled == Ok(None));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn complete_pending_login_all_cases() {
+        let client = ClientInfo::default();
+        let expired_pending_login = pending_login::Model {
+            expires_at: Local::now()
+                .checked_sub_signed(Duration::minutes(1))
+                .unwrap(),
+            ..test_pending_login()
+        };
+        let exhausted_pending_login = pending_login::Model {
+            failed_attempts: 4,
+            ..test_pending_login()
+        };
+        let no_limits = Vec::<rate_limit::Model>::new;
+        let locked_account = rate_limit::Model {
+            id: TEST_ID,
+            key: format!("login:account:{TEST_EMAIL}"),
+            count: 5,
+            window_started_at: Local::now(),
+            blocked_until: Some(Local::now() + Duration::minutes(1)),
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case, the failed logins of the account are forgotten
+            .append_query_results(vec![vec![test_pending_login()]])
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![no_limits()])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // unknown pending login
+            .append_query_results(vec![Vec::<pending_login::Model>::new()])
+            // expired pending login
+            .append_query_results(vec![vec![expired_pending_login]])
+            .append_exec_results(vec![exec_ok()])
+            // two-factor authentication disabled meanwhile
+            .append_query_results(vec![vec![test_pending_login()]])
+            .append_query_results(vec![Vec::<two_factor_auth::Model>::new()])
+            // invalid code, counted as a failed login of the account
+            .append_query_results(vec![vec![test_pending_login()]])
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![Vec::<recovery_code::Model>::new()])
+            .append_query_results(vec![no_limits()])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // invalid code - too many failed attempts
+            .append_query_results(vec![vec![exhausted_pending_login]])
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![Vec::<recovery_code::Model>::new()])
+            .append_query_results(vec![no_limits()])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // account locked by earlier failed logins
+            .append_query_results(vec![vec![test_pending_login()]])
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![locked_account]])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+        let request = |code: &str| TwoFactorLoginRequest {
+            pending_token: TEST_STR.to_string(),
+            code: code.to_string(),
+        };
+
+        let config = &test_app_config();
+
+        let (
+            completed,
+            unknown,
+            expired,
+            disabled,
+            invalid_code,
+            too_many_attempts,
+            locked,
+            db_error,
+        ) = tokio::join!(
+            complete_pending_login(config, &conn, &client, request(&current_code())),
+            complete_pending_login(config, &conn, &client, request(&current_code())),
+            complete_pending_login(config, &conn, &client, request(&current_code())),
+            complete_pending_login(config, &conn, &client, request(&current_code())),
+            complete_pending_login(config, &conn, &client, request("abcd-abcd-abcd-abcd")),
+            complete_pending_login(config, &conn, &client, request("abcd-abcd-abcd-abcd")),
+            complete_pending_login(config, &conn, &client, request(&current_code())),
+            complete_pending_login(config, &conn, &client, request(&current_code())),
+        );
+
+        let invalid_pending_login = Err(CompleteTwoFactorLoginError::InvalidPendingLogin);
+        let invalid_code_error = Err(CompleteTwoFactorLoginError::InvalidCode);
+        check!(completed == Ok(TEST_ID));
+        check!(unknown == invalid_pending_login);
+        check!(expired == invalid_pending_login);
+        check!(disabled == invalid_pending_login);
+        check!(invalid_code == invalid_code_error);
+        check!(too_many_attempts == invalid_code_error);
+        check!(let Err(CompleteTwoFactorLoginError::TemporarilyLocked(_)) = locked);
+        check!(db_error == Err(CompleteTwoFactorLoginError::DatabaseError(test_db_error())));
+    }
+
+    #[test]
+    fn totp_code_cannot_be_reused() {
+        let current_step = Local::now().timestamp() as u64 / TOTP_STEP_SECONDS;
+        let used = two_factor_auth::Model {
+            last_used_step: Some(current_step as i64 + 1),
+            ..test_two_factor_auth()
+        };
+
+        check!(super::verify_totp_code(&test_two_factor_auth(), &current_code()).is_some());
+        check!(super::verify_totp_code(&used, &current_code()).is_none());
+    }
+
+    #[test]
+    fn recovery_codes_are_normalized_before_hashing() {
+        let code = generate_recovery_code();
+
+        check!(code.len() == 19);
+        check!(code.chars().all(|c| c == '-' || c.is_ascii_hexdigit()));
+        check!(hash_recovery_code(&code) == hash_recovery_code(&code.to_uppercase()));
+        check!(hash_recovery_code(&code) == hash_recovery_code(&code.replace('-', "")));
+        check!(hash_recovery_code(&code) != hash_recovery_code(&generate_recovery_code()));
+    }
+}
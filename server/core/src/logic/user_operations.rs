@@ -1,24 +1,65 @@
-use self::errors::{ActivateAccountError, AuthorizeUserError, SaveUserError, VerifyLoginError};
+use self::errors::{
+    ActivateAccountError, AuthorizeUserError, ChangeEmailError, ChangePasswordError,
+    ConfirmEmailChangeError, DeleteAccountError, DeleteUserError, ExportUserDataError,
+    ResendActivationError, ResetPasswordError, SaveUserError, SetUserActivatedError,
+    UpdateProfileError, VerifyLoginError,
+};
 
 use crate::config;
-use crate::dto::users::{LoginRequest, NewUserRequest, UserResponse};
-use crate::email::{render_template, send_mail, ACTIVATION_EMAIL_TEMPLATE};
+use crate::dto::sessions::ClientInfo;
+use crate::dto::users::{
+    ChangeEmailRequest, ChangePasswordRequest, DeleteAccountRequest, ForgotPasswordRequest,
+    LoginRequest, NewUserRequest, ResendActivationRequest, ResetPasswordRequest,
+    UpdateProfileRequest, UserAccountResponse, UserDataExportResponse, UserResponse,
+};
+use crate::email::{
+    render_template, send_mail, ACTIVATION_EMAIL_TEMPLATE, CREDENTIALS_CHANGED_EMAIL_TEMPLATE,
+    EMAIL_CHANGE_EMAIL_TEMPLATE, PASSWORD_RESET_EMAIL_TEMPLATE,
+};
+use crate::storage::Storage;
 use chrono::Duration;
 use entity::account_activation::{self, Entity as AccountActivation};
-use entity::user::{self, Entity as User};
-use entity::Id;
+use entity::attachment::{self, Entity as Attachment};
+use entity::email_change::{self, Entity as EmailChange};
+use entity::expense::{self, Entity as Expense};
+use entity::password_reset::{self, Entity as PasswordReset};
+use entity::security_event::SecurityEventType;
+use entity::transaction::{self, Entity as Transaction};
+use entity::user::{self, DateFormat, Entity as User, UserRole};
+use entity::{currency, Id};
 use lettre::AsyncTransport;
-use pwhash::bcrypt;
 use sea_orm::prelude::Uuid;
+use sea_orm::sea_query::{Expr, Query};
 use sea_orm::ActiveValue::NotSet;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel,
-    QueryFilter, Set, TransactionTrait,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, Set, TransactionError, TransactionTrait,
 };
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use tracing::{debug, error};
 
+use super::api_token_operations::revoke_api_tokens_by_user_id;
+use super::attachment_operations::delete_stored_files;
 use super::common::find_entity_by_id;
+use super::expense_operations::{delete_own_predefined_expenses, load_expenses_by_user_id};
+use super::household_operations::hand_over_household_expenses;
+use super::password_hashing::{hash_password, needs_rehash, verify_password};
+use super::rate_limit_operations::{
+    find_login_block, record_login_failure, reset_login_failures, try_consume_registration_mail,
+    LoginBlock,
+};
+use super::security_event_operations::record_security_event;
+use super::session_operations::revoke_sessions_by_user_id;
+use super::two_factor_operations::is_two_factor_enabled;
+
+/// Activation tokens can be used for this many days after they were issued.
+const ACTIVATION_VALIDITY_DAYS: i64 = 1;
+/// Password reset tokens can be used for this many hours after they were requested.
+const PASSWORD_RESET_VALIDITY_HOURS: i64 = 1;
+/// Email change confirmation tokens can be used for this many hours after they were requested.
+const EMAIL_CHANGE_VALIDITY_HOURS: i64 = 24;
 
 pub async fn find_user_by_id(
     conn: &DatabaseConnection,
@@ -30,29 +71,317 @@ pub async fn find_user_by_id(
     Ok(user)
 }
 
+/// Replaces the profile preferences of the user, the preferred currency has to exist if it is set.
+pub async fn update_profile(
+    conn: &DatabaseConnection,
+    user_id: Id,
+    req: UpdateProfileRequest,
+) -> Result<(), UpdateProfileError> {
+    if let Some(currency_id) = req.preferred_currency_id {
+        let Some(_) = find_entity_by_id::<currency::Entity>(conn, currency_id).await? else {
+            return Err(UpdateProfileError::InvalidCurrency);
+        };
+    }
+    User::update_many()
+        .col_expr(
+            user::Column::PreferredCurrencyId,
+            Expr::value(req.preferred_currency_id),
+        )
+        .col_expr(user::Column::Locale, Expr::value(req.locale))
+        .col_expr(user::Column::Timezone, Expr::value(req.timezone))
+        .col_expr(user::Column::DateFormat, Expr::value(req.date_format))
+        .col_expr(
+            user::Column::FirstDayOfWeek,
+            Expr::value(req.first_day_of_week),
+        )
+        .filter(user::Column::Id.eq(user_id))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// Finds the date format preferred by the user, dates sent by them are parsed and rendered with it.
+pub(super) async fn find_date_format(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<DateFormat, DbErr> {
+    let date_format = find_entity_by_id::<user::Entity>(conn, user_id)
+        .await?
+        .map(|user| user.date_format)
+        .unwrap_or_default();
+    Ok(date_format)
+}
+
+/// Collects every personal data stored about the user: the profile and the expenses with their transactions and predefined expenses.
+pub async fn export_user_data(
+    conn: &DatabaseConnection,
+    user_id: Id,
+) -> Result<UserDataExportResponse, ExportUserDataError> {
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(ExportUserDataError::UserNotFound);
+    };
+    let expenses = load_expenses_by_user_id(conn, user_id, user.date_format).await?;
+    Ok((user, expenses).into())
+}
+
+/// Deletes the user and everything belonging to them, the current password has to be provided as a confirmation.
+/// The files of the deleted attachments are removed from the storage after the deletion is committed.
+pub async fn delete_account(
+    conn: &DatabaseConnection,
+    storage: &dyn Storage,
+    user_id: Id,
+    req: DeleteAccountRequest,
+) -> Result<(), DeleteAccountError> {
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(DeleteAccountError::UserNotFound);
+    };
+    if !verify_password(&req.password, &user.pw_hash) {
+        return Err(DeleteAccountError::IncorrectPassword);
+    };
+
+    let storage_keys = conn
+        .transaction::<_, Vec<String>, DeleteAccountError>(|txn| {
+            Box::pin(async move { Ok(delete_user_with_data(txn, user).await?) })
+        })
+        .await?;
+    delete_stored_files(storage, &storage_keys).await;
+    Ok(())
+}
+
+/// Lists every user with the state of their account, for administrators.
+pub async fn find_users(conn: &DatabaseConnection) -> Result<Vec<UserAccountResponse>, DbErr> {
+    let users = User::find()
+        .order_by_asc(user::Column::Id)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|user| user.into())
+        .collect();
+    Ok(users)
+}
+
+/// Activates or deactivates the account of a user by an administrator, who cannot change their own account.
+/// Activation removes the pending activation links, so the account is not swept as a stale unactivated one.
+/// Deactivation logs the user out everywhere and revokes the API tokens, as a deactivated user cannot log in anymore.
+pub async fn set_user_activated(
+    conn: &DatabaseConnection,
+    admin_id: Id,
+    user_id: Id,
+    activated: bool,
+) -> Result<(), SetUserActivatedError> {
+    if admin_id == user_id {
+        return Err(SetUserActivatedError::OwnAccount);
+    }
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(SetUserActivatedError::UserNotFound);
+    };
+
+    conn.transaction::<_, (), SetUserActivatedError>(|txn| {
+        Box::pin(async move {
+            let mut user = user.into_active_model();
+            user.activated = Set(activated);
+            user.update(txn).await?;
+            if activated {
+                AccountActivation::delete_many()
+                    .filter(account_activation::Column::UserId.eq(user_id))
+                    .exec(txn)
+                    .await?;
+            } else {
+                revoke_sessions_by_user_id(txn, user_id, None).await?;
+                revoke_api_tokens_by_user_id(txn, user_id).await?;
+            }
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Deletes the account of a user and everything belonging to them by an administrator, who cannot delete their own account.
+/// The files of the deleted attachments are removed from the storage after the deletion is committed.
+pub async fn delete_user(
+    conn: &DatabaseConnection,
+    storage: &dyn Storage,
+    admin_id: Id,
+    user_id: Id,
+) -> Result<(), DeleteUserError> {
+    if admin_id == user_id {
+        return Err(DeleteUserError::OwnAccount);
+    }
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(DeleteUserError::UserNotFound);
+    };
+
+    let storage_keys = conn
+        .transaction::<_, Vec<String>, DeleteUserError>(|txn| {
+            Box::pin(async move { Ok(delete_user_with_data(txn, user).await?) })
+        })
+        .await?;
+    delete_stored_files(storage, &storage_keys).await;
+    Ok(())
+}
+
+/// Tells whether the user has the administrator role, an unknown user is not an administrator.
+pub async fn is_admin(conn: &DatabaseConnection, user_id: Id) -> Result<bool, DbErr> {
+    let user = find_entity_by_id::<user::Entity>(conn, user_id).await?;
+    Ok(matches!(user, Some(user) if user.role == UserRole::Admin))
+}
+
+/// Grants the administrator role to the user with the given email, so the first administrator can be set up from the configuration.
+/// Returns false if there is no user with the email.
+pub async fn grant_admin_role_by_email(
+    conn: &DatabaseConnection,
+    email: &str,
+) -> Result<bool, DbErr> {
+    let Some(user) = User::find()
+        .filter(user::Column::Email.eq(email))
+        .one(conn)
+        .await?
+    else {
+        return Ok(false);
+    };
+    if user.role != UserRole::Admin {
+        User::update_many()
+            .col_expr(user::Column::Role, Expr::value(UserRole::Admin))
+            .filter(user::Column::Id.eq(user.id))
+            .exec(conn)
+            .await?;
+    }
+    Ok(true)
+}
+
+/// Deletes the user and everything belonging to them, should be run inside a database transaction.
+/// The household expenses and predefined expenses of the user are handed over to a remaining member of the household first,
+/// so the others keep them. The rest of the predefined expenses of the user are deleted, except the public ones.
+/// The expense related tables have no cascade rules, so the remaining transactions, expenses and activations are deleted explicitly,
+/// while the rest is removed by their cascading foreign keys.
+/// Returns the storage keys of the deleted attachments, whose files should be removed once the transaction is committed.
+async fn delete_user_with_data<C: ConnectionTrait>(
+    txn: &C,
+    user: user::Model,
+) -> Result<Vec<String>, DbErr> {
+    let user_id = user.id;
+    hand_over_household_expenses(txn, user_id).await?;
+    delete_own_predefined_expenses(txn, user_id).await?;
+    let expense_ids = Query::select()
+        .column(expense::Column::Id)
+        .from(Expense)
+        .and_where(Expr::col(expense::Column::UserId).eq(user_id))
+        .to_owned();
+    let storage_keys = Attachment::find()
+        .filter(
+            Condition::any()
+                .add(attachment::Column::UserId.eq(user_id))
+                .add(attachment::Column::ExpenseId.in_subquery(expense_ids.clone()))
+                .add(
+                    attachment::Column::TransactionId.in_subquery(
+                        Query::select()
+                            .column(transaction::Column::Id)
+                            .from(Transaction)
+                            .and_where(
+                                Expr::col(transaction::Column::ExpenseId)
+                                    .in_subquery(expense_ids.clone()),
+                            )
+                            .to_owned(),
+                    ),
+                ),
+        )
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|attachment| attachment.storage_key)
+        .collect();
+    Transaction::delete_many()
+        .filter(transaction::Column::ExpenseId.in_subquery(expense_ids))
+        .exec(txn)
+        .await?;
+    Expense::delete_many()
+        .filter(expense::Column::UserId.eq(user_id))
+        .exec(txn)
+        .await?;
+    AccountActivation::delete_many()
+        .filter(account_activation::Column::UserId.eq(user_id))
+        .exec(txn)
+        .await?;
+    user.into_active_model().delete(txn).await?;
+    Ok(storage_keys)
+}
+
+/// Checks the credentials of a login attempt.
+/// Failed attempts are counted per account and per ip address, and too many of them block further attempts for a while.
+/// A legacy bcrypt hash or an Argon2 hash weaker than configured is replaced with a new hash of the password on success.
+/// A wrong password for an existing account is recorded into its audit log.
 pub async fn verify_login(
+    config: &config::AppConfig,
     conn: &DatabaseConnection,
+    client: &ClientInfo,
     req: LoginRequest,
 ) -> Result<Id, VerifyLoginError> {
+    let rate_limit_config = &config.rate_limit_config;
+    let ip_address = client.ip_address.as_deref();
+    match find_login_block(conn, rate_limit_config, &req.email, ip_address).await? {
+        Some(LoginBlock::Backoff(seconds)) => {
+            return Err(VerifyLoginError::TooManyAttempts(seconds))
+        }
+        Some(LoginBlock::Locked(seconds)) => {
+            return Err(VerifyLoginError::TemporarilyLocked(seconds))
+        }
+        None => {}
+    }
+
     let Some(user) = User::find()
         .filter(user::Column::Email.eq(&req.email))
         .one(conn)
         .await? else {
+            record_login_failure(conn, rate_limit_config, &req.email, ip_address).await?;
             return Err( VerifyLoginError::IncorrectCredentials);
         };
     if !user.activated {
         return Err(VerifyLoginError::AccountNotActivated);
     };
-    if !bcrypt::verify(req.password, &user.pw_hash) {
+    if !verify_password(&req.password, &user.pw_hash) {
+        record_login_failure(conn, rate_limit_config, &req.email, ip_address).await?;
+        record_security_event(conn, user.id, SecurityEventType::LoginFailed, client).await?;
         return Err(VerifyLoginError::IncorrectCredentials);
     };
+    if needs_rehash(&config.password_hash_config, &user.pw_hash) {
+        rehash_password(config, conn, user.id, &req.password).await?;
+    }
+    // with a second factor the failures are only forgotten once the second step succeeds too
+    if !is_two_factor_enabled(conn, user.id).await? {
+        reset_login_failures(conn, &req.email).await?;
+    }
     Ok(user.id)
 }
 
+/// Stores a new hash of the password, a failed hashing is only logged, so it does not prevent the login.
+async fn rehash_password(
+    config: &config::AppConfig,
+    conn: &DatabaseConnection,
+    user_id: Id,
+    password: &str,
+) -> Result<(), DbErr> {
+    let pw_hash = match hash_password(&config.password_hash_config, password) {
+        Ok(hashed) => hashed,
+        Err(error) => {
+            error!("Password of user {user_id} cannot be rehashed: {error}");
+            return Ok(());
+        }
+    };
+    User::update_many()
+        .col_expr(user::Column::PwHash, Expr::value(pw_hash))
+        .filter(user::Column::Id.eq(user_id))
+        .exec(conn)
+        .await?;
+    debug!("Password hash of user {user_id} has been upgraded");
+    Ok(())
+}
+
 pub async fn save_user<M>(
     config: &config::AppConfig,
     conn: &DatabaseConnection,
     mail_transport: Arc<M>,
+    client: &ClientInfo,
     req: NewUserRequest,
 ) -> Result<UserResponse, SaveUserError>
 where
@@ -65,13 +394,18 @@ where
         .await? else {
             return Err(SaveUserError::UserAlreadyExists)
         };
+    let ip_address = client.ip_address.as_deref();
+    if !try_consume_registration_mail(conn, &config.rate_limit_config, ip_address).await? {
+        return Err(SaveUserError::TooManyRegistrations);
+    }
 
-    let pw_hash = match bcrypt::hash(req.password) {
+    let pw_hash = match hash_password(&config.password_hash_config, &req.password) {
         Ok(hashed) => hashed,
         Err(error) => return Err(SaveUserError::PasswordCannotBeHashed(format!("{error}"))),
     };
 
     let server_address = config.server_address;
+    let client = client.clone();
     let user = conn
         .transaction::<_, user::Model, SaveUserError>(|txn| {
             Box::pin(async move {
@@ -81,29 +415,26 @@ where
                     username: Set(req.username),
                     pw_hash: Set(pw_hash),
                     activated: NotSet,
+                    role: Set(UserRole::User),
+                    preferred_currency_id: NotSet,
+                    locale: NotSet,
+                    timezone: NotSet,
+                    date_format: NotSet,
+                    first_day_of_week: NotSet,
                 };
                 let user = user.insert(txn).await?;
+                record_security_event(txn, user.id, SecurityEventType::Registration, &client)
+                    .await?;
 
-                let activation = account_activation::ActiveModel {
-                    id: NotSet,
-                    token: Set(Uuid::new_v4().to_string()),
-                    user_id: Set(user.id),
-                    expiration: Set(chrono::Local::now()
-                        .checked_add_signed(Duration::days(1))
-                        .expect("we should not be this far ahead into the future Marty, the date overflowed the bounds")),
-                };
-                let activation = activation.insert(txn).await?;
-
-                let activation_link = format!(
-                    "http://{}/api/user/activate/{}",
-                    server_address, activation.token
-                );
-                let mut data = HashMap::new();
-                data.insert("username", &user.username);
-                data.insert("activation_link", &activation_link);
-                let body = render_template(ACTIVATION_EMAIL_TEMPLATE, &data);
-                let email = user.email.clone();
-                match send_mail(mail_transport, email, "Veryrezsi account activation", body).await {
+                let activation = new_account_activation(user.id).insert(txn).await?;
+                match send_activation_email(
+                    mail_transport,
+                    server_address,
+                    &user,
+                    &activation.token,
+                )
+                .await
+                {
                     Ok(_) => Ok(user),
                     Err(reason) => Err(SaveUserError::EmailCannotBeSent(reason)),
                 }
@@ -115,6 +446,7 @@ where
 
 pub async fn activate_account(
     conn: &DatabaseConnection,
+    client: &ClientInfo,
     token: String,
 ) -> Result<(), ActivateAccountError> {
     let Some(account_activation) = AccountActivation::find()
@@ -134,13 +466,16 @@ pub async fn activate_account(
         return Err(ActivateAccountError::InvalidToken);
     };
 
+    let client = client.clone();
     conn.transaction::<_, (), ActivateAccountError>(|txn| {
         Box::pin(async move {
+            let user_id = user.id;
             let mut user = user.into_active_model();
             user.activated = Set(true);
             user.update(txn).await?;
             let activation = account_activation.into_active_model();
             activation.delete(txn).await?;
+            record_security_event(txn, user_id, SecurityEventType::Activation, &client).await?;
             Ok(())
         })
     })
@@ -148,118 +483,777 @@ pub async fn activate_account(
     Ok(())
 }
 
-/// Utility method to authorize if a user should be able to access a resource.
-/// Checks the equality of two `user_id`s.
-///
-/// # Errors
-///
-/// This function will return an error if the two ids are not equal.
-pub fn authorize_user(user_id: Id, user_id_in_resource: Id) -> Result<(), AuthorizeUserError> {
-    if user_id != user_id_in_resource {
-        return Err(AuthorizeUserError);
+/// Sends a new activation link to the not yet activated user with the given email, invalidating the earlier one.
+/// A new link can only be requested after the configured cooldown passed since the last one was issued.
+/// Unknown and already activated emails and throttled requests are silently ignored, so the response does not reveal
+/// whether an account exists.
+pub async fn resend_activation_email<M>(
+    config: &config::AppConfig,
+    conn: &DatabaseConnection,
+    mail_transport: Arc<M>,
+    req: ResendActivationRequest,
+) -> Result<(), ResendActivationError>
+where
+    M: AsyncTransport + Send + Sync + 'static,
+    <M as AsyncTransport>::Error: std::fmt::Debug,
+{
+    let Some(user) = User::find()
+        .filter(user::Column::Email.eq(&req.email))
+        .filter(user::Column::Activated.eq(false))
+        .one(conn)
+        .await? else {
+            debug!("Activation email resend requested for an unknown or already activated email");
+            return Ok(());
+        };
+
+    let last_activation = AccountActivation::find()
+        .filter(account_activation::Column::UserId.eq(user.id))
+        .one(conn)
+        .await?;
+    if let Some(last_activation) = last_activation {
+        let issued_at = last_activation.expiration - Duration::days(ACTIVATION_VALIDITY_DAYS);
+        let cooldown = Duration::minutes(config.account_config.activation_resend_cooldown_minutes);
+        if issued_at + cooldown > chrono::Local::now() {
+            debug!("Activation email resend throttled");
+            return Ok(());
+        }
     }
+
+    let server_address = config.server_address;
+    conn.transaction::<_, (), ResendActivationError>(|txn| {
+        Box::pin(async move {
+            AccountActivation::delete_many()
+                .filter(account_activation::Column::UserId.eq(user.id))
+                .exec(txn)
+                .await?;
+            let activation = new_account_activation(user.id).insert(txn).await?;
+            match send_activation_email(mail_transport, server_address, &user, &activation.token)
+                .await
+            {
+                Ok(_) => Ok(()),
+                Err(reason) => Err(ResendActivationError::EmailCannotBeSent(reason)),
+            }
+        })
+    })
+    .await?;
     Ok(())
 }
 
-pub mod errors {
-    use migration::DbErr;
-    use sea_orm::TransactionError;
-    use thiserror::Error;
-
-    #[derive(Error, Debug, PartialEq, Eq)]
-    pub enum VerifyLoginError {
-        #[error("account not activated")]
-        AccountNotActivated,
-        #[error("incorrect credentials")]
-        IncorrectCredentials,
-        #[error("database error: '{0}'")]
-        DatabaseError(#[from] DbErr),
+/// Deletes the users who did not activate their account within the configured grace period after their activation link expired.
+/// This frees up their email address, so it can be registered again. Returns the number of deleted users.
+pub async fn delete_stale_unactivated_users(
+    conn: &DatabaseConnection,
+    config: &config::AccountConfig,
+) -> Result<u64, DbErr> {
+    let cutoff =
+        chrono::Local::now() - Duration::hours(config.unactivated_account_grace_period_hours);
+    let stale_user_ids: Vec<Id> = AccountActivation::find()
+        .filter(account_activation::Column::Expiration.lt(cutoff))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|activation| activation.user_id)
+        .collect();
+    if stale_user_ids.is_empty() {
+        return Ok(0);
     }
 
-    #[derive(Error, Debug, PartialEq, Eq)]
-    pub enum SaveUserError {
-        #[error("user already exists")]
-        UserAlreadyExists,
-        #[error("{0}")]
-        PasswordCannotBeHashed(String),
-        #[error("{0}")]
-        EmailCannotBeSent(String),
-        #[error("database error: '{0}'")]
-        DatabaseError(#[from] DbErr),
-    }
+    let deleted = conn
+        .transaction::<_, u64, DbErr>(|txn| {
+            Box::pin(async move {
+                AccountActivation::delete_many()
+                    .filter(account_activation::Column::UserId.is_in(stale_user_ids.clone()))
+                    .exec(txn)
+                    .await?;
+                let result = User::delete_many()
+                    .filter(user::Column::Id.is_in(stale_user_ids))
+                    .filter(user::Column::Activated.eq(false))
+                    .exec(txn)
+                    .await?;
+                Ok(result.rows_affected)
+            })
+        })
+        .await
+        .map_err(|e| match e {
+            TransactionError::Connection(e) | TransactionError::Transaction(e) => e,
+        })?;
+    Ok(deleted)
+}
 
-    impl From<TransactionError<SaveUserError>> for SaveUserError {
-        fn from(e: TransactionError<SaveUserError>) -> Self {
-            match e {
-                TransactionError::Connection(e) => e.into(),
-                TransactionError::Transaction(e) => e,
-            }
-        }
+fn new_account_activation(user_id: Id) -> account_activation::ActiveModel {
+    account_activation::ActiveModel {
+        id: NotSet,
+        token: Set(Uuid::new_v4().to_string()),
+        user_id: Set(user_id),
+        expiration: Set(chrono::Local::now()
+            .checked_add_signed(Duration::days(ACTIVATION_VALIDITY_DAYS))
+            .expect("we should not be this far ahead into the future Marty, the date overflowed the bounds")),
     }
+}
 
-    #[derive(Error, Debug, PartialEq, Eq)]
-    pub enum ActivateAccountError {
-        #[error("invalid token")]
-        InvalidToken,
-        #[error("database error: '{0}'")]
-        DatabaseError(#[from] DbErr),
-    }
+async fn send_activation_email<M>(
+    mail_transport: Arc<M>,
+    server_address: SocketAddr,
+    user: &user::Model,
+    token: &str,
+) -> Result<(), String>
+where
+    M: AsyncTransport + Send + Sync + 'static,
+    <M as AsyncTransport>::Error: std::fmt::Debug,
+{
+    let activation_link = format!("http://{}/api/user/activate/{}", server_address, token);
+    let mut data = HashMap::new();
+    data.insert("username", &user.username);
+    data.insert("activation_link", &activation_link);
+    let body = render_template(ACTIVATION_EMAIL_TEMPLATE, &data);
+    send_mail(
+        mail_transport,
+        user.email.clone(),
+        "Veryrezsi account activation",
+        body,
+    )
+    .await
+}
 
-    impl From<TransactionError<ActivateAccountError>> for ActivateAccountError {
-        fn from(e: TransactionError<ActivateAccountError>) -> Self {
-            match e {
-                TransactionError::Connection(e) => e.into(),
-                TransactionError::Transaction(e) => e,
-            }
-        }
-    }
+/// Sends a password reset link to the user with the given email, invalidating any earlier link.
+/// The result is the same whether the user exists or not, so the endpoint cannot be used to discover registered emails.
+/// For the same reason the email is sent in the background and a failure is only logged.
+pub async fn request_password_reset<M>(
+    config: &config::AppConfig,
+    conn: &DatabaseConnection,
+    mail_transport: Arc<M>,
+    req: ForgotPasswordRequest,
+) -> Result<(), DbErr>
+where
+    M: AsyncTransport + Send + Sync + 'static,
+    <M as AsyncTransport>::Error: std::fmt::Debug,
+{
+    let Some(user) = User::find()
+        .filter(user::Column::Email.eq(&req.email))
+        .filter(user::Column::Activated.eq(true))
+        .one(conn)
+        .await? else {
+            debug!("Password reset requested for an unknown or not activated email");
+            return Ok(());
+        };
 
-    #[derive(Error, Debug, PartialEq, Eq)]
-    #[error("user is not authorized")]
-    pub struct AuthorizeUserError;
+    PasswordReset::delete_many()
+        .filter(password_reset::Column::UserId.eq(user.id))
+        .exec(conn)
+        .await?;
+    let password_reset = password_reset::ActiveModel {
+        id: NotSet,
+        token: Set(Uuid::new_v4().to_string()),
+        user_id: Set(user.id),
+        expiration: Set(chrono::Local::now()
+            .checked_add_signed(Duration::hours(PASSWORD_RESET_VALIDITY_HOURS))
+            .expect("we should not be this far ahead into the future Marty, the date overflowed the bounds")),
+    };
+    let password_reset = password_reset.insert(conn).await?;
+
+    let reset_link = format!(
+        "{}/password/reset/{}",
+        config.client_url, password_reset.token
+    );
+    let validity_hours = PASSWORD_RESET_VALIDITY_HOURS.to_string();
+    let mut data = HashMap::new();
+    data.insert("username", &user.username);
+    data.insert("reset_link", &reset_link);
+    data.insert("validity_hours", &validity_hours);
+    let body = render_template(PASSWORD_RESET_EMAIL_TEMPLATE, &data);
+    let email = user.email.clone();
+    tokio::spawn(async move {
+        if let Err(reason) =
+            send_mail(mail_transport, email, "Veryrezsi password reset", body).await
+        {
+            error!("Password reset email could not be sent: {reason}");
+        }
+    });
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use std::sync::Arc;
+/// Sets a new password for the user the reset token belongs to.
+/// The token is used up and every session and API token of the user is revoked, as the old password may have been compromised.
+pub async fn reset_password(
+    config: &config::AppConfig,
+    conn: &DatabaseConnection,
+    client: &ClientInfo,
+    req: ResetPasswordRequest,
+) -> Result<(), ResetPasswordError> {
+    let Some(password_reset) = PasswordReset::find()
+        .filter(password_reset::Column::Token.eq(req.token))
+        .one(conn)
+        .await? else {
+        return Err(ResetPasswordError::InvalidToken);
+    };
 
-    use assert2::check;
-    use chrono::Duration;
-    use entity::{account_activation, user};
-    use lettre::transport::stub::AsyncStubTransport;
-    use pwhash::bcrypt;
-    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+    if password_reset.expiration < chrono::Local::now() {
+        return Err(ResetPasswordError::InvalidToken);
+    }
 
-    use crate::dto::users::{LoginRequest, UserResponse};
-    use crate::logic::user_operations::errors::VerifyLoginError;
-    use crate::logic::user_operations::{find_user_by_id, verify_login};
-    use crate::{
-        dto::users::NewUserRequest,
-        logic::{
-            common::tests::{
-                test_account_activation, test_app_config, test_db_error, test_user, TEST_EMAIL,
-                TEST_ID, TEST_STR,
-            },
-            user_operations::{
-                activate_account, authorize_user,
-                errors::{ActivateAccountError, AuthorizeUserError, SaveUserError},
-                save_user,
-            },
-        },
+    let Some(user) = User::find_by_id(password_reset.user_id).one(conn).await? else {
+        return Err(ResetPasswordError::InvalidToken);
     };
 
-    #[tokio::test]
-    async fn find_user_by_id_all_cases() {
-        let conn = MockDatabase::new(DatabaseBackend::MySql)
-            .append_query_results(vec![vec![test_user()], vec![]])
-            .append_query_errors(vec![test_db_error()])
-            .into_connection();
+    let pw_hash = match hash_password(&config.password_hash_config, &req.password) {
+        Ok(hashed) => hashed,
+        Err(error) => {
+            return Err(ResetPasswordError::PasswordCannotBeHashed(format!(
+                "{error}"
+            )))
+        }
+    };
 
-        let (user, not_found, db_error) = tokio::join!(
-            find_user_by_id(&conn, TEST_ID),
-            find_user_by_id(&conn, TEST_ID),
-            find_user_by_id(&conn, TEST_ID)
-        );
+    let client = client.clone();
+    conn.transaction::<_, (), ResetPasswordError>(|txn| {
+        Box::pin(async move {
+            let user_id = user.id;
+            let mut user = user.into_active_model();
+            user.pw_hash = Set(pw_hash);
+            user.update(txn).await?;
+            PasswordReset::delete_many()
+                .filter(password_reset::Column::UserId.eq(user_id))
+                .exec(txn)
+                .await?;
+            revoke_sessions_by_user_id(txn, user_id, None).await?;
+            revoke_api_tokens_by_user_id(txn, user_id).await?;
+            record_security_event(txn, user_id, SecurityEventType::PasswordReset, &client).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Changes the password of a logged in user, who has to provide the current password as well.
+/// Every other session and every API token of the user is revoked and the user is notified by email.
+pub async fn change_password<M>(
+    config: &config::AppConfig,
+    conn: &DatabaseConnection,
+    mail_transport: Arc<M>,
+    client: &ClientInfo,
+    user_id: Id,
+    session_id: Id,
+    req: ChangePasswordRequest,
+) -> Result<(), ChangePasswordError>
+where
+    M: AsyncTransport + Send + Sync + 'static,
+    <M as AsyncTransport>::Error: std::fmt::Debug,
+{
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(ChangePasswordError::UserNotFound);
+    };
+    if !verify_password(&req.current_password, &user.pw_hash) {
+        return Err(ChangePasswordError::IncorrectPassword);
+    };
+
+    let pw_hash = match hash_password(&config.password_hash_config, &req.password) {
+        Ok(hashed) => hashed,
+        Err(error) => {
+            return Err(ChangePasswordError::PasswordCannotBeHashed(format!(
+                "{error}"
+            )))
+        }
+    };
+
+    let (email, username) = (user.email.clone(), user.username.clone());
+    let client = client.clone();
+    conn.transaction::<_, (), ChangePasswordError>(|txn| {
+        Box::pin(async move {
+            let mut user = user.into_active_model();
+            user.pw_hash = Set(pw_hash);
+            user.update(txn).await?;
+            revoke_sessions_by_user_id(txn, user_id, Some(session_id)).await?;
+            revoke_api_tokens_by_user_id(txn, user_id).await?;
+            record_security_event(txn, user_id, SecurityEventType::PasswordChange, &client).await?;
+            Ok(())
+        })
+    })
+    .await?;
+
+    notify_credentials_changed(
+        mail_transport,
+        email,
+        &username,
+        "The password of your Veryrezsi account has been changed.",
+    );
+    Ok(())
+}
+
+/// Starts changing the email of a logged in user, who has to provide the current password as well.
+/// The new address has to be confirmed through the link sent to it, until then the old address stays in use.
+/// Every other session of the user is revoked and the old address is notified.
+pub async fn request_email_change<M>(
+    config: &config::AppConfig,
+    conn: &DatabaseConnection,
+    mail_transport: Arc<M>,
+    client: &ClientInfo,
+    user_id: Id,
+    session_id: Id,
+    req: ChangeEmailRequest,
+) -> Result<(), ChangeEmailError>
+where
+    M: AsyncTransport + Send + Sync + 'static,
+    <M as AsyncTransport>::Error: std::fmt::Debug,
+{
+    let Some(user) = find_entity_by_id::<user::Entity>(conn, user_id).await? else {
+        return Err(ChangeEmailError::UserNotFound);
+    };
+    if !verify_password(&req.password, &user.pw_hash) {
+        return Err(ChangeEmailError::IncorrectPassword);
+    };
+    let None = User::find()
+        .filter(user::Column::Email.eq(&req.email))
+        .one(conn)
+        .await? else {
+            return Err(ChangeEmailError::EmailAlreadyInUse);
+        };
+
+    let client_url = config.client_url.clone();
+    let notification_transport = mail_transport.clone();
+    let (email, username) = (user.email.clone(), user.username.clone());
+    let client = client.clone();
+    conn.transaction::<_, (), ChangeEmailError>(|txn| {
+        Box::pin(async move {
+            EmailChange::delete_many()
+                .filter(email_change::Column::UserId.eq(user.id))
+                .exec(txn)
+                .await?;
+            let email_change = email_change::ActiveModel {
+                id: NotSet,
+                token: Set(Uuid::new_v4().to_string()),
+                user_id: Set(user.id),
+                new_email: Set(req.email),
+                expiration: Set(chrono::Local::now()
+                    .checked_add_signed(Duration::hours(EMAIL_CHANGE_VALIDITY_HOURS))
+                    .expect("we should not be this far ahead into the future Marty, the date overflowed the bounds")),
+            };
+            let email_change = email_change.insert(txn).await?;
+            revoke_sessions_by_user_id(txn, user.id, Some(session_id)).await?;
+            record_security_event(txn, user.id, SecurityEventType::EmailChangeRequest, &client)
+                .await?;
+
+            let confirmation_link = format!(
+                "{}/email/confirm/{}",
+                client_url, email_change.token
+            );
+            let mut data = HashMap::new();
+            data.insert("username", &user.username);
+            data.insert("confirmation_link", &confirmation_link);
+            let body = render_template(EMAIL_CHANGE_EMAIL_TEMPLATE, &data);
+            match send_mail(mail_transport, email_change.new_email, "Veryrezsi email address confirmation", body).await {
+                Ok(_) => Ok(()),
+                Err(reason) => Err(ChangeEmailError::EmailCannotBeSent(reason)),
+            }
+        })
+    })
+    .await?;
+
+    notify_credentials_changed(
+        notification_transport,
+        email,
+        &username,
+        "A new email address has been requested for your Veryrezsi account, this address stays in use until the new one is confirmed.",
+    );
+    Ok(())
+}
+
+/// Confirms the new email address of a user, which replaces the old one.
+pub async fn confirm_email_change(
+    conn: &DatabaseConnection,
+    client: &ClientInfo,
+    token: String,
+) -> Result<(), ConfirmEmailChangeError> {
+    let Some(email_change) = EmailChange::find()
+        .filter(email_change::Column::Token.eq(token))
+        .one(conn)
+        .await? else {
+        return Err(ConfirmEmailChangeError::InvalidToken);
+    };
+
+    if email_change.expiration < chrono::Local::now() {
+        return Err(ConfirmEmailChangeError::InvalidToken);
+    }
+
+    let Some(user) = User::find_by_id(email_change.user_id).one(conn).await? else {
+        return Err(ConfirmEmailChangeError::InvalidToken);
+    };
+    let None = User::find()
+        .filter(user::Column::Email.eq(&email_change.new_email))
+        .one(conn)
+        .await? else {
+            return Err(ConfirmEmailChangeError::EmailAlreadyInUse);
+        };
+
+    let client = client.clone();
+    conn.transaction::<_, (), ConfirmEmailChangeError>(|txn| {
+        Box::pin(async move {
+            let user_id = user.id;
+            let mut user = user.into_active_model();
+            user.email = Set(email_change.new_email);
+            user.update(txn).await?;
+            EmailChange::delete_many()
+                .filter(email_change::Column::UserId.eq(user_id))
+                .exec(txn)
+                .await?;
+            record_security_event(txn, user_id, SecurityEventType::EmailChange, &client).await?;
+            Ok(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Notifies a user about a change of their credentials.
+/// The email is sent in the background, a failure is only logged as the change itself has already happened.
+fn notify_credentials_changed<M>(
+    mail_transport: Arc<M>,
+    email: String,
+    username: &str,
+    change_description: &str,
+) where
+    M: AsyncTransport + Send + Sync + 'static,
+    <M as AsyncTransport>::Error: std::fmt::Debug,
+{
+    let mut data = HashMap::new();
+    data.insert("username", username);
+    data.insert("change_description", change_description);
+    let body = render_template(CREDENTIALS_CHANGED_EMAIL_TEMPLATE, &data);
+    tokio::spawn(async move {
+        if let Err(reason) = send_mail(
+            mail_transport,
+            email,
+            "Veryrezsi account credentials changed",
+            body,
+        )
+        .await
+        {
+            error!("Credentials changed email could not be sent: {reason}");
+        }
+    });
+}
+
+/// Utility method to authorize if a user should be able to access a resource.
+/// Checks the equality of two `user_id`s.
+///
+/// # Errors
+///
+/// This function will return an error if the two ids are not equal.
+pub fn authorize_user(user_id: Id, user_id_in_resource: Id) -> Result<(), AuthorizeUserError> {
+    if user_id != user_id_in_resource {
+        return Err(AuthorizeUserError);
+    }
+    Ok(())
+}
+
+pub mod errors {
+    use migration::DbErr;
+    use sea_orm::TransactionError;
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum UpdateProfileError {
+        #[error("preferred currency is invalid")]
+        InvalidCurrency,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum VerifyLoginError {
+        #[error("account not activated")]
+        AccountNotActivated,
+        #[error("incorrect credentials")]
+        IncorrectCredentials,
+        #[error("too many failed login attempts, try again in {0} seconds")]
+        TooManyAttempts(i64),
+        #[error("login is temporarily locked because of too many failed attempts, try again in {0} seconds")]
+        TemporarilyLocked(i64),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum SaveUserError {
+        #[error("user already exists")]
+        UserAlreadyExists,
+        #[error("too many registrations, please try again later")]
+        TooManyRegistrations,
+        #[error("{0}")]
+        PasswordCannotBeHashed(String),
+        #[error("{0}")]
+        EmailCannotBeSent(String),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<SaveUserError>> for SaveUserError {
+        fn from(e: TransactionError<SaveUserError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ExportUserDataError {
+        #[error("user not found")]
+        UserNotFound,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteAccountError {
+        #[error("user not found")]
+        UserNotFound,
+        #[error("incorrect password")]
+        IncorrectPassword,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DeleteAccountError>> for DeleteAccountError {
+        fn from(e: TransactionError<DeleteAccountError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum SetUserActivatedError {
+        #[error("user not found")]
+        UserNotFound,
+        #[error("administrators cannot change their own account")]
+        OwnAccount,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<SetUserActivatedError>> for SetUserActivatedError {
+        fn from(e: TransactionError<SetUserActivatedError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum DeleteUserError {
+        #[error("user not found")]
+        UserNotFound,
+        #[error("administrators cannot delete their own account")]
+        OwnAccount,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<DeleteUserError>> for DeleteUserError {
+        fn from(e: TransactionError<DeleteUserError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ResendActivationError {
+        #[error("{0}")]
+        EmailCannotBeSent(String),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<ResendActivationError>> for ResendActivationError {
+        fn from(e: TransactionError<ResendActivationError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ActivateAccountError {
+        #[error("invalid token")]
+        InvalidToken,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<ActivateAccountError>> for ActivateAccountError {
+        fn from(e: TransactionError<ActivateAccountError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ResetPasswordError {
+        #[error("invalid token")]
+        InvalidToken,
+        #[error("{0}")]
+        PasswordCannotBeHashed(String),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<ResetPasswordError>> for ResetPasswordError {
+        fn from(e: TransactionError<ResetPasswordError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ChangePasswordError {
+        #[error("user not found")]
+        UserNotFound,
+        #[error("incorrect password")]
+        IncorrectPassword,
+        #[error("{0}")]
+        PasswordCannotBeHashed(String),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<ChangePasswordError>> for ChangePasswordError {
+        fn from(e: TransactionError<ChangePasswordError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ChangeEmailError {
+        #[error("user not found")]
+        UserNotFound,
+        #[error("incorrect password")]
+        IncorrectPassword,
+        #[error("email is already in use")]
+        EmailAlreadyInUse,
+        #[error("{0}")]
+        EmailCannotBeSent(String),
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<ChangeEmailError>> for ChangeEmailError {
+        fn from(e: TransactionError<ChangeEmailError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum ConfirmEmailChangeError {
+        #[error("invalid token")]
+        InvalidToken,
+        #[error("email is already in use")]
+        EmailAlreadyInUse,
+        #[error("database error: '{0}'")]
+        DatabaseError(#[from] DbErr),
+    }
+
+    impl From<TransactionError<ConfirmEmailChangeError>> for ConfirmEmailChangeError {
+        fn from(e: TransactionError<ConfirmEmailChangeError>) -> Self {
+            match e {
+                TransactionError::Connection(e) => e.into(),
+                TransactionError::Transaction(e) => e,
+            }
+        }
+    }
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    #[error("user is not authorized")]
+    pub struct AuthorizeUserError;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use assert2::{check, let_assert};
+    use chrono::Duration;
+    use entity::household_member::{self, HouseholdRole};
+    use entity::user::{DateFormat, FirstDayOfWeek};
+    use entity::{
+        account_activation, attachment, currency, email_change, expense, expense_pause,
+        expense_price, expense_share, expense_tag, participant, password_reset, predefined_expense,
+        rate_limit, transaction, two_factor_auth, user,
+    };
+    use lettre::transport::stub::AsyncStubTransport;
+    use pwhash::bcrypt;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+
+    use crate::config::PasswordHashConfig;
+    use crate::dto::expenses::ExpenseStatus;
+    use crate::dto::sessions::ClientInfo;
+    use crate::dto::users::{
+        ChangeEmailRequest, ChangePasswordRequest, DeleteAccountRequest, ForgotPasswordRequest,
+        LoginRequest, ResendActivationRequest, ResetPasswordRequest, UpdateProfileRequest,
+        UserDataExportResponse, UserResponse,
+    };
+    use crate::logic::common::tests::{
+        exec_ok, test_attachment, test_currency, test_email_change, test_expense,
+        test_household_member, test_participant, test_recurrence, test_transaction,
+        test_two_factor_auth,
+    };
+    use crate::logic::password_hashing::hash_password;
+    use crate::logic::user_operations::errors::{
+        ChangeEmailError, ChangePasswordError, ConfirmEmailChangeError, DeleteAccountError,
+        DeleteUserError, ExportUserDataError, ResendActivationError, ResetPasswordError,
+        SetUserActivatedError, UpdateProfileError, VerifyLoginError,
+    };
+    use crate::logic::user_operations::{
+        change_password, confirm_email_change, delete_account, delete_stale_unactivated_users,
+        delete_user, export_user_data, find_date_format, find_user_by_id, find_users,
+        grant_admin_role_by_email, is_admin, request_email_change, request_password_reset,
+        resend_activation_email, reset_password, set_user_activated, update_profile, verify_login,
+    };
+    use crate::storage::errors::StorageError;
+    use crate::storage::local::LocalStorage;
+    use crate::storage::Storage;
+    use crate::{
+        dto::users::NewUserRequest,
+        logic::{
+            common::tests::{
+                test_account_activation, test_admin, test_app_config, test_db_error,
+                test_password_reset, test_user, TEST_EMAIL, TEST_ID, TEST_STR,
+            },
+            user_operations::{
+                activate_account, authorize_user,
+                errors::{ActivateAccountError, AuthorizeUserError, SaveUserError},
+                save_user,
+            },
+        },
+    };
+
+    #[tokio::test]
+    async fn find_user_by_id_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_user()], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (user, not_found, db_error) = tokio::join!(
+            find_user_by_id(&conn, TEST_ID),
+            find_user_by_id(&conn, TEST_ID),
+            find_user_by_id(&conn, TEST_ID)
+        );
 
         check!(user == Ok(Some(test_user().into())));
         check!(not_found == Ok(None));
@@ -267,208 +1261,1225 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn verify_login_all_cases() {
-        let test_password = bcrypt::hash(TEST_STR.to_string()).unwrap();
-        let mut test_user_good_password = test_user();
-        test_user_good_password.pw_hash = test_password;
-        let mut test_user_not_activated = test_user();
-        test_user_not_activated.activated = false;
-        let test_user_bad_password = test_user();
+    async fn update_profile_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_currency()]])
+            .append_exec_results(vec![exec_ok()])
+            // happy case without preferred currency
+            .append_exec_results(vec![exec_ok()])
+            // currency not found
+            .append_query_results(vec![Vec::<currency::Model>::new()])
+            // db error
+            .append_query_results(vec![vec![test_currency()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let req = UpdateProfileRequest {
+            preferred_currency_id: Some(TEST_ID),
+            locale: "hu-HU".to_string(),
+            timezone: "Europe/Budapest".to_string(),
+            date_format: DateFormat::YearMonthDayDotted,
+            first_day_of_week: FirstDayOfWeek::Monday,
+        };
+        let without_currency = UpdateProfileRequest {
+            preferred_currency_id: None,
+            ..req.clone()
+        };
+
+        let (happy_case, without_currency, currency_not_found, db_error) = tokio::join!(
+            update_profile(&conn, TEST_ID, req.clone()),
+            update_profile(&conn, TEST_ID, without_currency),
+            update_profile(&conn, TEST_ID, req.clone()),
+            update_profile(&conn, TEST_ID, req)
+        );
+
+        check!(happy_case == Ok(()));
+        check!(without_currency == Ok(()));
+        check!(currency_not_found == Err(UpdateProfileError::InvalidCurrency));
+        check!(db_error == Err(UpdateProfileError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn find_date_format_all_cases() {
+        let user = user::Model {
+            date_format: DateFormat::MonthDayYear,
+            ..test_user()
+        };
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![user], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (preferred, not_found, db_error) = tokio::join!(
+            find_date_format(&conn, TEST_ID),
+            find_date_format(&conn, TEST_ID),
+            find_date_format(&conn, TEST_ID)
+        );
+
+        check!(preferred == Ok(DateFormat::MonthDayYear));
+        check!(not_found == Ok(DateFormat::DayMonthYear));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn verify_login_all_cases() {
+        let config = &test_app_config();
+        let test_password = bcrypt::hash(TEST_STR).unwrap();
+        let mut test_user_good_password = test_user();
+        test_user_good_password.pw_hash = test_password;
+        let test_user_argon2_password = user::Model {
+            pw_hash: hash_password(&config.password_hash_config, TEST_STR).unwrap(),
+            ..test_user()
+        };
+        let weaker_hash_config = PasswordHashConfig {
+            argon2_iterations: config.password_hash_config.argon2_iterations,
+            argon2_memory_kib: config.password_hash_config.argon2_memory_kib / 2,
+            argon2_parallelism: config.password_hash_config.argon2_parallelism,
+        };
+        let test_user_weak_argon2_password = user::Model {
+            pw_hash: hash_password(&weaker_hash_config, TEST_STR).unwrap(),
+            ..test_user()
+        };
+        let mut test_user_not_activated = test_user();
+        test_user_not_activated.activated = false;
+        let test_user_bad_password = test_user();
+        let no_limits = Vec::<rate_limit::Model>::new;
+        let account_limit = |count: i32| rate_limit::Model {
+            id: TEST_ID,
+            key: format!("login:account:{TEST_EMAIL}"),
+            count,
+            window_started_at: chrono::Local::now(),
+            blocked_until: Some(chrono::Local::now() + Duration::minutes(1)),
+        };
+        let no_two_factor_auth = Vec::<two_factor_auth::Model>::new;
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // success with a legacy bcrypt hash, the password gets rehashed
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![vec![test_user_good_password.clone()]])
+            .append_query_results(vec![no_two_factor_auth()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // success with a current argon2 hash
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![vec![test_user_argon2_password.clone()]])
+            .append_query_results(vec![no_two_factor_auth()])
+            .append_exec_results(vec![exec_ok()])
+            // success with two factor enabled, the failures are kept until the second step
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![vec![test_user_argon2_password]])
+            .append_query_results(vec![vec![test_two_factor_auth()]])
+            // success with a weaker argon2 hash, the password gets rehashed
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![vec![test_user_weak_argon2_password]])
+            .append_query_results(vec![no_two_factor_auth()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // db error on rehash
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![vec![test_user_good_password]])
+            .append_exec_errors(vec![test_db_error()])
+            // incorrect credentials
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            .append_query_results(vec![no_limits()])
+            .append_exec_results(vec![exec_ok()])
+            // db error
+            .append_query_results(vec![no_limits()])
+            .append_query_errors(vec![test_db_error()])
+            // user not activated
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![vec![test_user_not_activated]])
+            // bad password, recorded into the audit log
+            .append_query_results(vec![no_limits()])
+            .append_query_results(vec![vec![test_user_bad_password]])
+            .append_query_results(vec![no_limits()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // too many attempts
+            .append_query_results(vec![vec![account_limit(2)]])
+            // temporarily locked
+            .append_query_results(vec![vec![account_limit(5)]])
+            .into_connection();
+        let req = LoginRequest {
+            email: TEST_EMAIL.to_string(),
+            password: TEST_STR.to_string(),
+        };
+        let client = ClientInfo::default();
+
+        let (
+            success,
+            argon2_success,
+            two_factor_success,
+            weak_argon2_success,
+            rehash_db_error,
+            incorrect_credentials,
+            db_error,
+            user_not_activated,
+            bad_password,
+            too_many_attempts,
+            temporarily_locked,
+        ) = tokio::join!(
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req.clone()),
+            verify_login(config, &conn, &client, req)
+        );
+
+        check!(success == Ok(TEST_ID));
+        check!(argon2_success == Ok(TEST_ID));
+        check!(two_factor_success == Ok(TEST_ID));
+        check!(weak_argon2_success == Ok(TEST_ID));
+        check!(rehash_db_error == Err(VerifyLoginError::DatabaseError(test_db_error())));
+        check!(incorrect_credentials == Err(VerifyLoginError::IncorrectCredentials));
+        check!(db_error == Err(VerifyLoginError::DatabaseError(test_db_error())));
+        check!(user_not_activated == Err(VerifyLoginError::AccountNotActivated));
+        check!(bad_password == Err(VerifyLoginError::IncorrectCredentials));
+        check!(let Err(VerifyLoginError::TooManyAttempts(_)) = too_many_attempts);
+        check!(let Err(VerifyLoginError::TemporarilyLocked(_)) = temporarily_locked);
+    }
+
+    #[tokio::test]
+    async fn save_user_all_cases() {
+        let expected_saved_user: UserResponse = test_user().into();
+
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_account_activation()]])
+            // user already exists error
+            .append_query_results(vec![vec![test_user()]])
+            // password error cannot be tested as it only happens if system random number generator cannot be opened
+            // email_error
+            .append_query_results(vec![vec![], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_account_activation()]])
+            // db_error - on user by email query
+            .append_query_errors(vec![test_db_error()])
+            // db_error - on user insert
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            .append_exec_errors(vec![test_db_error()])
+            // db_error - on account activation insert
+            .append_query_results(vec![vec![], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            // too many registrations from the ip address
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            .append_query_results(vec![vec![rate_limit::Model {
+                id: TEST_ID,
+                key: format!("registration:ip:{TEST_STR}"),
+                count: 5,
+                window_started_at: chrono::Local::now(),
+                blocked_until: None,
+            }]])
+            .into_connection();
+        let ok_mail_transport = Arc::new(AsyncStubTransport::new_ok());
+        let error_mail_transport = Arc::new(AsyncStubTransport::new_error());
+        let request = NewUserRequest {
+            email: TEST_EMAIL.to_string(),
+            username: TEST_STR.to_string(),
+            password: TEST_STR.to_string(),
+            confirm_password: TEST_STR.to_string(),
+        };
+        let app_config = &test_app_config();
+        let client = ClientInfo::default();
+        let limited_client = ClientInfo {
+            ip_address: Some(TEST_STR.to_string()),
+            user_agent: None,
+        };
+
+        let (
+            user_saved,
+            user_already_exists_error,
+            email_error,
+            user_email_db_error,
+            user_insert_db_error,
+            activation_insert_db_error,
+            too_many_registrations,
+        ) = tokio::join!(
+            save_user(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                &client,
+                request.clone()
+            ),
+            save_user(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                &client,
+                request.clone()
+            ),
+            save_user(
+                app_config,
+                &conn,
+                error_mail_transport,
+                &client,
+                request.clone()
+            ),
+            save_user(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                &client,
+                request.clone()
+            ),
+            save_user(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                &client,
+                request.clone()
+            ),
+            save_user(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                &client,
+                request.clone()
+            ),
+            save_user(
+                app_config,
+                &conn,
+                ok_mail_transport,
+                &limited_client,
+                request
+            ),
+        );
+
+        let db_error = Err(SaveUserError::DatabaseError(test_db_error()));
+        check!(user_saved == Ok(expected_saved_user));
+        check!(user_already_exists_error == Err(SaveUserError::UserAlreadyExists));
+        check!(email_error == Err(SaveUserError::EmailCannotBeSent("Error".to_string())));
+        check!(user_email_db_error == db_error);
+        check!(user_insert_db_error == db_error);
+        check!(activation_insert_db_error == db_error);
+        check!(too_many_registrations == Err(SaveUserError::TooManyRegistrations));
+    }
+
+    #[tokio::test]
+    async fn activate_account_all_cases() {
+        let expired_activation = account_activation::Model {
+            id: TEST_ID,
+            user_id: TEST_ID,
+            expiration: chrono::Local::now()
+                .checked_sub_signed(Duration::days(15))
+                .unwrap(),
+            token: TEST_STR.to_string(),
+        };
+
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_account_activation()]])
+            .append_query_results(vec![vec![test_user()], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // account_activation not found
+            .append_query_results(vec![Vec::<account_activation::Model>::new()])
+            // user not found
+            .append_query_results(vec![vec![test_account_activation()]])
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // expired activation
+            .append_query_results(vec![vec![expired_activation]])
+            // db error - account activation query failed
+            .append_query_errors(vec![test_db_error()])
+            // db error - user query failed
+            .append_query_results(vec![vec![test_account_activation()]])
+            .append_query_errors(vec![test_db_error()])
+            // db error - user update failed
+            .append_query_results(vec![vec![test_account_activation()]])
+            .append_query_results(vec![vec![test_user()], vec![test_user()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let client = ClientInfo::default();
+
+        let (
+            happy_path,
+            account_activation_not_found,
+            user_not_found,
+            expired_activation,
+            activation_query_db_error,
+            user_query_db_error,
+            user_update_db_error,
+        ) = tokio::join!(
+            activate_account(&conn, &client, TEST_STR.to_string()),
+            activate_account(&conn, &client, TEST_STR.to_string()),
+            activate_account(&conn, &client, TEST_STR.to_string()),
+            activate_account(&conn, &client, TEST_STR.to_string()),
+            activate_account(&conn, &client, TEST_STR.to_string()),
+            activate_account(&conn, &client, TEST_STR.to_string()),
+            activate_account(&conn, &client, TEST_STR.to_string()),
+        );
+
+        let invalid_token_err = Err(ActivateAccountError::InvalidToken);
+        let db_error = Err(ActivateAccountError::DatabaseError(test_db_error()));
+        check!(happy_path == Ok(()));
+        check!(account_activation_not_found == invalid_token_err);
+        check!(user_not_found == invalid_token_err);
+        check!(expired_activation == invalid_token_err);
+        check!(activation_query_db_error == db_error);
+        check!(user_query_db_error == db_error);
+        check!(user_update_db_error == db_error);
+    }
+
+    #[tokio::test]
+    async fn resend_activation_email_all_cases() {
+        let mut not_activated_user = test_user();
+        not_activated_user.activated = false;
+        let mut old_activation = test_account_activation();
+        old_activation.expiration = chrono::Local::now()
+            .checked_sub_signed(Duration::hours(1))
+            .unwrap();
+
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case - no earlier activation
+            .append_query_results(vec![vec![not_activated_user.clone()]])
+            .append_query_results(vec![Vec::<account_activation::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_account_activation()]])
+            // happy case - earlier activation is past the cooldown
+            .append_query_results(vec![vec![not_activated_user.clone()]])
+            .append_query_results(vec![vec![old_activation]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_account_activation()]])
+            // unknown or already activated email
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // throttled
+            .append_query_results(vec![vec![not_activated_user.clone()]])
+            .append_query_results(vec![vec![test_account_activation()]])
+            // email error
+            .append_query_results(vec![vec![not_activated_user.clone()]])
+            .append_query_results(vec![Vec::<account_activation::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_account_activation()]])
+            // db error - on user query
+            .append_query_errors(vec![test_db_error()])
+            // db error - on activation insert
+            .append_query_results(vec![vec![not_activated_user]])
+            .append_query_results(vec![Vec::<account_activation::Model>::new()])
+            .append_exec_results(vec![exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let ok_mail_transport = Arc::new(AsyncStubTransport::new_ok());
+        let error_mail_transport = Arc::new(AsyncStubTransport::new_error());
+        let request = ResendActivationRequest {
+            email: TEST_EMAIL.to_string(),
+        };
+        let app_config = &test_app_config();
+
+        let (
+            resent,
+            resent_after_cooldown,
+            unknown_email,
+            throttled,
+            email_error,
+            user_query_db_error,
+            activation_insert_db_error,
+        ) = tokio::join!(
+            resend_activation_email(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                request.clone()
+            ),
+            resend_activation_email(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                request.clone()
+            ),
+            resend_activation_email(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                request.clone()
+            ),
+            resend_activation_email(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                request.clone()
+            ),
+            resend_activation_email(app_config, &conn, error_mail_transport, request.clone()),
+            resend_activation_email(
+                app_config,
+                &conn,
+                ok_mail_transport.clone(),
+                request.clone()
+            ),
+            resend_activation_email(app_config, &conn, ok_mail_transport, request),
+        );
+
+        let db_error = Err(ResendActivationError::DatabaseError(test_db_error()));
+        check!(resent == Ok(()));
+        check!(resent_after_cooldown == Ok(()));
+        check!(unknown_email == Ok(()));
+        check!(throttled == Ok(()));
+        check!(let Err(ResendActivationError::EmailCannotBeSent(_)) = email_error);
+        check!(user_query_db_error == db_error);
+        check!(activation_insert_db_error == db_error);
+    }
+
+    #[tokio::test]
+    async fn delete_stale_unactivated_users_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_account_activation()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // no stale activations
+            .append_query_results(vec![Vec::<account_activation::Model>::new()])
+            // db error - on activation query
+            .append_query_errors(vec![test_db_error()])
+            // db error - on user delete
+            .append_query_results(vec![vec![test_account_activation()]])
+            .append_exec_results(vec![exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let account_config = &test_app_config().account_config;
+
+        let (deleted, nothing_to_delete, activation_query_db_error, user_delete_db_error) = tokio::join!(
+            delete_stale_unactivated_users(&conn, account_config),
+            delete_stale_unactivated_users(&conn, account_config),
+            delete_stale_unactivated_users(&conn, account_config),
+            delete_stale_unactivated_users(&conn, account_config),
+        );
+
+        check!(deleted == Ok(1));
+        check!(nothing_to_delete == Ok(0));
+        check!(activation_query_db_error == Err(test_db_error()));
+        check!(user_delete_db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn request_password_reset_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![
+                MockExecResult {
+                    last_insert_id: TEST_ID,
+                    rows_affected: 1,
+                },
+                MockExecResult {
+                    last_insert_id: TEST_ID,
+                    rows_affected: 1,
+                },
+            ])
+            .append_query_results(vec![vec![test_password_reset()]])
+            // unknown email
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // db error - user query failed
+            .append_query_errors(vec![test_db_error()])
+            // db error - previous token deletion failed
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let mail_transport = Arc::new(AsyncStubTransport::new_ok());
+        let req = ForgotPasswordRequest {
+            email: TEST_EMAIL.to_string(),
+        };
+        let app_config = &test_app_config();
+
+        let (happy_case, unknown_email, user_query_db_error, token_delete_db_error) = tokio::join!(
+            request_password_reset(app_config, &conn, mail_transport.clone(), req.clone()),
+            request_password_reset(app_config, &conn, mail_transport.clone(), req.clone()),
+            request_password_reset(app_config, &conn, mail_transport.clone(), req.clone()),
+            request_password_reset(app_config, &conn, mail_transport, req),
+        );
+
+        check!(happy_case == Ok(()));
+        check!(unknown_email == Ok(()));
+        check!(user_query_db_error == Err(test_db_error()));
+        check!(token_delete_db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn reset_password_all_cases() {
+        let config = test_app_config();
+        let expired_password_reset = password_reset::Model {
+            expiration: chrono::Local::now()
+                .checked_sub_signed(Duration::hours(2))
+                .unwrap(),
+            ..test_password_reset()
+        };
+
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_password_reset()]])
+            .append_query_results(vec![vec![test_user()], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok(), exec_ok(), exec_ok()])
+            // token not found
+            .append_query_results(vec![Vec::<password_reset::Model>::new()])
+            // expired token
+            .append_query_results(vec![vec![expired_password_reset]])
+            // user not found
+            .append_query_results(vec![vec![test_password_reset()]])
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // db error - token query failed
+            .append_query_errors(vec![test_db_error()])
+            // db error - user update failed
+            .append_query_results(vec![vec![test_password_reset()]])
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let req = ResetPasswordRequest {
+            token: TEST_STR.to_string(),
+            password: TEST_STR.to_string(),
+            confirm_password: TEST_STR.to_string(),
+        };
+        let client = ClientInfo::default();
+
+        let (
+            happy_case,
+            token_not_found,
+            expired_token,
+            user_not_found,
+            token_query_db_error,
+            user_update_db_error,
+        ) = tokio::join!(
+            reset_password(&config, &conn, &client, req.clone()),
+            reset_password(&config, &conn, &client, req.clone()),
+            reset_password(&config, &conn, &client, req.clone()),
+            reset_password(&config, &conn, &client, req.clone()),
+            reset_password(&config, &conn, &client, req.clone()),
+            reset_password(&config, &conn, &client, req),
+        );
+
+        let invalid_token_err = Err(ResetPasswordError::InvalidToken);
+        let db_error = Err(ResetPasswordError::DatabaseError(test_db_error()));
+        check!(happy_case == Ok(()));
+        check!(token_not_found == invalid_token_err);
+        check!(expired_token == invalid_token_err);
+        check!(user_not_found == invalid_token_err);
+        check!(token_query_db_error == db_error);
+        check!(user_update_db_error == db_error);
+    }
+
+    #[tokio::test]
+    async fn password_reset_and_change_revoke_api_tokens() {
+        let config = test_app_config();
+        let user = test_user_with_password();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // password reset
+            .append_query_results(vec![vec![test_password_reset()]])
+            .append_query_results(vec![vec![user.clone()], vec![user.clone()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok(), exec_ok(), exec_ok()])
+            // password change
+            .append_query_results(vec![vec![user.clone()], vec![user]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok(), exec_ok()])
+            .into_connection();
+        let client = ClientInfo::default();
+        let reset_req = ResetPasswordRequest {
+            token: TEST_STR.to_string(),
+            password: TEST_STR.to_string(),
+            confirm_password: TEST_STR.to_string(),
+        };
+        let change_req = ChangePasswordRequest {
+            current_password: TEST_STR.to_string(),
+            password: TEST_STR.to_string(),
+            confirm_password: TEST_STR.to_string(),
+        };
+
+        let reset = reset_password(&config, &conn, &client, reset_req).await;
+        let change = change_password(
+            &config,
+            &conn,
+            Arc::new(AsyncStubTransport::new_ok()),
+            &client,
+            TEST_ID,
+            TEST_ID,
+            change_req,
+        )
+        .await;
+
+        check!(reset == Ok(()));
+        check!(change == Ok(()));
+        let api_token_deletes = conn
+            .into_transaction_log()
+            .iter()
+            .map(|statement| format!("{statement:?}"))
+            .filter(|statement| statement.contains("DELETE FROM `api_tokens`"))
+            .count();
+        check!(api_token_deletes == 2);
+    }
+
+    fn test_user_with_password() -> user::Model {
+        user::Model {
+            pw_hash: bcrypt::hash(TEST_STR).unwrap(),
+            ..test_user()
+        }
+    }
+
+    #[tokio::test]
+    async fn change_password_all_cases() {
+        let config = test_app_config();
+        let user = test_user_with_password();
         let conn = MockDatabase::new(DatabaseBackend::MySql)
-            .append_query_results(vec![vec![test_user_good_password], vec![]])
+            // happy case
+            .append_query_results(vec![vec![user.clone()], vec![user.clone()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok(), exec_ok()])
+            // user not found
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // incorrect password
+            .append_query_results(vec![vec![test_user()]])
+            // db error - user query failed
             .append_query_errors(vec![test_db_error()])
-            .append_query_results(vec![
-                vec![test_user_not_activated],
-                vec![test_user_bad_password],
-            ])
+            // db error - user update failed
+            .append_query_results(vec![vec![user]])
+            .append_exec_errors(vec![test_db_error()])
             .into_connection();
-        let req = LoginRequest {
-            email: TEST_EMAIL.to_string(),
+        let mail_transport = Arc::new(AsyncStubTransport::new_ok());
+        let req = ChangePasswordRequest {
+            current_password: TEST_STR.to_string(),
             password: TEST_STR.to_string(),
+            confirm_password: TEST_STR.to_string(),
         };
+        let client = ClientInfo::default();
 
-        let (success, incorrect_credentials, db_error, user_not_activated, bad_password) = tokio::join!(
-            verify_login(&conn, req.clone()),
-            verify_login(&conn, req.clone()),
-            verify_login(&conn, req.clone()),
-            verify_login(&conn, req.clone()),
-            verify_login(&conn, req)
+        let (happy_case, user_not_found, incorrect_password, query_db_error, update_db_error) = tokio::join!(
+            change_password(
+                &config,
+                &conn,
+                mail_transport.clone(),
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
+            ),
+            change_password(
+                &config,
+                &conn,
+                mail_transport.clone(),
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
+            ),
+            change_password(
+                &config,
+                &conn,
+                mail_transport.clone(),
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
+            ),
+            change_password(
+                &config,
+                &conn,
+                mail_transport.clone(),
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
+            ),
+            change_password(
+                &config,
+                &conn,
+                mail_transport,
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req
+            ),
         );
 
-        check!(success == Ok(TEST_ID));
-        check!(incorrect_credentials == Err(VerifyLoginError::IncorrectCredentials));
-        check!(db_error == Err(VerifyLoginError::DatabaseError(test_db_error())));
-        check!(user_not_activated == Err(VerifyLoginError::AccountNotActivated));
-        check!(bad_password == Err(VerifyLoginError::IncorrectCredentials));
+        let db_error = Err(ChangePasswordError::DatabaseError(test_db_error()));
+        check!(happy_case == Ok(()));
+        check!(user_not_found == Err(ChangePasswordError::UserNotFound));
+        check!(incorrect_password == Err(ChangePasswordError::IncorrectPassword));
+        check!(query_db_error == db_error);
+        check!(update_db_error == db_error);
     }
 
     #[tokio::test]
-    async fn save_user_all_cases() {
-        let expected_saved_user: UserResponse = test_user().into();
-
+    async fn export_user_data_all_cases() {
         let conn = MockDatabase::new(DatabaseBackend::MySql)
             // happy case
-            .append_query_results(vec![vec![], vec![test_user()]])
-            .append_exec_results(vec![MockExecResult {
-                last_insert_id: TEST_ID,
-                rows_affected: 1,
-            }])
-            .append_exec_results(vec![MockExecResult {
-                last_insert_id: TEST_ID,
-                rows_affected: 1,
-            }])
-            .append_query_results(vec![vec![test_account_activation()]])
-            // user already exists error
             .append_query_results(vec![vec![test_user()]])
-            // password error cannot be tested as it only happens if system random number generator cannot be opened
-            // email_error
-            .append_query_results(vec![vec![], vec![test_user()]])
-            .append_exec_results(vec![MockExecResult {
-                last_insert_id: TEST_ID,
-                rows_affected: 1,
-            }])
-            .append_exec_results(vec![MockExecResult {
-                last_insert_id: TEST_ID,
-                rows_affected: 1,
-            }])
-            .append_query_results(vec![vec![test_account_activation()]])
-            // db_error - on user by email query
+            .append_query_results(vec![vec![test_expense()]])
+            .append_query_results(vec![Vec::<predefined_expense::Model>::new()])
+            .append_query_results(vec![Vec::<expense_tag::Model>::new()])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_query_results(vec![Vec::<expense_pause::Model>::new()])
+            .append_query_results(vec![Vec::<expense_price::Model>::new()])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![vec![test_currency()]])
+            .append_query_results(vec![vec![test_recurrence()]])
+            // user not found
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // db error - on expense query
+            .append_query_results(vec![vec![test_user()]])
             .append_query_errors(vec![test_db_error()])
-            // db_error - on user insert
+            .into_connection();
+
+        let (exported, user_not_found, db_error) = tokio::join!(
+            export_user_data(&conn, TEST_ID),
+            export_user_data(&conn, TEST_ID),
+            export_user_data(&conn, TEST_ID),
+        );
+
+        let expected_expense = (
+            test_expense(),
+            test_currency(),
+            test_recurrence(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            ExpenseStatus::Active,
+            (test_expense().value, test_currency()),
+            DateFormat::DayMonthYear,
+        )
+            .into();
+        let expected_export: UserDataExportResponse = (test_user(), vec![expected_expense]).into();
+        check!(exported == Ok(expected_export));
+        check!(user_not_found == Err(ExportUserDataError::UserNotFound));
+        check!(db_error == Err(ExportUserDataError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_account_all_cases() {
+        let user = test_user_with_password();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case, the file of the attachment is deleted
+            .append_query_results(vec![vec![user.clone()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![vec![test_attachment()]])
+            .append_exec_results(vec![
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+            ])
+            // user not found
             .append_query_results(vec![Vec::<user::Model>::new()])
+            // incorrect password
+            .append_query_results(vec![vec![test_user()]])
+            // db error - user query failed
+            .append_query_errors(vec![test_db_error()])
+            // db error - expense delete failed
+            .append_query_results(vec![vec![user]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
             .append_exec_errors(vec![test_db_error()])
-            // db_error - on account activation insert
-            .append_query_results(vec![vec![], vec![test_user()]])
-            .append_exec_results(vec![MockExecResult {
-                last_insert_id: TEST_ID,
-                rows_affected: 1,
-            }])
+            .into_connection();
+        let req = DeleteAccountRequest {
+            password: TEST_STR.to_string(),
+        };
+        let root = std::env::temp_dir().join(format!("veryrezsi-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+        let storage_key = test_attachment().storage_key;
+        storage
+            .put(&storage_key, "application/pdf", b"%PDF-1.7".to_vec())
+            .await
+            .unwrap();
+
+        let deleted = delete_account(&conn, &storage, TEST_ID, req.clone()).await;
+        let stored_file = storage.get(&storage_key).await;
+        let user_not_found = delete_account(&conn, &storage, TEST_ID, req.clone()).await;
+        let incorrect_password = delete_account(&conn, &storage, TEST_ID, req.clone()).await;
+        let query_db_error = delete_account(&conn, &storage, TEST_ID, req.clone()).await;
+        let delete_db_error = delete_account(&conn, &storage, TEST_ID, req).await;
+        let _ = tokio::fs::remove_dir_all(&root).await;
+
+        let db_error = Err(DeleteAccountError::DatabaseError(test_db_error()));
+        check!(deleted == Ok(()));
+        check!(stored_file == Err(StorageError::NotFound));
+        check!(user_not_found == Err(DeleteAccountError::UserNotFound));
+        check!(incorrect_password == Err(DeleteAccountError::IncorrectPassword));
+        check!(query_db_error == db_error);
+        check!(delete_db_error == db_error);
+    }
+
+    #[tokio::test]
+    async fn find_users_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_user(), test_admin()], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (users, empty_vec, db_error) =
+            tokio::join!(find_users(&conn), find_users(&conn), find_users(&conn));
+
+        check!(users == Ok(vec![test_user().into(), test_admin().into()]));
+        check!(empty_vec == Ok(vec![]));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn set_user_activated_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // activation, pending activations are removed
+            .append_query_results(vec![vec![test_user()], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok()])
+            // deactivation, sessions and api tokens are revoked
+            .append_query_results(vec![vec![test_user()], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // user not found
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (activated, deactivated, user_not_found, own_account, db_error) = tokio::join!(
+            set_user_activated(&conn, TEST_ID + 1, TEST_ID, true),
+            set_user_activated(&conn, TEST_ID + 1, TEST_ID, false),
+            set_user_activated(&conn, TEST_ID + 1, TEST_ID, false),
+            set_user_activated(&conn, TEST_ID, TEST_ID, false),
+            set_user_activated(&conn, TEST_ID + 1, TEST_ID, false),
+        );
+
+        check!(activated == Ok(()));
+        check!(deactivated == Ok(()));
+        check!(user_not_found == Err(SetUserActivatedError::UserNotFound));
+        check!(own_account == Err(SetUserActivatedError::OwnAccount));
+        check!(db_error == Err(SetUserActivatedError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_user_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            .append_exec_results(vec![
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+            ])
+            // household expenses are handed over to the other member, who becomes the owner
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![expense::Model {
+                household_id: Some(TEST_ID),
+                ..test_expense()
+            }]])
+            .append_query_results(vec![vec![household_member::Model {
+                id: TEST_ID + 1,
+                user_id: TEST_ID + 1,
+                role: HouseholdRole::Member,
+                ..test_household_member()
+            }]])
+            .append_query_results(vec![Vec::<transaction::Model>::new()])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            .append_exec_results(vec![
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+            ])
+            // user not found
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // db error - expense delete failed
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![Vec::<household_member::Model>::new()])
+            .append_query_results(vec![Vec::<expense::Model>::new()])
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            .append_exec_errors(vec![test_db_error()])
+            .into_connection();
+        let root = std::env::temp_dir().join(format!("veryrezsi-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+
+        let (deleted, handed_over, user_not_found, own_account, db_error) = tokio::join!(
+            delete_user(&conn, &storage, TEST_ID + 1, TEST_ID),
+            delete_user(&conn, &storage, TEST_ID + 2, TEST_ID),
+            delete_user(&conn, &storage, TEST_ID + 1, TEST_ID),
+            delete_user(&conn, &storage, TEST_ID, TEST_ID),
+            delete_user(&conn, &storage, TEST_ID + 1, TEST_ID),
+        );
+
+        check!(deleted == Ok(()));
+        check!(handed_over == Ok(()));
+        check!(user_not_found == Err(DeleteUserError::UserNotFound));
+        check!(own_account == Err(DeleteUserError::OwnAccount));
+        check!(db_error == Err(DeleteUserError::DatabaseError(test_db_error())));
+    }
+
+    #[tokio::test]
+    async fn delete_user_replaces_their_participants_on_handed_over_expenses() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_user()]])
+            .append_query_results(vec![vec![test_household_member()]])
+            .append_query_results(vec![vec![expense::Model {
+                household_id: Some(TEST_ID),
+                ..test_expense()
+            }]])
+            .append_query_results(vec![vec![household_member::Model {
+                id: TEST_ID + 1,
+                user_id: TEST_ID + 1,
+                role: HouseholdRole::Member,
+                ..test_household_member()
+            }]])
+            .append_query_results(vec![vec![test_transaction()]])
+            .append_query_results(vec![Vec::<expense_share::Model>::new()])
+            .append_query_results(vec![vec![test_participant()]])
+            .append_query_results(vec![Vec::<participant::Model>::new()])
+            .append_query_results(vec![Vec::<attachment::Model>::new()])
+            .append_exec_results(vec![
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+                exec_ok(),
+            ])
+            .into_connection();
+        let root = std::env::temp_dir().join(format!("veryrezsi-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+
+        let deleted = delete_user(&conn, &storage, TEST_ID + 1, TEST_ID).await;
+
+        check!(deleted == Ok(()));
+        // the deletion is a single transaction, so the order of its statements is compared in the log
+        let log = format!("{:?}", conn.into_transaction_log());
+        let position = |statement: &str| log.find(statement);
+        let_assert!(
+            Some(participant_replaced) = position("UPDATE `transactions` SET `participant_id`")
+        );
+        let_assert!(Some(user_deleted) = position("DELETE FROM `users`"));
+        // the household entries go to the new owner, the private ones are deleted with the user
+        let_assert!(Some(predefined_handed_over) = position("UPDATE `predefined_expenses`"));
+        let_assert!(Some(predefined_deleted) = position("DELETE FROM `predefined_expenses`"));
+        check!(position("INSERT INTO `participants`").is_some());
+        check!(participant_replaced < user_deleted);
+        check!(predefined_handed_over < predefined_deleted);
+        check!(predefined_deleted < user_deleted);
+    }
+
+    #[tokio::test]
+    async fn is_admin_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            .append_query_results(vec![vec![test_admin()], vec![test_user()], vec![]])
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (admin, user, not_found, db_error) = tokio::join!(
+            is_admin(&conn, TEST_ID),
+            is_admin(&conn, TEST_ID),
+            is_admin(&conn, TEST_ID),
+            is_admin(&conn, TEST_ID)
+        );
+
+        check!(admin == Ok(true));
+        check!(user == Ok(false));
+        check!(not_found == Ok(false));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn grant_admin_role_by_email_all_cases() {
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // user gets promoted
+            .append_query_results(vec![vec![test_user()]])
+            .append_exec_results(vec![exec_ok()])
+            // user is already an admin
+            .append_query_results(vec![vec![test_admin()]])
+            // user not found
+            .append_query_results(vec![Vec::<user::Model>::new()])
+            // db error
+            .append_query_errors(vec![test_db_error()])
+            .into_connection();
+
+        let (promoted, already_admin, not_found, db_error) = tokio::join!(
+            grant_admin_role_by_email(&conn, TEST_EMAIL),
+            grant_admin_role_by_email(&conn, TEST_EMAIL),
+            grant_admin_role_by_email(&conn, TEST_EMAIL),
+            grant_admin_role_by_email(&conn, TEST_EMAIL)
+        );
+
+        check!(promoted == Ok(true));
+        check!(already_admin == Ok(true));
+        check!(not_found == Ok(false));
+        check!(db_error == Err(test_db_error()));
+    }
+
+    #[tokio::test]
+    async fn request_email_change_all_cases() {
+        let user = test_user_with_password();
+        let conn = MockDatabase::new(DatabaseBackend::MySql)
+            // happy case
+            .append_query_results(vec![vec![user.clone()], vec![]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_email_change()]])
+            // incorrect password
+            .append_query_results(vec![vec![test_user()]])
+            // email already in use
+            .append_query_results(vec![vec![user.clone()], vec![test_user()]])
+            // email cannot be sent
+            .append_query_results(vec![vec![user.clone()], vec![]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok(), exec_ok()])
+            .append_query_results(vec![vec![test_email_change()]])
+            // db error - user query failed
+            .append_query_errors(vec![test_db_error()])
+            // db error - email change insert failed
+            .append_query_results(vec![vec![user], vec![]])
+            .append_exec_results(vec![exec_ok()])
             .append_exec_errors(vec![test_db_error()])
             .into_connection();
         let ok_mail_transport = Arc::new(AsyncStubTransport::new_ok());
         let error_mail_transport = Arc::new(AsyncStubTransport::new_error());
-        let request = NewUserRequest {
+        let req = ChangeEmailRequest {
             email: TEST_EMAIL.to_string(),
-            username: TEST_STR.to_string(),
             password: TEST_STR.to_string(),
-            confirm_password: TEST_STR.to_string(),
         };
         let app_config = &test_app_config();
+        let client = ClientInfo::default();
 
         let (
-            user_saved,
-            user_already_exists_error,
+            happy_case,
+            incorrect_password,
+            email_already_in_use,
             email_error,
-            user_email_db_error,
-            user_insert_db_error,
-            activation_insert_db_error,
+            query_db_error,
+            insert_db_error,
         ) = tokio::join!(
-            save_user(
+            request_email_change(
                 app_config,
                 &conn,
                 ok_mail_transport.clone(),
-                request.clone()
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
             ),
-            save_user(
+            request_email_change(
                 app_config,
                 &conn,
                 ok_mail_transport.clone(),
-                request.clone()
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
             ),
-            save_user(app_config, &conn, error_mail_transport, request.clone()),
-            save_user(
+            request_email_change(
                 app_config,
                 &conn,
                 ok_mail_transport.clone(),
-                request.clone()
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
             ),
-            save_user(
+            request_email_change(
+                app_config,
+                &conn,
+                error_mail_transport,
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
+            ),
+            request_email_change(
                 app_config,
                 &conn,
                 ok_mail_transport.clone(),
-                request.clone()
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req.clone()
+            ),
+            request_email_change(
+                app_config,
+                &conn,
+                ok_mail_transport,
+                &client,
+                TEST_ID,
+                TEST_ID,
+                req
             ),
-            save_user(app_config, &conn, ok_mail_transport, request),
         );
 
-        let db_error = Err(SaveUserError::DatabaseError(test_db_error()));
-        check!(user_saved == Ok(expected_saved_user));
-        check!(user_already_exists_error == Err(SaveUserError::UserAlreadyExists));
-        check!(email_error == Err(SaveUserError::EmailCannotBeSent("Error".to_string())));
-        check!(user_email_db_error == db_error);
-        check!(user_insert_db_error == db_error);
-        check!(activation_insert_db_error == db_error);
+        let db_error = Err(ChangeEmailError::DatabaseError(test_db_error()));
+        check!(happy_case == Ok(()));
+        check!(incorrect_password == Err(ChangeEmailError::IncorrectPassword));
+        check!(email_already_in_use == Err(ChangeEmailError::EmailAlreadyInUse));
+        check!(email_error == Err(ChangeEmailError::EmailCannotBeSent("Error".to_string())));
+        check!(query_db_error == db_error);
+        check!(insert_db_error == db_error);
     }
 
     #[tokio::test]
-    async fn activate_account_all_cases() {
-        let expired_activation = account_activation::Model {
-            id: TEST_ID,
-            user_id: TEST_ID,
+    async fn confirm_email_change_all_cases() {
+        let expired_email_change = email_change::Model {
             expiration: chrono::Local::now()
-                .checked_sub_signed(Duration::days(15))
+                .checked_sub_signed(Duration::days(2))
                 .unwrap(),
-            token: TEST_STR.to_string(),
+            ..test_email_change()
         };
 
         let conn = MockDatabase::new(DatabaseBackend::MySql)
             // happy case
-            .append_query_results(vec![vec![test_account_activation()]])
+            .append_query_results(vec![vec![test_email_change()]])
+            .append_query_results(vec![vec![test_user()], vec![], vec![test_user()]])
+            .append_exec_results(vec![exec_ok(), exec_ok(), exec_ok()])
+            // token not found
+            .append_query_results(vec![Vec::<email_change::Model>::new()])
+            // expired token
+            .append_query_results(vec![vec![expired_email_change]])
+            // email already in use
+            .append_query_results(vec![vec![test_email_change()]])
             .append_query_results(vec![vec![test_user()], vec![test_user()]])
-            .append_exec_results(vec![
-                MockExecResult {
-                    last_insert_id: TEST_ID,
-                    rows_affected: 1,
-                },
-                MockExecResult {
-                    last_insert_id: TEST_ID,
-                    rows_affected: 1,
-                },
-            ])
-            // account_activation not found
-            .append_query_results(vec![Vec::<account_activation::Model>::new()])
-            // user not found
-            .append_query_results(vec![vec![test_account_activation()]])
-            .append_query_results(vec![Vec::<user::Model>::new()])
-            // expired activation
-            .append_query_results(vec![vec![expired_activation]])
-            // db error - account activation query failed
-            .append_query_errors(vec![test_db_error()])
-            // db error - user query failed
-            .append_query_results(vec![vec![test_account_activation()]])
+            // db error - token query failed
             .append_query_errors(vec![test_db_error()])
             // db error - user update failed
-            .append_query_results(vec![vec![test_account_activation()]])
-            .append_query_results(vec![vec![test_user()], vec![test_user()]])
+            .append_query_results(vec![vec![test_email_change()]])
+            .append_query_results(vec![vec![test_user()], vec![]])
             .append_exec_errors(vec![test_db_error()])
             .into_connection();
+        let client = ClientInfo::default();
 
         let (
-            happy_path,
-            account_activation_not_found,
-            user_not_found,
-            expired_activation,
-            activation_query_db_error,
-            user_query_db_error,
+            happy_case,
+            token_not_found,
+            expired_token,
+            email_already_in_use,
+            token_query_db_error,
             user_update_db_error,
         ) = tokio::join!(
-            activate_account(&conn, TEST_STR.to_string()),
-            activate_account(&conn, TEST_STR.to_string()),
-            activate_account(&conn, TEST_STR.to_string()),
-            activate_account(&conn, TEST_STR.to_string()),
-            activate_account(&conn, TEST_STR.to_string()),
-            activate_account(&conn, TEST_STR.to_string()),
-            activate_account(&conn, TEST_STR.to_string()),
+            confirm_email_change(&conn, &client, TEST_STR.to_string()),
+            confirm_email_change(&conn, &client, TEST_STR.to_string()),
+            confirm_email_change(&conn, &client, TEST_STR.to_string()),
+            confirm_email_change(&conn, &client, TEST_STR.to_string()),
+            confirm_email_change(&conn, &client, TEST_STR.to_string()),
+            confirm_email_change(&conn, &client, TEST_STR.to_string()),
         );
 
-        let invalid_token_err = Err(ActivateAccountError::InvalidToken);
-        let db_error = Err(ActivateAccountError::DatabaseError(test_db_error()));
-        check!(happy_path == Ok(()));
-        check!(account_activation_not_found == invalid_token_err);
-        check!(user_not_found == invalid_token_err);
-        check!(expired_activation == invalid_token_err);
-        check!(activation_query_db_error == db_error);
-        check!(user_query_db_error == db_error);
+        let invalid_token_err = Err(ConfirmEmailChangeError::InvalidToken);
+        let db_error = Err(ConfirmEmailChangeError::DatabaseError(test_db_error()));
+        check!(happy_case == Ok(()));
+        check!(token_not_found == invalid_token_err);
+        check!(expired_token == invalid_token_err);
+        check!(email_already_in_use == Err(ConfirmEmailChangeError::EmailAlreadyInUse));
+        check!(token_query_db_error == db_error);
         check!(user_update_db_error == db_error);
     }
 
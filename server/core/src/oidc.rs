@@ -0,0 +1,212 @@
+use self::errors::OidcError;
+
+use crate::config::OidcProviderConfig;
+use openidconnect::core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata};
+use openidconnect::reqwest::async_http_client;
+use openidconnect::{
+    AuthorizationCode, ClientId, ClientSecret, CsrfToken, IssuerUrl, Nonce, PkceCodeChallenge,
+    PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
+};
+use serde::{Deserialize, Serialize};
+
+/// Usernames are cut to this length, as the column does not allow longer ones.
+const MAX_USERNAME_LENGTH: usize = 255;
+
+/// Everything that has to be remembered between redirecting the user to the provider and the callback.
+/// It is kept in a private cookie on the client, so no server side state is needed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OidcLoginState {
+    pub provider: String,
+    pub csrf_token: String,
+    pub nonce: String,
+    pub pkce_verifier: String,
+}
+
+/// A user authenticated by a provider, with the claims needed to find or create the local account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OidcIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub email: String,
+    pub username: String,
+}
+
+/// Starts the authorization code flow with PKCE.
+/// Returns the url of the provider the user has to be redirected to, and the state needed to complete the login.
+pub async fn begin_login(
+    provider: &OidcProviderConfig,
+) -> Result<(String, OidcLoginState), OidcError> {
+    let client = discover_client(provider).await?;
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let (authorize_url, csrf_token, nonce) = client
+        .authorize_url(
+            CoreAuthenticationFlow::AuthorizationCode,
+            CsrfToken::new_random,
+            Nonce::new_random,
+        )
+        .add_scope(Scope::new("email".to_string()))
+        .add_scope(Scope::new("profile".to_string()))
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    let login_state = OidcLoginState {
+        provider: provider.name.clone(),
+        csrf_token: csrf_token.secret().clone(),
+        nonce: nonce.secret().clone(),
+        pkce_verifier: pkce_verifier.secret().clone(),
+    };
+    Ok((authorize_url.to_string(), login_state))
+}
+
+/// Completes the authorization code flow: exchanges the code for tokens and verifies the id token.
+/// Only identities with a verified email are accepted, as local accounts are linked by their email.
+pub async fn complete_login(
+    provider: &OidcProviderConfig,
+    login_state: OidcLoginState,
+    code: String,
+    state: &str,
+) -> Result<OidcIdentity, OidcError> {
+    if login_state.provider != provider.name || login_state.csrf_token != state {
+        return Err(OidcError::InvalidState);
+    }
+
+    let client = discover_client(provider).await?;
+    let token_response = client
+        .exchange_code(AuthorizationCode::new(code))
+        .set_pkce_verifier(PkceCodeVerifier::new(login_state.pkce_verifier))
+        .request_async(async_http_client)
+        .await
+        .map_err(|e| OidcError::TokenExchange(e.to_string()))?;
+    let Some(id_token) = token_response.id_token() else {
+        return Err(OidcError::InvalidIdToken("id token is missing".to_string()));
+    };
+    let claims = id_token
+        .claims(&client.id_token_verifier(), &Nonce::new(login_state.nonce))
+        .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+    let Some(email) = claims.email() else {
+        return Err(OidcError::EmailNotVerified);
+    };
+    if claims.email_verified() != Some(true) {
+        return Err(OidcError::EmailNotVerified);
+    }
+    let username = username_from_claims(
+        claims
+            .preferred_username()
+            .map(|username| username.as_str()),
+        claims
+            .name()
+            .and_then(|name| name.get(None))
+            .map(|name| name.as_str()),
+        email.as_str(),
+    );
+
+    Ok(OidcIdentity {
+        provider: provider.name.clone(),
+        subject: claims.subject().as_str().to_string(),
+        email: email.as_str().to_string(),
+        username,
+    })
+}
+
+/// Fetches the metadata of the provider and creates a client for it.
+async fn discover_client(provider: &OidcProviderConfig) -> Result<CoreClient, OidcError> {
+    let issuer_url = IssuerUrl::new(provider.issuer_url.clone())
+        .map_err(|e| OidcError::Configuration(e.to_string()))?;
+    let redirect_url = RedirectUrl::new(provider.redirect_url.clone())
+        .map_err(|e| OidcError::Configuration(e.to_string()))?;
+    let metadata = CoreProviderMetadata::discover_async(issuer_url, async_http_client)
+        .await
+        .map_err(|e| OidcError::Discovery(e.to_string()))?;
+
+    let client = CoreClient::from_provider_metadata(
+        metadata,
+        ClientId::new(provider.client_id.clone()),
+        Some(ClientSecret::new(provider.client_secret.clone())),
+    )
+    .set_redirect_uri(redirect_url);
+    Ok(client)
+}
+
+/// Chooses the username of a new user: the preferred username, or the name, or the local part of the email.
+fn username_from_claims(
+    preferred_username: Option<&str>,
+    name: Option<&str>,
+    email: &str,
+) -> String {
+    let username = preferred_username
+        .or(name)
+        .map(str::trim)
+        .filter(|username| !username.is_empty())
+        .unwrap_or_else(|| email.split('@').next().unwrap_or(email));
+    username.chars().take(MAX_USERNAME_LENGTH).collect()
+}
+
+pub mod errors {
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum OidcError {
+        #[error("provider is misconfigured: '{0}'")]
+        Configuration(String),
+        #[error("provider discovery failed: '{0}'")]
+        Discovery(String),
+        #[error("login state is invalid")]
+        InvalidState,
+        #[error("code exchange failed: '{0}'")]
+        TokenExchange(String),
+        #[error("id token is invalid: '{0}'")]
+        InvalidIdToken(String),
+        #[error("email is not verified by the provider")]
+        EmailNotVerified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::common::tests::{TEST_EMAIL, TEST_STR};
+    use assert2::check;
+
+    #[test]
+    fn username_from_claims_all_cases() {
+        let long_name = "a".repeat(MAX_USERNAME_LENGTH + 1);
+
+        check!(username_from_claims(Some(TEST_STR), Some("name"), TEST_EMAIL) == TEST_STR);
+        check!(username_from_claims(None, Some(" name "), TEST_EMAIL) == "name");
+        check!(username_from_claims(Some(" "), None, TEST_EMAIL) == "test");
+        check!(
+            username_from_claims(None, Some(&long_name), TEST_EMAIL).len() == MAX_USERNAME_LENGTH
+        );
+    }
+
+    #[tokio::test]
+    async fn complete_login_rejects_invalid_state() {
+        let provider = OidcProviderConfig {
+            name: TEST_STR.to_string(),
+            display_name: TEST_STR.to_string(),
+            issuer_url: "http://localhost:1".to_string(),
+            client_id: TEST_STR.to_string(),
+            client_secret: TEST_STR.to_string(),
+            redirect_url: "http://localhost:1/callback".to_string(),
+        };
+        let login_state = OidcLoginState {
+            provider: TEST_STR.to_string(),
+            csrf_token: TEST_STR.to_string(),
+            nonce: TEST_STR.to_string(),
+            pkce_verifier: TEST_STR.to_string(),
+        };
+        let other_provider = OidcLoginState {
+            provider: "other".to_string(),
+            ..login_state.clone()
+        };
+
+        let (wrong_state, wrong_provider) = tokio::join!(
+            complete_login(&provider, login_state, TEST_STR.to_string(), "other"),
+            complete_login(&provider, other_provider, TEST_STR.to_string(), TEST_STR)
+        );
+
+        check!(wrong_state == Err(OidcError::InvalidState));
+        check!(wrong_provider == Err(OidcError::InvalidState));
+    }
+}
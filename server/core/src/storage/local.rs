@@ -0,0 +1,92 @@
+use super::errors::StorageError;
+use super::{is_valid_key, Storage};
+
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Keeps the files in a directory of the local filesystem, the key is used as the relative path of the file.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_of(&self, key: &str) -> Result<PathBuf, StorageError> {
+        if !is_valid_key(key) {
+            return Err(StorageError::InvalidKey(key.to_string()));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    /// The content is written next to its final place first and moved there,
+    /// so a failed write never leaves a partial file behind under the key.
+    async fn put(&self, key: &str, _content_type: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.path_of(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+        let partial_path = path.with_extension("part");
+        tokio::fs::write(&partial_path, data)
+            .await
+            .map_err(io_error)?;
+        tokio::fs::rename(&partial_path, &path)
+            .await
+            .map_err(io_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let path = self.path_of(key)?;
+        tokio::fs::read(path).await.map_err(io_error)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path_of(key)?;
+        match tokio::fs::remove_file(path).await.map_err(io_error) {
+            Err(StorageError::NotFound) => Ok(()),
+            result => result,
+        }
+    }
+}
+
+fn io_error(e: std::io::Error) -> StorageError {
+    match e.kind() {
+        ErrorKind::NotFound => StorageError::NotFound,
+        _ => StorageError::Io(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[tokio::test]
+    async fn local_storage_all_cases() {
+        let root = std::env::temp_dir().join(format!("veryrezsi-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+
+        let put = storage
+            .put("1/receipt", "application/pdf", b"%PDF-1.7".to_vec())
+            .await;
+        let get = storage.get("1/receipt").await;
+        let delete = storage.delete("1/receipt").await;
+        let get_deleted = storage.get("1/receipt").await;
+        let delete_deleted = storage.delete("1/receipt").await;
+        let invalid_key = storage.get("../receipt").await;
+        let _ = tokio::fs::remove_dir_all(&root).await;
+
+        check!(put == Ok(()));
+        check!(get == Ok(b"%PDF-1.7".to_vec()));
+        check!(delete == Ok(()));
+        check!(get_deleted == Err(StorageError::NotFound));
+        check!(delete_deleted == Ok(()));
+        check!(invalid_key == Err(StorageError::InvalidKey("../receipt".to_string())));
+    }
+}
@@ -0,0 +1,91 @@
+use self::errors::StorageError;
+use self::local::LocalStorage;
+use self::s3::S3Storage;
+
+use crate::config::{StorageBackend, StorageConfig};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub mod local;
+pub mod s3;
+
+/// Keeps the content of the uploaded files under the keys generated for them.
+/// A key is made of path segments separated by slashes, like `1/6f1c3a2e-...`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores the content under the key, replacing anything stored there before.
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<(), StorageError>;
+    /// Reads the content stored under the key.
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+    /// Removes the content stored under the key, removing a missing key is not an error.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Creates the storage backend chosen in the configuration.
+/// Panics if the `s3` backend is chosen without its settings, as the server can not work without a storage.
+pub fn init(config: &StorageConfig) -> Arc<dyn Storage> {
+    match config.backend {
+        StorageBackend::Local => Arc::new(LocalStorage::new(&config.local_path)),
+        StorageBackend::S3 => {
+            let setting = |value: &Option<String>, name: &str| {
+                value
+                    .clone()
+                    .unwrap_or_else(|| panic!("{name} has to be set for the s3 storage backend"))
+            };
+            Arc::new(S3Storage::new(
+                setting(&config.s3_endpoint, "s3_endpoint"),
+                setting(&config.s3_bucket, "s3_bucket"),
+                config.s3_region.clone(),
+                setting(&config.s3_access_key, "s3_access_key"),
+                setting(&config.s3_secret_key, "s3_secret_key"),
+            ))
+        }
+    }
+}
+
+/// Accepts only keys which can not escape the root of the storage or be misread as a url.
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        })
+}
+
+pub mod errors {
+    use thiserror::Error;
+
+    #[derive(Error, Debug, PartialEq, Eq)]
+    pub enum StorageError {
+        #[error("nothing is stored under the key")]
+        NotFound,
+        #[error("storage key is invalid: '{0}'")]
+        InvalidKey(String),
+        #[error("storage i/o failed: '{0}'")]
+        Io(String),
+        #[error("storage request failed: '{0}'")]
+        Request(String),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert2::check;
+
+    #[test]
+    fn is_valid_key_all_cases() {
+        check!(is_valid_key("1/6f1c3a2e-7d4b-4c1a-9b0e-2f8d5c3a1b7e"));
+        check!(is_valid_key("receipt.pdf"));
+        check!(!is_valid_key(""));
+        check!(!is_valid_key("1//file"));
+        check!(!is_valid_key("/1/file"));
+        check!(!is_valid_key("1/../../etc/passwd"));
+        check!(!is_valid_key("1/file?acl"));
+        check!(!is_valid_key("1\\file"));
+    }
+}
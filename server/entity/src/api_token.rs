@@ -0,0 +1,62 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// Defines what a request authenticated with an API token is allowed to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    #[sea_orm(string_value = "read_only")]
+    ReadOnly,
+    #[sea_orm(string_value = "read_write")]
+    ReadWrite,
+}
+
+impl ApiTokenScope {
+    pub fn allows_writes(&self) -> bool {
+        matches!(self, ApiTokenScope::ReadWrite)
+    }
+}
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "api_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub user_id: Id,
+    pub name: String,
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    pub scope: ApiTokenScope,
+    pub created_at: DateTimeLocal,
+    #[sea_orm(nullable)]
+    pub expires_at: Option<DateTimeLocal>,
+    #[sea_orm(nullable)]
+    pub last_used_at: Option<DateTimeLocal>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
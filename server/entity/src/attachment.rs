@@ -0,0 +1,72 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// A file attached to either an expense or a transaction, like a bill or a receipt.
+/// The content is kept in the storage under the key, the size is counted against the quota of the uploader.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "attachments")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub user_id: Id,
+    #[sea_orm(nullable)]
+    pub expense_id: Option<Id>,
+    #[sea_orm(nullable)]
+    pub transaction_id: Option<Id>,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: u64,
+    #[sea_orm(unique)]
+    pub storage_key: String,
+    pub created_at: DateTimeLocal,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::expense::Entity",
+        from = "Column::ExpenseId",
+        to = "super::expense::Column::Id"
+    )]
+    Expense,
+    #[sea_orm(
+        belongs_to = "super::transaction::Entity",
+        from = "Column::TransactionId",
+        to = "super::transaction::Column::Id"
+    )]
+    Transaction,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::expense::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Expense.def()
+    }
+}
+
+impl Related<super::transaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transaction.def()
+    }
+}
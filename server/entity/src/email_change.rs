@@ -0,0 +1,34 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "email_changes")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    #[sea_orm(unique)]
+    pub token: String,
+    pub user_id: Id,
+    pub new_email: String,
+    pub expiration: DateTimeLocal,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
@@ -2,6 +2,25 @@ use crate::{Id, MoneyAmount};
 use sea_orm::entity::prelude::*;
 use serde::{self, Deserialize, Serialize};
 
+/// How the costs of an expense are divided between its participants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum SplitMethod {
+    /// Every participant pays the same part.
+    #[sea_orm(string_value = "equal")]
+    Equal,
+    /// Every participant pays a percentage, the percentages add up to 100.
+    #[sea_orm(string_value = "percentage")]
+    Percentage,
+    /// Every participant pays a fixed amount of each payment, the rest is divided equally.
+    #[sea_orm(string_value = "fixed")]
+    Fixed,
+    /// Every participant pays in proportion to their weight.
+    #[sea_orm(string_value = "weight")]
+    Weight,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -20,11 +39,27 @@ pub struct Model {
     pub description: String,
     pub value: MoneyAmount,
     pub start_date: Date,
+    /// The expense is cancelled from this date, it is not paid on or after it.
+    #[sea_orm(nullable)]
+    pub end_date: Option<Date>,
     pub user_id: Id,
     pub currency_id: Id,
     pub recurrence_id: Id,
     #[sea_orm(nullable)]
     pub predefined_expense_id: Option<Id>,
+    #[sea_orm(nullable)]
+    pub category_id: Option<Id>,
+    /// The expense is shared with the members of this household, otherwise only its creator can see it.
+    #[sea_orm(nullable)]
+    pub household_id: Option<Id>,
+    /// The costs are divided between the participants of the shares with this method, they are not divided without one.
+    #[sea_orm(nullable)]
+    pub split_method: Option<SplitMethod>,
+    /// Archived expenses are kept in the history, but left out of the listings by default.
+    pub archived: bool,
+    /// The expense is in the trash since this time, it is purged after the retention period.
+    #[sea_orm(nullable)]
+    pub deleted_at: Option<DateTimeLocal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -37,6 +72,22 @@ pub enum Relation {
     User,
     #[sea_orm(has_many = "super::transaction::Entity")]
     Transaction,
+    #[sea_orm(has_many = "super::expense_pause::Entity")]
+    ExpensePause,
+    #[sea_orm(has_many = "super::expense_price::Entity")]
+    ExpensePrice,
+    #[sea_orm(has_many = "super::expense_tag::Entity")]
+    ExpenseTag,
+    #[sea_orm(has_many = "super::expense_share::Entity")]
+    ExpenseShare,
+    #[sea_orm(has_many = "super::attachment::Entity")]
+    Attachment,
+    #[sea_orm(
+        belongs_to = "super::category::Entity",
+        from = "Column::CategoryId",
+        to = "super::category::Column::Id"
+    )]
+    Category,
     #[sea_orm(
         belongs_to = "super::currency::Entity",
         from = "Column::CurrencyId",
@@ -55,6 +106,12 @@ pub enum Relation {
         to = "super::predefined_expense::Column::Id"
     )]
     PredefinedExpense,
+    #[sea_orm(
+        belongs_to = "super::household::Entity",
+        from = "Column::HouseholdId",
+        to = "super::household::Column::Id"
+    )]
+    Household,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -69,6 +126,48 @@ impl Related<super::transaction::Entity> for Entity {
     }
 }
 
+impl Related<super::expense_pause::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExpensePause.def()
+    }
+}
+
+impl Related<super::expense_price::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExpensePrice.def()
+    }
+}
+
+impl Related<super::expense_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExpenseTag.def()
+    }
+}
+
+impl Related<super::attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attachment.def()
+    }
+}
+
+impl Related<super::expense_share::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExpenseShare.def()
+    }
+}
+
+impl Related<super::household::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Household.def()
+    }
+}
+
+impl Related<super::category::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
 impl Related<super::currency::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Currency.def()
@@ -0,0 +1,41 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// An interval the expense is not paid in. It starts on the start date and the expense is resumed on the end date,
+/// a pause without an end date lasts until it is given one.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "expense_pauses")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub expense_id: Id,
+    pub start_date: Date,
+    #[sea_orm(nullable)]
+    pub end_date: Option<Date>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::expense::Entity",
+        from = "Column::ExpenseId",
+        to = "super::expense::Column::Id"
+    )]
+    Expense,
+}
+
+impl Related<super::expense::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Expense.def()
+    }
+}
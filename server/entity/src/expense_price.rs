@@ -0,0 +1,53 @@
+use crate::{Id, MoneyAmount};
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// A price change of an expense. The value and currency of the expense are its price from its start date,
+/// a change replaces them from its effective date until the next change.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "expense_prices")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub expense_id: Id,
+    pub value: MoneyAmount,
+    pub currency_id: Id,
+    pub effective_from: Date,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::currency::Entity",
+        from = "Column::CurrencyId",
+        to = "super::currency::Column::Id"
+    )]
+    Currency,
+    #[sea_orm(
+        belongs_to = "super::expense::Entity",
+        from = "Column::ExpenseId",
+        to = "super::expense::Column::Id"
+    )]
+    Expense,
+}
+
+impl Related<super::currency::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Currency.def()
+    }
+}
+
+impl Related<super::expense::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Expense.def()
+    }
+}
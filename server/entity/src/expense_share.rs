@@ -0,0 +1,53 @@
+use crate::{Id, MoneyAmount};
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// The share of a participant in the costs of an expense, its value is read by the split method of the expense.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "expense_shares")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub expense_id: Id,
+    pub participant_id: Id,
+    /// A percentage, a fixed amount or a weight, it is not used by the equal split.
+    #[sea_orm(nullable)]
+    pub value: Option<MoneyAmount>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::expense::Entity",
+        from = "Column::ExpenseId",
+        to = "super::expense::Column::Id"
+    )]
+    Expense,
+    #[sea_orm(
+        belongs_to = "super::participant::Entity",
+        from = "Column::ParticipantId",
+        to = "super::participant::Column::Id"
+    )]
+    Participant,
+}
+
+impl Related<super::expense::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Expense.def()
+    }
+}
+
+impl Related<super::participant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Participant.def()
+    }
+}
@@ -0,0 +1,38 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// A free-form tag of an expense.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "expense_tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub expense_id: Id,
+    pub name: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::expense::Entity",
+        from = "Column::ExpenseId",
+        to = "super::expense::Column::Id"
+    )]
+    Expense,
+}
+
+impl Related<super::expense::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Expense.def()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// A group of users living together, who share the expenses owned by the household.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "households")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub name: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::household_member::Entity")]
+    HouseholdMember,
+    #[sea_orm(has_many = "super::household_invitation::Entity")]
+    HouseholdInvitation,
+    #[sea_orm(has_many = "super::expense::Entity")]
+    Expense,
+}
+
+impl Related<super::household_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::HouseholdMember.def()
+    }
+}
+
+impl Related<super::household_invitation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::HouseholdInvitation.def()
+    }
+}
+
+impl Related<super::expense::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Expense.def()
+    }
+}
@@ -1,11 +1,32 @@
 use sea_orm::prelude::Decimal;
 
 pub mod account_activation;
+pub mod api_token;
+pub mod attachment;
+pub mod category;
 pub mod currency;
+pub mod email_change;
 pub mod expense;
+pub mod expense_pause;
+pub mod expense_price;
+pub mod expense_share;
+pub mod expense_tag;
+pub mod household;
+pub mod household_invitation;
+pub mod household_member;
+pub mod oidc_identity;
+pub mod participant;
+pub mod password_reset;
+pub mod pending_login;
 pub mod predefined_expense;
+pub mod rate_limit;
+pub mod recovery_code;
 pub mod recurrence;
+pub mod security_event;
+pub mod session;
 pub mod transaction;
+pub mod transaction_tag;
+pub mod two_factor_auth;
 pub mod user;
 
 pub type Id = u64;
@@ -0,0 +1,73 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// Someone who pays for expenses, owned either by a user or by a household.
+/// It can be linked to the account of a user. The participants are deleted together with their owner.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "participants")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub name: String,
+    #[sea_orm(nullable)]
+    pub user_id: Option<Id>,
+    #[sea_orm(nullable)]
+    pub household_id: Option<Id>,
+    /// The user account of the participant, which is not necessarily the owner.
+    #[sea_orm(nullable)]
+    pub account_id: Option<Id>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    Owner,
+    #[sea_orm(
+        belongs_to = "super::household::Entity",
+        from = "Column::HouseholdId",
+        to = "super::household::Column::Id"
+    )]
+    Household,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::AccountId",
+        to = "super::user::Column::Id"
+    )]
+    Account,
+    #[sea_orm(has_many = "super::transaction::Entity")]
+    Transaction,
+    #[sea_orm(has_many = "super::expense_share::Entity")]
+    ExpenseShare,
+}
+
+impl Related<super::household::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Household.def()
+    }
+}
+
+impl Related<super::transaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transaction.def()
+    }
+}
+
+impl Related<super::expense_share::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ExpenseShare.def()
+    }
+}
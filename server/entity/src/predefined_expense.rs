@@ -2,6 +2,27 @@ use crate::{Id, MoneyAmount};
 use sea_orm::entity::prelude::*;
 use serde::{self, Deserialize, Serialize};
 
+/// Who can see a predefined expense and choose it for their expenses.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum PredefinedExpenseVisibility {
+    /// Only the owner.
+    #[default]
+    #[sea_orm(string_value = "private")]
+    Private,
+    /// The members of the household of the predefined expense.
+    #[sea_orm(string_value = "household")]
+    Household,
+    /// Everyone, these form the curated catalog managed by the administrators.
+    #[sea_orm(string_value = "public")]
+    Public,
+}
+
+/// An entry of the catalog expenses can be created from.
+/// The owner is the user who created it, the entries from before owners were recorded have none.
 #[derive(
     Clone,
     Debug,
@@ -21,6 +42,15 @@ pub struct Model {
     pub value: MoneyAmount,
     pub currency_id: Id,
     pub recurrence_id: Id,
+    /// The name of the category the expenses created from it are put into by default.
+    #[sea_orm(nullable)]
+    pub default_category: Option<String>,
+    #[sea_orm(nullable)]
+    pub user_id: Option<Id>,
+    /// Only set for household visibility.
+    #[sea_orm(nullable)]
+    pub household_id: Option<Id>,
+    pub visibility: PredefinedExpenseVisibility,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -37,6 +67,18 @@ pub enum Relation {
         to = "super::recurrence::Column::Id"
     )]
     Recurrence,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::household::Entity",
+        from = "Column::HouseholdId",
+        to = "super::household::Column::Id"
+    )]
+    Household,
 }
 
 impl Related<super::currency::Entity> for Entity {
@@ -50,3 +92,15 @@ impl Related<super::recurrence::Entity> for Entity {
         Relation::Recurrence.def()
     }
 }
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::household::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Household.def()
+    }
+}
@@ -0,0 +1,73 @@
+use crate::Id;
+use sea_orm::entity::prelude::*;
+use serde::{self, Deserialize, Serialize};
+
+/// The account events recorded into the audit log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(32))")]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityEventType {
+    #[sea_orm(string_value = "login")]
+    Login,
+    #[sea_orm(string_value = "login_failed")]
+    LoginFailed,
+    #[sea_orm(string_value = "logout")]
+    Logout,
+    #[sea_orm(string_value = "registration")]
+    Registration,
+    #[sea_orm(string_value = "activation")]
+    Activation,
+    #[sea_orm(string_value = "password_change")]
+    PasswordChange,
+    #[sea_orm(string_value = "password_reset")]
+    PasswordReset,
+    #[sea_orm(string_value = "email_change_request")]
+    EmailChangeRequest,
+    #[sea_orm(string_value = "email_change")]
+    EmailChange,
+    #[sea_orm(string_value = "two_factor_enabled")]
+    TwoFactorEnabled,
+    #[sea_orm(string_value = "two_factor_disabled")]
+    TwoFactorDisabled,
+    #[sea_orm(string_value = "two_factor_failed")]
+    TwoFactorFailed,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    DeriveActiveModelBehavior,
+    DeriveEntityModel,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(table_name = "security_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub user_id: Id,
+    pub event_type: SecurityEventType,
+    #[sea_orm(nullable)]
+    pub ip_address: Option<String>,
+    #[sea_orm(nullable)]
+    pub user_agent: Option<String>,
+    pub created_at: DateTimeLocal,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
@@ -16,11 +16,16 @@ use serde::{self, Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: Id,
-    pub donor_name: String,
+    pub participant_id: Id,
     pub value: MoneyAmount,
     pub date: Date,
     pub currency_id: Id,
     pub expense_id: Id,
+    #[sea_orm(nullable)]
+    pub category_id: Option<Id>,
+    /// The transaction is in the trash since this time, it is purged after the retention period.
+    #[sea_orm(nullable)]
+    pub deleted_at: Option<DateTimeLocal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -37,6 +42,22 @@ pub enum Relation {
         to = "super::expense::Column::Id"
     )]
     Expense,
+    #[sea_orm(
+        belongs_to = "super::category::Entity",
+        from = "Column::CategoryId",
+        to = "super::category::Column::Id"
+    )]
+    Category,
+    #[sea_orm(
+        belongs_to = "super::participant::Entity",
+        from = "Column::ParticipantId",
+        to = "super::participant::Column::Id"
+    )]
+    Participant,
+    #[sea_orm(has_many = "super::transaction_tag::Entity")]
+    TransactionTag,
+    #[sea_orm(has_many = "super::attachment::Entity")]
+    Attachment,
 }
 
 impl Related<super::expense::Entity> for Entity {
@@ -50,3 +71,27 @@ impl Related<super::currency::Entity> for Entity {
         Relation::Currency.def()
     }
 }
+
+impl Related<super::category::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
+impl Related<super::participant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Participant.def()
+    }
+}
+
+impl Related<super::transaction_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TransactionTag.def()
+    }
+}
+
+impl Related<super::attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attachment.def()
+    }
+}
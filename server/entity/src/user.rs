@@ -2,6 +2,73 @@ use crate::Id;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Administrators can manage the reference data and the accounts of other users.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+    #[sea_orm(string_value = "user")]
+    User,
+    #[sea_orm(string_value = "admin")]
+    Admin,
+}
+
+/// The date formats users can choose from, serialized by their human readable form.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum DateFormat {
+    #[default]
+    #[sea_orm(string_value = "day_month_year")]
+    #[serde(rename = "dd-mm-yyyy")]
+    DayMonthYear,
+    #[sea_orm(string_value = "day_month_year_slashed")]
+    #[serde(rename = "dd/mm/yyyy")]
+    DayMonthYearSlashed,
+    #[sea_orm(string_value = "day_month_year_dotted")]
+    #[serde(rename = "dd.mm.yyyy")]
+    DayMonthYearDotted,
+    #[sea_orm(string_value = "month_day_year")]
+    #[serde(rename = "mm/dd/yyyy")]
+    MonthDayYear,
+    #[sea_orm(string_value = "year_month_day")]
+    #[serde(rename = "yyyy-mm-dd")]
+    YearMonthDay,
+    #[sea_orm(string_value = "year_month_day_dotted")]
+    #[serde(rename = "yyyy.mm.dd")]
+    YearMonthDayDotted,
+}
+
+impl DateFormat {
+    /// The chrono format string used to parse and render dates in this format.
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            DateFormat::DayMonthYear => "%d-%m-%Y",
+            DateFormat::DayMonthYearSlashed => "%d/%m/%Y",
+            DateFormat::DayMonthYearDotted => "%d.%m.%Y",
+            DateFormat::MonthDayYear => "%m/%d/%Y",
+            DateFormat::YearMonthDay => "%Y-%m-%d",
+            DateFormat::YearMonthDayDotted => "%Y.%m.%d",
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum FirstDayOfWeek {
+    #[default]
+    #[sea_orm(string_value = "monday")]
+    Monday,
+    #[sea_orm(string_value = "saturday")]
+    Saturday,
+    #[sea_orm(string_value = "sunday")]
+    Sunday,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -22,6 +89,12 @@ pub struct Model {
     pub pw_hash: String,
     #[sea_orm(default_value = "false")]
     pub activated: bool,
+    pub role: UserRole,
+    pub preferred_currency_id: Option<Id>,
+    pub locale: String,
+    pub timezone: String,
+    pub date_format: DateFormat,
+    pub first_day_of_week: FirstDayOfWeek,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
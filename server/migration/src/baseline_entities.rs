@@ -0,0 +1,92 @@
+//! The entities in the shape the first migrations created their tables in.
+//! Those migrations insert the dummy data through these and read it back, which only works
+//! with the columns existing at that point, while the entities of the application have more.
+
+pub mod user {
+    use entity::Id;
+    use sea_orm_migration::sea_orm;
+    use sea_orm_migration::sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveActiveModelBehavior, DeriveEntityModel)]
+    #[sea_orm(table_name = "users")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: Id,
+        #[sea_orm(unique)]
+        pub email: String,
+        pub username: String,
+        pub pw_hash: String,
+        #[sea_orm(default_value = "false")]
+        pub activated: bool,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+}
+
+pub mod predefined_expense {
+    use entity::{Id, MoneyAmount};
+    use sea_orm_migration::sea_orm;
+    use sea_orm_migration::sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, DeriveActiveModelBehavior)]
+    #[sea_orm(table_name = "predefined_expenses")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: Id,
+        pub name: String,
+        pub description: String,
+        pub value: MoneyAmount,
+        pub currency_id: Id,
+        pub recurrence_id: Id,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+}
+
+pub mod expense {
+    use entity::{Id, MoneyAmount};
+    use sea_orm_migration::sea_orm;
+    use sea_orm_migration::sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, DeriveActiveModelBehavior)]
+    #[sea_orm(table_name = "expenses")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: Id,
+        pub name: String,
+        pub description: String,
+        pub value: MoneyAmount,
+        pub start_date: Date,
+        pub user_id: Id,
+        pub currency_id: Id,
+        pub recurrence_id: Id,
+        #[sea_orm(nullable)]
+        pub predefined_expense_id: Option<Id>,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+}
+
+pub mod transaction {
+    use entity::{Id, MoneyAmount};
+    use sea_orm_migration::sea_orm;
+    use sea_orm_migration::sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, DeriveActiveModelBehavior)]
+    #[sea_orm(table_name = "transactions")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: Id,
+        pub donor_name: String,
+        pub value: MoneyAmount,
+        pub date: Date,
+        pub currency_id: Id,
+        pub expense_id: Id,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+}
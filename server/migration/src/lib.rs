@@ -1,5 +1,6 @@
 pub use sea_orm_migration::prelude::*;
 
+mod baseline_entities;
 mod m20220520_203901_create_users_table;
 mod m20220707_222235_create_account_activation_table;
 mod m20220811_190343_create_currency_table;
@@ -7,6 +8,34 @@ mod m20220811_190827_create_recurrence_table;
 mod m20220811_190836_create_predefined_expenses_table;
 mod m20220811_190845_create_expenses_table;
 mod m20220811_190853_create_transactions_table;
+mod m20230822_191512_create_sessions_table;
+mod m20230823_174209_create_password_resets_table;
+mod m20230824_203117_create_email_changes_table;
+mod m20230826_101522_create_two_factor_auths_table;
+mod m20230826_101547_create_recovery_codes_table;
+mod m20230826_101604_create_pending_logins_table;
+mod m20230827_143015_create_rate_limits_table;
+mod m20230828_160241_create_api_tokens_table;
+mod m20230829_091733_add_role_to_users_table;
+mod m20230830_184512_add_preferences_to_users_table;
+mod m20230901_112348_create_oidc_identities_table;
+mod m20230902_153827_create_security_events_table;
+mod m20230903_102114_add_end_date_to_expenses_table;
+mod m20230903_102131_create_expense_pauses_table;
+mod m20230904_181405_create_expense_prices_table;
+mod m20230905_090312_create_categories_table;
+mod m20230905_090338_add_category_to_expenses_and_transactions;
+mod m20230905_090401_create_expense_tags_table;
+mod m20230905_090417_create_transaction_tags_table;
+mod m20230906_142208_add_soft_delete_to_expenses_and_transactions;
+mod m20230907_183520_create_households_table;
+mod m20230907_183534_create_household_members_table;
+mod m20230907_183551_create_household_invitations_table;
+mod m20230907_183608_add_household_to_expenses_table;
+mod m20230908_201245_create_expense_shares_table;
+mod m20230909_164730_create_participants_table;
+mod m20230910_112650_create_attachments_table;
+mod m20230911_093415_add_owner_and_visibility_to_predefined_expenses_table;
 
 pub struct Migrator;
 
@@ -21,6 +50,36 @@ impl MigratorTrait for Migrator {
             Box::new(m20220811_190836_create_predefined_expenses_table::Migration),
             Box::new(m20220811_190845_create_expenses_table::Migration),
             Box::new(m20220811_190853_create_transactions_table::Migration),
+            Box::new(m20230822_191512_create_sessions_table::Migration),
+            Box::new(m20230823_174209_create_password_resets_table::Migration),
+            Box::new(m20230824_203117_create_email_changes_table::Migration),
+            Box::new(m20230826_101522_create_two_factor_auths_table::Migration),
+            Box::new(m20230826_101547_create_recovery_codes_table::Migration),
+            Box::new(m20230826_101604_create_pending_logins_table::Migration),
+            Box::new(m20230827_143015_create_rate_limits_table::Migration),
+            Box::new(m20230828_160241_create_api_tokens_table::Migration),
+            Box::new(m20230829_091733_add_role_to_users_table::Migration),
+            Box::new(m20230830_184512_add_preferences_to_users_table::Migration),
+            Box::new(m20230901_112348_create_oidc_identities_table::Migration),
+            Box::new(m20230902_153827_create_security_events_table::Migration),
+            Box::new(m20230903_102114_add_end_date_to_expenses_table::Migration),
+            Box::new(m20230903_102131_create_expense_pauses_table::Migration),
+            Box::new(m20230904_181405_create_expense_prices_table::Migration),
+            Box::new(m20230905_090312_create_categories_table::Migration),
+            Box::new(m20230905_090338_add_category_to_expenses_and_transactions::Migration),
+            Box::new(m20230905_090401_create_expense_tags_table::Migration),
+            Box::new(m20230905_090417_create_transaction_tags_table::Migration),
+            Box::new(m20230906_142208_add_soft_delete_to_expenses_and_transactions::Migration),
+            Box::new(m20230907_183520_create_households_table::Migration),
+            Box::new(m20230907_183534_create_household_members_table::Migration),
+            Box::new(m20230907_183551_create_household_invitations_table::Migration),
+            Box::new(m20230907_183608_add_household_to_expenses_table::Migration),
+            Box::new(m20230908_201245_create_expense_shares_table::Migration),
+            Box::new(m20230909_164730_create_participants_table::Migration),
+            Box::new(m20230910_112650_create_attachments_table::Migration),
+            Box::new(
+                m20230911_093415_add_owner_and_visibility_to_predefined_expenses_table::Migration,
+            ),
         ]
     }
 }
@@ -1,4 +1,4 @@
-use entity::user;
+use crate::baseline_entities::user;
 
 use sea_orm_migration::prelude::*;
 use sea_orm_migration::sea_orm::entity::ActiveModelTrait;
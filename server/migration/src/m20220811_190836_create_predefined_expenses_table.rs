@@ -1,4 +1,5 @@
-use entity::{currency, predefined_expense, recurrence};
+use crate::baseline_entities::predefined_expense;
+use entity::{currency, recurrence};
 
 use sea_orm_migration::prelude::*;
 use sea_orm_migration::sea_orm::entity::ActiveModelTrait;
@@ -1,4 +1,5 @@
-use entity::{currency, expense, predefined_expense, recurrence, user};
+use crate::baseline_entities::{expense, predefined_expense, user};
+use entity::{currency, recurrence};
 
 use chrono::NaiveDate;
 use sea_orm_migration::prelude::*;
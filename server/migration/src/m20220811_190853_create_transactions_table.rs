@@ -1,4 +1,5 @@
-use entity::{currency, expense, transaction};
+use crate::baseline_entities::{expense, transaction};
+use entity::currency;
 
 use chrono::NaiveDate;
 use sea_orm_migration::prelude::*;
@@ -0,0 +1,53 @@
+use entity::rate_limit;
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(rate_limit::Entity)
+                    .col(
+                        ColumnDef::new(rate_limit::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(rate_limit::Column::Key)
+                            .string_len(400)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(rate_limit::Column::Count)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(rate_limit::Column::WindowStartedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(rate_limit::Column::BlockedUntil)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(rate_limit::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,80 @@
+use entity::{currency, user};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(user::Entity)
+                    .add_column(
+                        ColumnDef::new(user::Column::PreferredCurrencyId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(user::Column::Locale)
+                            .string_len(35)
+                            .not_null()
+                            .default("en"),
+                    )
+                    .add_column(
+                        ColumnDef::new(user::Column::Timezone)
+                            .string_len(64)
+                            .not_null()
+                            .default("UTC"),
+                    )
+                    .add_column(
+                        ColumnDef::new(user::Column::DateFormat)
+                            .string_len(16)
+                            .not_null()
+                            .default("day_month_year"),
+                    )
+                    .add_column(
+                        ColumnDef::new(user::Column::FirstDayOfWeek)
+                            .string_len(16)
+                            .not_null()
+                            .default("monday"),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_user-preferred_currency")
+                            .from_tbl(user::Entity)
+                            .from_col(user::Column::PreferredCurrencyId)
+                            .to_tbl(currency::Entity)
+                            .to_col(currency::Column::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(user::Entity)
+                    .drop_foreign_key(Alias::new("fk_user-preferred_currency"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(user::Entity)
+                    .drop_column(user::Column::PreferredCurrencyId)
+                    .drop_column(user::Column::Locale)
+                    .drop_column(user::Column::Timezone)
+                    .drop_column(user::Column::DateFormat)
+                    .drop_column(user::Column::FirstDayOfWeek)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,31 @@
+use entity::expense;
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .add_column(ColumnDef::new(expense::Column::EndDate).date().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .drop_column(expense::Column::EndDate)
+                    .to_owned(),
+            )
+            .await
+    }
+}
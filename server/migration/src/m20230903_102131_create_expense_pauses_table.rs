@@ -0,0 +1,52 @@
+use entity::{expense, expense_pause};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(expense_pause::Entity)
+                    .col(
+                        ColumnDef::new(expense_pause::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_pause::Column::ExpenseId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_pause::Column::StartDate)
+                            .date()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(expense_pause::Column::EndDate).date().null())
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_expense_pause-expense")
+                            .from_tbl(expense_pause::Entity)
+                            .from_col(expense_pause::Column::ExpenseId)
+                            .to_tbl(expense::Entity)
+                            .to_col(expense::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(expense_pause::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,76 @@
+use entity::{currency, expense, expense_price};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(expense_price::Entity)
+                    .col(
+                        ColumnDef::new(expense_price::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_price::Column::ExpenseId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_price::Column::Value)
+                            .decimal_len(12, 2)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_price::Column::CurrencyId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_price::Column::EffectiveFrom)
+                            .date()
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_expense_price-expense-effective_from")
+                            .col(expense_price::Column::ExpenseId)
+                            .col(expense_price::Column::EffectiveFrom)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_expense_price-currency")
+                            .from_tbl(expense_price::Entity)
+                            .from_col(expense_price::Column::CurrencyId)
+                            .to_tbl(currency::Entity)
+                            .to_col(currency::Column::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_expense_price-expense")
+                            .from_tbl(expense_price::Entity)
+                            .from_col(expense_price::Column::ExpenseId)
+                            .to_tbl(expense::Entity)
+                            .to_col(expense::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(expense_price::Entity).to_owned())
+            .await
+    }
+}
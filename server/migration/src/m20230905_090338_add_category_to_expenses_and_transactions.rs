@@ -0,0 +1,113 @@
+use entity::{category, expense, predefined_expense, transaction};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .add_column(
+                        ColumnDef::new(expense::Column::CategoryId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_expense-category")
+                    .from(expense::Entity, expense::Column::CategoryId)
+                    .to(category::Entity, category::Column::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .add_column(
+                        ColumnDef::new(transaction::Column::CategoryId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_transaction-category")
+                    .from(transaction::Entity, transaction::Column::CategoryId)
+                    .to(category::Entity, category::Column::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(predefined_expense::Entity)
+                    .add_column(
+                        ColumnDef::new(predefined_expense::Column::DefaultCategory)
+                            .string_len(255)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(predefined_expense::Entity)
+                    .drop_column(predefined_expense::Column::DefaultCategory)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_transaction-category")
+                    .table(transaction::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .drop_column(transaction::Column::CategoryId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_expense-category")
+                    .table(expense::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .drop_column(expense::Column::CategoryId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,58 @@
+use entity::{expense, expense_tag};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(expense_tag::Entity)
+                    .col(
+                        ColumnDef::new(expense_tag::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_tag::Column::ExpenseId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_tag::Column::Name)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_expense_tag-expense-name")
+                            .col(expense_tag::Column::ExpenseId)
+                            .col(expense_tag::Column::Name)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_expense_tag-expense")
+                            .from_tbl(expense_tag::Entity)
+                            .from_col(expense_tag::Column::ExpenseId)
+                            .to_tbl(expense::Entity)
+                            .to_col(expense::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(expense_tag::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,58 @@
+use entity::{transaction, transaction_tag};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(transaction_tag::Entity)
+                    .col(
+                        ColumnDef::new(transaction_tag::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(transaction_tag::Column::TransactionId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(transaction_tag::Column::Name)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_transaction_tag-transaction-name")
+                            .col(transaction_tag::Column::TransactionId)
+                            .col(transaction_tag::Column::Name)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_transaction_tag-transaction")
+                            .from_tbl(transaction_tag::Entity)
+                            .from_col(transaction_tag::Column::TransactionId)
+                            .to_tbl(transaction::Entity)
+                            .to_col(transaction::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(transaction_tag::Entity).to_owned())
+            .await
+    }
+}
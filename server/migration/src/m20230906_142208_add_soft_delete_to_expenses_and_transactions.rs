@@ -0,0 +1,97 @@
+use entity::{expense, transaction};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .add_column(
+                        ColumnDef::new(expense::Column::Archived)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(expense::Column::DeletedAt)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_expense-deleted_at")
+                    .table(expense::Entity)
+                    .col(expense::Column::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .add_column(
+                        ColumnDef::new(transaction::Column::DeletedAt)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transaction-deleted_at")
+                    .table(transaction::Entity)
+                    .col(transaction::Column::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_transaction-deleted_at")
+                    .table(transaction::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .drop_column(transaction::Column::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_expense-deleted_at")
+                    .table(expense::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .drop_column(expense::Column::DeletedAt)
+                    .drop_column(expense::Column::Archived)
+                    .to_owned(),
+            )
+            .await
+    }
+}
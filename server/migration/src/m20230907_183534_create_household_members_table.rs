@@ -0,0 +1,72 @@
+use entity::{household, household_member, user};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(household_member::Entity)
+                    .col(
+                        ColumnDef::new(household_member::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(household_member::Column::HouseholdId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(household_member::Column::UserId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(household_member::Column::Role)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_household_member-household-user")
+                            .col(household_member::Column::HouseholdId)
+                            .col(household_member::Column::UserId)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_household_member-household")
+                            .from_tbl(household_member::Entity)
+                            .from_col(household_member::Column::HouseholdId)
+                            .to_tbl(household::Entity)
+                            .to_col(household::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_household_member-user")
+                            .from_tbl(household_member::Entity)
+                            .from_col(household_member::Column::UserId)
+                            .to_tbl(user::Entity)
+                            .to_col(user::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(household_member::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,53 @@
+use entity::{expense, household};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .add_column(
+                        ColumnDef::new(expense::Column::HouseholdId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_expense-household")
+                    .from(expense::Entity, expense::Column::HouseholdId)
+                    .to(household::Entity, household::Column::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_expense-household")
+                    .table(expense::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .drop_column(expense::Column::HouseholdId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,89 @@
+use entity::{expense, expense_share};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The participant name of the shares, which is replaced with a participant by a later migration.
+#[derive(Iden)]
+enum ExpenseShare {
+    Participant,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .add_column(
+                        ColumnDef::new(expense::Column::SplitMethod)
+                            .string_len(16)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(expense_share::Entity)
+                    .col(
+                        ColumnDef::new(expense_share::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_share::Column::ExpenseId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ExpenseShare::Participant)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(expense_share::Column::Value)
+                            .decimal_len(12, 2)
+                            .null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_expense_share-expense-participant")
+                            .col(expense_share::Column::ExpenseId)
+                            .col(ExpenseShare::Participant)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_expense_share-expense")
+                            .from_tbl(expense_share::Entity)
+                            .from_col(expense_share::Column::ExpenseId)
+                            .to_tbl(expense::Entity)
+                            .to_col(expense::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(expense_share::Entity).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense::Entity)
+                    .drop_column(expense::Column::SplitMethod)
+                    .to_owned(),
+            )
+            .await
+    }
+}
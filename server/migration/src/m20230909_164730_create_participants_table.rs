@@ -0,0 +1,306 @@
+use entity::{expense_share, household, participant, transaction, user};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// The free text payers, which are replaced with participants.
+#[derive(Iden)]
+enum Legacy {
+    DonorName,
+    Participant,
+}
+
+/// Every distinct donor name becomes a participant of the owner of the expense, or of its household if it is shared.
+/// The names are compared by the collation of the database, so the names only differing in case are merged.
+const INSERT_PARTICIPANTS: &str = "
+    INSERT INTO participants (name, user_id, household_id)
+    SELECT MIN(names.name), names.user_id, names.household_id FROM (
+        SELECT TRIM(t.donor_name) AS name,
+            IF(e.household_id IS NULL, e.user_id, NULL) AS user_id, e.household_id
+        FROM transactions t JOIN expenses e ON e.id = t.expense_id
+        UNION
+        SELECT TRIM(s.participant) AS name,
+            IF(e.household_id IS NULL, e.user_id, NULL) AS user_id, e.household_id
+        FROM expense_shares s JOIN expenses e ON e.id = s.expense_id
+    ) names
+    GROUP BY names.name, names.user_id, names.household_id";
+
+const LINK_TRANSACTIONS: &str = "
+    UPDATE transactions t
+    JOIN expenses e ON e.id = t.expense_id
+    JOIN participants p ON p.name = TRIM(t.donor_name)
+        AND (p.household_id = e.household_id OR (e.household_id IS NULL AND p.user_id = e.user_id))
+    SET t.participant_id = p.id";
+
+const LINK_SHARES: &str = "
+    UPDATE expense_shares s
+    JOIN expenses e ON e.id = s.expense_id
+    JOIN participants p ON p.name = TRIM(s.participant)
+        AND (p.household_id = e.household_id OR (e.household_id IS NULL AND p.user_id = e.user_id))
+    SET s.participant_id = p.id";
+
+const RESTORE_DONOR_NAMES: &str = "
+    UPDATE transactions t JOIN participants p ON p.id = t.participant_id
+    SET t.donor_name = p.name";
+
+const RESTORE_SHARE_NAMES: &str = "
+    UPDATE expense_shares s JOIN participants p ON p.id = s.participant_id
+    SET s.participant = p.name";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(participant::Entity)
+                    .col(
+                        ColumnDef::new(participant::Column::Id)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(participant::Column::Name)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(participant::Column::UserId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(participant::Column::HouseholdId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(participant::Column::AccountId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_participant-user-name")
+                            .col(participant::Column::UserId)
+                            .col(participant::Column::Name)
+                            .unique(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_participant-household-name")
+                            .col(participant::Column::HouseholdId)
+                            .col(participant::Column::Name)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_participant-user")
+                            .from_tbl(participant::Entity)
+                            .from_col(participant::Column::UserId)
+                            .to_tbl(user::Entity)
+                            .to_col(user::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_participant-household")
+                            .from_tbl(participant::Entity)
+                            .from_col(participant::Column::HouseholdId)
+                            .to_tbl(household::Entity)
+                            .to_col(household::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .name("fk_participant-account")
+                            .from_tbl(participant::Entity)
+                            .from_col(participant::Column::AccountId)
+                            .to_tbl(user::Entity)
+                            .to_col(user::Column::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .add_column(
+                        ColumnDef::new(transaction::Column::ParticipantId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense_share::Entity)
+                    .add_column(
+                        ColumnDef::new(expense_share::Column::ParticipantId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(INSERT_PARTICIPANTS).await?;
+        db.execute_unprepared(LINK_TRANSACTIONS).await?;
+        db.execute_unprepared(LINK_SHARES).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .modify_column(
+                        ColumnDef::new(transaction::Column::ParticipantId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_transaction-participant")
+                            .from_tbl(transaction::Entity)
+                            .from_col(transaction::Column::ParticipantId)
+                            .to_tbl(participant::Entity)
+                            .to_col(participant::Column::Id),
+                    )
+                    .drop_column(Legacy::DonorName)
+                    .to_owned(),
+            )
+            .await?;
+        // the new unique index is created first, as the foreign key of the expense needs one of them
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_expense_share-expense-participant_id")
+                    .table(expense_share::Entity)
+                    .col(expense_share::Column::ExpenseId)
+                    .col(expense_share::Column::ParticipantId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_expense_share-expense-participant")
+                    .table(expense_share::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense_share::Entity)
+                    .modify_column(
+                        ColumnDef::new(expense_share::Column::ParticipantId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_expense_share-participant")
+                            .from_tbl(expense_share::Entity)
+                            .from_col(expense_share::Column::ParticipantId)
+                            .to_tbl(participant::Entity)
+                            .to_col(participant::Column::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .drop_column(Legacy::Participant)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .add_column(
+                        ColumnDef::new(Legacy::DonorName)
+                            .string_len(255)
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense_share::Entity)
+                    .add_column(
+                        ColumnDef::new(Legacy::Participant)
+                            .string_len(255)
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(RESTORE_DONOR_NAMES).await?;
+        db.execute_unprepared(RESTORE_SHARE_NAMES).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(transaction::Entity)
+                    .drop_foreign_key(Alias::new("fk_transaction-participant"))
+                    .drop_column(transaction::Column::ParticipantId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_expense_share-expense-participant")
+                    .table(expense_share::Entity)
+                    .col(expense_share::Column::ExpenseId)
+                    .col(Legacy::Participant)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense_share::Entity)
+                    .drop_foreign_key(Alias::new("fk_expense_share-participant"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_expense_share-expense-participant_id")
+                    .table(expense_share::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(expense_share::Entity)
+                    .drop_column(expense_share::Column::ParticipantId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(participant::Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,91 @@
+use entity::{household, predefined_expense, user};
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(predefined_expense::Entity)
+                    .add_column(
+                        ColumnDef::new(predefined_expense::Column::UserId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(predefined_expense::Column::HouseholdId)
+                            .big_unsigned()
+                            .null(),
+                    )
+                    // the existing entries stay in the public catalog without an owner
+                    .add_column(
+                        ColumnDef::new(predefined_expense::Column::Visibility)
+                            .string_len(16)
+                            .not_null()
+                            .default("public"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_predefined_expense-user")
+                    .from(
+                        predefined_expense::Entity,
+                        predefined_expense::Column::UserId,
+                    )
+                    .to(user::Entity, user::Column::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_predefined_expense-household")
+                    .from(
+                        predefined_expense::Entity,
+                        predefined_expense::Column::HouseholdId,
+                    )
+                    .to(household::Entity, household::Column::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_predefined_expense-household")
+                    .table(predefined_expense::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_predefined_expense-user")
+                    .table(predefined_expense::Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(predefined_expense::Entity)
+                    .drop_column(predefined_expense::Column::Visibility)
+                    .drop_column(predefined_expense::Column::HouseholdId)
+                    .drop_column(predefined_expense::Column::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+}